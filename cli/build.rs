@@ -0,0 +1,93 @@
+//! Records what `hammer --version --build-info` reports about the build, and the revm
+//! version reports name.
+
+use std::process::Command;
+
+fn main() {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|out| out.status.success())
+            .map(|out| String::from_utf8_lossy(&out.stdout).trim().to_string())
+    };
+    let commit = match git(&["rev-parse", "--short=12", "HEAD"]) {
+        Some(hash)
+            if git(&["status", "--porcelain", "--untracked-files=no"])
+                .is_some_and(|status| !status.is_empty()) =>
+        {
+            format!("{}-dirty", hash)
+        }
+        Some(hash) => hash,
+        // Built from a source archive.
+        None => "unknown".to_string(),
+    };
+    println!("cargo:rustc-env=HAMMER_GIT_COMMIT={}", commit);
+    println!(
+        "cargo:rustc-env=HAMMER_TARGET={}",
+        std::env::var("TARGET").unwrap_or_default()
+    );
+    println!(
+        "cargo:rustc-env=HAMMER_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_default()
+    );
+    // The revm the lockfile pins, recorded in reports (see `commands::engine`): the
+    // workspace's in a checkout, the package's own once published, else the requirement
+    // in Cargo.toml.
+    let revm = ["../Cargo.lock", "Cargo.lock"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .find_map(|lock| locked_version(&lock, "revm"))
+        .or_else(|| {
+            let manifest = std::fs::read_to_string("Cargo.toml").ok()?;
+            required_version(&manifest, "revm")
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=HAMMER_REVM_VERSION={}", revm);
+    println!("cargo:rerun-if-changed=../Cargo.lock");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+    println!("cargo:rerun-if-changed=Cargo.toml");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/index");
+}
+
+/// The version of package `name` in a Cargo.lock.
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let header = format!("name = \"{}\"", name);
+    let mut lines = lock.lines();
+    lines.find(|line| *line == header)?;
+    lines
+        .next()?
+        .strip_prefix("version = \"")?
+        .strip_suffix('"')
+        .map(str::to_string)
+}
+
+/// The version requirement on dependency `name` in a Cargo.toml, inline (`name = ".."`,
+/// `name = { version = "..", .. }`) or as `cargo package` normalizes it
+/// (`[dependencies.name]` then `version = ".."`).
+fn required_version(manifest: &str, name: &str) -> Option<String> {
+    let quoted = |line: &str| {
+        let rest = &line[line.find("version = \"")? + "version = \"".len()..];
+        rest.split('"').next().map(str::to_string)
+    };
+    let inline = format!("{} = ", name);
+    let table = format!("[dependencies.{}]", name);
+    let mut lines = manifest.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if let Some(value) = line.strip_prefix(&inline) {
+            return match value.strip_prefix('"') {
+                Some(plain) => plain.split('"').next().map(str::to_string),
+                None => quoted(value),
+            };
+        }
+        if line == table {
+            return lines
+                .take_while(|line| !line.starts_with('['))
+                .find(|line| line.starts_with("version = "))
+                .and_then(quoted);
+        }
+    }
+    None
+}
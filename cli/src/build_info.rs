@@ -0,0 +1,36 @@
+//! `hammer --version` and `hammer --version --build-info`.
+//!
+//! The build info names the exact binary behind a report: its commit, target, profile and
+//! TLS backend. Git details are captured by `build.rs`.
+
+const FEATURES: &[(&str, bool)] = &[
+    ("rustls", cfg!(feature = "rustls")),
+    ("native-tls", cfg!(feature = "native-tls")),
+];
+
+/// `hammer 0.1.0`.
+pub fn version() -> String {
+    format!("hammer {}", env!("CARGO_PKG_VERSION"))
+}
+
+/// The version followed by one `key: value` line per build detail.
+pub fn build_info() -> String {
+    let features: Vec<&str> = FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    let features = if features.is_empty() {
+        "none (no TLS: http:// endpoints only)".to_string()
+    } else {
+        features.join(", ")
+    };
+    format!(
+        "{}\ncommit:   {}\ntarget:   {}\nprofile:  {}\nfeatures: {}",
+        version(),
+        env!("HAMMER_GIT_COMMIT"),
+        env!("HAMMER_TARGET"),
+        env!("HAMMER_PROFILE"),
+        features
+    )
+}
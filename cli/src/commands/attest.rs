@@ -0,0 +1,212 @@
+//! Signed reports (`--sign-key`) and checking them (`hammer verify-signature`).
+//!
+//! A signed report is printed as `{"report": ..., "attestation": {...}}`. The attestation
+//! holds the keccak-256 hash of the report's canonical JSON (compact, object keys sorted),
+//! the signer's address, and its secp256k1 signature over that hash. Anyone holding the
+//! envelope can recompute the hash and recover the signer; whether that address is one to
+//! trust is for them to decide.
+
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::SignerSync;
+use alloy_primitives::{keccak256, Address, Signature, B256};
+use eyre::{Context, Result};
+use serde_json::{json, Value};
+use std::path::Path;
+
+use super::sink::{self, Sink};
+
+/// Read a hex private key (`0x` optional) from `path`.
+pub fn load_key(path: &Path) -> Result<PrivateKeySigner> {
+    let text = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read signing key {}", path.display()))?;
+    text.trim().parse().map_err(|_| {
+        eyre::eyre!(
+            "invalid signing key in {}: expected a hex private key",
+            path.display()
+        )
+    })
+}
+
+/// Compact JSON with every object's keys in sorted order, so equal reports hash equally
+/// whatever order their fields were written in.
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(&mut out, value);
+    out
+}
+
+fn write_canonical(out: &mut String, value: &Value) {
+    match value {
+        Value::Object(fields) => {
+            let mut keys: Vec<&String> = fields.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(out, &fields[key]);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, item);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Wrap `report` in an envelope with `signer`'s attestation.
+pub fn sign(report: Value, signer: &PrivateKeySigner) -> Result<Value> {
+    let hash = keccak256(canonical_json(&report));
+    let signature = signer
+        .sign_hash_sync(&hash)
+        .wrap_err("failed to sign report")?;
+    Ok(json!({
+        "report": report,
+        "attestation": {
+            "hash": hash,
+            "signer": signer.address(),
+            "signature": format!("0x{}", hex::encode(signature.as_bytes())),
+        },
+    }))
+}
+
+/// Write `report` as JSON to `sink`, signed when a key was given.
+pub async fn write_report(
+    report: Value,
+    signer: Option<&PrivateKeySigner>,
+    sink: &Sink,
+) -> Result<()> {
+    let value = match signer {
+        Some(signer) => sign(report, signer)?,
+        None => report,
+    };
+    sink.write(serde_json::to_string_pretty(&value)?, sink::JSON)
+        .await
+}
+
+/// The report inside a signed envelope, or `value` itself when it is not one. The
+/// signature is not checked.
+pub fn unwrap_report(value: Value) -> Value {
+    match value {
+        Value::Object(mut fields)
+            if fields.len() == 2
+                && fields.contains_key("attestation")
+                && fields.contains_key("report") =>
+        {
+            fields.remove("report").unwrap_or_default()
+        }
+        value => value,
+    }
+}
+
+/// Who signed an envelope, and the hash they signed.
+#[derive(Debug, PartialEq)]
+pub struct Attestation {
+    pub signer: Address,
+    pub hash: B256,
+}
+
+/// Check that `envelope`'s report hashes to the attested hash and that the signature over
+/// it recovers to the attested signer.
+pub fn verify(envelope: &Value) -> Result<Attestation> {
+    let (Some(report), Some(attestation)) = (envelope.get("report"), envelope.get("attestation"))
+    else {
+        eyre::bail!("not a signed report: expected `report` and `attestation` fields");
+    };
+    let field = |name: &str| {
+        attestation
+            .get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| eyre::eyre!("attestation has no `{}`", name))
+    };
+    let hash: B256 = field("hash")?
+        .parse()
+        .wrap_err("invalid attestation hash")?;
+    let signer: Address = field("signer")?
+        .parse()
+        .wrap_err("invalid attestation signer")?;
+    let bytes = hex::decode(field("signature")?.trim_start_matches("0x"))
+        .wrap_err("invalid attestation signature")?;
+    let signature = Signature::from_raw(&bytes)
+        .map_err(|e| eyre::eyre!("invalid attestation signature: {}", e))?;
+
+    let actual = keccak256(canonical_json(report));
+    if actual != hash {
+        eyre::bail!(
+            "report was modified after signing: it hashes to {}, the attestation to {}",
+            actual,
+            hash
+        );
+    }
+    let recovered = signature
+        .recover_address_from_prehash(&hash)
+        .map_err(|e| eyre::eyre!("signature does not recover: {}", e))?;
+    if recovered != signer {
+        eyre::bail!(
+            "signature is by {}, not the attested signer {}",
+            recovered,
+            signer
+        );
+    }
+    Ok(Attestation { signer, hash })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> PrivateKeySigner {
+        "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let a: Value =
+            serde_json::from_str(r#"{"b": 1, "a": {"d": [true, null], "c": "x"}}"#).unwrap();
+        assert_eq!(
+            canonical_json(&a),
+            r#"{"a":{"c":"x","d":[true,null]},"b":1}"#
+        );
+    }
+
+    #[test]
+    fn test_signed_report_verifies_and_detects_tampering() {
+        let report = json!({"is_valid": false, "gas_summary": {"waste_per_tx": 2400}});
+        let envelope = sign(report.clone(), &signer()).unwrap();
+        let attestation = verify(&envelope).unwrap();
+        assert_eq!(attestation.signer, signer().address());
+        assert_eq!(unwrap_report(envelope.clone()), report);
+
+        let mut tampered = envelope.clone();
+        tampered["report"]["gas_summary"]["waste_per_tx"] = json!(0);
+        let err = verify(&tampered).unwrap_err();
+        assert!(err.to_string().contains("modified"), "{err}");
+
+        let mut forged = envelope;
+        forged["attestation"]["signer"] = json!(Address::with_last_byte(0x40));
+        let err = verify(&forged).unwrap_err();
+        assert!(err.to_string().contains("not the attested signer"), "{err}");
+    }
+
+    #[test]
+    fn test_unsigned_report_rejected() {
+        assert!(verify(&json!({"is_valid": true})).is_err());
+        assert_eq!(
+            unwrap_report(json!({"is_valid": true})),
+            json!({"is_valid": true})
+        );
+    }
+}
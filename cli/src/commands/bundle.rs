@@ -0,0 +1,250 @@
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{AccessList, TransactionRequest};
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{access_list_gas_cost, generate_queue_with, merge_bundle};
+use revm::context::TxEnv;
+use revm::primitives::TxKind;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::attest;
+use super::config::OptimizerArgs;
+use super::global::GlobalArgs;
+use super::util::{
+    apply_chain_rules, assert_post_berlin, block_env_from_header, fetch_block, parse_block_id,
+    parse_hex_bytes, parse_u256, state_read_error, traced,
+};
+
+/// Gas limit for entries that do not set `gas`, as in `generate`.
+const DEFAULT_GAS: u64 = 30_000_000;
+
+#[derive(Args)]
+pub struct BundleArgs {
+    /// JSON array of transactions in bundle order:
+    /// `{"from", "to", "data"?, "value"?, "gas"?, "nonce"?}`
+    #[arg(long)]
+    pub bundle: PathBuf,
+    #[arg(long, default_value = "latest")]
+    pub block: String,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+/// One entry of the `--bundle` file.
+#[derive(Debug, PartialEq)]
+struct BundledTx {
+    from: Address,
+    to: Address,
+    data: Vec<u8>,
+    value: U256,
+    gas: u64,
+    nonce: Option<u64>,
+}
+
+/// Run the generate-bundle command.
+///
+/// Runs the bundle's transactions back to back, each on the state the earlier ones left,
+/// and prints one list for all of them: every entry any transaction needs, once. Entries
+/// without a `nonce` take their sender's next one at the block.
+///
+/// # Test boundary
+///
+/// This function requires a live RPC connection. Tracing is `queue`'s and the merge is
+/// core's (`merge_bundle`); parsing is covered by the unit tests below.
+pub async fn run(args: BundleArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("json", &["json", "human"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let mut config = args.optimizer.config()?;
+    let bundle = parse_bundle(
+        &std::fs::read_to_string(&args.bundle)
+            .wrap_err_with(|| format!("failed to read {}", args.bundle.display()))?,
+    )
+    .wrap_err_with(|| format!("invalid bundle in {}", args.bundle.display()))?;
+    let block_id = parse_block_id(&args.block)?;
+
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
+    let (block, _) = fetch_block(&provider, block_id, None).await?;
+    timer.phase("block");
+    let header = &block.header;
+    assert_post_berlin(header.number)?;
+    apply_chain_rules(&mut config, global.chain_id()?, header.number);
+    let block_env = block_env_from_header(header);
+    let state_block_id = BlockId::hash(header.hash);
+
+    let mut next_nonce: BTreeMap<Address, u64> = BTreeMap::new();
+    let gas_price = block_env.basefee.max(1_000_000_000) as u128;
+    let mut txs = Vec::with_capacity(bundle.len());
+    for b in &bundle {
+        let nonce = match (b.nonce, next_nonce.get(&b.from)) {
+            (Some(nonce), _) | (None, Some(&nonce)) => nonce,
+            (None, None) => provider
+                .get_transaction_count(b.from)
+                .block_id(state_block_id)
+                .await
+                .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?,
+        };
+        next_nonce.insert(b.from, nonce + 1);
+        txs.push(
+            TxEnv::builder()
+                .caller(b.from)
+                .nonce(nonce)
+                .kind(TxKind::Call(b.to))
+                .gas_limit(b.gas)
+                .gas_price(gas_price)
+                .value(b.value)
+                .data(b.data.clone().into())
+                .build()
+                .unwrap(),
+        );
+    }
+
+    // Prefetch every entry against the base state before anything executes, as `queue`
+    // does: prewarming after a commit would overwrite state the bundle itself wrote.
+    let mut db = super::prefetch::empty(provider.clone(), state_block_id)?;
+    for b in &bundle {
+        let tx_req = TransactionRequest {
+            from: Some(b.from),
+            to: Some(TxKind::Call(b.to)),
+            value: Some(b.value),
+            input: alloy_rpc_types_eth::TransactionInput::new(b.data.clone().into()),
+            gas: Some(b.gas),
+            ..Default::default()
+        };
+        super::prefetch::prewarm(
+            &mut db,
+            provider.clone(),
+            state_block_id,
+            state_block_id,
+            tx_req,
+            &AccessList::default(),
+        )
+        .await;
+    }
+    timer.phase("state");
+
+    let lists = traced(header.number, || {
+        generate_queue_with(db, txs, block_env, &config)
+    })
+    .wrap_err("bundle simulation failed")?;
+    timer.phase("trace");
+    timer.total();
+
+    let merged = merge_bundle(&lists);
+    let list_cost = access_list_gas_cost(&merged.list);
+    let separate_cost: u64 = lists.iter().map(|l| access_list_gas_cost(&l.list)).sum();
+    match output {
+        "json" => {
+            let report = json!({
+                "block_number": header.number,
+                "transactions": bundle.len(),
+                "access_list": serde_json::to_value(&merged.list)?,
+                "list_cost": list_cost,
+                "separate_list_cost": separate_cost,
+            });
+            attest::write_report(report, signer.as_ref(), &sink).await?;
+        }
+        "human" => {
+            println!(
+                "Bundle of {} transactions at block {} (cost: {}; {} as separate lists):",
+                bundle.len(),
+                header.number,
+                units.gas(list_cost),
+                units.gas(separate_cost)
+            );
+            for item in &merged.list.0 {
+                println!("  {}:", item.address);
+                for key in &item.storage_keys {
+                    println!("    - {}", key);
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn parse_bundle(text: &str) -> Result<Vec<BundledTx>> {
+    let value: serde_json::Value = serde_json::from_str(text)?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| eyre::eyre!("expected a JSON array of transactions"))?;
+    if entries.is_empty() {
+        eyre::bail!("bundle is empty");
+    }
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let field = |name: &str| entry.get(name).and_then(|v| v.as_str());
+            let address = |name: &str| -> Result<Address> {
+                field(name)
+                    .ok_or_else(|| eyre::eyre!("entry {}: missing \"{}\"", i, name))?
+                    .parse()
+                    .wrap_err_with(|| format!("entry {}: invalid \"{}\"", i, name))
+            };
+            let number = |name: &str| -> Result<Option<u64>> {
+                entry
+                    .get(name)
+                    .map(|v| {
+                        v.as_u64().ok_or_else(|| {
+                            eyre::eyre!("entry {}: \"{}\" must be a number", i, name)
+                        })
+                    })
+                    .transpose()
+            };
+            Ok(BundledTx {
+                from: address("from")?,
+                to: address("to")?,
+                data: parse_hex_bytes(field("data").unwrap_or("0x"))
+                    .wrap_err_with(|| format!("entry {}: invalid \"data\"", i))?,
+                value: parse_u256(field("value").unwrap_or("0"))
+                    .wrap_err_with(|| format!("entry {}: invalid \"value\"", i))?,
+                gas: number("gas")?.unwrap_or(DEFAULT_GAS),
+                nonce: number("nonce")?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bundle() {
+        let bundle = parse_bundle(
+            r#"[
+                {"from": "0x0000000000000000000000000000000000000001", "to": "0x0000000000000000000000000000000000000002"},
+                {"from": "0x0000000000000000000000000000000000000003", "to": "0x0000000000000000000000000000000000000002", "data": "0xabcd", "gas": 100000, "nonce": 9}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            bundle[0],
+            BundledTx {
+                from: Address::with_last_byte(1),
+                to: Address::with_last_byte(2),
+                data: vec![],
+                value: U256::ZERO,
+                gas: DEFAULT_GAS,
+                nonce: None,
+            }
+        );
+        assert_eq!(
+            (bundle[1].from, bundle[1].gas, bundle[1].nonce),
+            (Address::with_last_byte(3), 100_000, Some(9))
+        );
+
+        let err =
+            parse_bundle(r#"[{"to": "0x0000000000000000000000000000000000000002"}]"#).unwrap_err();
+        assert_eq!(err.to_string(), "entry 0: missing \"from\"");
+        assert!(parse_bundle("[]").is_err());
+    }
+}
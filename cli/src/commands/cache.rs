@@ -0,0 +1,440 @@
+//! Persistent state cache: the state `compare` fetched for a block, kept on disk so a
+//! later run against the same block reads it back instead of fetching it again.
+//!
+//! One JSON file per block, `<dir>/<chain id>/<block hash>.json`, holds every account,
+//! storage slot and block hash the runs against that block loaded, and the transactions
+//! whose state was pre-warmed into it. State at a block hash never changes, so entries
+//! never go stale; only blocks named by hash are cached. The directory is
+//! `$HAMMER_CACHE_DIR`, else `~/.hammer/cache`. `--no-cache` bypasses it; `hammer cache
+//! stats` sizes it, `hammer cache gc --max-size` bounds it and `hammer cache clear`
+//! empties it.
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+use clap::{Args, Subcommand};
+use eyre::{Context, Result};
+use revm::database::{AccountState, DbAccount};
+use revm::primitives::KECCAK_EMPTY;
+use revm::state::{AccountInfo, Bytecode};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::compare::BlockState;
+use super::global::GlobalArgs;
+use super::prefetch::PrewarmedDB;
+
+/// Bumped when the file layout changes; files of another version are ignored.
+const VERSION: u64 = 1;
+
+#[derive(Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    pub command: CacheCommand,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    /// Show how many blocks are cached and their size, per chain
+    Stats,
+    /// Delete the least recently written blocks until the cache fits in a size
+    Gc {
+        /// Size to shrink the cache to, in bytes or with a K, M or G suffix (e.g. 500M)
+        #[arg(long, value_parser = parse_size)]
+        max_size: u64,
+    },
+    /// Delete every cached block
+    Clear,
+}
+
+/// Run the cache command.
+pub fn run(args: CacheArgs, global: &GlobalArgs) -> Result<()> {
+    let dir = StateCache::default_dir()
+        .ok_or_else(|| eyre::eyre!("no cache directory: set HAMMER_CACHE_DIR or HOME"))?;
+    let cache = StateCache::new(dir.clone());
+    match args.command {
+        CacheCommand::Stats => {
+            let entries = cache.entries();
+            let mut chains: BTreeMap<u64, (usize, u64)> = BTreeMap::new();
+            for entry in &entries {
+                let chain = chains.entry(entry.chain_id).or_default();
+                chain.0 += 1;
+                chain.1 += entry.bytes;
+            }
+            for (chain_id, (blocks, bytes)) in &chains {
+                println!("Chain {}: {} blocks, {} bytes", chain_id, blocks, bytes);
+            }
+            println!(
+                "Total: {} blocks, {} bytes in {}",
+                entries.len(),
+                entries.iter().map(|e| e.bytes).sum::<u64>(),
+                dir.display()
+            );
+        }
+        CacheCommand::Gc { max_size } => {
+            let (removed, freed, kept) = cache.gc(max_size)?;
+            if !global.quiet {
+                println!(
+                    "Removed {} cached blocks ({} bytes); {} bytes remain",
+                    removed, freed, kept
+                );
+            }
+        }
+        CacheCommand::Clear => {
+            let removed = cache.clear()?;
+            if !global.quiet {
+                println!("Removed {} cached blocks from {}", removed, dir.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A `--max-size` value: bytes, or kibi-, mebi- or gibibytes with a K, M or G suffix.
+fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let (digits, shift) = match text.char_indices().last() {
+        Some((at, 'K' | 'k')) => (&text[..at], 10),
+        Some((at, 'M' | 'm')) => (&text[..at], 20),
+        Some((at, 'G' | 'g')) => (&text[..at], 30),
+        _ => (text, 0),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| {
+            format!(
+                "invalid size {:?}: expected e.g. 1048576, 512K, 500M or 2G",
+                text
+            )
+        })
+}
+
+/// One cached block's file.
+#[derive(Debug)]
+struct CacheEntry {
+    chain_id: u64,
+    path: PathBuf,
+    bytes: u64,
+    modified: SystemTime,
+}
+
+/// The on-disk cache under one directory.
+#[derive(Debug, Clone)]
+pub struct StateCache {
+    dir: PathBuf,
+}
+
+impl StateCache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// `$HAMMER_CACHE_DIR`, else `~/.hammer/cache`.
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Some(dir) = std::env::var_os("HAMMER_CACHE_DIR") {
+            return Some(PathBuf::from(dir));
+        }
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".hammer").join("cache"))
+    }
+
+    fn path(&self, chain_id: u64, block_hash: B256) -> PathBuf {
+        self.dir
+            .join(chain_id.to_string())
+            .join(format!("{}.json", block_hash))
+    }
+
+    /// Fill `db` with what is cached for the block, and return the transactions whose
+    /// state it already holds. A missing or unreadable file is a miss.
+    pub fn load(&self, chain_id: u64, block_hash: B256, db: &mut PrewarmedDB) -> BTreeSet<B256> {
+        let path = self.path(chain_id, block_hash);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            return BTreeSet::new();
+        };
+        match serde_json::from_str(&text)
+            .map_err(eyre::Report::from)
+            .and_then(|value| restore(&value, db))
+        {
+            Ok(prewarmed) => {
+                tracing::debug!(
+                    target: "hammer::cache",
+                    block = %block_hash,
+                    accounts = db.cache.accounts.len(),
+                    "state read from cache"
+                );
+                prewarmed
+            }
+            Err(e) => {
+                tracing::warn!(
+                    target: "hammer::cache",
+                    path = %path.display(),
+                    error = %e,
+                    "ignoring unreadable cache file"
+                );
+                BTreeSet::new()
+            }
+        }
+    }
+
+    /// Write what `db` holds for the block, replacing the block's file.
+    pub fn save(
+        &self,
+        chain_id: u64,
+        block_hash: B256,
+        db: &PrewarmedDB,
+        prewarmed: &BTreeSet<B256>,
+    ) -> Result<()> {
+        let path = self.path(chain_id, block_hash);
+        let dir = path.parent().unwrap_or(&self.dir);
+        std::fs::create_dir_all(dir)
+            .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+        // Written aside and renamed, so a concurrent run never reads half a file.
+        let partial = path.with_extension(format!("json.{}", std::process::id()));
+        std::fs::write(&partial, snapshot(db, prewarmed).to_string())
+            .and_then(|()| std::fs::rename(&partial, &path))
+            .wrap_err_with(|| format!("failed to write {}", path.display()))
+    }
+
+    /// Save every block of a run. Failures are logged, not returned: the run's results
+    /// stand without the cache.
+    pub(super) fn save_blocks(&self, blocks: &BTreeMap<(u64, B256), BlockState>) {
+        for (&(chain_id, block_hash), state) in blocks {
+            if let Err(e) = self.save(chain_id, block_hash, &state.db, &state.prewarmed) {
+                tracing::warn!(target: "hammer::cache", error = %format!("{:#}", e), "state not cached");
+            }
+        }
+    }
+
+    /// Every cached block's file, oldest written first.
+    fn entries(&self) -> Vec<CacheEntry> {
+        let Ok(chains) = std::fs::read_dir(&self.dir) else {
+            return vec![];
+        };
+        let mut entries = vec![];
+        for chain in chains.flatten() {
+            let Some(chain_id) = chain.file_name().to_str().and_then(|n| n.parse().ok()) else {
+                continue;
+            };
+            let Ok(files) = std::fs::read_dir(chain.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                let path = file.path();
+                let Ok(meta) = file.metadata() else { continue };
+                if !meta.is_file() || path.extension().is_none_or(|ext| ext != "json") {
+                    continue;
+                }
+                entries.push(CacheEntry {
+                    chain_id,
+                    path,
+                    bytes: meta.len(),
+                    modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+                });
+            }
+        }
+        entries.sort_by(|a, b| (a.modified, &a.path).cmp(&(b.modified, &b.path)));
+        entries
+    }
+
+    /// Delete the oldest written blocks until the rest take at most `max_size` bytes;
+    /// returns how many were deleted, the bytes they took and the bytes left.
+    pub fn gc(&self, max_size: u64) -> Result<(usize, u64, u64)> {
+        let entries = self.entries();
+        let mut kept: u64 = entries.iter().map(|e| e.bytes).sum();
+        let (mut removed, mut freed) = (0, 0);
+        for entry in entries {
+            if kept <= max_size {
+                break;
+            }
+            std::fs::remove_file(&entry.path)
+                .wrap_err_with(|| format!("failed to remove {}", entry.path.display()))?;
+            removed += 1;
+            freed += entry.bytes;
+            kept -= entry.bytes;
+        }
+        Ok((removed, freed, kept))
+    }
+
+    /// Delete every cached block; returns how many there were.
+    pub fn clear(&self) -> Result<usize> {
+        let mut removed = 0;
+        let Ok(chains) = std::fs::read_dir(&self.dir) else {
+            return Ok(0);
+        };
+        for chain in chains.flatten() {
+            let path = chain.path();
+            if !path.is_dir() {
+                continue;
+            }
+            removed += count_files(&path);
+            std::fs::remove_dir_all(&path)
+                .wrap_err_with(|| format!("failed to remove {}", path.display()))?;
+        }
+        Ok(removed)
+    }
+}
+
+fn count_files(dir: &Path) -> usize {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                .count()
+        })
+        .unwrap_or(0)
+}
+
+/// The loaded state of `db` as JSON: each account with its code and slots, or null for
+/// one that does not exist.
+fn snapshot(db: &PrewarmedDB, prewarmed: &BTreeSet<B256>) -> Value {
+    let mut accounts = BTreeMap::new();
+    for (address, account) in &db.cache.accounts {
+        let value = match account.account_state {
+            AccountState::NotExisting => Value::Null,
+            _ => {
+                let info = &account.info;
+                let code = info
+                    .code
+                    .as_ref()
+                    .or_else(|| db.cache.contracts.get(&info.code_hash))
+                    .map(|code| code.original_bytes())
+                    .unwrap_or_default();
+                let storage: BTreeMap<&U256, &U256> = account.storage.iter().collect();
+                json!({
+                    "balance": info.balance,
+                    "nonce": info.nonce,
+                    "code": code,
+                    "storage": storage,
+                })
+            }
+        };
+        accounts.insert(*address, value);
+    }
+    let block_hashes: BTreeMap<&U256, &B256> = db.cache.block_hashes.iter().collect();
+    json!({
+        "version": VERSION,
+        "prewarmed": prewarmed,
+        "accounts": accounts,
+        "block_hashes": block_hashes,
+    })
+}
+
+/// Insert a [`snapshot`] into `db`'s cache, returning its pre-warmed transactions.
+fn restore(value: &Value, db: &mut PrewarmedDB) -> Result<BTreeSet<B256>> {
+    if value.get("version").and_then(Value::as_u64) != Some(VERSION) {
+        eyre::bail!("cache file version is not {}", VERSION);
+    }
+    let field = |name: &str| value.get(name).cloned().unwrap_or(Value::Null);
+    let prewarmed: BTreeSet<B256> = serde_json::from_value(field("prewarmed"))?;
+    let accounts: BTreeMap<Address, Option<Map<String, Value>>> =
+        serde_json::from_value(field("accounts"))?;
+    let block_hashes: BTreeMap<U256, B256> = serde_json::from_value(field("block_hashes"))?;
+
+    for (address, account) in accounts {
+        let Some(account) = account else {
+            db.cache
+                .accounts
+                .insert(address, DbAccount::new_not_existing());
+            continue;
+        };
+        let get = |name: &str| account.get(name).cloned().unwrap_or(Value::Null);
+        let code: Bytes = serde_json::from_value(get("code"))?;
+        let storage: BTreeMap<U256, U256> = serde_json::from_value(get("storage"))?;
+        let bytecode = if code.is_empty() {
+            Bytecode::default()
+        } else {
+            Bytecode::new_raw(code)
+        };
+        let code_hash = if bytecode.is_empty() {
+            KECCAK_EMPTY
+        } else {
+            bytecode.hash_slow()
+        };
+        if !bytecode.is_empty() {
+            db.cache.contracts.insert(code_hash, bytecode.clone());
+        }
+        db.cache.accounts.insert(
+            address,
+            DbAccount {
+                info: AccountInfo {
+                    balance: serde_json::from_value(get("balance"))?,
+                    nonce: serde_json::from_value(get("nonce"))?,
+                    code_hash,
+                    code: Some(bytecode),
+                    account_id: None,
+                },
+                account_state: AccountState::None,
+                storage: storage.into_iter().collect(),
+            },
+        );
+    }
+    db.cache.block_hashes.extend(block_hashes);
+    Ok(prewarmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_provider::{Provider, ProviderBuilder};
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1048576"), Ok(1 << 20));
+        assert_eq!(parse_size("512K"), Ok(512 << 10));
+        assert_eq!(parse_size("500m"), Ok(500 << 20));
+        assert_eq!(parse_size("2G"), Ok(2 << 30));
+        for bad in ["", "M", "1.5G", "12T", "-1"] {
+            assert!(parse_size(bad).is_err(), "{}", bad);
+        }
+    }
+
+    /// A database whose every miss would go to an endpoint that is not there.
+    fn empty_db() -> PrewarmedDB {
+        let provider = ProviderBuilder::new()
+            .disable_recommended_fillers()
+            .connect_http("http://127.0.0.1:1".parse().unwrap())
+            .erased();
+        super::super::prefetch::empty(provider, B256::ZERO.into()).unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_snapshot_round_trip() {
+        let mut db = empty_db();
+        let contract = Address::with_last_byte(0x40);
+        db.insert_account_info(
+            contract,
+            AccountInfo {
+                balance: U256::from(7),
+                nonce: 1,
+                code: Some(Bytecode::new_raw(Bytes::from_static(&[0x60, 0x00, 0x54]))),
+                ..Default::default()
+            },
+        );
+        db.insert_account_storage(contract, U256::from(1), U256::from(42))
+            .unwrap();
+        db.cache
+            .accounts
+            .insert(Address::with_last_byte(0x41), DbAccount::new_not_existing());
+        db.cache
+            .block_hashes
+            .insert(U256::from(9), B256::with_last_byte(9));
+        let prewarmed = BTreeSet::from([B256::with_last_byte(1)]);
+
+        let saved = snapshot(&db, &prewarmed);
+        let mut fresh = empty_db();
+        assert_eq!(restore(&saved, &mut fresh).unwrap(), prewarmed);
+        assert_eq!(snapshot(&fresh, &prewarmed), saved);
+        let account = &fresh.cache.accounts[&contract];
+        assert_eq!(account.storage[&U256::from(1)], U256::from(42));
+        assert_eq!(
+            account.info.code_hash,
+            db.cache.accounts[&contract].info.code_hash
+        );
+
+        let mut stale = saved;
+        stale["version"] = json!(VERSION + 1);
+        assert!(restore(&stale, &mut empty_db()).is_err());
+    }
+}
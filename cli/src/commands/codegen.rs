@@ -0,0 +1,230 @@
+use alloy_primitives::{Address, B256};
+use alloy_rpc_types_eth::AccessList;
+use clap::{Args, ValueEnum};
+use eyre::{Context, Result};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use super::layout;
+
+/// Slot labels from `--storage-layout`, by contract then slot.
+type Labels = BTreeMap<Address, BTreeMap<B256, Option<String>>>;
+
+#[derive(Args)]
+pub struct CodegenArgs {
+    /// Access list JSON, e.g. the output of `hammer generate`
+    #[arg(long)]
+    pub access_list: PathBuf,
+    #[arg(long, value_enum, default_value = "solidity")]
+    pub lang: Lang,
+    /// Name of the generated library, constant or local variable
+    /// [default: HammerAccessList for solidity, accessList otherwise]
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Solc storage layout for a contract, as ADDRESS=PATH (repeatable); labels slots in comments
+    #[arg(long = "storage-layout", visible_alias = "labels")]
+    pub storage_layouts: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Lang {
+    /// Solidity library returning the list, for Foundry tests and contracts
+    Solidity,
+    /// TypeScript `as const` array, for frontends and SDKs
+    Typescript,
+    /// Forge test snippet that applies the list with the `vm.accessList` cheatcode
+    Forge,
+}
+
+/// Print source code that embeds the access list, so teams can ship a validated list
+/// without transcribing it by hand.
+pub fn run(args: CodegenArgs) -> Result<()> {
+    let list: AccessList = serde_json::from_str(
+        &std::fs::read_to_string(&args.access_list)
+            .wrap_err_with(|| format!("failed to read {}", args.access_list.display()))?,
+    )
+    .wrap_err_with(|| format!("invalid access list in {}", args.access_list.display()))?;
+    let layouts = layout::load(&args.storage_layouts)?;
+    let labels = layout::labels(&layouts, &[&list], &[]);
+
+    let source = match args.lang {
+        Lang::Solidity => solidity(
+            &list,
+            args.name.as_deref().unwrap_or("HammerAccessList"),
+            &labels,
+        ),
+        Lang::Typescript => {
+            typescript(&list, args.name.as_deref().unwrap_or("accessList"), &labels)
+        }
+        Lang::Forge => forge(&list, args.name.as_deref().unwrap_or("accessList"), &labels),
+    };
+    print!("{}", source);
+    Ok(())
+}
+
+fn label<'a>(labels: &'a Labels, address: &Address, slot: &B256) -> Option<&'a str> {
+    labels.get(address)?.get(slot)?.as_deref()
+}
+
+fn solidity(list: &AccessList, name: &str, labels: &Labels) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// SPDX-License-Identifier: MIT");
+    let _ = writeln!(out, "pragma solidity ^0.8.0;");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "/// Access list generated by hammer.");
+    let _ = writeln!(out, "library {} {{", name);
+    let _ = writeln!(out, "    struct Entry {{");
+    let _ = writeln!(out, "        address target;");
+    let _ = writeln!(out, "        bytes32[] storageKeys;");
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out);
+    let _ = writeln!(
+        out,
+        "    function entries() internal pure returns (Entry[] memory list) {{"
+    );
+    let _ = writeln!(out, "        list = new Entry[]({});", list.0.len());
+    for (i, item) in list.0.iter().enumerate() {
+        let _ = writeln!(out, "        list[{}].target = {};", i, item.address);
+        let _ = writeln!(
+            out,
+            "        list[{}].storageKeys = new bytes32[]({});",
+            i,
+            item.storage_keys.len()
+        );
+        for (k, key) in item.storage_keys.iter().enumerate() {
+            let _ = write!(out, "        list[{}].storageKeys[{}] = {};", i, k, key);
+            if let Some(label) = label(labels, &item.address, key) {
+                let _ = write!(out, " // {}", label);
+            }
+            let _ = writeln!(out);
+        }
+    }
+    let _ = writeln!(out, "    }}");
+    let _ = writeln!(out, "}}");
+    out
+}
+
+fn typescript(list: &AccessList, name: &str, labels: &Labels) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// Access list generated by hammer.");
+    let _ = writeln!(out, "export const {} = [", name);
+    for item in &list.0 {
+        let _ = writeln!(out, "  {{");
+        let _ = writeln!(out, "    address: \"{}\",", item.address);
+        let _ = writeln!(out, "    storageKeys: [");
+        for key in &item.storage_keys {
+            let _ = write!(out, "      \"{}\",", key);
+            if let Some(label) = label(labels, &item.address, key) {
+                let _ = write!(out, " // {}", label);
+            }
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(out, "    ],");
+        let _ = writeln!(out, "  }},");
+    }
+    let _ = writeln!(out, "] as const;");
+    out
+}
+
+/// Statements for a forge test body. `Vm.AccessListItem` is forge-std's
+/// `{ address target; bytes32[] storageKeys; }`; the cheatcode applies the list to the
+/// next call made by the test.
+fn forge(list: &AccessList, name: &str, labels: &Labels) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "// Access list generated by hammer; applies to the next call."
+    );
+    let _ = writeln!(
+        out,
+        "Vm.AccessListItem[] memory {} = new Vm.AccessListItem[]({});",
+        name,
+        list.0.len()
+    );
+    for (i, item) in list.0.iter().enumerate() {
+        let keys = format!("{}Keys{}", name, i);
+        let _ = writeln!(
+            out,
+            "bytes32[] memory {} = new bytes32[]({});",
+            keys,
+            item.storage_keys.len()
+        );
+        for (k, key) in item.storage_keys.iter().enumerate() {
+            let _ = write!(out, "{}[{}] = {};", keys, k, key);
+            if let Some(label) = label(labels, &item.address, key) {
+                let _ = write!(out, " // {}", label);
+            }
+            let _ = writeln!(out);
+        }
+        let _ = writeln!(
+            out,
+            "{}[{}] = Vm.AccessListItem({{target: {}, storageKeys: {}}});",
+            name, i, item.address, keys
+        );
+    }
+    let _ = writeln!(out, "vm.accessList({});", name);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use alloy_rpc_types_eth::AccessListItem;
+
+    fn sample() -> (AccessList, Labels) {
+        let token = address!("A0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48");
+        let slot = B256::with_last_byte(3);
+        let list = AccessList(vec![AccessListItem {
+            address: token,
+            storage_keys: vec![slot],
+        }]);
+        let labels = BTreeMap::from([(
+            token,
+            BTreeMap::from([(slot, Some("totalSupply".to_string()))]),
+        )]);
+        (list, labels)
+    }
+
+    #[test]
+    fn test_solidity_uses_checksummed_literals_and_labels() {
+        let (list, labels) = sample();
+        let src = solidity(&list, "SwapList", &labels);
+        assert!(src.contains("library SwapList {"));
+        assert!(src.contains("list = new Entry[](1);"));
+        assert!(src.contains("list[0].target = 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48;"));
+        assert!(src.contains(
+            "list[0].storageKeys[0] = 0x0000000000000000000000000000000000000000000000000000000000000003; // totalSupply"
+        ));
+    }
+
+    #[test]
+    fn test_typescript_const_array() {
+        let (list, labels) = sample();
+        let src = typescript(&list, "swapList", &labels);
+        assert!(src.starts_with("// Access list generated by hammer.\nexport const swapList = ["));
+        assert!(src.contains("address: \"0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48\","));
+        assert!(src.contains("// totalSupply"));
+        assert!(src.trim_end().ends_with("] as const;"));
+    }
+
+    #[test]
+    fn test_forge_cheatcode_snippet() {
+        let (list, labels) = sample();
+        let src = forge(&list, "al", &labels);
+        assert!(src.contains("Vm.AccessListItem[] memory al = new Vm.AccessListItem[](1);"));
+        assert!(src.contains("bytes32[] memory alKeys0 = new bytes32[](1);"));
+        assert!(src.contains("// totalSupply"));
+        assert!(src.contains(
+            "al[0] = Vm.AccessListItem({target: 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48, storageKeys: alKeys0});"
+        ));
+        assert!(src.trim_end().ends_with("vm.accessList(al);"));
+    }
+
+    #[test]
+    fn test_empty_list() {
+        let src = solidity(&AccessList::default(), "Empty", &Labels::new());
+        assert!(src.contains("list = new Entry[](0);"));
+    }
+}
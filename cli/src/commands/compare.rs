@@ -1,43 +1,422 @@
+use alloy::network::Ethereum;
 use alloy_eips::BlockId;
-use alloy_primitives::{Address, U256};
-use alloy_provider::Provider;
-use alloy_rpc_types_eth::{TransactionRequest, TransactionTrait};
+use alloy_primitives::{Address, B256};
+use alloy_provider::{DynProvider, Provider};
+use alloy_rpc_types_eth::{AccessList, Header, Transaction, TransactionRequest, TransactionTrait};
 use clap::Args;
 use eyre::{Context, Result};
-use hammer_core::validate_replay;
+use hammer_core::{
+    foreign_addresses, validate_replay_at_spec_with, DiffEntry, ForeignAddress, ListOrigin,
+    ListScore, OptimizerConfig, Refund, ValidationReport,
+};
 use reqwest::Url;
 use revm::context::{BlockEnv, TxEnv};
+use revm::database::Database;
+use revm::primitives::hardfork::SpecId;
+use revm::primitives::map::Entry as HashEntry;
 use revm::primitives::TxKind;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
 
-use super::util::{assert_not_blob, assert_not_create, assert_post_berlin};
+use super::attest;
+use super::cache::StateCache;
+use super::engine;
+use super::global::{parse_chain, GlobalArgs};
+use super::prefetch::PrewarmedDB;
+use super::units::Units;
+use super::util::{
+    apply_chain_rules, assert_not_blob, assert_post_berlin, block_env_from_header, fork_at,
+    print_budget, print_new_accounts, print_precompile_calls, traced,
+};
+use super::{html, layout, seen};
 
 #[derive(Args)]
 pub struct CompareArgs {
-    #[arg(long, default_value = "https://eth.llamarpc.com")]
-    pub rpc_url: String,
+    /// Transaction hash to compare, as HASH or CHAIN:HASH (repeatable)
+    #[arg(long = "tx-hash", required_unless_present = "tx_hashes")]
+    pub tx_hash: Vec<String>,
+    /// File of transaction hashes, one HASH or CHAIN:HASH per line (blank lines and `#`
+    /// comments ignored)
     #[arg(long)]
-    pub tx_hash: String,
+    pub tx_hashes: Option<PathBuf>,
+    /// RPC endpoint for transactions tagged with CHAIN (a name or id), as CHAIN=URL
+    /// (repeatable); tagged transactions without one use --rpc-url
+    #[arg(long = "chain-rpc", value_name = "CHAIN=URL")]
+    pub chain_rpcs: Vec<String>,
+    /// Solc storage layout for a contract, as ADDRESS=PATH (repeatable); labels slots in human output
+    #[arg(long = "storage-layout", visible_alias = "labels")]
+    pub storage_layouts: Vec<String>,
+    /// Net the savings against the refund cap of the fork the transaction was mined in
+    /// (half the gas spent before London, a fifth after)
+    #[arg(long)]
+    pub historical_refunds: bool,
+    /// Flag stale entries with no code on this chain: a list likely copied from another
+    /// chain's deployment
+    #[arg(long)]
+    pub check_foreign: bool,
+    /// Also replay the transaction at BLOCK, where it was first seen in the mempool, and
+    /// split the gas its list lost into latency and generation
+    #[arg(long, value_name = "BLOCK")]
+    pub as_of_seen: Option<u64>,
+}
+
+/// Chain whose fork blocks `assert_post_berlin` and `fork_spec` encode.
+const MAINNET_CHAIN_ID: u64 = 1;
+
+/// One transaction to compare, with the chain its input tagged it with, if any.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Job {
+    chain: Option<u64>,
+    tx_hash: B256,
+}
+
+/// One compared transaction, with what the output needs besides the report.
+pub(super) struct Comparison {
+    pub(super) chain_id: u64,
+    pub(super) tx_hash: B256,
+    pub(super) block_number: u64,
+    from: Address,
+    /// The call target, or the address a creation deploys to.
+    to: Address,
+    coinbase: Address,
+    pub(super) declared: AccessList,
+    pub(super) report: ValidationReport,
+    /// How the refund was paid under the block's fork, with `--historical-refunds`.
+    refund: Option<Refund>,
+    /// Stale entries with no code on this chain, with `--check-foreign`.
+    foreign: Option<Vec<ForeignAddress>>,
+    /// The spec the transaction was replayed under.
+    spec: SpecId,
+}
+
+/// Block-level state shared by every compared transaction mined in that block.
+pub(super) struct BlockState {
+    pub(super) block_number: u64,
+    pub(super) block_env: BlockEnv,
+    pub(super) db: PrewarmedDB,
+    /// Transactions whose state `db` already holds, pre-warmed in this run or read back
+    /// from the [`StateCache`].
+    pub(super) prewarmed: BTreeSet<B256>,
+}
+
+impl BlockState {
+    /// An empty cache over the state `header`'s block starts from.
+    pub(super) fn at(provider: &DynProvider<Ethereum>, header: &Header) -> Result<Self> {
+        Ok(Self {
+            block_number: header.number,
+            block_env: block_env_from_header(header),
+            db: super::prefetch::empty(provider.clone(), BlockId::hash(header.hash))?,
+            prewarmed: BTreeSet::new(),
+        })
+    }
+
+    /// Add what `other`, state of the same block, holds and this does not.
+    pub(super) fn absorb(&mut self, other: BlockState) {
+        let cache = &mut self.db.cache;
+        for (address, account) in other.db.cache.accounts {
+            match cache.accounts.entry(address) {
+                HashEntry::Vacant(entry) => {
+                    entry.insert(account);
+                }
+                HashEntry::Occupied(mut entry) => {
+                    for (slot, value) in account.storage {
+                        entry.get_mut().storage.entry(slot).or_insert(value);
+                    }
+                }
+            }
+        }
+        for (hash, code) in other.db.cache.contracts {
+            cache.contracts.entry(hash).or_insert(code);
+        }
+        for (number, hash) in other.db.cache.block_hashes {
+            cache.block_hashes.entry(number).or_insert(hash);
+        }
+        self.prewarmed.extend(other.prewarmed);
+    }
 }
 
 /// Run the compare command.
 ///
+/// With several hashes, the provider and per-block state are shared: transactions mined
+/// in the same block reuse one block fetch and one pre-warmed database. Hashes tagged
+/// with a chain are compared against that chain's endpoint, one connection per chain. A
+/// failing transaction is reported and skipped; the command fails at the end if any did.
+///
 /// # Test boundary
 ///
 /// This function requires a live RPC connection and cannot be unit tested in isolation.
-/// Its guard logic (`assert_not_create`, `assert_not_blob`, `assert_post_berlin`) is
-/// covered by unit tests in `cli::commands::util`. The diffing and report formatting
-/// delegates entirely to `validate_replay()` + `ValidationReport`, which are covered
-/// exhaustively in `hammer_core::validator` tests. End-to-end behaviour is verified
+/// Its guard logic (`assert_not_blob`, `assert_post_berlin`) is covered by unit tests
+/// in `cli::commands::util`. The diffing and report formatting delegates entirely to
+/// `validate_replay()` + `ValidationReport`, which are covered exhaustively in
+/// `hammer_core::validator` tests. End-to-end behaviour is verified
 /// by the CLI integration tests in `cli/tests/cli_test.rs` (error-path only, no RPC).
-pub async fn run(args: CompareArgs) -> Result<()> {
-    let tx_hash = args.tx_hash.parse().wrap_err("invalid tx hash")?;
+pub async fn run(args: CompareArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human", "markdown", "html"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let jobs = collect_jobs(&args.tx_hash, args.tx_hashes.as_deref())?;
+    let chain_rpcs = parse_chain_rpcs(&args.chain_rpcs)?;
+    let layouts = layout::load(&args.storage_layouts)?;
+    if args.as_of_seen.is_some() {
+        if jobs.len() > 1 {
+            eyre::bail!("--as-of-seen takes a single transaction");
+        }
+        if !matches!(output, "json" | "human") {
+            eyre::bail!("--as-of-seen supports --output json or human");
+        }
+    }
+
+    let mut timer = global.timer();
+    let providers = connect(global, &jobs, &chain_rpcs).await?;
+
+    let mut blocks: BTreeMap<(u64, B256), BlockState> = BTreeMap::new();
+    let cache = global.state_cache();
+
+    if let [job] = jobs.as_slice() {
+        let (provider, chain_id) = &providers[&job.chain];
+        let comparison = compare_one(
+            provider,
+            *chain_id,
+            job.tx_hash,
+            &mut blocks,
+            args.historical_refunds,
+            args.check_foreign,
+            cache.as_ref(),
+        )
+        .await?;
+        if let Some(cache) = &cache {
+            cache.save_blocks(&blocks);
+        }
+        timer.phase("compare");
+        let as_of_seen = match args.as_of_seen {
+            Some(block) => {
+                let replayed = seen::replay(provider, &comparison, block).await?;
+                timer.phase("as_of_seen");
+                Some(replayed)
+            }
+            None => None,
+        };
+        timer.total();
+        match output {
+            "json" => {
+                let mut value = to_json(&comparison)?;
+                if let (Some(replayed), Some(fields)) = (&as_of_seen, value.as_object_mut()) {
+                    fields.insert("as_of_seen".into(), seen::to_json(replayed));
+                }
+                attest::write_report(value, signer.as_ref(), &sink).await?
+            }
+            "markdown" => print_markdown(&comparison),
+            "html" => print!("{}", html::render(std::slice::from_ref(&comparison), 0)),
+            "human" => {
+                print_comparison(&comparison, &layouts, &units, global.quiet);
+                if let Some(replayed) = &as_of_seen {
+                    seen::print(replayed, &units);
+                }
+            }
+            _ => unreachable!(),
+        }
+        return Ok(());
+    }
+
+    let human = output == "human";
+    let mut compared = Vec::new();
+    let mut failed = 0usize;
+    for job in &jobs {
+        let (provider, chain_id) = &providers[&job.chain];
+        if human {
+            println!("== {} (chain {}) ==", job.tx_hash, chain_id);
+        }
+        let result = compare_one(
+            provider,
+            *chain_id,
+            job.tx_hash,
+            &mut blocks,
+            args.historical_refunds,
+            args.check_foreign,
+            cache.as_ref(),
+        )
+        .await;
+        timer.phase(&format!("{:.10}", job.tx_hash.to_string()));
+        match result {
+            Ok(comparison) => {
+                match output {
+                    "markdown" => print_markdown(&comparison),
+                    "human" => print_comparison(&comparison, &layouts, &units, global.quiet),
+                    _ => {}
+                }
+                compared.push(comparison);
+            }
+            Err(e) => {
+                failed += 1;
+                // Machine-readable output keeps stdout parseable; failures go to stderr.
+                if human {
+                    println!("Error: {:#}", e);
+                } else {
+                    eprintln!("{}: {:#}", job.tx_hash, e);
+                }
+            }
+        }
+        if matches!(output, "human" | "markdown") {
+            println!();
+        }
+    }
+    if let Some(cache) = &cache {
+        cache.save_blocks(&blocks);
+    }
+    timer.total();
+
+    match output {
+        "json" => {
+            let reports = compared.iter().map(to_json).collect::<Result<Vec<_>>>()?;
+            attest::write_report(reports.into(), signer.as_ref(), &sink).await?;
+        }
+        "markdown" => print_markdown_summary(&compared, failed),
+        "html" => print!("{}", html::render(&compared, failed)),
+        "human" => print_batch_summary(&compared, failed, &units),
+        _ => unreachable!(),
+    }
+    if failed > 0 {
+        eyre::bail!(
+            "{} of {} transactions could not be compared",
+            failed,
+            jobs.len()
+        );
+    }
+    Ok(())
+}
+
+/// Merge `--tx-hash` values with the `--tx-hashes` file, parsing all of them before any
+/// network call. Duplicates (same chain tag and hash) are dropped, keeping the first.
+fn collect_jobs(inline: &[String], file: Option<&Path>) -> Result<Vec<Job>> {
+    let mut raw: Vec<String> = inline.to_vec();
+    if let Some(path) = file {
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        raw.extend(
+            text.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    if raw.is_empty() {
+        eyre::bail!("no transaction hashes given");
+    }
+
+    let mut jobs: Vec<Job> = Vec::new();
+    for s in &raw {
+        let (chain, hash) = match s.split_once(':') {
+            Some((chain, hash)) => (Some(parse_chain(chain.trim())?), hash.trim()),
+            None => (None, s.as_str()),
+        };
+        let tx_hash: B256 = hash
+            .parse()
+            .wrap_err_with(|| format!("invalid tx hash {}", s))?;
+        let job = Job { chain, tx_hash };
+        if !jobs.contains(&job) {
+            jobs.push(job);
+        }
+    }
+    Ok(jobs)
+}
 
-    let url = Url::parse(&args.rpc_url).wrap_err("invalid RPC URL")?;
-    let provider = alloy_provider::ProviderBuilder::new()
-        .disable_recommended_fillers()
-        .connect_http(url)
-        .erased();
+/// `--chain-rpc CHAIN=URL` values by chain id.
+fn parse_chain_rpcs(values: &[String]) -> Result<BTreeMap<u64, String>> {
+    values
+        .iter()
+        .map(|value| {
+            let (chain, url) = value
+                .split_once('=')
+                .ok_or_else(|| eyre::eyre!("invalid --chain-rpc {}: expected CHAIN=URL", value))?;
+            Url::parse(url).wrap_err_with(|| format!("invalid --chain-rpc URL {}", url))?;
+            Ok((parse_chain(chain)?, url.to_string()))
+        })
+        .collect()
+}
+
+/// One provider per chain the jobs name, with its chain id. Untagged jobs use the global
+/// endpoint; a tagged one uses its `--chain-rpc`, else `--rpc-url`, checked to be on
+/// that chain.
+async fn connect(
+    global: &GlobalArgs,
+    jobs: &[Job],
+    chain_rpcs: &BTreeMap<u64, String>,
+) -> Result<BTreeMap<Option<u64>, (DynProvider<Ethereum>, u64)>> {
+    let mut providers = BTreeMap::new();
+    for job in jobs {
+        if providers.contains_key(&job.chain) {
+            continue;
+        }
+        let connected = match job.chain {
+            None => {
+                let provider = global.provider().await?;
+                let chain_id = match global.chain_id()? {
+                    Some(id) => id,
+                    None => provider
+                        .get_chain_id()
+                        .await
+                        .wrap_err("failed to fetch chain id")?,
+                };
+                (provider, chain_id)
+            }
+            Some(id) => {
+                let scoped = GlobalArgs {
+                    chain: Some(id.to_string()),
+                    rpc_url: chain_rpcs.get(&id).or(global.rpc_url.as_ref()).cloned(),
+                    ..global.clone()
+                };
+                let provider = scoped.provider().await.wrap_err_with(|| {
+                    format!("chain {}: pass --chain-rpc {}=URL for its endpoint", id, id)
+                })?;
+                (provider, id)
+            }
+        };
+        providers.insert(job.chain, connected);
+    }
+    Ok(providers)
+}
 
+#[tracing::instrument(name = "compare", skip_all, fields(tx = %tx_hash))]
+pub(super) async fn compare_one(
+    provider: &DynProvider<Ethereum>,
+    chain_id: u64,
+    tx_hash: B256,
+    blocks: &mut BTreeMap<(u64, B256), BlockState>,
+    historical_refunds: bool,
+    check_foreign: bool,
+    cache: Option<&StateCache>,
+) -> Result<Comparison> {
+    let prepared = prepare_one(provider, chain_id, tx_hash, blocks, cache).await?;
+    let block_state = blocks
+        .get_mut(&(chain_id, prepared.block_hash))
+        .expect("prepare_one caches the block's state");
+    prepared.compare(block_state, chain_id, historical_refunds, check_foreign)
+}
+
+/// A mined transaction fetched, checked and with its state pre-warmed into its block's
+/// [`BlockState`], ready to replay.
+pub(super) struct Prepared {
+    pub(super) tx_hash: B256,
+    pub(super) block_hash: B256,
+    from: Address,
+    /// The call target, or the address a creation deploys to.
+    to: Address,
+    pub(super) declared: AccessList,
+    tx_env: TxEnv,
+}
+
+/// Fetch `tx_hash`, reject what `compare` cannot replay, and pre-warm its block's state
+/// in `blocks` (fetching the block the first time one of its transactions is seen, and
+/// reading what `cache` holds for it).
+pub(super) async fn prepare_one(
+    provider: &DynProvider<Ethereum>,
+    chain_id: u64,
+    tx_hash: B256,
+    blocks: &mut BTreeMap<(u64, B256), BlockState>,
+    cache: Option<&StateCache>,
+) -> Result<Prepared> {
     // Fetch tx and receipt in parallel — both need only the tx hash.
     let (tx, receipt) = tokio::try_join!(
         async {
@@ -54,13 +433,10 @@ pub async fn run(args: CompareArgs) -> Result<()> {
         },
     )?;
 
-    // Guard 1: Reject contract creation transactions
-    assert_not_create(tx.inner.to())?;
-
-    // Guard 2: Reject blob transactions (EIP-4844, Type 3)
+    // Guard 1: Reject blob transactions (EIP-4844, Type 3)
     assert_not_blob(tx.inner.blob_versioned_hashes())?;
 
-    // Guard 4: Reject reverted transactions
+    // Guard 3: Reject reverted transactions
     if !receipt.status() {
         eyre::bail!("transaction reverted on-chain — access list comparison is not meaningful for failed transactions");
     }
@@ -68,46 +444,145 @@ pub async fn run(args: CompareArgs) -> Result<()> {
     let block_hash = tx
         .block_hash
         .ok_or_else(|| eyre::eyre!("Transaction not mined"))?;
-    let block = provider
-        .get_block_by_hash(block_hash)
-        .await?
-        .ok_or_else(|| eyre::eyre!("Block not found"))?;
-
-    let header = &block.header;
-    // Guard 3: Reject pre-Berlin blocks
-    assert_post_berlin(header.number)?;
-    let block_env = BlockEnv {
-        number: U256::from(header.number),
-        beneficiary: header.beneficiary,
-        timestamp: U256::from(header.timestamp),
-        gas_limit: header.gas_limit,
-        basefee: header.base_fee_per_gas.unwrap_or(0),
-        difficulty: header.difficulty,
-        prevrandao: Some(header.mix_hash),
-        blob_excess_gas_and_price: header.excess_blob_gas.map(|excess| {
-            revm::context_interface::block::BlobExcessGasAndPrice::new(
-                excess,
-                revm::primitives::eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
-            )
-        }),
+    // Pre-warm the database: fetch all storage/account state in parallel before
+    // revm runs, eliminating sequential AlloyDB RPC calls during EVM execution.
+    let state_block_id = BlockId::hash(block_hash);
+    let block_state = match blocks.entry((chain_id, block_hash)) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => {
+            let block = provider
+                .get_block_by_hash(block_hash)
+                .await?
+                .ok_or_else(|| eyre::eyre!("Block not found"))?;
+
+            let header = &block.header;
+            // Guard 2: Reject pre-Berlin blocks. Other chains launched with or
+            // after Berlin, or number their blocks unrelated to mainnet's forks.
+            if chain_id == MAINNET_CHAIN_ID {
+                assert_post_berlin(header.number)?;
+            }
+            let mut state = BlockState::at(provider, header)?;
+            if let Some(cache) = cache {
+                state.prewarmed = cache.load(chain_id, block_hash, &mut state.db);
+            }
+            entry.insert(state)
+        }
     };
 
     let from = tx.inner.signer();
-    let to = tx.inner.to().unwrap_or(Address::ZERO);
+    // A creation runs its constructor at the address it deploys to, warm like a call target.
+    let to = match tx.inner.kind() {
+        TxKind::Call(to) => to,
+        TxKind::Create => from.create(tx.inner.nonce()),
+    };
+    let declared = tx.inner.access_list().cloned().unwrap_or_default();
+    let (tx_env, tx_req) = replay_env(&tx, &block_state.block_env);
+
+    // A transaction replayed before against this state needs nothing fetched.
+    if block_state.prewarmed.insert(tx_hash) {
+        super::prefetch::prewarm(
+            &mut block_state.db,
+            provider.clone(),
+            state_block_id,
+            state_block_id,
+            tx_req,
+            &declared,
+        )
+        .await;
+    }
+
+    Ok(Prepared {
+        tx_hash,
+        block_hash,
+        from,
+        to,
+        declared,
+        tx_env,
+    })
+}
+
+impl Prepared {
+    /// Replay over the block's state and compare the declared list with the optimum.
+    pub(super) fn compare(
+        self,
+        block_state: &mut BlockState,
+        chain_id: u64,
+        historical_refunds: bool,
+        check_foreign: bool,
+    ) -> Result<Comparison> {
+        let (report, refund) = self.replay(
+            &mut block_state.db,
+            block_state.block_number,
+            &block_state.block_env,
+            chain_id,
+            historical_refunds,
+        )?;
+        let foreign = if check_foreign {
+            Some(
+                foreign_addresses(&mut block_state.db, &report)
+                    .wrap_err("failed to look up stale addresses")?,
+            )
+        } else {
+            None
+        };
+
+        Ok(Comparison {
+            chain_id,
+            tx_hash: self.tx_hash,
+            block_number: block_state.block_number,
+            from: self.from,
+            to: self.to,
+            coinbase: block_state.block_env.beneficiary,
+            declared: self.declared,
+            report,
+            refund,
+            foreign,
+            spec: replay_spec(chain_id, block_state.block_number),
+        })
+    }
+
+    /// Replay over `db` in block `block_number` under the rules of the fork the block ran
+    /// under (see [`replay_spec`]), skipping the nonce check. With `historical_refunds`,
+    /// also return the refund to net savings against.
+    pub(super) fn replay<DB>(
+        &self,
+        db: DB,
+        block_number: u64,
+        block_env: &BlockEnv,
+        chain_id: u64,
+        historical_refunds: bool,
+    ) -> Result<(ValidationReport, Option<Refund>)>
+    where
+        DB: Database,
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        let tx_env = self.tx_env.clone();
+        let block_env = block_env.clone();
+        let declared = self.declared.clone();
+        let mut config = OptimizerConfig::default();
+        apply_chain_rules(&mut config, Some(chain_id), block_number);
+        let spec = replay_spec(chain_id, block_number);
+        traced(block_number, || {
+            validate_replay_at_spec_with(db, tx_env, block_env, declared, spec, &config)
+                .map(|(report, refund)| (report, historical_refunds.then_some(refund)))
+        })
+        .wrap_err("validation failed")
+    }
+}
+
+/// The mined transaction as revm runs it over `block_env`, and as the prefetch hint
+/// (`eth_createAccessList`) sends it.
+pub(super) fn replay_env(tx: &Transaction, block_env: &BlockEnv) -> (TxEnv, TransactionRequest) {
+    let from = tx.inner.signer();
+    let kind = tx.inner.kind();
     let value = tx.inner.value();
     let data = tx.inner.input().clone();
-    let declared = tx
-        .inner
-        .access_list()
-        .cloned()
-        .unwrap_or_else(|| alloy_rpc_types_eth::AccessList::default());
-
     let basefee = block_env.basefee as u128;
     let gas_price = tx.inner.max_fee_per_gas().max(basefee);
     let mut builder = TxEnv::builder()
         .caller(from)
         .nonce(tx.inner.nonce())
-        .kind(TxKind::Call(to))
+        .kind(kind)
         .gas_limit(tx.inner.gas_limit())
         .gas_price(gas_price)
         .value(value)
@@ -116,72 +591,488 @@ pub async fn run(args: CompareArgs) -> Result<()> {
     if let Some(priority) = tx.inner.max_priority_fee_per_gas() {
         builder = builder.gas_priority_fee(Some(priority));
     }
+    // EIP-7702: each authorization delegates its authority's code and warms the authority
+    // before execution.
+    let authorization_list = tx.inner.authorization_list().map(<[_]>::to_vec);
+    if let Some(authorizations) = &authorization_list {
+        builder = builder.authorization_list_signed(authorizations.clone());
+    }
 
     let tx_env = builder.build().unwrap();
-
-    // Build a TransactionRequest for the prefetch hint (eth_createAccessList).
     let tx_req = TransactionRequest {
         from: Some(from),
-        to: Some(TxKind::Call(to)),
+        to: Some(kind),
         value: Some(value),
         input: alloy_rpc_types_eth::TransactionInput::new(data),
         gas: Some(tx.inner.gas_limit()),
+        authorization_list,
         ..Default::default()
     };
+    (tx_env, tx_req)
+}
 
-    // Pre-warm the database: fetch all storage/account state in parallel before
-    // revm runs, eliminating sequential AlloyDB RPC calls during EVM execution.
-    let state_block_id = BlockId::hash(block_hash);
-    let db = super::prefetch::build(provider, state_block_id, state_block_id, tx_req, &declared)
-        .await
-        .wrap_err("prefetch failed")?;
-
-    let report = validate_replay(db, tx_env, block_env, declared).wrap_err("validation failed")?;
-
+fn print_comparison(
+    comparison: &Comparison,
+    layouts: &layout::Layouts,
+    units: &Units,
+    quiet: bool,
+) {
+    let report = &comparison.report;
     let s = &report.gas_summary;
-    let sign = if s.waste_per_tx >= 0 { "+" } else { "-" };
     println!(
-        "List cost:  {} gas declared  →  {} gas optimal  ({}{}  upfront)",
-        s.declared_list_cost,
-        s.optimal_list_cost,
-        sign,
-        s.waste_per_tx.unsigned_abs(),
+        "List cost:  {} declared  →  {} optimal  ({}  upfront)",
+        units.gas(s.declared_list_cost),
+        units.gas(s.optimal_list_cost),
+        units.signed(s.waste_per_tx),
     );
+    println!("Origin:     {}", report.declared_origin.as_str());
 
-    let execution_penalty: u64 = report
-        .entries
-        .iter()
-        .filter(|e| {
-            matches!(
-                e,
-                hammer_core::types::DiffEntry::Missing { .. }
-                    | hammer_core::types::DiffEntry::Incomplete { .. }
-            )
-        })
-        .map(|e| e.gas_waste())
-        .sum();
+    let execution_penalty = execution_penalty(report);
     if execution_penalty > 0 {
         let missing_count = report
             .entries
             .iter()
-            .filter(|e| matches!(e, hammer_core::types::DiffEntry::Missing { .. }))
+            .filter(|e| matches!(e, DiffEntry::Missing { .. }))
             .count();
         let incomplete_count = report
             .entries
             .iter()
-            .filter(|e| matches!(e, hammer_core::types::DiffEntry::Incomplete { .. }))
+            .filter(|e| matches!(e, DiffEntry::Incomplete { .. }))
             .count();
         println!(
-            "Execution:  {} missing / {} incomplete  →  +{} gas at runtime",
-            missing_count, incomplete_count, execution_penalty,
+            "Execution:  {} missing / {} incomplete  →  {} at runtime",
+            missing_count,
+            incomplete_count,
+            units.signed(execution_penalty as i64),
+        );
+    }
+    if let Some(refund) = &comparison.refund {
+        let at_stake = savings_at_stake(report);
+        println!(
+            "Refunds:    {} of {} paid (cap 1/{}{})  →  {} of {} saved net",
+            units.gas(refund.paid()),
+            units.gas(refund.counter),
+            refund.quotient,
+            if refund.is_capped() { ", capped" } else { "" },
+            units.gas(refund.net_savings(at_stake)),
+            units.gas(at_stake),
         );
     }
 
+    print_budget(&report.budget_exceeded, units);
+
     if !report.is_valid {
         println!("Issues: {} entries", report.entries.len());
         for e in &report.entries {
             println!("  {:?}", e);
         }
     }
-    Ok(())
+    if let Some(foreign) = comparison.foreign.as_deref().filter(|f| !f.is_empty()) {
+        println!(
+            "Foreign: {} stale entries have no code on chain {} (list copied from another chain?)",
+            foreign.len(),
+            comparison.chain_id
+        );
+        for f in foreign {
+            let what = if f.exists { "no code" } else { "no account" };
+            println!("  {}  {}", f.address, what);
+        }
+    }
+    if !quiet {
+        print_new_accounts(&report.new_accounts, units);
+        print_precompile_calls(&report.precompile_calls);
+    }
+    layout::print_labels(
+        layouts,
+        &[&comparison.declared, &report.optimal_list],
+        &[comparison.from, comparison.to, comparison.coinbase],
+    );
+}
+
+/// The full `ValidationReport` with the compare-specific metadata alongside it.
+pub(super) fn to_json(comparison: &Comparison) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(&comparison.report)?;
+    let fields = value
+        .as_object_mut()
+        .ok_or_else(|| eyre::eyre!("report did not serialize to an object"))?;
+    fields.insert("chain_id".into(), comparison.chain_id.into());
+    fields.insert("tx_hash".into(), serde_json::to_value(comparison.tx_hash)?);
+    fields.insert(
+        "block_number".into(),
+        serde_json::to_value(comparison.block_number)?,
+    );
+    fields.insert("miner".into(), serde_json::to_value(comparison.coinbase)?);
+    fields.insert("from".into(), serde_json::to_value(comparison.from)?);
+    fields.insert("to".into(), serde_json::to_value(comparison.to)?);
+    fields.insert(
+        "declared_list".into(),
+        serde_json::to_value(&comparison.declared)?,
+    );
+    if let Some(refund) = &comparison.refund {
+        fields.insert("refund".into(), serde_json::to_value(refund)?);
+        fields.insert(
+            "net_savings".into(),
+            refund
+                .net_savings(savings_at_stake(&comparison.report))
+                .into(),
+        );
+    }
+    if let Some(foreign) = &comparison.foreign {
+        fields.insert("foreign".into(), serde_json::to_value(foreign)?);
+    }
+    engine::stamp(&mut value, comparison.spec);
+    Ok(value)
+}
+
+fn print_markdown(comparison: &Comparison) {
+    let report = &comparison.report;
+    let s = &report.gas_summary;
+    println!(
+        "### `{}` (chain {}, block {})",
+        comparison.tx_hash, comparison.chain_id, comparison.block_number
+    );
+    println!();
+    println!("| | Gas |");
+    println!("|---|---:|");
+    println!("| Declared list | {} |", s.declared_list_cost);
+    println!("| Optimal list | {} |", s.optimal_list_cost);
+    println!("| Upfront waste | {} |", s.waste_per_tx);
+    println!("| Runtime penalty | {} |", execution_penalty(report));
+    println!();
+    println!(
+        "Declared list origin: `{}`",
+        report.declared_origin.as_str()
+    );
+    println!();
+    for e in &report.budget_exceeded {
+        println!(
+            "**Budget:** the {} list needs {} gas ({} intrinsic + {} list + {} execution) of a {} limit.",
+            e.list.as_str(),
+            e.gas_limit + e.shortfall,
+            e.intrinsic_gas,
+            e.list_cost,
+            e.execution_gas,
+            e.gas_limit
+        );
+        println!();
+    }
+    if report.is_valid {
+        println!("No issues: the declared list is optimal.");
+        return;
+    }
+    println!("| Issue | Address | Slots | Gas |");
+    println!("|---|---|---:|---:|");
+    for e in &report.entries {
+        let (kind, address, slots) = issue_row(e);
+        let foreign = comparison
+            .foreign
+            .iter()
+            .flatten()
+            .any(|f| matches!(e, DiffEntry::Stale { .. }) && f.address == *address);
+        println!(
+            "| {}{} | `{}` | {} | {} |",
+            kind,
+            if foreign { " (foreign)" } else { "" },
+            address,
+            slots.len(),
+            e.gas_waste()
+        );
+    }
+}
+
+/// Kind, address and slots of a finding: the row markdown and HTML output share.
+pub(super) fn issue_row(entry: &DiffEntry) -> (&'static str, &Address, &[B256]) {
+    match entry {
+        DiffEntry::Missing {
+            address,
+            storage_keys,
+            ..
+        } => ("missing", address, storage_keys),
+        DiffEntry::Stale {
+            address,
+            storage_keys,
+            ..
+        } => ("stale", address, storage_keys),
+        DiffEntry::Incomplete {
+            address,
+            missing_slots,
+            ..
+        } => ("incomplete", address, missing_slots),
+        DiffEntry::Redundant { address, .. } => ("redundant", address, &[]),
+        DiffEntry::Duplicate {
+            address,
+            storage_key,
+            ..
+        } => ("duplicate", address, std::slice::from_ref(storage_key)),
+    }
+}
+
+fn print_markdown_summary(compared: &[Comparison], failed: usize) {
+    println!("### Summary");
+    println!();
+    println!(
+        "| Chain | Transaction | Origin | Declared | Optimal | Upfront waste | Runtime penalty |"
+    );
+    println!("|---:|---|---|---:|---:|---:|---:|");
+    for c in compared {
+        let s = &c.report.gas_summary;
+        println!(
+            "| {} | `{}` | {} | {} | {} | {} | {} |",
+            c.chain_id,
+            c.tx_hash,
+            c.report.declared_origin.as_str(),
+            s.declared_list_cost,
+            s.optimal_list_cost,
+            s.waste_per_tx,
+            execution_penalty(&c.report)
+        );
+    }
+    if failed > 0 {
+        println!();
+        println!("{} transaction(s) could not be compared.", failed);
+    }
+}
+
+/// Totals across every transaction that compared successfully.
+fn print_batch_summary(compared: &[Comparison], failed: usize, units: &Units) {
+    let declared: u64 = compared
+        .iter()
+        .map(|c| c.report.gas_summary.declared_list_cost)
+        .sum();
+    let optimal: u64 = compared
+        .iter()
+        .map(|c| c.report.gas_summary.optimal_list_cost)
+        .sum();
+    let waste: i64 = compared
+        .iter()
+        .map(|c| c.report.gas_summary.waste_per_tx)
+        .sum();
+    let execution_penalty: u64 = compared.iter().map(|c| execution_penalty(&c.report)).sum();
+    let invalid = compared.iter().filter(|c| !c.report.is_valid).count();
+
+    println!(
+        "Batch: {} compared, {} with issues, {} failed",
+        compared.len(),
+        invalid,
+        failed
+    );
+    println!(
+        "List cost:  {} declared  →  {} optimal  ({}  upfront)",
+        units.gas(declared),
+        units.gas(optimal),
+        units.signed(waste),
+    );
+    if execution_penalty > 0 {
+        println!(
+            "Execution:  {} at runtime",
+            units.signed(execution_penalty as i64)
+        );
+    }
+
+    let by_origin = waste_by_origin(compared);
+    if by_origin.len() > 1 {
+        println!("By origin (most waste first):");
+        for (origin, t) in by_origin {
+            println!(
+                "  {:<15} {:>4} txs  {:>12} upfront  {} at runtime",
+                origin.as_str(),
+                t.count,
+                units.signed(t.upfront),
+                units.signed(t.runtime as i64)
+            );
+        }
+    }
+}
+
+/// Per-origin totals for a batch.
+#[derive(Default)]
+struct OriginTotals {
+    count: usize,
+    upfront: i64,
+    runtime: u64,
+}
+
+/// Group a batch by declared-list origin, so the list-building habits that waste
+/// the most gas come first.
+fn waste_by_origin(compared: &[Comparison]) -> Vec<(ListOrigin, OriginTotals)> {
+    let mut totals: BTreeMap<ListOrigin, OriginTotals> = BTreeMap::new();
+    for c in compared {
+        let t = totals.entry(c.report.declared_origin).or_default();
+        t.count += 1;
+        t.upfront += c.report.gas_summary.waste_per_tx;
+        t.runtime += execution_penalty(&c.report);
+    }
+    let mut totals: Vec<_> = totals.into_iter().collect();
+    totals.sort_by_key(|(_, t)| std::cmp::Reverse(t.upfront.max(0) as u64 + t.runtime));
+    totals
+}
+
+/// Gas the optimal list would save: upfront waste of the declared list plus the runtime
+/// penalty of what it misses.
+pub(super) fn savings_at_stake(report: &ValidationReport) -> u64 {
+    report.gas_summary.waste_per_tx.max(0) as u64 + execution_penalty(report)
+}
+
+/// The spec [`Prepared::replay`] runs a transaction under: that of the fork whose gas
+/// schedule prices it (see [`fork_at`]), so execution and pricing agree on what is warm.
+pub(super) fn replay_spec(chain_id: u64, block_number: u64) -> SpecId {
+    fork_at(Some(chain_id), block_number).spec()
+}
+
+/// Runtime gas lost to missing and incomplete entries.
+pub(super) fn execution_penalty(report: &ValidationReport) -> u64 {
+    ListScore::of(report).runtime_penalty
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const H1: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+    const H2: &str = "0x2222222222222222222222222222222222222222222222222222222222222222";
+
+    #[test]
+    fn test_replay_spec_follows_mainnet_forks() {
+        assert_eq!(replay_spec(MAINNET_CHAIN_ID, 12_244_000), SpecId::BERLIN);
+        assert_eq!(replay_spec(MAINNET_CHAIN_ID, 12_964_999), SpecId::BERLIN);
+        assert_eq!(replay_spec(MAINNET_CHAIN_ID, 12_965_000), SpecId::LONDON);
+        assert_eq!(replay_spec(MAINNET_CHAIN_ID, 16_000_000), SpecId::LONDON);
+        assert_eq!(replay_spec(MAINNET_CHAIN_ID, 17_034_870), SpecId::SHANGHAI);
+        assert_eq!(replay_spec(MAINNET_CHAIN_ID, 23_000_000), SpecId::PRAGUE);
+        assert_eq!(replay_spec(10, 1), SpecId::default());
+    }
+
+    /// A transfer declaring the block's coinbase, replayed over an in-memory state.
+    fn coinbase_transfer() -> (Prepared, BlockEnv, revm::database::InMemoryDB) {
+        let from = Address::repeat_byte(0xaa);
+        let to = Address::repeat_byte(0xbb);
+        let coinbase = Address::repeat_byte(0xcc);
+        let mut db = revm::database::InMemoryDB::default();
+        db.insert_account_info(
+            from,
+            revm::state::AccountInfo {
+                balance: alloy_primitives::U256::from(10u64.pow(18)),
+                ..Default::default()
+            },
+        );
+        let tx_env = TxEnv::builder()
+            .caller(from)
+            .kind(TxKind::Call(to))
+            .gas_limit(100_000)
+            .build()
+            .unwrap();
+        let declared = AccessList(vec![alloy_rpc_types_eth::AccessListItem {
+            address: coinbase,
+            storage_keys: vec![],
+        }]);
+        let prepared = Prepared {
+            tx_hash: B256::ZERO,
+            block_hash: B256::ZERO,
+            from,
+            to,
+            declared,
+            tx_env,
+        };
+        let block_env = BlockEnv {
+            beneficiary: coinbase,
+            ..Default::default()
+        };
+        (prepared, block_env, db)
+    }
+
+    #[test]
+    fn test_replay_prices_with_the_blocks_fork() {
+        let (prepared, block_env, db) = coinbase_transfer();
+        let coinbase = block_env.beneficiary;
+        let redundant = |block_number: u64, historical_refunds: bool| {
+            let (report, _) = prepared
+                .replay(
+                    db.clone(),
+                    block_number,
+                    &block_env,
+                    MAINNET_CHAIN_ID,
+                    historical_refunds,
+                )
+                .unwrap();
+            report
+                .entries
+                .iter()
+                .any(|e| matches!(e, DiffEntry::Redundant { address, .. } if *address == coinbase))
+        };
+        // The coinbase is warm from Shanghai (EIP-3651) on, not before.
+        assert!(redundant(20_000_000, false));
+        assert!(!redundant(16_000_000, false));
+        assert!(!redundant(12_500_000, true));
+    }
+
+    #[test]
+    fn test_replay_applies_chain_warm_addresses() {
+        let (mut prepared, block_env, db) = coinbase_transfer();
+        let arb_sys = Address::with_last_byte(0x64);
+        prepared.declared.0[0].address = arb_sys;
+        let redundant = |chain_id: u64| {
+            let (report, _) = prepared
+                .replay(db.clone(), 20_000_000, &block_env, chain_id, false)
+                .unwrap();
+            report
+                .entries
+                .iter()
+                .any(|e| matches!(e, DiffEntry::Redundant { address, .. } if *address == arb_sys))
+        };
+        // ArbSys is warm on Arbitrum One only.
+        assert!(redundant(42_161));
+        assert!(!redundant(MAINNET_CHAIN_ID));
+    }
+
+    #[test]
+    fn test_collect_jobs_merges_file_and_dedupes() {
+        let path = std::env::temp_dir().join("hammer_compare_tx_hashes.txt");
+        std::fs::write(&path, format!("# incident txs\n{}\n\n  {}  \n", H2, H1)).unwrap();
+        let jobs = collect_jobs(&[H1.to_string()], Some(&path)).unwrap();
+        std::fs::remove_file(&path).ok();
+        let hashes: Vec<B256> = jobs.iter().map(|job| job.tx_hash).collect();
+        assert_eq!(
+            hashes,
+            vec![H1.parse().unwrap(), H2.parse::<B256>().unwrap()]
+        );
+        assert!(jobs.iter().all(|job| job.chain.is_none()));
+    }
+
+    #[test]
+    fn test_collect_jobs_tags_chains() {
+        let inline = [
+            format!("mainnet:{}", H1),
+            format!("8453:{}", H1),
+            format!("1:{}", H1),
+            H1.to_string(),
+        ];
+        let jobs = collect_jobs(&inline, None).unwrap();
+        let chains: Vec<Option<u64>> = jobs.iter().map(|job| job.chain).collect();
+        assert_eq!(chains, vec![Some(1), Some(8453), None]);
+        assert!(collect_jobs(&[format!("nope:{}", H1)], None).is_err());
+    }
+
+    #[test]
+    fn test_parse_chain_rpcs() {
+        let rpcs = parse_chain_rpcs(&["sepolia=http://localhost:8545".to_string()]).unwrap();
+        assert_eq!(rpcs[&11_155_111], "http://localhost:8545");
+        for bad in ["sepolia", "nope=http://localhost", "1=not a url"] {
+            assert!(parse_chain_rpcs(&[bad.to_string()]).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_collect_jobs_invalid_names_hash() {
+        let err = collect_jobs(&["0x12".to_string()], None).unwrap_err();
+        assert!(format!("{:#}", err).contains("invalid tx hash 0x12"));
+    }
+
+    #[test]
+    fn test_collect_jobs_empty_file_rejected() {
+        let path = std::env::temp_dir().join("hammer_compare_tx_hashes_empty.txt");
+        std::fs::write(&path, "# nothing yet\n").unwrap();
+        let err = collect_jobs(&[], Some(&path)).unwrap_err();
+        std::fs::remove_file(&path).ok();
+        assert!(err.to_string().contains("no transaction hashes"));
+    }
 }
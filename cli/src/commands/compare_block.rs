@@ -0,0 +1,345 @@
+use alloy_eips::BlockNumberOrTag;
+use alloy_primitives::B256;
+use alloy_provider::Provider;
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{AccessList, ListOrigin, ValidationReport};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+use super::attest;
+use super::compare::{execution_penalty, prepare_one, replay_spec, savings_at_stake};
+use super::engine;
+use super::global::GlobalArgs;
+use super::units::Units;
+
+#[derive(Args)]
+pub struct CompareBlockArgs {
+    /// Block whose transactions to compare
+    #[arg(long)]
+    pub block: u64,
+    /// Net the savings against the refund cap of the fork the block was mined in (half
+    /// the gas spent before London, a fifth after)
+    #[arg(long)]
+    pub historical_refunds: bool,
+    /// Replay the block's transactions on this many threads
+    #[arg(long, default_value_t = 1)]
+    pub jobs: usize,
+}
+
+/// How one transaction of the block fared.
+struct TxRow {
+    index: usize,
+    tx_hash: B256,
+    /// Why the transaction was not replayed (a blob or reverted transaction, or a failed
+    /// fetch), else its figures.
+    outcome: Result<TxFigures, String>,
+}
+
+/// The figures of a replayed transaction the block report needs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TxFigures {
+    declared: bool,
+    origin: ListOrigin,
+    declared_list_cost: u64,
+    optimal_list_cost: u64,
+    /// Upfront gas the declared list pays beyond the optimal one.
+    upfront_waste: u64,
+    /// Runtime gas lost to entries the declared list misses.
+    runtime_penalty: u64,
+    /// What the optimal list would save over the declared one.
+    savings_at_stake: u64,
+}
+
+impl TxFigures {
+    fn of(declared: &AccessList, report: &ValidationReport) -> Self {
+        Self {
+            declared: !declared.0.is_empty(),
+            origin: report.declared_origin,
+            declared_list_cost: report.gas_summary.declared_list_cost,
+            optimal_list_cost: report.gas_summary.optimal_list_cost,
+            upfront_waste: report.gas_summary.waste_per_tx.max(0) as u64,
+            runtime_penalty: execution_penalty(report),
+            savings_at_stake: savings_at_stake(report),
+        }
+    }
+}
+
+/// Totals across a block's replayed transactions.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct BlockTotals {
+    transactions: usize,
+    replayed: usize,
+    declared_lists: usize,
+    upfront_waste: u64,
+    runtime_penalty: u64,
+    savings_at_stake: u64,
+}
+
+impl BlockTotals {
+    fn of(rows: &[TxRow]) -> Self {
+        let mut totals = Self {
+            transactions: rows.len(),
+            ..Self::default()
+        };
+        for figures in rows.iter().filter_map(|row| row.outcome.as_ref().ok()) {
+            totals.replayed += 1;
+            totals.declared_lists += usize::from(figures.declared);
+            totals.upfront_waste += figures.upfront_waste;
+            totals.runtime_penalty += figures.runtime_penalty;
+            totals.savings_at_stake += figures.savings_at_stake;
+        }
+        totals
+    }
+}
+
+/// Run the compare-block command.
+///
+/// Every transaction of the block is compared as `compare` would, sharing one block fetch
+/// and one pre-warmed database. The replays run after all transactions are pre-warmed,
+/// on `--jobs` threads. Transactions `compare` rejects (blob and reverted
+/// transactions) are listed as skipped, with the reason, and left out of the totals
+/// rather than failing the command.
+///
+/// # Test boundary
+///
+/// This function requires a live RPC connection. The per-transaction replay is
+/// `compare`'s; the totals are covered by the unit tests below.
+pub async fn run(args: CompareBlockArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human", "markdown"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
+    let chain_id = match global.chain_id()? {
+        Some(id) => id,
+        None => provider
+            .get_chain_id()
+            .await
+            .wrap_err("failed to fetch chain id")?,
+    };
+    let block = provider
+        .get_block_by_number(BlockNumberOrTag::Number(args.block))
+        .await
+        .wrap_err_with(|| format!("failed to fetch block {}", args.block))?
+        .ok_or_else(|| eyre::eyre!("Block {} not found", args.block))?;
+    let hashes: Vec<B256> = block.transactions.hashes().collect();
+    timer.phase("fetch");
+
+    // Fetch and pre-warm every transaction first; the replays then only read the block's
+    // state and can run side by side, each thread over its own cache layer.
+    let mut blocks = BTreeMap::new();
+    let cache = global.state_cache();
+    let mut rows = Vec::with_capacity(hashes.len());
+    let mut prepared = Vec::new();
+    for (index, tx_hash) in hashes.into_iter().enumerate() {
+        match prepare_one(&provider, chain_id, tx_hash, &mut blocks, cache.as_ref()).await {
+            Ok(p) => prepared.push((index, p)),
+            Err(e) => rows.push(TxRow {
+                index,
+                tx_hash,
+                outcome: Err(format!("{:#}", e)),
+            }),
+        }
+    }
+    timer.phase("prewarm");
+
+    if let Some(state) = blocks.values().next() {
+        let replayed = tokio::task::block_in_place(|| {
+            hammer_core::replay_parallel(&state.db, prepared, args.jobs, |db, (index, p)| {
+                let outcome = p
+                    .replay(
+                        db,
+                        state.block_number,
+                        &state.block_env,
+                        chain_id,
+                        args.historical_refunds,
+                    )
+                    .map(|(report, _)| TxFigures::of(&p.declared, &report))
+                    .map_err(|e| format!("{:#}", e));
+                TxRow {
+                    index,
+                    tx_hash: p.tx_hash,
+                    outcome,
+                }
+            })
+        });
+        rows.extend(replayed);
+    }
+    rows.sort_by_key(|row| row.index);
+    if let Some(cache) = &cache {
+        cache.save_blocks(&blocks);
+    }
+    timer.phase("compare");
+    timer.total();
+
+    let totals = BlockTotals::of(&rows);
+    match output {
+        "json" => {
+            attest::write_report(
+                to_json(chain_id, args.block, &totals, &rows),
+                signer.as_ref(),
+                &sink,
+            )
+            .await?
+        }
+        "markdown" => print_markdown(args.block, &totals, &rows),
+        "human" => print_human(args.block, &totals, &rows, &units, global.quiet),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+fn to_json(chain_id: u64, block: u64, totals: &BlockTotals, rows: &[TxRow]) -> serde_json::Value {
+    let transactions: Vec<serde_json::Value> = rows
+        .iter()
+        .map(|row| match &row.outcome {
+            Ok(f) => json!({
+                "index": row.index,
+                "tx_hash": row.tx_hash,
+                "declared": f.declared,
+                "declared_origin": f.origin.as_str(),
+                "declared_list_cost": f.declared_list_cost,
+                "optimal_list_cost": f.optimal_list_cost,
+                "upfront_waste": f.upfront_waste,
+                "runtime_penalty": f.runtime_penalty,
+                "savings_at_stake": f.savings_at_stake,
+            }),
+            Err(reason) => json!({
+                "index": row.index,
+                "tx_hash": row.tx_hash,
+                "skipped": reason,
+            }),
+        })
+        .collect();
+    json!({
+        "chain_id": chain_id,
+        "block_number": block,
+        "transactions": totals.transactions,
+        "replayed": totals.replayed,
+        "declared_lists": totals.declared_lists,
+        "upfront_waste": totals.upfront_waste,
+        "runtime_penalty": totals.runtime_penalty,
+        "savings_at_stake": totals.savings_at_stake,
+        "breakdown": transactions,
+        "engine": engine::metadata(replay_spec(chain_id, block)),
+    })
+}
+
+fn print_human(block: u64, totals: &BlockTotals, rows: &[TxRow], units: &Units, quiet: bool) {
+    println!(
+        "Block {}: {} transactions, {} replayed, {} declared a list",
+        block, totals.transactions, totals.replayed, totals.declared_lists
+    );
+    println!(
+        "Waste:      {} upfront  +  {} at runtime",
+        units.gas(totals.upfront_waste),
+        units.gas(totals.runtime_penalty),
+    );
+    println!(
+        "At stake:   {} the optimal lists would save",
+        units.gas(totals.savings_at_stake)
+    );
+    println!();
+    for row in rows {
+        match &row.outcome {
+            Ok(f) => println!(
+                "  {:>4}  {}  {:<15} {:>12} declared  {:>12} optimal  {:>12} at stake",
+                row.index,
+                row.tx_hash,
+                f.origin.as_str(),
+                units.gas(f.declared_list_cost),
+                units.gas(f.optimal_list_cost),
+                units.gas(f.savings_at_stake),
+            ),
+            // Skips are routine in a block (reverts, blobs); --quiet keeps the table
+            // to what was replayed.
+            Err(reason) if !quiet => {
+                println!("  {:>4}  {}  skipped: {}", row.index, row.tx_hash, reason)
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn print_markdown(block: u64, totals: &BlockTotals, rows: &[TxRow]) {
+    println!("### Block {}", block);
+    println!();
+    println!("| | |");
+    println!("|---|---:|");
+    println!("| Transactions | {} |", totals.transactions);
+    println!("| Replayed | {} |", totals.replayed);
+    println!("| Declared a list | {} |", totals.declared_lists);
+    println!("| Upfront waste | {} |", totals.upfront_waste);
+    println!("| Runtime penalty | {} |", totals.runtime_penalty);
+    println!("| Savings at stake | {} |", totals.savings_at_stake);
+    println!();
+    println!("| # | Transaction | Origin | Declared | Optimal | Upfront waste | Runtime penalty |");
+    println!("|---:|---|---|---:|---:|---:|---:|");
+    for row in rows {
+        match &row.outcome {
+            Ok(f) => println!(
+                "| {} | `{}` | {} | {} | {} | {} | {} |",
+                row.index,
+                row.tx_hash,
+                f.origin.as_str(),
+                f.declared_list_cost,
+                f.optimal_list_cost,
+                f.upfront_waste,
+                f.runtime_penalty
+            ),
+            Err(reason) => println!(
+                "| {} | `{}` | skipped: {} | | | | |",
+                row.index, row.tx_hash, reason
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(index: usize, outcome: Result<TxFigures, String>) -> TxRow {
+        TxRow {
+            index,
+            tx_hash: B256::with_last_byte(index as u8),
+            outcome,
+        }
+    }
+
+    fn figures(declared: bool, upfront_waste: u64, runtime_penalty: u64) -> TxFigures {
+        TxFigures {
+            declared,
+            origin: ListOrigin::None,
+            declared_list_cost: 0,
+            optimal_list_cost: 0,
+            upfront_waste,
+            runtime_penalty,
+            savings_at_stake: upfront_waste + runtime_penalty,
+        }
+    }
+
+    #[test]
+    fn test_block_totals_skip_unreplayed_transactions() {
+        let rows = [
+            row(0, Ok(figures(true, 2_400, 0))),
+            row(1, Err("transaction reverted on-chain".to_string())),
+            row(2, Ok(figures(false, 0, 4_600))),
+            row(3, Ok(figures(true, 1_900, 100))),
+        ];
+        assert_eq!(
+            BlockTotals::of(&rows),
+            BlockTotals {
+                transactions: 4,
+                replayed: 3,
+                declared_lists: 2,
+                upfront_waste: 4_300,
+                runtime_penalty: 4_700,
+                savings_at_stake: 9_000,
+            }
+        );
+    }
+}
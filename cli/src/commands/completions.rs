@@ -0,0 +1,17 @@
+use clap::Args;
+use clap_complete::Shell;
+use eyre::Result;
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    pub shell: Shell,
+}
+
+/// Print the completion script for `shell` to stdout, e.g.
+/// `hammer completions zsh > ~/.zfunc/_hammer`.
+pub fn run(args: CompletionsArgs, mut cmd: clap::Command) -> Result<()> {
+    let name = cmd.get_name().to_string();
+    clap_complete::generate(args.shell, &mut cmd, name, &mut std::io::stdout());
+    Ok(())
+}
@@ -0,0 +1,204 @@
+//! Optimizer options shared by generate, validate, deploy, and queue, mapped onto
+//! [`OptimizerConfig`], and overrides of the simulated block and state.
+
+use alloy_primitives::{Address, B256, U256};
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{OptimizerConfig, StateOverrides};
+use revm::context::BlockEnv;
+use std::path::PathBuf;
+
+use super::util::parse_u256;
+
+#[derive(Args, Clone, Debug, Default)]
+pub struct OptimizerArgs {
+    /// Address already warm when the transaction runs, e.g. warmed earlier in your own
+    /// bundle (repeatable); stripped from the list and Redundant if declared
+    #[arg(long = "assume-warm", value_name = "ADDRESS")]
+    pub assume_warm: Vec<String>,
+    /// Entry kept in the generated list whatever the trace shows, as ADDRESS or ADDRESS:SLOT
+    /// (repeatable), for state you pre-warm for subcalls hammer cannot see
+    #[arg(long = "force-include", value_name = "ADDRESS[:SLOT]")]
+    pub force_include: Vec<String>,
+    /// Slot known to move as a price feed updates, as ADDRESS or ADDRESS:SLOT (repeatable);
+    /// flagged volatile where listed. Reads of Chainlink-style feeds are flagged without it
+    #[arg(long = "volatile", value_name = "ADDRESS[:SLOT]")]
+    pub volatile: Vec<String>,
+}
+
+impl OptimizerArgs {
+    /// Parse into a core config. Runs before any network call.
+    pub fn config(&self) -> Result<OptimizerConfig> {
+        let assume_warm = self
+            .assume_warm
+            .iter()
+            .map(|a| {
+                a.parse::<Address>()
+                    .wrap_err_with(|| format!("invalid --assume-warm {}", a))
+            })
+            .collect::<Result<_>>()?;
+        let mut config = OptimizerConfig {
+            assume_warm,
+            ..Default::default()
+        };
+        for spec in &self.force_include {
+            let (address, slot) =
+                parse_entry(spec).wrap_err_with(|| format!("invalid --force-include {}", spec))?;
+            config
+                .force_include
+                .entry(address)
+                .or_default()
+                .extend(slot);
+        }
+        for spec in &self.volatile {
+            let (address, slot) =
+                parse_entry(spec).wrap_err_with(|| format!("invalid --volatile {}", spec))?;
+            config.volatile.entry(address).or_default().extend(slot);
+        }
+        Ok(config)
+    }
+}
+
+/// Block fields to simulate with instead of the fetched block's, for code that depends on
+/// the time or randomness it runs at (auctions, interest accrual, lotteries).
+#[derive(Args, Clone, Debug, Default)]
+pub struct BlockOverrideArgs {
+    /// Unix timestamp to simulate at instead of the block's, e.g. a future moment; state
+    /// is still read at the block
+    #[arg(long, value_name = "SECONDS")]
+    pub block_timestamp: Option<u64>,
+    /// PREVRANDAO value (32-byte hex) to simulate with instead of the block's
+    #[arg(long, value_name = "HASH")]
+    pub prevrandao: Option<B256>,
+}
+
+impl BlockOverrideArgs {
+    /// Apply the overrides to the environment built from the fetched block.
+    pub fn apply(&self, block_env: &mut BlockEnv) {
+        if let Some(timestamp) = self.block_timestamp {
+            block_env.timestamp = U256::from(timestamp);
+        }
+        if let Some(prevrandao) = self.prevrandao {
+            block_env.prevrandao = Some(prevrandao);
+        }
+    }
+}
+
+/// Account state to patch in before tracing, for transactions that depend on approvals,
+/// balances or code not yet on chain.
+#[derive(Args, Clone, Debug, Default)]
+pub struct StateOverrideArgs {
+    /// JSON file of state overrides in `eth_call`'s schema: per address, `balance`,
+    /// `nonce`, `code`, and `state` (replaces all storage) or `stateDiff` (sets slots)
+    #[arg(long, value_name = "FILE")]
+    pub state_override: Option<PathBuf>,
+}
+
+impl StateOverrideArgs {
+    /// The overrides in the file, or none without `--state-override`.
+    pub fn load(&self) -> Result<StateOverrides> {
+        let Some(path) = &self.state_override else {
+            return Ok(StateOverrides::default());
+        };
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+        let overrides: StateOverrides = serde_json::from_str(&text)
+            .wrap_err_with(|| format!("invalid state override in {}", path.display()))?;
+        overrides.check()?;
+        Ok(overrides)
+    }
+}
+
+/// `ADDRESS` or `ADDRESS:SLOT`, the slot as 0x-hex or decimal.
+fn parse_entry(spec: &str) -> Result<(Address, Option<B256>)> {
+    let (address, slot) = match spec.split_once(':') {
+        Some((address, slot)) => (address, Some(slot)),
+        None => (spec, None),
+    };
+    let address = address.parse().wrap_err("invalid address")?;
+    let slot = slot
+        .map(|s| parse_u256(s).map(B256::from).wrap_err("invalid slot"))
+        .transpose()?;
+    Ok((address, slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assume_warm_deduplicated() {
+        let args = OptimizerArgs {
+            assume_warm: vec![
+                "0x0000000000000000000000000000000000000001".into(),
+                "0x0000000000000000000000000000000000000001".into(),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(args.config().unwrap().assume_warm.len(), 1);
+    }
+
+    #[test]
+    fn test_assume_warm_invalid() {
+        let args = OptimizerArgs {
+            assume_warm: vec!["nope".into()],
+            ..Default::default()
+        };
+        assert_eq!(
+            args.config().unwrap_err().to_string(),
+            "invalid --assume-warm nope"
+        );
+    }
+
+    #[test]
+    fn test_force_include_groups_slots_by_address() {
+        let args = OptimizerArgs {
+            force_include: vec![
+                "0x0000000000000000000000000000000000000001:0x2".into(),
+                "0x0000000000000000000000000000000000000001:3".into(),
+                "0x0000000000000000000000000000000000000002".into(),
+            ],
+            ..Default::default()
+        };
+        let forced = args.config().unwrap().force_include;
+        assert_eq!(
+            forced[&Address::with_last_byte(1)],
+            [B256::with_last_byte(2), B256::with_last_byte(3)].into()
+        );
+        assert!(forced[&Address::with_last_byte(2)].is_empty());
+    }
+
+    #[test]
+    fn test_force_include_invalid_slot() {
+        let args = OptimizerArgs {
+            force_include: vec!["0x0000000000000000000000000000000000000001:zz".into()],
+            ..Default::default()
+        };
+        let err = args.config().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid --force-include 0x0000000000000000000000000000000000000001:zz"
+        );
+        assert!(format!("{:#}", err).contains("invalid slot"), "{:#}", err);
+    }
+
+    #[test]
+    fn test_block_overrides_replace_only_what_is_given() {
+        let fetched = BlockEnv {
+            timestamp: U256::from(1_700_000_000u64),
+            prevrandao: Some(B256::with_last_byte(1)),
+            ..Default::default()
+        };
+        let mut block_env = fetched.clone();
+        BlockOverrideArgs::default().apply(&mut block_env);
+        assert_eq!(block_env, fetched);
+
+        let args = BlockOverrideArgs {
+            block_timestamp: Some(1_800_000_000),
+            prevrandao: None,
+        };
+        args.apply(&mut block_env);
+        assert_eq!(block_env.timestamp, U256::from(1_800_000_000u64));
+        assert_eq!(block_env.prevrandao, fetched.prevrandao);
+    }
+}
@@ -0,0 +1,260 @@
+use alloy_eips::BlockId;
+use alloy_primitives::B256;
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::TransactionRequest;
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{access_list_gas_cost, generate_with};
+use revm::context::TxEnv;
+use revm::primitives::TxKind;
+
+use super::attest;
+use super::config::OptimizerArgs;
+use super::events;
+use super::global::GlobalArgs;
+use super::util::{
+    access_list_json, apply_chain_rules, assert_post_berlin, balances_json, block_env_from_header,
+    callback_tag, callback_warning, fetch_block, gas_sensitive_warning, parse_block_id,
+    parse_hex_bytes, parse_u256, participant_balances, print_balances, print_callbacks,
+    print_gas_sensitive, print_new_accounts, print_precompile_calls, print_removed,
+    print_state_diff, read_bytecode, state_read_error, traced, volatile_tag,
+    NEAREST_AVAILABLE_DEFAULT,
+};
+
+#[derive(Args)]
+pub struct DeployArgs {
+    #[arg(long)]
+    pub from: String,
+    /// Creation bytecode: 0x-hex, a file containing hex, or a forge/hardhat artifact JSON
+    #[arg(long)]
+    pub bytecode: String,
+    /// ABI-encoded constructor arguments, appended to the creation bytecode
+    #[arg(long, default_value = "0x")]
+    pub args: String,
+    /// Deploy through a CREATE2 factory that takes `salt ++ initcode` as calldata
+    /// (e.g. the deterministic deployment proxy 0x4e59b44847b379578588920cA78FbF26c0B4956C)
+    #[arg(long, requires = "salt")]
+    pub create2_factory: Option<String>,
+    /// 32-byte CREATE2 salt (0x-hex or decimal)
+    #[arg(long, requires = "create2_factory")]
+    pub salt: Option<String>,
+    #[arg(long, default_value = "0")]
+    pub value: String,
+    #[arg(long, default_value = "latest")]
+    pub block: String,
+    /// If the block's state is pruned, simulate against the nearest block within BLOCKS
+    /// that still has it [default BLOCKS: 64]
+    #[arg(long, value_name = "BLOCKS", num_args = 0..=1, default_missing_value = NEAREST_AVAILABLE_DEFAULT)]
+    pub nearest_available: Option<u64>,
+    /// Also report the state changes (balances, nonces, code, storage) the simulated
+    /// execution made; JSON output becomes `{"access_list", "state_diff"}`
+    #[arg(long)]
+    pub state_diff: bool,
+    /// Also report the logs the simulated execution emitted; JSON output gains a `logs` section
+    #[arg(long)]
+    pub logs: bool,
+    /// Contract ABI as ADDRESS=PATH (repeatable; bare ABI or forge/hardhat artifact); decodes
+    /// that contract's logs
+    #[arg(long = "abi", requires = "logs")]
+    pub abis: Vec<String>,
+    /// Also report net ETH and ERC-20 balance changes of the sender, new contract and coinbase
+    #[arg(long)]
+    pub balances: bool,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+/// Run the deploy command: simulate a contract creation transaction and report the access
+/// list it should carry (external reads during the constructor, e.g. registry lookups).
+pub async fn run(args: DeployArgs, global: &GlobalArgs) -> Result<()> {
+    // Validate all local arguments before any network calls.
+    let output = global.output("json", &["json", "human"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let mut config = args.optimizer.config()?;
+    let abis = events::load(&args.abis)?;
+    let from: alloy_primitives::Address = args.from.parse().wrap_err("invalid --from")?;
+    let mut initcode = read_bytecode(&args.bytecode).wrap_err("invalid --bytecode")?;
+    if initcode.is_empty() {
+        eyre::bail!("invalid --bytecode: creation bytecode is empty");
+    }
+    initcode.extend(parse_hex_bytes(&args.args).wrap_err("invalid --args")?);
+    let value = parse_u256(&args.value)?;
+    let block_id = parse_block_id(&args.block)?;
+    let create2 = match (&args.create2_factory, &args.salt) {
+        (Some(factory), Some(salt)) => {
+            let factory: alloy_primitives::Address =
+                factory.parse().wrap_err("invalid --create2-factory")?;
+            let salt = B256::from(parse_u256(salt).wrap_err("invalid --salt")?);
+            Some((factory, salt))
+        }
+        _ => None,
+    };
+
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
+
+    let (block, substitution) = fetch_block(&provider, block_id, args.nearest_available).await?;
+    timer.phase("block");
+    // Every later state read must target the block actually simulated.
+    let block_id = match substitution {
+        Some(s) => BlockId::number(s.used),
+        None => block_id,
+    };
+
+    let header = &block.header;
+    // Guard 3: Reject pre-Berlin blocks
+    assert_post_berlin(header.number)?;
+    apply_chain_rules(&mut config, global.chain_id()?, header.number);
+    let block_env = block_env_from_header(header);
+
+    let nonce = provider
+        .get_transaction_count(from)
+        .block_id(block_id)
+        .await
+        .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?;
+
+    // A factory deployment is an ordinary call whose CREATE2 target is precomputed here;
+    // the tracer treats that address as created (warm) once the factory deploys it.
+    let (kind, data, deployed) = match create2 {
+        Some((factory, salt)) => {
+            let deployed = factory.create2_from_code(salt, &initcode);
+            let mut data = salt.to_vec();
+            data.extend_from_slice(&initcode);
+            (TxKind::Call(factory), data, deployed)
+        }
+        None => (TxKind::Create, initcode, from.create(nonce)),
+    };
+
+    let gas_price = block_env.basefee.max(1_000_000_000) as u128;
+    let tx_env = TxEnv::builder()
+        .caller(from)
+        .nonce(nonce)
+        .kind(kind)
+        .gas_limit(30_000_000)
+        .gas_price(gas_price)
+        .value(value)
+        .data(data.clone().into())
+        .build()
+        .unwrap();
+
+    let tx_req = TransactionRequest {
+        from: Some(from),
+        to: Some(kind),
+        value: Some(value),
+        input: alloy_rpc_types_eth::TransactionInput::new(data.into()),
+        gas: Some(30_000_000),
+        ..Default::default()
+    };
+
+    let state_block_id = BlockId::hash(header.hash);
+
+    let db = super::prefetch::build(
+        provider,
+        state_block_id,
+        state_block_id,
+        tx_req,
+        &alloy_rpc_types_eth::AccessList::default(),
+    )
+    .await
+    .wrap_err("prefetch failed")?;
+    timer.phase("state");
+
+    let coinbase = block_env.beneficiary;
+    let optimal = traced(header.number, || {
+        generate_with(db, tx_env, block_env, &config)
+    })
+    .wrap_err("access list generation failed")?;
+    timer.phase("trace");
+    timer.total();
+
+    let balances = participant_balances(
+        &optimal,
+        &[
+            ("from", from),
+            ("deployed", deployed),
+            ("coinbase", coinbase),
+        ],
+    );
+
+    match output {
+        "json" => {
+            let mut sections = Vec::new();
+            if args.state_diff {
+                sections.push(("state_diff", serde_json::to_value(&optimal.state_diff)?));
+            }
+            if args.logs {
+                sections.push(("logs", events::to_json(&abis, &optimal.logs)));
+            }
+            if args.balances {
+                sections.push(("balance_changes", balances_json(&balances)?));
+            }
+            // The output shape is unchanged; notices go to stderr.
+            if !global.quiet {
+                if let Some(s) = substitution {
+                    eprintln!("{}", s.notice());
+                }
+                if let Some(warning) = callback_warning(&optimal.callbacks) {
+                    eprintln!("{}", warning);
+                }
+                if let Some(warning) = gas_sensitive_warning(&optimal.gas_sensitive) {
+                    eprintln!("{}", warning);
+                }
+            }
+            attest::write_report(
+                access_list_json(&optimal, sections)?,
+                signer.as_ref(),
+                &sink,
+            )
+            .await?
+        }
+        "human" => {
+            if let Some(s) = substitution {
+                println!("{}", s.notice());
+            }
+            match create2 {
+                Some((factory, salt)) => println!(
+                    "Deploys to: {} (CREATE2 via {}, salt {})",
+                    deployed, factory, salt
+                ),
+                None => println!("Deploys to: {} (nonce {})", deployed, nonce),
+            }
+            let cost = access_list_gas_cost(&optimal.list);
+            println!("Access list (cost: {}):", units.gas(cost));
+            for item in &optimal.list.0 {
+                println!(
+                    "  {}:{}",
+                    item.address,
+                    callback_tag(&optimal.callbacks, &item.address, None)
+                );
+                for key in &item.storage_keys {
+                    println!(
+                        "    - {}{}{}",
+                        key,
+                        callback_tag(&optimal.callbacks, &item.address, Some(key)),
+                        volatile_tag(&optimal.volatile, &item.address, key)
+                    );
+                }
+            }
+            print_removed(&optimal, &units);
+            if !global.quiet {
+                print_new_accounts(&optimal.new_accounts, &units);
+                print_precompile_calls(&optimal.precompile_calls);
+                print_callbacks(&optimal.callbacks);
+                print_gas_sensitive(&optimal.gas_sensitive);
+            }
+            if args.state_diff {
+                print_state_diff(&optimal.state_diff);
+            }
+            if args.logs {
+                events::print_logs(&abis, &optimal.logs);
+            }
+            if args.balances {
+                print_balances(&balances);
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
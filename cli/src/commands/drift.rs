@@ -0,0 +1,300 @@
+use alloy::network::Ethereum;
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_provider::{DynProvider, Provider};
+use alloy_rpc_types_eth::TransactionRequest;
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{
+    access_list_gas_cost, generate_with_strategy, AccessList, OptimizerConfig, Strategy,
+};
+use revm::context::TxEnv;
+use revm::primitives::TxKind;
+use serde_json::json;
+
+use super::attest;
+use super::config::OptimizerArgs;
+use super::global::GlobalArgs;
+use super::report_diff::{list_entries, optimal_line};
+use super::units::Units;
+use super::util::{
+    apply_chain_rules, assert_post_berlin, block_env_from_header, fetch_block, parse_block_id,
+    parse_hex_bytes, parse_u256, state_read_error, traced,
+};
+
+#[derive(Args)]
+pub struct DriftArgs {
+    #[arg(long)]
+    pub from: String,
+    #[arg(long)]
+    pub to: String,
+    #[arg(long, default_value = "0x")]
+    pub data: String,
+    #[arg(long, default_value = "0")]
+    pub value: String,
+    /// Earlier block to generate the list at
+    #[arg(long)]
+    pub before: String,
+    /// Later block to generate the list at
+    #[arg(long, default_value = "latest")]
+    pub after: String,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+/// The call both lists are generated for.
+struct Call {
+    from: Address,
+    to: Address,
+    data: Bytes,
+    value: U256,
+}
+
+/// The list generated at one block.
+struct Generated {
+    block: u64,
+    list: AccessList,
+}
+
+/// How the list moved between the two blocks.
+#[derive(Debug, PartialEq, Eq)]
+pub(super) struct Drift {
+    /// Entries only in the later list: an address, or one of its slots.
+    pub(super) added: Vec<(Address, Option<B256>)>,
+    /// Entries only in the earlier list.
+    pub(super) removed: Vec<(Address, Option<B256>)>,
+    /// Entries in both.
+    pub(super) kept: usize,
+}
+
+impl Drift {
+    pub(super) fn between(before: &AccessList, after: &AccessList) -> Self {
+        let (before, after) = (list_entries(before), list_entries(after));
+        Self {
+            added: after.difference(&before).copied().collect(),
+            removed: before.difference(&after).copied().collect(),
+            kept: before.intersection(&after).count(),
+        }
+    }
+
+    pub(super) fn is_stable(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Share of all entries of either list that both lists have; 1 when both are empty.
+    pub(super) fn stability(&self) -> f64 {
+        let total = self.kept + self.added.len() + self.removed.len();
+        if total == 0 {
+            1.0
+        } else {
+            self.kept as f64 / total as f64
+        }
+    }
+}
+
+/// Run the drift command.
+///
+/// Generates the optimal list for the same call at two blocks, each against its own
+/// state with the sender's nonce there, and reports the addresses and slots that entered
+/// or left it. A list that drifts between blocks a few hours apart will not survive being
+/// hardcoded.
+///
+/// # Test boundary
+///
+/// This function requires a live RPC connection. Generation is `generate`'s; the diff is
+/// covered by the unit tests below.
+pub async fn run(args: DriftArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let config = args.optimizer.config()?;
+    let call = Call {
+        from: args.from.parse().wrap_err("invalid --from")?,
+        to: args.to.parse().wrap_err("invalid --to")?,
+        data: parse_hex_bytes(&args.data)?.into(),
+        value: parse_u256(&args.value)?,
+    };
+    let before_id = parse_block_id(&args.before).wrap_err("invalid --before")?;
+    let after_id = parse_block_id(&args.after).wrap_err("invalid --after")?;
+
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
+    let chain_id = global.chain_id()?;
+    let before = generate_at(&provider, chain_id, &call, before_id, &config).await?;
+    timer.phase("before");
+    let after = generate_at(&provider, chain_id, &call, after_id, &config).await?;
+    timer.phase("after");
+    timer.total();
+
+    let drift = Drift::between(&before.list, &after.list);
+    match output {
+        "json" => {
+            attest::write_report(
+                to_json(&call, &before, &after, &drift),
+                signer.as_ref(),
+                &sink,
+            )
+            .await?
+        }
+        "human" => print_human(&before, &after, &drift, &units),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// `generate`'s list for `call` at `block_id`.
+async fn generate_at(
+    provider: &DynProvider<Ethereum>,
+    chain_id: Option<u64>,
+    call: &Call,
+    block_id: BlockId,
+    config: &OptimizerConfig,
+) -> Result<Generated> {
+    let (block, _) = fetch_block(provider, block_id, None).await?;
+    let header = &block.header;
+    assert_post_berlin(header.number)?;
+    let mut config = config.clone();
+    apply_chain_rules(&mut config, chain_id, header.number);
+    let block_env = block_env_from_header(header);
+    let state_block_id = BlockId::hash(header.hash);
+
+    let nonce = provider
+        .get_transaction_count(call.from)
+        .block_id(state_block_id)
+        .await
+        .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?;
+    let gas_price = block_env.basefee.max(1_000_000_000) as u128;
+    let tx_env = TxEnv::builder()
+        .caller(call.from)
+        .nonce(nonce)
+        .kind(TxKind::Call(call.to))
+        .gas_limit(30_000_000)
+        .gas_price(gas_price)
+        .value(call.value)
+        .data(call.data.clone())
+        .build()
+        .unwrap();
+    let tx_req = TransactionRequest {
+        from: Some(call.from),
+        to: Some(TxKind::Call(call.to)),
+        value: Some(call.value),
+        input: alloy_rpc_types_eth::TransactionInput::new(call.data.clone()),
+        gas: Some(30_000_000),
+        ..Default::default()
+    };
+
+    let db = super::prefetch::build(
+        provider.clone(),
+        state_block_id,
+        state_block_id,
+        tx_req,
+        &AccessList::default(),
+    )
+    .await
+    .wrap_err("prefetch failed")?;
+    let optimal = traced(header.number, || {
+        generate_with_strategy(db, tx_env, block_env, &config, Strategy::default())
+    })
+    .wrap_err_with(|| format!("access list generation failed at block {}", header.number))?;
+    Ok(Generated {
+        block: header.number,
+        list: optimal.list,
+    })
+}
+
+fn to_json(call: &Call, before: &Generated, after: &Generated, drift: &Drift) -> serde_json::Value {
+    let entries = |list: &[(Address, Option<B256>)]| -> Vec<serde_json::Value> {
+        list.iter()
+            .map(|(address, slot)| json!({ "address": address, "storage_key": slot }))
+            .collect()
+    };
+    let side = |generated: &Generated| {
+        json!({
+            "block_number": generated.block,
+            "access_list": generated.list,
+            "list_cost": access_list_gas_cost(&generated.list),
+        })
+    };
+    json!({
+        "from": call.from,
+        "to": call.to,
+        "before": side(before),
+        "after": side(after),
+        "stable": drift.is_stable(),
+        "stability": drift.stability(),
+        "added": entries(&drift.added),
+        "removed": entries(&drift.removed),
+    })
+}
+
+fn print_human(before: &Generated, after: &Generated, drift: &Drift, units: &Units) {
+    println!("Blocks:     {} → {}", before.block, after.block);
+    println!(
+        "List cost:  {} → {}",
+        units.gas(access_list_gas_cost(&before.list)),
+        units.gas(access_list_gas_cost(&after.list))
+    );
+    if drift.is_stable() {
+        println!("Stable: the same list at both blocks.");
+        return;
+    }
+    println!(
+        "Drifted: {} entries kept, {} added, {} removed ({:.0}% stable)",
+        drift.kept,
+        drift.added.len(),
+        drift.removed.len(),
+        drift.stability() * 100.0
+    );
+    for (address, slot) in &drift.added {
+        println!("  + {}", optimal_line(address, slot));
+    }
+    for (address, slot) in &drift.removed {
+        println!("  - {}", optimal_line(address, slot));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hammer_core::AccessListItem;
+
+    fn list(items: &[(u8, &[u8])]) -> AccessList {
+        AccessList(
+            items
+                .iter()
+                .map(|&(a, slots)| AccessListItem {
+                    address: Address::with_last_byte(a),
+                    storage_keys: slots.iter().map(|&s| B256::with_last_byte(s)).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_drift_between_lists() {
+        // An oracle moved to a new round slot; a pool dropped out of the route.
+        let before = list(&[(0x40, &[1, 2]), (0x41, &[7])]);
+        let after = list(&[(0x40, &[1, 3])]);
+        let drift = Drift::between(&before, &after);
+        let (a, b) = (Address::with_last_byte(0x40), Address::with_last_byte(0x41));
+        assert_eq!(
+            drift,
+            Drift {
+                added: vec![(a, Some(B256::with_last_byte(3)))],
+                removed: vec![
+                    (a, Some(B256::with_last_byte(2))),
+                    (b, None),
+                    (b, Some(B256::with_last_byte(7))),
+                ],
+                kept: 2,
+            }
+        );
+        assert!(!drift.is_stable());
+        assert_eq!(drift.stability(), 2.0 / 6.0);
+
+        let same = Drift::between(&before, &before);
+        assert!(same.is_stable());
+        assert_eq!(Drift::between(&list(&[]), &list(&[])).stability(), 1.0);
+    }
+}
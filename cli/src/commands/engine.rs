@@ -0,0 +1,85 @@
+//! Which engine produced a report: the hammer and revm versions, and the spec the
+//! transaction was replayed under.
+//!
+//! Reports record it under `engine`. EVM behavior fixes in a new revm release can
+//! legitimately change an optimal list, so `report-diff` warns when the two reports it
+//! compares came from different revm versions or specs: a changed finding may be the
+//! engine's, not the contract's.
+
+use revm::primitives::hardfork::SpecId;
+use serde_json::{json, Value};
+
+/// The revm version the lockfile pinned at build time.
+pub const REVM_VERSION: &str = env!("HAMMER_REVM_VERSION");
+
+/// The `engine` object of a report replayed under `spec`.
+pub fn metadata(spec: SpecId) -> Value {
+    json!({
+        "hammer": env!("CARGO_PKG_VERSION"),
+        "revm": REVM_VERSION,
+        "spec": <&'static str>::from(spec),
+    })
+}
+
+/// Add the `engine` object to `report`, when it is a JSON object.
+pub fn stamp(report: &mut Value, spec: SpecId) {
+    if let Some(fields) = report.as_object_mut() {
+        fields.insert("engine".into(), metadata(spec));
+    }
+}
+
+/// How the engines of two reports differ, if they do in revm version or spec. Reports
+/// from before `engine` was recorded only differ from ones that record it.
+pub fn mismatch(before: Option<&Value>, after: Option<&Value>) -> Option<String> {
+    let describe = |engine: Option<&Value>| match engine {
+        Some(engine) => format!(
+            "revm {} under {}",
+            engine["revm"].as_str().unwrap_or("unknown"),
+            engine["spec"].as_str().unwrap_or("unknown")
+        ),
+        None => "an engine it does not record".to_string(),
+    };
+    let key = |engine: Option<&Value>| engine.map(|e| (e["revm"].clone(), e["spec"].clone()));
+    match (before, after) {
+        (None, None) => None,
+        _ if key(before) == key(after) => None,
+        _ => Some(format!(
+            "warning: the reports were replayed by different engines (before: {}; after: {}); \
+             EVM fixes between releases can change optimal lists",
+            describe(before),
+            describe(after)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_mismatch() {
+        let prague = metadata(SpecId::PRAGUE);
+        assert_eq!(prague["spec"], "Prague");
+        assert_eq!(prague["revm"], REVM_VERSION);
+        assert_eq!(
+            mismatch(Some(&prague), Some(&metadata(SpecId::PRAGUE))),
+            None
+        );
+        assert_eq!(mismatch(None, None), None);
+
+        let berlin = metadata(SpecId::BERLIN);
+        let warning = mismatch(Some(&berlin), Some(&prague)).unwrap();
+        assert!(warning.contains("under Berlin"));
+        assert!(warning.contains("under Prague"));
+
+        let mut older = prague.clone();
+        older["revm"] = json!("27.0.0");
+        older["hammer"] = json!("0.0.1");
+        assert!(mismatch(Some(&older), Some(&prague))
+            .unwrap()
+            .contains("revm 27.0.0"));
+        assert!(mismatch(None, Some(&prague))
+            .unwrap()
+            .contains("does not record"));
+    }
+}
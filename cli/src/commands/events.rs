@@ -0,0 +1,237 @@
+//! `--logs` / `--abi ADDRESS=PATH` handling shared by generate and deploy: logs emitted
+//! during the trace, decoded where an ABI for the emitting contract was given.
+
+use alloy_dyn_abi::{DynSolValue, EventExt};
+use alloy_json_abi::{Event, JsonAbi};
+use alloy_primitives::{hex, Address, Log};
+use eyre::{Context, Result};
+use std::collections::BTreeMap;
+
+/// Contract ABIs keyed by the address they describe.
+pub type Abis = BTreeMap<Address, JsonAbi>;
+
+/// A log decoded against its contract's ABI: the event name and its arguments in
+/// declaration order.
+#[derive(Debug, PartialEq)]
+pub struct Decoded {
+    pub event: String,
+    pub args: Vec<(String, String)>,
+}
+
+/// Parse and load every `ADDRESS=PATH` spec. Runs before any network call. PATH is a bare
+/// ABI array or a forge/hardhat artifact with an `abi` field.
+pub fn load(specs: &[String]) -> Result<Abis> {
+    let mut abis = Abis::new();
+    for spec in specs {
+        let (addr, path) = spec
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!("invalid --abi {}: expected ADDRESS=PATH", spec))?;
+        let addr: Address = addr
+            .parse()
+            .wrap_err_with(|| format!("invalid --abi address {}", addr))?;
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read ABI {}", path))?;
+        abis.insert(
+            addr,
+            parse_abi(&text).wrap_err_with(|| format!("invalid ABI in {}", path))?,
+        );
+    }
+    Ok(abis)
+}
+
+fn parse_abi(text: &str) -> Result<JsonAbi> {
+    let mut value: serde_json::Value = serde_json::from_str(text)?;
+    if let Some(abi) = value.get_mut("abi") {
+        value = abi.take();
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Decode `log` with the emitting contract's ABI; `None` when no ABI was given for it or
+/// no event in the ABI matches.
+pub fn decode(abis: &Abis, log: &Log) -> Option<Decoded> {
+    let abi = abis.get(&log.address)?;
+    let selector = log.topics().first()?;
+    abi.events()
+        .filter(|event| !event.anonymous && event.selector() == *selector)
+        .find_map(|event| {
+            let decoded = event.decode_log(&log.data).ok()?;
+            Some(Decoded {
+                event: event.name.clone(),
+                args: named_args(event, decoded.indexed, decoded.body),
+            })
+        })
+}
+
+/// Interleave indexed and body values back into declaration order.
+fn named_args(
+    event: &Event,
+    indexed: Vec<DynSolValue>,
+    body: Vec<DynSolValue>,
+) -> Vec<(String, String)> {
+    let mut indexed = indexed.into_iter();
+    let mut body = body.into_iter();
+    event
+        .inputs
+        .iter()
+        .enumerate()
+        .filter_map(|(i, param)| {
+            let value = if param.indexed {
+                indexed.next()
+            } else {
+                body.next()
+            }?;
+            let name = if param.name.is_empty() {
+                format!("arg{}", i)
+            } else {
+                param.name.clone()
+            };
+            Some((name, format_value(&value)))
+        })
+        .collect()
+}
+
+/// Human rendering of an ABI value: decimal integers, checksummed addresses, 0x-hex bytes.
+/// Indexed dynamic values arrive as their 32-byte hash and print as such.
+fn format_value(value: &DynSolValue) -> String {
+    match value {
+        DynSolValue::Bool(b) => b.to_string(),
+        DynSolValue::Int(i, _) => i.to_string(),
+        DynSolValue::Uint(u, _) => u.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Address(a) => a.to_string(),
+        DynSolValue::Function(f) => hex::encode_prefixed(f.as_slice()),
+        DynSolValue::Bytes(b) => hex::encode_prefixed(b),
+        DynSolValue::String(s) => format!("{:?}", s),
+        DynSolValue::Array(items) | DynSolValue::FixedArray(items) => format!(
+            "[{}]",
+            items
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        DynSolValue::Tuple(items) => format!(
+            "({})",
+            items
+                .iter()
+                .map(format_value)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+/// JSON for each log: the raw topics and data, plus `event`/`args` when decoded.
+pub fn to_json(abis: &Abis, logs: &[Log]) -> serde_json::Value {
+    logs.iter()
+        .map(|log| {
+            let mut entry = serde_json::json!({
+                "address": log.address,
+                "topics": log.topics(),
+                "data": log.data.data,
+            });
+            if let Some(decoded) = decode(abis, log) {
+                entry["event"] = decoded.event.into();
+                entry["args"] = decoded
+                    .args
+                    .into_iter()
+                    .map(|(name, value)| (name, serde_json::Value::String(value)))
+                    .collect::<serde_json::Map<_, _>>()
+                    .into();
+            }
+            entry
+        })
+        .collect()
+}
+
+/// Print each log, decoded where possible, in emission order.
+pub fn print_logs(abis: &Abis, logs: &[Log]) {
+    println!("Logs ({}):", logs.len());
+    for (i, log) in logs.iter().enumerate() {
+        match decode(abis, log) {
+            Some(decoded) => {
+                let args: Vec<String> = decoded
+                    .args
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}", name, value))
+                    .collect();
+                println!(
+                    "  #{} {} {}({})",
+                    i,
+                    log.address,
+                    decoded.event,
+                    args.join(", ")
+                );
+            }
+            None => {
+                println!("  #{} {}", i, log.address);
+                for topic in log.topics() {
+                    println!("    topic: {}", topic);
+                }
+                println!("    data:  {}", log.data.data);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{keccak256, B256, U256};
+
+    const ERC20_TRANSFER: &str = r#"[{
+        "type": "event",
+        "name": "Transfer",
+        "anonymous": false,
+        "inputs": [
+            {"name": "from", "type": "address", "indexed": true},
+            {"name": "to", "type": "address", "indexed": true},
+            {"name": "value", "type": "uint256", "indexed": false}
+        ]
+    }]"#;
+
+    fn transfer_log(token: Address) -> Log {
+        Log::new_unchecked(
+            token,
+            vec![
+                keccak256("Transfer(address,address,uint256)"),
+                Address::repeat_byte(0x11).into_word(),
+                Address::repeat_byte(0x22).into_word(),
+            ],
+            B256::from(U256::from(1000)).to_vec().into(),
+        )
+    }
+
+    #[test]
+    fn test_decode_transfer_in_declaration_order() {
+        let token = Address::repeat_byte(0xaa);
+        let abis = Abis::from([(token, parse_abi(ERC20_TRANSFER).unwrap())]);
+        let decoded = decode(&abis, &transfer_log(token)).unwrap();
+        assert_eq!(decoded.event, "Transfer");
+        assert_eq!(
+            decoded.args,
+            vec![
+                ("from".to_string(), Address::repeat_byte(0x11).to_string()),
+                ("to".to_string(), Address::repeat_byte(0x22).to_string()),
+                ("value".to_string(), "1000".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_undecoded_without_abi_for_emitter() {
+        let abis = Abis::from([(Address::ZERO, parse_abi(ERC20_TRANSFER).unwrap())]);
+        let log = transfer_log(Address::repeat_byte(0xaa));
+        assert_eq!(decode(&abis, &log), None);
+        let json = to_json(&abis, &[log]);
+        assert!(json[0].get("event").is_none());
+        assert_eq!(json[0]["topics"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_parse_abi_from_artifact() {
+        let artifact = format!(r#"{{"abi": {}, "bytecode": "0x"}}"#, ERC20_TRANSFER);
+        assert_eq!(parse_abi(&artifact).unwrap().events().count(), 1);
+    }
+}
@@ -0,0 +1,207 @@
+//! `hammer examples`: copy-pasteable invocations for common workflows.
+//!
+//! Placeholders are written `<NAME>`. `<RPC_URL>` is filled from the global `--rpc-url` and
+//! a global `--chain` is carried over, so `hammer --rpc-url $RPC examples compare` prints
+//! commands ready to run against that endpoint.
+
+use clap::Args;
+use eyre::Result;
+
+use super::global::GlobalArgs;
+
+#[derive(Args)]
+pub struct ExamplesArgs {
+    /// Workflow to show; lists the workflows when omitted
+    pub topic: Option<String>,
+}
+
+/// One workflow: a topic name, a title, and its steps as `(explanation, command)`.
+struct Example {
+    topic: &'static str,
+    title: &'static str,
+    steps: &'static [(&'static str, &'static str)],
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        topic: "generate",
+        title: "Generate an access list for a call",
+        steps: &[
+            (
+                "Optimal list as JSON, ready to attach to the transaction",
+                "hammer generate --from <FROM> --to <TO> --data <CALLDATA> --rpc-url <RPC_URL>",
+            ),
+            (
+                "Same, readable, with the state changes and emitted events",
+                "hammer generate --from <FROM> --to <TO> --data <CALLDATA> --state-diff --logs --output human --rpc-url <RPC_URL>",
+            ),
+        ],
+    },
+    Example {
+        topic: "validate-wallet",
+        title: "Check the list a wallet attached before signing",
+        steps: &[
+            (
+                "Report missing, stale and redundant entries with their gas cost",
+                "hammer validate --from <FROM> --to <TO> --data <CALLDATA> --access-list <LIST_JSON> --output human --rpc-url <RPC_URL>",
+            ),
+            (
+                "Write the corrected list next to the original",
+                "hammer validate --from <FROM> --to <TO> --data <CALLDATA> --access-list <LIST_JSON> --fix fixed.json --rpc-url <RPC_URL>",
+            ),
+        ],
+    },
+    Example {
+        topic: "compare",
+        title: "Compare a mined transaction's list to the optimal one",
+        steps: &[
+            (
+                "One transaction, replayed at its block",
+                "hammer compare --tx-hash <TX_HASH> --output human --rpc-url <RPC_URL>",
+            ),
+            (
+                "A file of hashes, summarized as a markdown report",
+                "hammer compare --tx-hashes hashes.txt --output markdown --rpc-url <RPC_URL>",
+            ),
+            (
+                "Every transaction of a block, with totals and a per-transaction breakdown",
+                "hammer compare-block --block <BLOCK> --rpc-url <RPC_URL>",
+            ),
+        ],
+    },
+    Example {
+        topic: "audit-contract",
+        title: "Audit what a contract call touches",
+        steps: &[
+            (
+                "Name each slot after its variable using the solc storage layout",
+                "hammer generate --from <FROM> --to <CONTRACT> --data <CALLDATA> --storage-layout <CONTRACT>=layout.json --output human --rpc-url <RPC_URL>",
+            ),
+            (
+                "List the internal calls with their paths",
+                "hammer generate --from <FROM> --to <CONTRACT> --data <CALLDATA> --calls --output human --rpc-url <RPC_URL>",
+            ),
+            (
+                "Analyze one of them as if it were the transaction",
+                "hammer generate --from <FROM> --to <CONTRACT> --data <CALLDATA> --entry-call <CALL_PATH> --output human --rpc-url <RPC_URL>",
+            ),
+            (
+                "Slot of balances[<HOLDER>] for a mapping declared at slot 0",
+                "hammer slot mapping --base 0 --key <HOLDER>",
+            ),
+        ],
+    },
+    Example {
+        topic: "deploy",
+        title: "Generate the list for a contract deployment",
+        steps: &[(
+            "From a forge artifact, with ABI-encoded constructor arguments",
+            "hammer deploy --from <FROM> --bytecode out/Token.sol/Token.json --args <CONSTRUCTOR_ARGS> --rpc-url <RPC_URL>",
+        )],
+    },
+    Example {
+        topic: "codegen",
+        title: "Embed a list in Solidity, TypeScript or a forge test",
+        steps: &[(
+            "Solidity library returning the list",
+            "hammer access-list-to-sol --access-list <LIST_JSON> --lang solidity",
+        )],
+    },
+];
+
+/// Print one workflow, or the list of workflows.
+pub fn run(args: ExamplesArgs, global: &GlobalArgs) -> Result<()> {
+    let Some(topic) = args.topic else {
+        println!("Workflows (hammer examples <TOPIC>):");
+        for example in EXAMPLES {
+            println!("  {:<16} {}", example.topic, example.title);
+        }
+        return Ok(());
+    };
+    let example = EXAMPLES
+        .iter()
+        .find(|example| example.topic == topic)
+        .ok_or_else(|| {
+            let topics: Vec<&str> = EXAMPLES.iter().map(|example| example.topic).collect();
+            eyre::eyre!(
+                "unknown example {}: expected one of {}",
+                topic,
+                topics.join(", ")
+            )
+        })?;
+    println!("{}", example.title);
+    for (explanation, command) in example.steps {
+        println!();
+        println!("# {}", explanation);
+        println!("{}", fill(command, global));
+    }
+    Ok(())
+}
+
+/// Substitute the placeholders the global options supply; append `--chain` when given.
+fn fill(command: &str, global: &GlobalArgs) -> String {
+    let mut command = command.to_string();
+    if let Some(url) = &global.rpc_url {
+        command = command.replace("<RPC_URL>", url);
+    }
+    if let Some(chain) = &global.chain {
+        if command.contains("--rpc-url") {
+            command.push_str(&format!(" --chain {}", chain));
+        }
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    /// Stand-ins that parse wherever the placeholder appears.
+    const VALUES: &[(&str, &str)] = &[
+        ("<FROM>", "0x0000000000000000000000000000000000000001"),
+        ("<TO>", "0x0000000000000000000000000000000000000002"),
+        ("<CONTRACT>", "0x0000000000000000000000000000000000000003"),
+        ("<HOLDER>", "0x0000000000000000000000000000000000000004"),
+        ("<CALLDATA>", "0x"),
+        ("<CALL_PATH>", "0.1"),
+        ("<CONSTRUCTOR_ARGS>", "0x"),
+        ("<LIST_JSON>", "list.json"),
+        ("<BLOCK>", "19000000"),
+        (
+            "<TX_HASH>",
+            "0x0000000000000000000000000000000000000000000000000000000000000001",
+        ),
+    ];
+
+    #[test]
+    fn test_every_example_parses() {
+        let global = GlobalArgs {
+            rpc_url: Some("http://localhost:8545".to_string()),
+            ..Default::default()
+        };
+        for example in EXAMPLES {
+            for (_, command) in example.steps {
+                let mut command = fill(command, &global);
+                for (placeholder, value) in VALUES {
+                    command = command.replace(placeholder, value);
+                }
+                assert!(!command.contains('<'), "unfilled: {}", command);
+                if let Err(e) = crate::Cli::try_parse_from(command.split_whitespace()) {
+                    panic!("{}: {}", command, e);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_fill_leaves_placeholders_without_globals() {
+        let command = "hammer compare --tx-hash <TX_HASH> --rpc-url <RPC_URL>";
+        assert_eq!(fill(command, &GlobalArgs::default()), command);
+        let global = GlobalArgs {
+            chain: Some("sepolia".to_string()),
+            ..Default::default()
+        };
+        assert!(fill(command, &global).ends_with("--rpc-url <RPC_URL> --chain sepolia"));
+    }
+}
@@ -0,0 +1,235 @@
+//! Calldata perturbation for `generate --fuzz-arg` (see `hammer_core::pad`).
+//!
+//! The transaction's calldata is decoded with `--fuzz-sig`, and each run replaces the
+//! arguments named by `--fuzz-arg INDEX=RANGE` with a value drawn from their range: an
+//! inclusive integer range `LO..HI` for uint arguments, or a comma-separated list of
+//! values for any type. Runs are drawn from a seeded generator, so the same options
+//! give the same variants. Everything is simulated; nothing is sent.
+
+use alloy_dyn_abi::{DynSolType, DynSolValue, JsonAbiExt, Specifier};
+use alloy_json_abi::Function;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use eyre::{Context, Result};
+use hammer_core::PaddedList;
+
+/// Values one argument takes across runs.
+#[derive(Debug, Clone, PartialEq)]
+enum Range {
+    /// Inclusive bounds of a uint argument.
+    Uint(U256, U256),
+    Choice(Vec<DynSolValue>),
+}
+
+/// Argument index and the values it takes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuzzArg {
+    index: usize,
+    range: Range,
+}
+
+/// Parse every `INDEX=RANGE` against the function's inputs.
+pub fn parse_args(function: &Function, specs: &[String]) -> Result<Vec<FuzzArg>> {
+    specs
+        .iter()
+        .map(|spec| {
+            parse_arg(function, spec).wrap_err_with(|| format!("invalid --fuzz-arg {}", spec))
+        })
+        .collect()
+}
+
+fn parse_arg(function: &Function, spec: &str) -> Result<FuzzArg> {
+    let (index, range) = spec
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("expected INDEX=LO..HI or INDEX=V1,V2,..."))?;
+    let index: usize = index.trim().parse().wrap_err("argument index")?;
+    let param = function.inputs.get(index).ok_or_else(|| {
+        eyre::eyre!(
+            "{} takes {} arguments",
+            function.signature(),
+            function.inputs.len()
+        )
+    })?;
+    let ty: DynSolType = param.resolve()?;
+    let range = match range.split_once("..") {
+        Some((lo, hi)) => {
+            if !matches!(ty, DynSolType::Uint(_)) {
+                eyre::bail!(
+                    "LO..HI ranges need a uint argument, argument {} is {}",
+                    index,
+                    ty
+                );
+            }
+            let lo: U256 = lo.trim().parse().wrap_err("range start")?;
+            let hi: U256 = hi.trim().parse().wrap_err("range end")?;
+            if lo > hi {
+                eyre::bail!("range start {} is above its end {}", lo, hi);
+            }
+            Range::Uint(lo, hi)
+        }
+        None => Range::Choice(
+            range
+                .split(',')
+                .map(|v| {
+                    ty.coerce_str(v.trim())
+                        .wrap_err_with(|| format!("not a {}: {}", ty, v))
+                })
+                .collect::<Result<_>>()?,
+        ),
+    };
+    Ok(FuzzArg { index, range })
+}
+
+/// `runs` variants of `data`, a call to `function`, with the fuzzed arguments redrawn.
+pub fn variants(
+    function: &Function,
+    data: &[u8],
+    args: &[FuzzArg],
+    runs: usize,
+    seed: u64,
+) -> Result<Vec<Bytes>> {
+    let selector = function.selector();
+    if data.get(..4) != Some(selector.as_slice()) {
+        eyre::bail!(
+            "--data does not call {} (selector {})",
+            function.signature(),
+            selector
+        );
+    }
+    let base = function
+        .abi_decode_input(&data[4..])
+        .wrap_err_with(|| format!("--data does not decode as {}", function.signature()))?;
+    let mut rng = SplitMix64(seed);
+    (0..runs)
+        .map(|_| {
+            let mut values = base.clone();
+            for arg in args {
+                values[arg.index] = match &arg.range {
+                    Range::Uint(lo, hi) => {
+                        let bits = values[arg.index].as_uint().map_or(256, |(_, bits)| bits);
+                        DynSolValue::Uint(rng.between(*lo, *hi), bits)
+                    }
+                    Range::Choice(choices) => {
+                        choices[(rng.next() % choices.len() as u64) as usize].clone()
+                    }
+                };
+            }
+            Ok(function.abi_encode_input(&values)?.into())
+        })
+        .collect()
+}
+
+/// ` [hit k/n]` for an entry not every run touched; nothing without padding.
+pub fn hit_tag(padded: Option<&PaddedList>, address: &Address, slot: Option<&B256>) -> String {
+    match padded.and_then(|padded| Some((padded.hits_of(address, slot)?, padded.runs))) {
+        Some((hits, runs)) if hits < runs => format!(" [hit {}/{}]", hits, runs),
+        _ => String::new(),
+    }
+}
+
+/// How many runs the padded list covers, for human output.
+pub fn print_summary(padded: &PaddedList) {
+    println!(
+        "Padded over {} runs ({} reverted and left out); [hit k/n] marks entries not every run touched",
+        padded.runs + padded.reverted,
+        padded.reverted
+    );
+}
+
+/// The `fuzz` section of JSON output.
+pub fn to_json(padded: &PaddedList) -> serde_json::Value {
+    serde_json::json!({
+        "runs": padded.runs,
+        "reverted": padded.reverted,
+        "hits": padded.hits,
+    })
+}
+
+/// Small deterministic generator; quality is ample for picking test inputs.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform-enough value in `lo..=hi`.
+    fn between(&mut self, lo: U256, hi: U256) -> U256 {
+        let random = U256::from_limbs([self.next(), self.next(), self.next(), self.next()]);
+        match (hi - lo).checked_add(U256::from(1)) {
+            Some(span) => lo + random % span,
+            // The full 256-bit range.
+            None => random,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer() -> Function {
+        Function::parse("transfer(address,uint256)").unwrap()
+    }
+
+    fn call(to: Address, amount: u64) -> Vec<u8> {
+        transfer()
+            .abi_encode_input(&[
+                DynSolValue::Address(to),
+                DynSolValue::Uint(U256::from(amount), 256),
+            ])
+            .unwrap()
+    }
+
+    #[test]
+    fn test_variants_stay_in_range_and_keep_other_args() {
+        let to = Address::with_last_byte(0x40);
+        let args = parse_args(&transfer(), &["1=10..12".to_string()]).unwrap();
+        let variants = variants(&transfer(), &call(to, 5), &args, 20, 7).unwrap();
+        assert_eq!(variants.len(), 20);
+        for data in &variants {
+            let values = transfer().abi_decode_input(&data[4..]).unwrap();
+            assert_eq!(values[0], DynSolValue::Address(to));
+            let (amount, _) = values[1].as_uint().unwrap();
+            assert!((10..=12).contains(&amount.to::<u64>()), "{}", amount);
+        }
+        // Same seed, same runs.
+        assert_eq!(
+            variants,
+            super::variants(&transfer(), &call(to, 5), &args, 20, 7).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_choice_lists_coerce_to_the_argument_type() {
+        let (a, b) = (Address::with_last_byte(0x40), Address::with_last_byte(0x41));
+        let spec = format!("0={},{}", a, b);
+        let args = parse_args(&transfer(), &[spec]).unwrap();
+        let variants = variants(&transfer(), &call(a, 1), &args, 16, 0).unwrap();
+        let targets: std::collections::BTreeSet<_> = variants
+            .iter()
+            .map(|data| {
+                transfer().abi_decode_input(&data[4..]).unwrap()[0]
+                    .as_address()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(targets.len(), 2);
+    }
+
+    #[test]
+    fn test_invalid_specs_rejected() {
+        for spec in ["1", "2=1..2", "0=1..2", "1=5..1", "0=nope"] {
+            assert!(
+                parse_args(&transfer(), &[spec.to_string()]).is_err(),
+                "{}",
+                spec
+            );
+        }
+        let wrong_selector = [0u8; 36];
+        assert!(variants(&transfer(), &wrong_selector, &[], 1, 0).is_err());
+    }
+}
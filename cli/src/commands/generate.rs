@@ -1,20 +1,34 @@
 use alloy_eips::BlockId;
-use alloy_primitives::U256;
 use alloy_provider::Provider;
 use alloy_rpc_types_eth::TransactionRequest;
-use clap::Args;
+use clap::{Args, ValueEnum};
 use eyre::{Context, Result};
-use hammer_core::{access_list_gas_cost, generate};
-use reqwest::Url;
-use revm::context::{BlockEnv, TxEnv};
+use hammer_core::{
+    access_list_gas_cost, advise, generate_entry_call, generate_padded, generate_self_checked,
+    generate_split_plans, generate_with_strategy, CallPath, Strategy,
+};
+use revm::context::TxEnv;
+use revm::context_interface::transaction::TransactionType;
 use revm::primitives::TxKind;
 
-use super::util::{assert_post_berlin, parse_block_id, parse_hex_bytes, parse_u256};
+use super::attest;
+use super::config::{BlockOverrideArgs, OptimizerArgs, StateOverrideArgs};
+use super::events;
+use super::fuzz;
+use super::global::GlobalArgs;
+use super::layout;
+use super::util::{
+    access_list_json, apply_chain_rules, assert_post_berlin, balances_json, block_env_from_header,
+    callback_tag, callback_warning, fetch_block, gas_sensitive_warning, parse_block_arg,
+    parse_hex_bytes, parse_split_points, parse_u256, participant_balances, print_advice,
+    print_balances, print_callbacks, print_calls, print_gas_sensitive, print_new_accounts,
+    print_precompile_calls, print_removed, print_self_check, print_split_plans, print_state_diff,
+    print_transient, state_read_error, traced, volatile_tag, FutureBlock,
+    NEAREST_AVAILABLE_DEFAULT,
+};
 
 #[derive(Args)]
 pub struct GenerateArgs {
-    #[arg(long, default_value = "https://eth.llamarpc.com")]
-    pub rpc_url: String,
     #[arg(long)]
     pub from: String,
     #[arg(long)]
@@ -23,55 +37,195 @@ pub struct GenerateArgs {
     pub data: String,
     #[arg(long, default_value = "0")]
     pub value: String,
+    /// Block to simulate against: latest, pending, a number, or future:+N for a synthetic
+    /// block N past the latest (estimated number, timestamp and basefee over the latest state)
     #[arg(long, default_value = "latest")]
     pub block: String,
-    #[arg(long, default_value = "json", value_parser = ["json", "human"])]
-    pub output: String,
+    /// If the block's state is pruned, simulate against the nearest block within BLOCKS
+    /// that still has it [default BLOCKS: 64]
+    #[arg(long, value_name = "BLOCKS", num_args = 0..=1, default_missing_value = NEAREST_AVAILABLE_DEFAULT)]
+    pub nearest_available: Option<u64>,
+    /// Solc storage layout for a contract, as ADDRESS=PATH (repeatable); labels slots in human
+    /// output and under `slot_labels` in JSON
+    #[arg(long = "storage-layout", visible_alias = "labels")]
+    pub storage_layouts: Vec<String>,
+    /// Also report the state changes (balances, nonces, code, storage) the simulated
+    /// execution made; JSON output becomes `{"access_list", "state_diff"}`
+    #[arg(long)]
+    pub state_diff: bool,
+    /// Also report the logs the simulated execution emitted; JSON output gains a `logs` section
+    #[arg(long)]
+    pub logs: bool,
+    /// Contract ABI as ADDRESS=PATH (repeatable; bare ABI or forge/hardhat artifact); decodes
+    /// that contract's logs
+    #[arg(long = "abi", requires = "logs")]
+    pub abis: Vec<String>,
+    /// Also report net ETH and ERC-20 balance changes of the sender, target and coinbase
+    #[arg(long)]
+    pub balances: bool,
+    /// Also report the internal calls, each with the path `--entry-call` takes
+    #[arg(long)]
+    pub calls: bool,
+    /// Say why each dropped address was dropped and what declaring it would waste; JSON
+    /// output gains a `removed` section (human output always explains)
+    #[arg(long)]
+    pub explain_removed: bool,
+    /// Add an `advice` section to JSON output: access patterns the list can only partly
+    /// pay for, with what restructuring would help (human output advises unless --quiet)
+    #[arg(long)]
+    pub advise: bool,
+    /// Generate the list for the internal call at PATH (e.g. 0.1, from `--calls`) as if it
+    /// were the transaction, sent by its caller; JSON output gains an `entry_call` section
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["state_diff", "logs", "balances", "calls"])]
+    pub entry_call: Option<String>,
+    /// Plan sending the top-level calls as several transactions, each starting at one of
+    /// these call indices (e.g. 3,7; repeatable, one plan each); reports every plan's lists
+    /// and upfront cost next to the unsplit one. JSON output gains a `split_plans` section
+    #[arg(long = "split", value_name = "POINTS", conflicts_with = "entry_call")]
+    pub splits: Vec<String>,
+    /// Size budget for --split: most list entries (addresses plus storage keys) per transaction
+    #[arg(long, value_name = "ENTRIES", requires = "splits")]
+    pub max_size: Option<usize>,
+    /// Also trace the call with perturbed arguments and declare whatever any run touched;
+    /// the signature decodes --data (e.g. "swap(address,uint256,bool)")
+    #[arg(long, value_name = "SIGNATURE", requires = "fuzz_args", conflicts_with_all = ["entry_call", "splits"])]
+    pub fuzz_sig: Option<String>,
+    /// Argument to perturb for --fuzz-sig, as INDEX=LO..HI (uint arguments, inclusive) or
+    /// INDEX=V1,V2,... (any type); repeatable
+    #[arg(long = "fuzz-arg", value_name = "INDEX=RANGE", requires = "fuzz_sig")]
+    pub fuzz_args: Vec<String>,
+    /// Perturbed runs for --fuzz-sig, besides the call as given
+    #[arg(long, value_name = "N", default_value_t = 32, requires = "fuzz_sig")]
+    pub fuzz_runs: usize,
+    /// Seed for drawing --fuzz-arg values; the same seed gives the same runs
+    #[arg(long, value_name = "SEED", default_value_t = 0, requires = "fuzz_sig")]
+    pub fuzz_seed: u64,
+    /// Which touched entries to declare
+    #[arg(long, value_enum, default_value_t = StrategyArg::Complete, conflicts_with_all = ["entry_call", "splits", "fuzz_sig"])]
+    pub strategy: StrategyArg,
+    /// Trace the transaction a second time with the generated list declared and report
+    /// whether its calls and touched state changed (gas-dependent branches can change
+    /// them); JSON output gains a `self_check` section
+    #[arg(long, conflicts_with_all = ["entry_call", "splits", "fuzz_sig"])]
+    pub self_check: bool,
+    /// What JSON output carries: the access list, or a transaction request ready to sign
+    /// (`cast send --json`, ethers), with the list, fees and a gas limit traced with the
+    /// list declared
+    #[arg(long, value_enum, default_value_t = Emit::List, conflicts_with_all = ["entry_call", "splits"])]
+    pub emit: Emit,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+    #[command(flatten)]
+    pub block_overrides: BlockOverrideArgs,
+    #[command(flatten)]
+    pub state_overrides: StateOverrideArgs,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StrategyArg {
+    /// Every touched entry that is not warm by default
+    Complete,
+    /// Only entries whose cold touches save more than they cost upfront; dropped ones are
+    /// listed with --explain-removed
+    NetSavings,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Emit {
+    /// The access list array
+    List,
+    /// An EIP-1559 transaction request (EIP-2930 before London) carrying the list
+    Tx,
 }
 
-pub async fn run(args: GenerateArgs) -> Result<()> {
+/// Headroom `--emit tx` adds over the gas the transaction spent with its list: a call
+/// forwards at most 63/64 of the gas left, so a limit of exactly the gas spent can starve
+/// a nested call.
+const GAS_HEADROOM_PERCENT: u64 = 10;
+
+impl From<StrategyArg> for Strategy {
+    fn from(arg: StrategyArg) -> Self {
+        match arg {
+            StrategyArg::Complete => Strategy::Complete,
+            StrategyArg::NetSavings => Strategy::NetSavings,
+        }
+    }
+}
+
+pub async fn run(args: GenerateArgs, global: &GlobalArgs) -> Result<()> {
     // Validate all local arguments before any network calls.
+    let output = global.output("json", &["json", "human"])?;
+    if args.emit == Emit::Tx && output != "json" {
+        eyre::bail!("--emit tx writes JSON; drop --output {}", output);
+    }
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let mut config = args.optimizer.config()?;
+    let overrides = args.state_overrides.load()?;
+    let abis = events::load(&args.abis)?;
     let from: alloy_primitives::Address = args.from.parse().wrap_err("invalid --from")?;
     let to: alloy_primitives::Address = args.to.parse().wrap_err("invalid --to")?;
     let value = parse_u256(&args.value)?;
     let data = parse_hex_bytes(&args.data)?;
-    let block_id = parse_block_id(&args.block)?;
+    let (block_id, ahead) = parse_block_arg(&args.block)?;
+    let layouts = layout::load(&args.storage_layouts)?;
+    let entry_call: Option<CallPath> = args
+        .entry_call
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .wrap_err("invalid --entry-call")?;
+    // The unsplit batch comes first, as the baseline.
+    let split_points: Vec<Vec<usize>> = std::iter::once(Ok(Vec::new()))
+        .chain(args.splits.iter().map(|s| parse_split_points(s)))
+        .collect::<Result<_>>()?;
+    let variants = match &args.fuzz_sig {
+        Some(sig) => {
+            let function = alloy_json_abi::Function::parse(sig).wrap_err("invalid --fuzz-sig")?;
+            let fuzz_args = fuzz::parse_args(&function, &args.fuzz_args)?;
+            fuzz::variants(&function, &data, &fuzz_args, args.fuzz_runs, args.fuzz_seed)?
+        }
+        None => Vec::new(),
+    };
 
-    let url = Url::parse(&args.rpc_url).wrap_err("invalid RPC URL")?;
-    let provider = alloy_provider::ProviderBuilder::new()
-        .disable_recommended_fillers()
-        .connect_http(url)
-        .erased();
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
 
-    let block = provider
-        .get_block(block_id)
-        .await?
-        .ok_or_else(|| eyre::eyre!("Block not found"))?;
+    let (block, substitution) = fetch_block(&provider, block_id, args.nearest_available).await?;
+    timer.phase("block");
+    // Every later state read must target the block actually simulated.
+    let block_id = match substitution {
+        Some(s) => BlockId::number(s.used),
+        None => block_id,
+    };
 
     let header = &block.header;
     // Guard 3: Reject pre-Berlin blocks
     assert_post_berlin(header.number)?;
-    let block_env = BlockEnv {
-        number: U256::from(header.number),
-        beneficiary: header.beneficiary,
-        timestamp: U256::from(header.timestamp),
-        gas_limit: header.gas_limit,
-        basefee: header.base_fee_per_gas.unwrap_or(0),
-        difficulty: header.difficulty,
-        prevrandao: Some(header.mix_hash),
-        blob_excess_gas_and_price: header.excess_blob_gas.map(|excess| {
-            revm::context_interface::block::BlobExcessGasAndPrice::new(
-                excess,
-                revm::primitives::eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
-            )
-        }),
+    let future = ahead.map(|ahead| FutureBlock::after(header, ahead));
+    let number = future.map_or(header.number, |f| f.number);
+    apply_chain_rules(&mut config, global.chain_id()?, number);
+    let mut block_env = block_env_from_header(header);
+    if let Some(future) = &future {
+        future.apply(&mut block_env);
+    }
+    args.block_overrides.apply(&mut block_env);
+
+    let nonce = match overrides.0.get(&from).and_then(|o| o.nonce) {
+        Some(nonce) => nonce,
+        None => provider
+            .get_transaction_count(from)
+            .block_id(block_id)
+            .await
+            .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?,
     };
 
-    let nonce = provider
-        .get_transaction_count(from)
-        .block_id(block_id)
-        .await
-        .wrap_err("failed to fetch nonce")?;
+    // Fetched now: the provider moves into the state database below.
+    let fees = match args.emit {
+        Emit::Tx => Some(TxFees::fetch(&provider, global.chain_id()?, block_env.basefee).await?),
+        Emit::List => None,
+    };
 
     let gas_price = block_env.basefee.max(1_000_000_000) as u128;
     let tx_env = TxEnv::builder()
@@ -94,9 +248,10 @@ pub async fn run(args: GenerateArgs) -> Result<()> {
         ..Default::default()
     };
 
+    let call = tx_req.clone();
     let state_block_id = BlockId::hash(header.hash);
 
-    let db = super::prefetch::build(
+    let mut db = super::prefetch::build(
         provider,
         state_block_id,
         state_block_id,
@@ -105,25 +260,336 @@ pub async fn run(args: GenerateArgs) -> Result<()> {
     )
     .await
     .wrap_err("prefetch failed")?;
+    overrides
+        .apply(&mut db)
+        .wrap_err("failed to apply state overrides")?;
+    timer.phase("state");
+
+    let coinbase = block_env.beneficiary;
+    let (listed_tx, listed_block) = (tx_env.clone(), block_env.clone());
+    let (entry, mut optimal, plans, padded, check) = traced(header.number, || match &entry_call {
+        Some(path) => generate_entry_call(&mut db, tx_env, block_env, path, &config)
+            .map(|(frame, optimal)| (Some(frame), optimal, Vec::new(), None, None)),
+        None if !args.splits.is_empty() => generate_split_plans(
+            &mut db,
+            tx_env,
+            block_env,
+            &split_points,
+            args.max_size,
+            &config,
+        )
+        .map(|(optimal, plans)| (None, optimal, plans, None, None)),
+        None if args.fuzz_sig.is_some() => {
+            generate_padded(&mut db, tx_env, block_env, &variants, &config)
+                .map(|(optimal, padded)| (None, optimal, Vec::new(), Some(padded), None))
+        }
+        None if args.self_check => {
+            generate_self_checked(&mut db, tx_env, block_env, &config, args.strategy.into())
+                .map(|(optimal, check)| (None, optimal, Vec::new(), None, Some(check)))
+        }
+        None => generate_with_strategy(&mut db, tx_env, block_env, &config, args.strategy.into())
+            .map(|optimal| (None, optimal, Vec::new(), None, None)),
+    })
+    .wrap_err("access list generation failed")?;
+    // The padded list is the one to send; the rest of the report is the call as given.
+    if let Some(padded) = &padded {
+        optimal.list = padded.list.clone();
+    }
+    let request = match fees {
+        Some(fees) => {
+            let gas = traced(header.number, || {
+                listed_gas(&mut db, listed_tx, listed_block, &optimal.list)
+            })
+            .wrap_err("tracing with the list declared failed")?;
+            Some(fees.request(call, nonce, optimal.list.clone(), gas))
+        }
+        None => None,
+    };
+    timer.phase("trace");
+    timer.total();
 
-    let optimal = generate(db, tx_env, block_env).wrap_err("access list generation failed")?;
+    let balances = participant_balances(
+        &optimal,
+        &[("from", from), ("to", to), ("coinbase", coinbase)],
+    );
 
-    match args.output.as_str() {
-        "json" => println!("{}", serde_json::to_string_pretty(&optimal.list)?),
+    match output {
+        "json" => {
+            let mut sections = Vec::new();
+            if args.state_diff {
+                sections.push(("state_diff", serde_json::to_value(&optimal.state_diff)?));
+            }
+            if args.logs {
+                sections.push(("logs", events::to_json(&abis, &optimal.logs)));
+            }
+            if args.balances {
+                sections.push(("balance_changes", balances_json(&balances)?));
+            }
+            if args.calls {
+                sections.push(("calls", serde_json::to_value(&optimal.calls)?));
+            }
+            if args.explain_removed {
+                sections.push(("removed", serde_json::to_value(&optimal.removed)?));
+                sections.push(("warm_kept", serde_json::to_value(&optimal.warm_kept)?));
+            }
+            if args.advise {
+                sections.push(("advice", serde_json::to_value(advise(&optimal.list))?));
+            }
+            if let Some(frame) = &entry {
+                sections.push(("entry_call", serde_json::to_value(frame)?));
+            }
+            if let Some(labels) = layout::to_json(&layouts, &[&optimal.list], &[from, to, coinbase])
+            {
+                sections.push(("slot_labels", labels));
+            }
+            if !args.splits.is_empty() {
+                sections.push(("split_plans", serde_json::to_value(&plans)?));
+            }
+            if let Some(padded) = &padded {
+                sections.push(("fuzz", fuzz::to_json(padded)));
+            }
+            if let Some(check) = &check {
+                sections.push(("self_check", serde_json::to_value(check)?));
+            }
+            if let Some(f) = future {
+                sections.push(("synthetic_block", f.to_json()));
+            }
+            // The output shape is unchanged; notices go to stderr.
+            if !global.quiet {
+                if let Some(s) = substitution {
+                    eprintln!("{}", s.notice());
+                }
+                if let Some(warning) = callback_warning(&optimal.callbacks) {
+                    eprintln!("{}", warning);
+                }
+                if let Some(warning) = gas_sensitive_warning(&optimal.gas_sensitive) {
+                    eprintln!("{}", warning);
+                }
+                if check.as_ref().is_some_and(|c| !c.is_consistent()) {
+                    eprintln!("warning: the transaction behaves differently with the list declared; see `self_check`");
+                }
+            }
+            let out = match &request {
+                Some(request) => transaction_json(request, sections)?,
+                None => access_list_json(&optimal, sections)?,
+            };
+            attest::write_report(out, signer.as_ref(), &sink).await?
+        }
         "human" => {
+            if let Some(s) = substitution {
+                println!("{}", s.notice());
+            }
+            if let Some(f) = future {
+                println!("{}", f.notice());
+            }
+            if let Some(frame) = &entry {
+                println!(
+                    "Entry call {}: {} {} → {}",
+                    frame.path,
+                    frame.kind.as_str(),
+                    frame.caller,
+                    frame.target
+                );
+            }
             let cost = access_list_gas_cost(&optimal.list);
-            println!("Access list (gas cost: {}):", cost);
+            println!("Access list (cost: {}):", units.gas(cost));
             for item in &optimal.list.0 {
-                println!("  {}:", item.address);
+                println!(
+                    "  {}:{}{}",
+                    item.address,
+                    callback_tag(&optimal.callbacks, &item.address, None),
+                    fuzz::hit_tag(padded.as_ref(), &item.address, None)
+                );
                 for key in &item.storage_keys {
-                    println!("    - {}", key);
+                    println!(
+                        "    - {}{}{}{}",
+                        key,
+                        callback_tag(&optimal.callbacks, &item.address, Some(key)),
+                        volatile_tag(&optimal.volatile, &item.address, key),
+                        fuzz::hit_tag(padded.as_ref(), &item.address, Some(key))
+                    );
                 }
             }
-            if !optimal.removed_addresses.is_empty() {
-                println!("Removed (warm): {:?}", optimal.removed_addresses);
+            if let Some(padded) = &padded {
+                fuzz::print_summary(padded);
             }
+            print_removed(&optimal, &units);
+            if let Some(check) = &check {
+                print_self_check(check);
+            }
+            if !global.quiet {
+                print_new_accounts(&optimal.new_accounts, &units);
+                print_precompile_calls(&optimal.precompile_calls);
+                print_callbacks(&optimal.callbacks);
+                print_gas_sensitive(&optimal.gas_sensitive);
+                print_transient(&optimal.transient);
+                print_advice(&advise(&optimal.list), &units);
+            }
+            if args.state_diff {
+                print_state_diff(&optimal.state_diff);
+            }
+            if args.logs {
+                events::print_logs(&abis, &optimal.logs);
+            }
+            if args.balances {
+                print_balances(&balances);
+            }
+            if args.calls {
+                print_calls(&optimal.calls);
+            }
+            if !args.splits.is_empty() {
+                print_split_plans(&plans, args.max_size, &units);
+            }
+            layout::print_labels(&layouts, &[&optimal.list], &[from, to, coinbase]);
         }
         _ => unreachable!(),
     }
     Ok(())
 }
+
+/// Chain id and fees for `--emit tx`.
+struct TxFees {
+    chain_id: u64,
+    /// Priority fee after London; the legacy gas price before it.
+    price: u128,
+    /// Basefee of the simulated block; zero before London.
+    basefee: u128,
+}
+
+impl TxFees {
+    async fn fetch(
+        provider: &alloy_provider::DynProvider,
+        chain_id: Option<u64>,
+        basefee: u64,
+    ) -> Result<Self> {
+        let chain_id = match chain_id {
+            Some(id) => id,
+            None => provider
+                .get_chain_id()
+                .await
+                .wrap_err("failed to fetch chain id")?,
+        };
+        let price = if basefee > 0 {
+            provider.get_max_priority_fee_per_gas().await
+        } else {
+            provider.get_gas_price().await
+        }
+        .wrap_err("failed to fetch fees")?;
+        Ok(Self {
+            chain_id,
+            price,
+            basefee: basefee as u128,
+        })
+    }
+
+    /// `call` sent with `list` as a signable request: type 2 after London, type 1 before.
+    /// The max fee is twice the basefee plus the priority fee, as wallets default it.
+    fn request(
+        &self,
+        call: TransactionRequest,
+        nonce: u64,
+        list: hammer_core::AccessList,
+        gas: u64,
+    ) -> TransactionRequest {
+        let mut request = TransactionRequest {
+            nonce: Some(nonce),
+            chain_id: Some(self.chain_id),
+            gas: Some(gas),
+            access_list: Some(list),
+            ..call
+        };
+        if self.basefee > 0 {
+            request.transaction_type = Some(TransactionType::Eip1559 as u8);
+            request.max_priority_fee_per_gas = Some(self.price);
+            request.max_fee_per_gas = Some(2 * self.basefee + self.price);
+        } else {
+            request.transaction_type = Some(TransactionType::Eip2930 as u8);
+            request.gas_price = Some(self.price);
+        }
+        request
+    }
+}
+
+/// The gas limit for `tx` sent with `list`: what it spends (before refunds, which are
+/// paid only at the end) traced with the list declared, plus [`GAS_HEADROOM_PERCENT`],
+/// within the block's gas limit.
+fn listed_gas(
+    db: &mut super::prefetch::PrewarmedDB,
+    tx: TxEnv,
+    block: revm::context::BlockEnv,
+    list: &hammer_core::AccessList,
+) -> Result<u64, hammer_core::HammerError> {
+    let block_gas_limit = block.gas_limit;
+    let listed = TxEnv {
+        access_list: list.clone(),
+        tx_type: TransactionType::Eip2930 as u8,
+        ..tx
+    };
+    let raw = hammer_core::generate_access_list(db, listed, block, false)?;
+    let spent = raw.gas_used + raw.gas_refunded;
+    Ok((spent + spent * GAS_HEADROOM_PERCENT / 100).min(block_gas_limit))
+}
+
+/// JSON output for `--emit tx`: the request alone, or under `transaction` next to the
+/// requested sections.
+fn transaction_json(
+    request: &TransactionRequest,
+    sections: Vec<(&str, serde_json::Value)>,
+) -> Result<serde_json::Value> {
+    let request = serde_json::to_value(request)?;
+    if sections.is_empty() {
+        return Ok(request);
+    }
+    let mut out = serde_json::Map::new();
+    out.insert("transaction".to_string(), request);
+    for (name, value) in sections {
+        out.insert(name.to_string(), value);
+    }
+    Ok(out.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256, U256};
+    use hammer_core::{AccessList, AccessListItem};
+    use serde_json::json;
+
+    #[test]
+    fn test_emitted_request() {
+        let call = TransactionRequest {
+            from: Some(Address::with_last_byte(1)),
+            to: Some(TxKind::Call(Address::with_last_byte(2))),
+            value: Some(U256::from(5)),
+            input: alloy_rpc_types_eth::TransactionInput::new(vec![0xab].into()),
+            gas: Some(30_000_000),
+            ..Default::default()
+        };
+        let list = AccessList(vec![AccessListItem {
+            address: Address::with_last_byte(3),
+            storage_keys: vec![B256::ZERO],
+        }]);
+        let fees = TxFees {
+            chain_id: 1,
+            price: 2,
+            basefee: 10,
+        };
+        let request =
+            serde_json::to_value(fees.request(call.clone(), 7, list.clone(), 50_000)).unwrap();
+        assert_eq!(request["type"], json!("0x2"));
+        assert_eq!(request["gas"], json!("0xc350"));
+        assert_eq!(request["nonce"], json!("0x7"));
+        assert_eq!(request["chainId"], json!("0x1"));
+        assert_eq!(request["maxFeePerGas"], json!("0x16"));
+        assert_eq!(request["maxPriorityFeePerGas"], json!("0x2"));
+        assert_eq!(request["input"], json!("0xab"));
+        assert_eq!(request["accessList"], serde_json::to_value(&list).unwrap());
+        assert!(request.get("gasPrice").is_none());
+
+        let berlin = TxFees { basefee: 0, ..fees };
+        let request = serde_json::to_value(berlin.request(call, 7, list, 50_000)).unwrap();
+        assert_eq!(request["type"], json!("0x1"));
+        assert_eq!(request["gasPrice"], json!("0x2"));
+        assert!(request.get("maxFeePerGas").is_none());
+    }
+}
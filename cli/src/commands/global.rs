@@ -0,0 +1,281 @@
+//! Options shared by every subcommand, parsed once in `main.rs`.
+//!
+//! They are declared `global`, so `hammer --output human generate ...` and
+//! `hammer generate ... --output human` are equivalent; a value given after the
+//! subcommand overrides one given before it.
+
+use alloy::network::Ethereum;
+use alloy::signers::local::PrivateKeySigner;
+use alloy_provider::{DynProvider, Provider};
+use clap::Args;
+use eyre::{Context, Result};
+use reqwest::Url;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::attest;
+use super::cache::StateCache;
+use super::sink::Sink;
+use super::units::Units;
+
+/// Public endpoint used when `--rpc-url` is omitted on mainnet.
+const DEFAULT_MAINNET_RPC: &str = "https://eth.llamarpc.com";
+
+/// Chain names accepted by `--chain`, with their chain ids.
+const CHAINS: &[(&str, u64)] = &[
+    ("mainnet", 1),
+    ("sepolia", 11_155_111),
+    ("holesky", 17_000),
+    ("hoodi", 560_048),
+];
+
+/// A chain by name (see `--chain`) or id.
+pub fn parse_chain(chain: &str) -> Result<u64> {
+    if let Some((_, id)) = CHAINS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(chain))
+    {
+        return Ok(*id);
+    }
+    chain
+        .parse::<u64>()
+        .wrap_err_with(|| format!("unknown chain {}: expected a name or chain id", chain))
+}
+
+#[derive(Args, Clone, Debug, Default)]
+pub struct GlobalArgs {
+    /// JSON-RPC endpoint [default: https://eth.llamarpc.com on mainnet]
+    #[arg(long, global = true)]
+    pub rpc_url: Option<String>,
+    /// Output format; each subcommand supports a subset and has its own default
+    #[arg(long, global = true, value_parser = ["json", "human", "markdown", "html", "ndjson"])]
+    pub output: Option<String>,
+    /// Expected chain, by name (mainnet, sepolia, holesky, hoodi) or id; the RPC
+    /// endpoint's chain id is checked against it
+    #[arg(long, global = true)]
+    pub chain: Option<String>,
+    /// Print results only: no logs (even with RUST_LOG set) or informational notes
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+    /// Print how long each phase took to stderr
+    #[arg(long, global = true)]
+    pub timings: bool,
+    /// Unit for gas amounts in human output; gwei and eth need --gas-price, usd also --eth-price
+    #[arg(long, global = true, value_parser = ["gas", "gwei", "eth", "usd"])]
+    pub units: Option<String>,
+    /// Gas price in gwei for converting amounts with --units
+    #[arg(long, global = true, value_name = "GWEI")]
+    pub gas_price: Option<f64>,
+    /// ETH price in USD for --units usd
+    #[arg(long, global = true, value_name = "USD")]
+    pub eth_price: Option<f64>,
+    /// Group digits of amounts in human output with thousands separators (1,900,137)
+    #[arg(long, global = true)]
+    pub separators: bool,
+    /// Sign JSON reports with the hex private key in PATH; check them with `hammer
+    /// verify-signature`
+    #[arg(long, global = true, value_name = "PATH")]
+    pub sign_key: Option<PathBuf>,
+    /// Write JSON reports to a file, s3://BUCKET/KEY or an http(s):// endpoint instead
+    /// of stdout
+    #[arg(long, global = true, value_name = "URI")]
+    pub out: Option<String>,
+    /// Neither read nor write the on-disk state cache (compare, compare-block)
+    #[arg(long, global = true)]
+    pub no_cache: bool,
+}
+
+impl GlobalArgs {
+    /// The output format for a subcommand, falling back to its `default`.
+    pub fn output(&self, default: &'static str, supported: &[&str]) -> Result<&str> {
+        let output = self.output.as_deref().unwrap_or(default);
+        if !supported.contains(&output) {
+            eyre::bail!(
+                "--output {} is not supported by this command (expected one of: {})",
+                output,
+                supported.join(", ")
+            );
+        }
+        Ok(output)
+    }
+
+    /// The chain id named by `--chain`, if given.
+    pub fn chain_id(&self) -> Result<Option<u64>> {
+        let Some(chain) = self.chain.as_deref() else {
+            return Ok(None);
+        };
+        parse_chain(chain)
+            .map(Some)
+            .map_err(|_| eyre::eyre!("unknown --chain {}: expected a name or chain id", chain))
+    }
+
+    /// The RPC endpoint: `--rpc-url` if given, else the public default for mainnet.
+    pub fn rpc_url(&self) -> Result<Url> {
+        match &self.rpc_url {
+            Some(url) => Url::parse(url).wrap_err("invalid RPC URL"),
+            None => match self.chain_id()? {
+                None | Some(1) => Ok(Url::parse(DEFAULT_MAINNET_RPC)?),
+                Some(id) => eyre::bail!("no default RPC endpoint for chain {}; pass --rpc-url", id),
+            },
+        }
+    }
+
+    /// Connect to the RPC endpoint, checking its chain id when `--chain` is given.
+    pub async fn provider(&self) -> Result<DynProvider<Ethereum>> {
+        let expected = self.chain_id()?;
+        let provider = alloy_provider::ProviderBuilder::new()
+            .disable_recommended_fillers()
+            .connect_http(self.rpc_url()?)
+            .erased();
+        if let Some(expected) = expected {
+            let actual = provider
+                .get_chain_id()
+                .await
+                .wrap_err("failed to fetch chain id")?;
+            if actual != expected {
+                eyre::bail!(
+                    "RPC endpoint is on chain {} but --chain expects {}",
+                    actual,
+                    expected
+                );
+            }
+        }
+        Ok(provider)
+    }
+
+    /// The key from `--sign-key`, if given.
+    pub fn signer(&self) -> Result<Option<PrivateKeySigner>> {
+        self.sign_key.as_deref().map(attest::load_key).transpose()
+    }
+
+    /// Reject `--out` and `--sign-key` on a command that writes no JSON report, and
+    /// `--sign-key` on one whose reports are not signed.
+    pub fn report_flags(&self, writes_reports: bool, signs_reports: bool) -> Result<()> {
+        if self.out.is_some() && !writes_reports {
+            eyre::bail!("--out is not supported by this command: it writes no JSON report");
+        }
+        if self.sign_key.is_some() && !signs_reports {
+            eyre::bail!("--sign-key is not supported by this command: it writes no signed report");
+        }
+        Ok(())
+    }
+
+    /// Where JSON reports go (`--out`), stdout by default.
+    pub fn sink(&self) -> Result<Sink> {
+        self.out.as_deref().map_or(Ok(Sink::Stdout), Sink::parse)
+    }
+
+    /// The on-disk state cache, unless `--no-cache` is set or there is no directory for
+    /// it.
+    pub fn state_cache(&self) -> Option<StateCache> {
+        if self.no_cache {
+            return None;
+        }
+        StateCache::default_dir().map(StateCache::new)
+    }
+
+    /// How human output shows gas amounts (`--units`).
+    pub fn units(&self) -> Result<Units> {
+        Units::from_global(self)
+    }
+
+    /// A phase timer that reports to stderr when `--timings` is set.
+    pub fn timer(&self) -> Timer {
+        Timer::new(self.timings)
+    }
+}
+
+/// Wall-clock timing of a command's phases (`--timings`).
+pub struct Timer {
+    enabled: bool,
+    start: Instant,
+    last: Instant,
+}
+
+impl Timer {
+    fn new(enabled: bool) -> Self {
+        let now = Instant::now();
+        Self {
+            enabled,
+            start: now,
+            last: now,
+        }
+    }
+
+    /// Report the time since the previous phase ended.
+    pub fn phase(&mut self, name: &str) {
+        let now = Instant::now();
+        if self.enabled {
+            eprintln!("[timing] {:<10} {}", name, format_duration(now - self.last));
+        }
+        self.last = now;
+    }
+
+    /// Report the time since the timer started.
+    pub fn total(&self) {
+        if self.enabled {
+            eprintln!(
+                "[timing] {:<10} {}",
+                "total",
+                format_duration(self.start.elapsed())
+            );
+        }
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    format!("{:.1}ms", d.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(f: impl FnOnce(&mut GlobalArgs)) -> GlobalArgs {
+        let mut g = GlobalArgs::default();
+        f(&mut g);
+        g
+    }
+
+    #[test]
+    fn test_output_default_and_unsupported() {
+        let g = GlobalArgs::default();
+        assert_eq!(g.output("json", &["json", "human"]).unwrap(), "json");
+        let g = with(|g| g.output = Some("markdown".into()));
+        let err = g.output("json", &["json", "human"]).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("--output markdown is not supported"));
+    }
+
+    #[test]
+    fn test_chain_id_by_name_and_number() {
+        assert_eq!(GlobalArgs::default().chain_id().unwrap(), None);
+        let g = with(|g| g.chain = Some("Sepolia".into()));
+        assert_eq!(g.chain_id().unwrap(), Some(11_155_111));
+        let g = with(|g| g.chain = Some("8453".into()));
+        assert_eq!(g.chain_id().unwrap(), Some(8453));
+        let g = with(|g| g.chain = Some("nope".into()));
+        assert!(g.chain_id().is_err());
+    }
+
+    #[test]
+    fn test_rpc_url_default_only_on_mainnet() {
+        assert_eq!(
+            GlobalArgs::default().rpc_url().unwrap().as_str(),
+            "https://eth.llamarpc.com/"
+        );
+        let g = with(|g| g.chain = Some("sepolia".into()));
+        assert!(g
+            .rpc_url()
+            .unwrap_err()
+            .to_string()
+            .contains("pass --rpc-url"));
+        let g = with(|g| g.rpc_url = Some("not-a-url".into()));
+        assert!(g
+            .rpc_url()
+            .unwrap_err()
+            .to_string()
+            .contains("invalid RPC URL"));
+    }
+}
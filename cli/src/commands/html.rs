@@ -0,0 +1,205 @@
+//! Standalone HTML report for `compare --output html`, rendered from the same rows as
+//! the markdown output. No scripts or external assets: the file can be attached to a
+//! ticket or opened straight from disk.
+
+use hammer_core::DiffEntry;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::compare::{execution_penalty, issue_row, Comparison};
+
+const STYLE: &str = "\
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 960px; color: #1f2328; }
+code { font-family: ui-monospace, monospace; font-size: 0.9em; }
+table { border-collapse: collapse; margin: 0.5rem 0 1rem; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; text-align: left; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+.bar { display: flex; align-items: center; gap: 0.5rem; margin: 0.2rem 0; }
+.bar .label { width: 7rem; }
+.bar .fill { background: #cf222e; height: 0.9rem; }
+details { margin: 0.2rem 0; }
+summary { cursor: pointer; }
+";
+
+/// The complete HTML document for a set of comparisons.
+pub(super) fn render(compared: &[Comparison], failed: usize) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "<!DOCTYPE html>");
+    let _ = writeln!(out, "<html lang=\"en\">");
+    let _ = writeln!(out, "<head>");
+    let _ = writeln!(out, "<meta charset=\"utf-8\">");
+    let _ = writeln!(out, "<title>hammer access list report</title>");
+    let _ = writeln!(out, "<style>\n{}</style>", STYLE);
+    let _ = writeln!(out, "</head>");
+    let _ = writeln!(out, "<body>");
+    let _ = writeln!(out, "<h1>Access list report</h1>");
+    if compared.len() > 1 || failed > 0 {
+        summary(&mut out, compared, failed);
+    }
+    for c in compared {
+        transaction(&mut out, c);
+    }
+    let _ = writeln!(out, "</body>");
+    let _ = writeln!(out, "</html>");
+    out
+}
+
+fn summary(out: &mut String, compared: &[Comparison], failed: usize) {
+    let _ = writeln!(out, "<h2>Summary</h2>");
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(
+        out,
+        "<tr><th>Chain</th><th>Transaction</th><th>Origin</th><th>Declared</th><th>Optimal</th><th>Upfront waste</th><th>Runtime penalty</th></tr>"
+    );
+    for c in compared {
+        let s = &c.report.gas_summary;
+        let _ = writeln!(
+            out,
+            "<tr><td class=\"num\">{}</td><td><a href=\"#{hash}\"><code>{hash}</code></a></td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            c.chain_id,
+            c.report.declared_origin.as_str(),
+            s.declared_list_cost,
+            s.optimal_list_cost,
+            s.waste_per_tx,
+            execution_penalty(&c.report),
+            hash = c.tx_hash,
+        );
+    }
+    let _ = writeln!(out, "</table>");
+    if failed > 0 {
+        let _ = writeln!(
+            out,
+            "<p>{} transaction(s) could not be compared.</p>",
+            failed
+        );
+    }
+}
+
+fn transaction(out: &mut String, c: &Comparison) {
+    let report = &c.report;
+    let s = &report.gas_summary;
+    let _ = writeln!(
+        out,
+        "<h2 id=\"{hash}\"><code>{hash}</code> (chain {}, block {})</h2>",
+        c.chain_id,
+        c.block_number,
+        hash = c.tx_hash,
+    );
+    let _ = writeln!(out, "<table>");
+    let _ = writeln!(out, "<tr><th></th><th>Gas</th></tr>");
+    for (name, gas) in [
+        ("Declared list", s.declared_list_cost as i64),
+        ("Optimal list", s.optimal_list_cost as i64),
+        ("Upfront waste", s.waste_per_tx),
+        ("Runtime penalty", execution_penalty(report) as i64),
+    ] {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td class=\"num\">{}</td></tr>",
+            name, gas
+        );
+    }
+    let _ = writeln!(out, "</table>");
+    let _ = writeln!(
+        out,
+        "<p>Declared list origin: <code>{}</code></p>",
+        report.declared_origin.as_str()
+    );
+    if report.is_valid {
+        let _ = writeln!(out, "<p>No issues: the declared list is optimal.</p>");
+        return;
+    }
+
+    let mut by_kind: BTreeMap<&str, u64> = BTreeMap::new();
+    for e in &report.entries {
+        *by_kind.entry(issue_row(e).0).or_default() += e.gas_waste();
+    }
+    let _ = writeln!(out, "<h3>Waste by issue</h3>");
+    bars(out, &by_kind);
+
+    let _ = writeln!(out, "<h3>Issues</h3>");
+    for e in &report.entries {
+        issue(out, e);
+    }
+}
+
+/// Horizontal bar chart, scaled to the largest value.
+fn bars(out: &mut String, values: &BTreeMap<&str, u64>) {
+    let max = values.values().copied().max().unwrap_or(0).max(1);
+    for (label, value) in values {
+        let _ = writeln!(
+            out,
+            "<div class=\"bar\"><span class=\"label\">{}</span><span class=\"fill\" style=\"width: {:.1}%\"></span><span>{} gas</span></div>",
+            label,
+            *value as f64 * 60.0 / max as f64,
+            value
+        );
+    }
+}
+
+/// One finding, expandable to the slots it concerns.
+fn issue(out: &mut String, entry: &DiffEntry) {
+    let (kind, address, slots) = issue_row(entry);
+    let _ = write!(
+        out,
+        "<details><summary>{} <code>{}</code>: {} slots, {} gas</summary>",
+        kind,
+        address,
+        slots.len(),
+        entry.gas_waste()
+    );
+    if !slots.is_empty() {
+        let _ = write!(out, "<ul>");
+        for slot in slots {
+            let _ = write!(out, "<li><code>{}</code></li>", slot);
+        }
+        let _ = write!(out, "</ul>");
+    }
+    let _ = writeln!(out, "</details>");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+
+    #[test]
+    fn test_issue_expands_to_slots() {
+        let mut out = String::new();
+        issue(
+            &mut out,
+            &DiffEntry::Stale {
+                address: Address::repeat_byte(0xaa),
+                storage_keys: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+                gas_waste: 3800,
+            },
+        );
+        assert!(out.starts_with(&format!(
+            "<details><summary>stale <code>{}</code>",
+            Address::repeat_byte(0xaa)
+        )));
+        assert!(out.contains("2 slots, 3800 gas</summary><ul>"));
+        assert_eq!(out.matches("<li>").count(), 2);
+    }
+
+    #[test]
+    fn test_redundant_issue_has_no_slot_list() {
+        let mut out = String::new();
+        issue(
+            &mut out,
+            &DiffEntry::Redundant {
+                address: Address::ZERO,
+                gas_waste: 2400,
+            },
+        );
+        assert!(!out.contains("<ul>"));
+    }
+
+    #[test]
+    fn test_bars_scale_to_largest() {
+        let mut out = String::new();
+        bars(&mut out, &BTreeMap::from([("missing", 100), ("stale", 50)]));
+        assert!(out.contains("missing</span><span class=\"fill\" style=\"width: 60.0%\">"));
+        assert!(out.contains("stale</span><span class=\"fill\" style=\"width: 30.0%\">"));
+    }
+}
@@ -0,0 +1,94 @@
+//! `--storage-layout ADDRESS=PATH` (alias `--labels`) handling shared by generate, validate,
+//! and compare.
+
+use alloy_primitives::{Address, B256};
+use alloy_rpc_types_eth::AccessList;
+use eyre::{Context, Result};
+use hammer_core::{SlotResolver, StorageLayout};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Storage layouts keyed by the contract address they describe.
+pub type Layouts = BTreeMap<Address, StorageLayout>;
+
+/// Parse and load every `ADDRESS=PATH` spec. Runs before any network call.
+pub fn load(specs: &[String]) -> Result<Layouts> {
+    let mut layouts = Layouts::new();
+    for spec in specs {
+        let (addr, path) = spec.split_once('=').ok_or_else(|| {
+            eyre::eyre!("invalid --storage-layout {}: expected ADDRESS=PATH", spec)
+        })?;
+        let addr: Address = addr
+            .parse()
+            .wrap_err_with(|| format!("invalid --storage-layout address {}", addr))?;
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read storage layout {}", path))?;
+        let layout: StorageLayout = serde_json::from_str(&text)
+            .wrap_err_with(|| format!("invalid storage layout in {}", path))?;
+        layouts.insert(addr, layout);
+    }
+    Ok(layouts)
+}
+
+/// Variable expression of every slot in `lists` that belongs to a contract with a loaded
+/// layout, or `None` where the layout cannot explain the slot. Mapping keys are matched
+/// against `participants` and all listed addresses.
+pub fn labels(
+    layouts: &Layouts,
+    lists: &[&AccessList],
+    participants: &[Address],
+) -> BTreeMap<Address, BTreeMap<B256, Option<String>>> {
+    let mut slots: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    let mut keys: BTreeSet<Address> = participants.iter().copied().collect();
+    for list in lists {
+        for item in list.0.iter() {
+            keys.insert(item.address);
+            if layouts.contains_key(&item.address) {
+                slots
+                    .entry(item.address)
+                    .or_default()
+                    .extend(item.storage_keys.iter().copied());
+            }
+        }
+    }
+    let keys: Vec<Address> = keys.into_iter().collect();
+
+    slots
+        .into_iter()
+        .map(|(addr, addr_slots)| {
+            let resolver = SlotResolver::new(&layouts[&addr], &keys);
+            let resolved = addr_slots
+                .into_iter()
+                .map(|slot| (slot, resolver.resolve(slot)))
+                .collect();
+            (addr, resolved)
+        })
+        .collect()
+}
+
+/// [`labels`] for JSON output: `{address: {slot: label}}`, with `null` for slots the layout
+/// cannot explain. `None` when no layout is loaded, so reports without one keep their shape.
+pub fn to_json(
+    layouts: &Layouts,
+    lists: &[&AccessList],
+    participants: &[Address],
+) -> Option<serde_json::Value> {
+    if layouts.is_empty() {
+        return None;
+    }
+    Some(serde_json::json!(labels(layouts, lists, participants)))
+}
+
+/// Print the variable expression of every slot in `lists` that belongs to a contract with a
+/// loaded layout. Mapping keys are matched against `participants` and all listed addresses.
+pub fn print_labels(layouts: &Layouts, lists: &[&AccessList], participants: &[Address]) {
+    if layouts.is_empty() {
+        return;
+    }
+    println!("Storage layout:");
+    for (addr, addr_slots) in labels(layouts, lists, participants) {
+        println!("  {}:", addr);
+        for (slot, label) in addr_slots {
+            println!("    {}  {}", slot, label.as_deref().unwrap_or("?"));
+        }
+    }
+}
@@ -0,0 +1,277 @@
+//! Limits `serve` puts on each request, and API keys with per-key quotas, so the server
+//! can face callers it does not trust.
+//!
+//! Size limits are checked before any state is read: the body as it arrives, the batch
+//! once parsed, each call's input and gas once its params are. The number of entries a
+//! trace produced can only be checked after it ran. Quotas count JSON-RPC requests (each
+//! call of a batch) per key in fixed one-minute windows.
+
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::AccessList;
+use hyper::StatusCode;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Quota window.
+const WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Args, Clone, Debug)]
+pub struct LimitArgs {
+    /// Largest request body accepted, in bytes
+    #[arg(long, default_value_t = 1 << 20)]
+    pub max_body_bytes: usize,
+    /// Most requests accepted in one batch
+    #[arg(long)]
+    pub max_batch: Option<usize>,
+    /// Largest call input (calldata or initcode) accepted, in bytes
+    #[arg(long)]
+    pub max_calldata_bytes: Option<usize>,
+    /// Highest `gas` a call may set; also the gas of calls that set none, if below 30M
+    #[arg(long)]
+    pub max_gas: Option<u64>,
+    /// Most entries (addresses plus slots) a traced list may hold; larger results are
+    /// refused
+    #[arg(long)]
+    pub max_trace_entries: Option<usize>,
+    /// File of API keys, one per line as `KEY [REQUESTS_PER_MINUTE]`; requests must then
+    /// carry one in `x-api-key` or `Authorization: Bearer`
+    #[arg(long, value_name = "PATH")]
+    pub api_keys: Option<PathBuf>,
+}
+
+/// A request, or part of one, over one of the `--max-*` limits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LimitExceeded {
+    /// The flag setting the limit, without its dashes.
+    pub limit: &'static str,
+    pub max: u64,
+    pub actual: u64,
+}
+
+impl std::fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is over the server's limit of {} (--{})",
+            self.actual, self.max, self.limit
+        )
+    }
+}
+
+impl std::error::Error for LimitExceeded {}
+
+impl LimitExceeded {
+    /// The HTTP status to answer with: 413 for what is too large to accept, 422 for what
+    /// is too expensive to serve.
+    pub fn status(&self) -> StatusCode {
+        match self.limit {
+            "max-body-bytes" | "max-batch" | "max-calldata-bytes" => StatusCode::PAYLOAD_TOO_LARGE,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+
+    /// The JSON-RPC error's `data`.
+    pub fn data(&self) -> Value {
+        json!({"limit": self.limit, "max": self.max, "actual": self.actual})
+    }
+}
+
+fn check(limit: &'static str, max: Option<u64>, actual: u64) -> Result<(), LimitExceeded> {
+    match max {
+        Some(max) if actual > max => Err(LimitExceeded { limit, max, actual }),
+        _ => Ok(()),
+    }
+}
+
+impl LimitArgs {
+    pub fn check_body(&self, len: usize) -> Result<(), LimitExceeded> {
+        check(
+            "max-body-bytes",
+            Some(self.max_body_bytes as u64),
+            len as u64,
+        )
+    }
+
+    pub fn check_batch(&self, len: usize) -> Result<(), LimitExceeded> {
+        check("max-batch", self.max_batch.map(|m| m as u64), len as u64)
+    }
+
+    /// Check a call's input length and the gas it sets, if any.
+    pub fn check_call(&self, input_len: usize, gas: Option<u64>) -> Result<(), LimitExceeded> {
+        check(
+            "max-calldata-bytes",
+            self.max_calldata_bytes.map(|m| m as u64),
+            input_len as u64,
+        )?;
+        check("max-gas", self.max_gas, gas.unwrap_or_default())
+    }
+
+    /// The gas of a call that sets none.
+    pub fn default_gas(&self, default: u64) -> u64 {
+        self.max_gas.map_or(default, |max| max.min(default))
+    }
+
+    /// Check the size of a traced list.
+    pub fn check_trace(&self, list: &AccessList) -> Result<(), LimitExceeded> {
+        let entries: usize = list.0.iter().map(|i| 1 + i.storage_keys.len()).sum();
+        check(
+            "max-trace-entries",
+            self.max_trace_entries.map(|m| m as u64),
+            entries as u64,
+        )
+    }
+}
+
+/// Why `--api-keys` turned a request away.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Denied {
+    /// No key, or one not in the file.
+    Unauthorized,
+    /// The key's quota for this window is spent; it renews after the duration.
+    OverQuota(Duration),
+}
+
+/// The keys from `--api-keys` and what each has used of its quota.
+#[derive(Debug)]
+pub struct ApiKeys {
+    /// Requests per minute per key; `None` for no quota.
+    quotas: BTreeMap<String, Option<u32>>,
+    /// Start of each key's current window and the requests counted in it.
+    used: Mutex<BTreeMap<String, (Instant, u32)>>,
+}
+
+impl ApiKeys {
+    /// Read a key file: one key per line, optionally followed by its requests per minute.
+    /// Blank lines and lines starting with `#` are skipped.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read API keys {}", path.display()))?;
+        Self::parse(&text).wrap_err_with(|| format!("invalid API keys in {}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        let mut quotas = BTreeMap::new();
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let key = fields.next().unwrap_or_default();
+            let quota = fields
+                .next()
+                .map(|q| q.parse::<u32>())
+                .transpose()
+                .wrap_err_with(|| format!("line {}: expected KEY [REQUESTS_PER_MINUTE]", n + 1))?;
+            if fields.next().is_some() {
+                eyre::bail!("line {}: expected KEY [REQUESTS_PER_MINUTE]", n + 1);
+            }
+            quotas.insert(key.to_string(), quota);
+        }
+        if quotas.is_empty() {
+            eyre::bail!("no keys");
+        }
+        Ok(Self {
+            quotas,
+            used: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.quotas.contains_key(key)
+    }
+
+    /// Count `requests` against `key`'s quota at `now`. A batch is admitted whole or not
+    /// at all.
+    pub fn admit(&self, key: Option<&str>, requests: u32, now: Instant) -> Result<(), Denied> {
+        let (key, quota) = key
+            .and_then(|key| self.quotas.get_key_value(key))
+            .ok_or(Denied::Unauthorized)?;
+        let Some(quota) = *quota else {
+            return Ok(());
+        };
+        let mut used = self.used.lock().unwrap_or_else(|e| e.into_inner());
+        let (start, count) = used.entry(key.clone()).or_insert((now, 0));
+        if now.duration_since(*start) >= WINDOW {
+            *start = now;
+            *count = 0;
+        }
+        if count.saturating_add(requests) > quota {
+            return Err(Denied::OverQuota(WINDOW - now.duration_since(*start)));
+        }
+        *count += requests;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256};
+    use hammer_core::AccessListItem;
+
+    fn limits() -> LimitArgs {
+        LimitArgs {
+            max_body_bytes: 1_000,
+            max_batch: Some(4),
+            max_calldata_bytes: Some(100),
+            max_gas: Some(5_000_000),
+            max_trace_entries: Some(2),
+            api_keys: None,
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = limits();
+        assert!(limits.check_body(1_000).is_ok());
+        let over = limits.check_body(1_001).unwrap_err();
+        assert_eq!(over.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(
+            over.data(),
+            json!({"limit": "max-body-bytes", "max": 1_000, "actual": 1_001})
+        );
+        assert!(limits.check_call(100, None).is_ok());
+        assert_eq!(
+            limits.check_call(10, Some(5_000_001)).unwrap_err().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(limits.default_gas(30_000_000), 5_000_000);
+
+        let list = AccessList(vec![AccessListItem {
+            address: Address::with_last_byte(0x40),
+            storage_keys: vec![B256::ZERO, B256::with_last_byte(1)],
+        }]);
+        assert_eq!(limits.check_trace(&list).unwrap_err().actual, 3);
+        let unlimited = LimitArgs {
+            max_trace_entries: None,
+            ..limits
+        };
+        assert!(unlimited.check_trace(&list).is_ok());
+    }
+
+    #[test]
+    fn test_api_key_quotas() {
+        let keys = ApiKeys::parse("# ops\nalice 3\n\nbob\n").unwrap();
+        let now = Instant::now();
+        assert_eq!(keys.admit(None, 1, now), Err(Denied::Unauthorized));
+        assert_eq!(keys.admit(Some("eve"), 1, now), Err(Denied::Unauthorized));
+        assert_eq!(keys.admit(Some("bob"), 1_000, now), Ok(()));
+
+        assert_eq!(keys.admit(Some("alice"), 2, now), Ok(()));
+        let later = now + Duration::from_secs(20);
+        assert_eq!(
+            keys.admit(Some("alice"), 2, later),
+            Err(Denied::OverQuota(Duration::from_secs(40)))
+        );
+        assert_eq!(keys.admit(Some("alice"), 1, later), Ok(()));
+        assert_eq!(keys.admit(Some("alice"), 3, now + WINDOW), Ok(()));
+
+        assert!(ApiKeys::parse("alice many\n").is_err());
+        assert!(ApiKeys::parse("# none\n").is_err());
+    }
+}
@@ -0,0 +1,555 @@
+//! Source positions of validation findings inside the declared access list file
+//! (`validate --locations`), so editors can underline the exact offending entry, and
+//! fixes spliced into that file's text (`validate --fix`) so it keeps its layout.
+
+use alloy_primitives::{Address, B256};
+use eyre::Result;
+use hammer_core::{AccessList, DiffEntry, ACCESS_LIST_STORAGE_KEY_COST};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+/// Where a finding points in the declared file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// Byte offset of the value's first character.
+    pub offset: usize,
+    /// 1-based line.
+    pub line: usize,
+    /// 1-based column, counted in characters.
+    pub column: usize,
+    /// RFC 6901 JSON pointer to the value, e.g. `/2/storageKeys/0`.
+    pub pointer: String,
+}
+
+/// One finding anchored to a position in the declared file.
+#[derive(Debug, Clone)]
+pub struct Located<'a> {
+    pub entry: &'a DiffEntry,
+    pub location: Location,
+}
+
+/// One-line explanation of a finding, for diagnostics output.
+pub fn message(entry: &DiffEntry) -> String {
+    match entry {
+        DiffEntry::Missing {
+            address,
+            storage_keys,
+            ..
+        } => format!(
+            "{} is accessed but not declared ({} slots)",
+            address,
+            storage_keys.len()
+        ),
+        DiffEntry::Stale {
+            address,
+            storage_keys,
+            ..
+        } => format!(
+            "{} declared but never accessed ({} slots)",
+            address,
+            storage_keys.len()
+        ),
+        DiffEntry::Incomplete {
+            address,
+            missing_slots,
+            ..
+        } => format!(
+            "{} is missing {} accessed slots",
+            address,
+            missing_slots.len()
+        ),
+        DiffEntry::Redundant { address, .. } => format!("{} is warm by default", address),
+        DiffEntry::Duplicate {
+            address,
+            storage_key,
+            ..
+        } => format!("{} slot {} is declared twice", address, storage_key),
+    }
+}
+
+/// A JSON value with the bytes it spans.
+enum Node {
+    Array(Range<usize>, Vec<Node>),
+    Object(Range<usize>, Vec<(String, Node)>),
+    String(Range<usize>, String),
+    Other(Range<usize>),
+}
+
+impl Node {
+    fn span(&self) -> Range<usize> {
+        match self {
+            Node::Array(span, _)
+            | Node::Object(span, _)
+            | Node::String(span, _)
+            | Node::Other(span) => span.clone(),
+        }
+    }
+
+    fn offset(&self) -> usize {
+        self.span().start
+    }
+
+    fn field(&self, name: &str) -> Option<&Node> {
+        match self {
+            Node::Object(_, fields) => fields.iter().find(|(k, _)| k == name).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+/// Positions of every item and storage key in a declared access list.
+pub struct SourceMap<'s> {
+    text: &'s str,
+    root: usize,
+    /// (item index, address, address offset)
+    items: Vec<(usize, Address, usize)>,
+    /// (item index, key index, address, key, offset)
+    keys: Vec<(usize, usize, Address, B256, usize)>,
+}
+
+impl<'s> SourceMap<'s> {
+    /// Index the positions in `text`, which must already parse as an access list.
+    pub fn new(text: &'s str) -> Result<Self> {
+        let mut parser = Parser { text, pos: 0 };
+        let root = parser.value()?;
+        let mut map = SourceMap {
+            text,
+            root: root.offset(),
+            items: Vec::new(),
+            keys: Vec::new(),
+        };
+        let Node::Array(_, items) = root else {
+            eyre::bail!("access list must be a JSON array");
+        };
+        for (i, item) in items.iter().enumerate() {
+            let Some(Node::String(addr_span, addr)) = item.field("address") else {
+                continue;
+            };
+            let Ok(address) = addr.parse::<Address>() else {
+                continue;
+            };
+            map.items.push((i, address, addr_span.start));
+            if let Some(Node::Array(_, keys)) = item.field("storageKeys") {
+                for (k, key) in keys.iter().enumerate() {
+                    if let Node::String(span, s) = key {
+                        if let Ok(key) = s.parse::<B256>() {
+                            map.keys.push((i, k, address, key, span.start));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(map)
+    }
+
+    /// Anchor each finding to the declared entries it concerns. Missing findings have
+    /// no declared entry and point at the list itself; everything else points at the
+    /// address or storage key to edit.
+    pub fn locate<'a>(&self, entries: &'a [DiffEntry]) -> Vec<Located<'a>> {
+        let mut out = Vec::new();
+        let mut duplicates_seen: BTreeMap<(Address, B256), usize> = BTreeMap::new();
+        for entry in entries {
+            let mut push = |location| out.push(Located { entry, location });
+            match entry {
+                DiffEntry::Missing { .. } => push(self.location(self.root, String::new())),
+                DiffEntry::Redundant { address, .. } => {
+                    self.item_addresses(address).for_each(&mut push)
+                }
+                DiffEntry::Incomplete { address, .. } => {
+                    if let Some(loc) = self.item_addresses(address).next() {
+                        push(loc);
+                    }
+                }
+                DiffEntry::Stale {
+                    address,
+                    storage_keys,
+                    gas_waste,
+                } => {
+                    // A whole-address stale entry also wastes the address cost.
+                    let whole =
+                        *gas_waste > storage_keys.len() as u64 * ACCESS_LIST_STORAGE_KEY_COST;
+                    if whole {
+                        self.item_addresses(address).for_each(&mut push);
+                    } else {
+                        for key in storage_keys {
+                            self.key_locations(address, key).for_each(&mut push);
+                        }
+                    }
+                }
+                DiffEntry::Duplicate {
+                    address,
+                    storage_key,
+                    ..
+                } => {
+                    // The validator reports each repeat after the first occurrence in order.
+                    let seen = duplicates_seen.entry((*address, *storage_key)).or_default();
+                    *seen += 1;
+                    if let Some(loc) = self.key_locations(address, storage_key).nth(*seen) {
+                        push(loc);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    fn item_addresses<'m>(&'m self, address: &'m Address) -> impl Iterator<Item = Location> + 'm {
+        self.items
+            .iter()
+            .filter(move |(_, a, _)| a == address)
+            .map(|(i, _, offset)| self.location(*offset, format!("/{}/address", i)))
+    }
+
+    fn key_locations<'m>(
+        &'m self,
+        address: &'m Address,
+        key: &'m B256,
+    ) -> impl Iterator<Item = Location> + 'm {
+        self.keys
+            .iter()
+            .filter(move |(_, _, a, k, _)| a == address && k == key)
+            .map(|(i, k, _, _, offset)| self.location(*offset, format!("/{}/storageKeys/{}", i, k)))
+    }
+
+    fn location(&self, offset: usize, pointer: String) -> Location {
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Location {
+            offset,
+            line,
+            column: before[line_start..].chars().count() + 1,
+            pointer,
+        }
+    }
+}
+
+/// `text`, a declared list, edited into `fixed` (the list with `apply_fixes`' edits
+/// applied): dropped entries and slots are cut out and added ones appended, following
+/// the separators and indentation around them. Everything else, hex case and layout
+/// included, is kept byte for byte.
+pub fn splice_fixes(text: &str, fixed: &AccessList) -> Result<String> {
+    let root = Parser { text, pos: 0 }.value()?;
+    let Node::Array(span, items) = &root else {
+        eyre::bail!("access list must be a JSON array");
+    };
+    // The edits only drop entries and slots and append new ones, so what is kept of
+    // the file lines up, in order, with the start of `fixed`.
+    let mut wanted = fixed.0.iter().peekable();
+    let mut kept = Vec::new();
+    for item in items {
+        let Some(Node::String(_, address)) = item.field("address") else {
+            eyre::bail!("access list item without an address");
+        };
+        let address: Address = address.parse()?;
+        let Some(fixed_item) = wanted.next_if(|i| i.address == address) else {
+            continue;
+        };
+        let Some(Node::Array(keys_span, keys)) = item.field("storageKeys") else {
+            eyre::bail!("access list item without storageKeys");
+        };
+        let mut wanted_keys = fixed_item.storage_keys.iter().peekable();
+        let mut kept_keys = Vec::new();
+        for key in keys {
+            let Node::String(key_span, value) = key else {
+                eyre::bail!("storage key is not a string");
+            };
+            let key: B256 = value.parse()?;
+            if wanted_keys.next_if(|k| **k == key).is_some() {
+                kept_keys.push(text[key_span.clone()].to_string());
+            }
+        }
+        let added_keys: Vec<Value> = wanted_keys.map(|k| serde_json::json!(k)).collect();
+        let item_span = item.span();
+        if kept_keys.len() == keys.len() && added_keys.is_empty() {
+            kept.push(text[item_span].to_string());
+        } else {
+            kept.push(format!(
+                "{}{}{}",
+                &text[item_span.start..keys_span.start],
+                splice_array(text, keys_span.clone(), keys, kept_keys, &added_keys)?,
+                &text[keys_span.end..item_span.end]
+            ));
+        }
+    }
+    let added: Vec<Value> = wanted.map(serde_json::to_value).collect::<Result<_, _>>()?;
+    let spliced = format!(
+        "{}{}{}",
+        &text[..span.start],
+        splice_array(text, span.clone(), items, kept, &added)?,
+        &text[span.end..]
+    );
+    if serde_json::from_str::<AccessList>(&spliced)? != *fixed {
+        eyre::bail!("spliced list does not match the fixed one");
+    }
+    Ok(spliced)
+}
+
+/// The array at `span` holding `kept` (the text of the elements it keeps) then `added`,
+/// separated and indented like its original `elements`.
+fn splice_array(
+    text: &str,
+    span: Range<usize>,
+    elements: &[Node],
+    kept: Vec<String>,
+    added: &[Value],
+) -> Result<String> {
+    let (open, close, separator) = match elements {
+        [] => (String::new(), String::new(), ", ".to_string()),
+        [only] => {
+            let open = &text[span.start + 1..only.offset()];
+            let close = &text[only.span().end..span.end - 1];
+            (open.to_string(), close.to_string(), format!(",{}", open))
+        }
+        [first, second, ..] => (
+            text[span.start + 1..first.offset()].to_string(),
+            text[elements[elements.len() - 1].span().end..span.end - 1].to_string(),
+            text[first.span().end..second.offset()].to_string(),
+        ),
+    };
+    // Added values are pretty-printed at the elements' indentation in a multi-line
+    // array, and compact in a one-line one.
+    let indent = open.rfind('\n').map(|at| &open[at..]);
+    let mut parts = kept;
+    for value in added {
+        parts.push(match indent {
+            Some(indent) => serde_json::to_string_pretty(value)?.replace('\n', indent),
+            None => serde_json::to_string(value)?,
+        });
+    }
+    if parts.is_empty() {
+        return Ok("[]".to_string());
+    }
+    Ok(format!("[{}{}{}]", open, parts.join(&separator), close))
+}
+
+/// Minimal JSON reader that records the bytes each value spans. It only needs to be as
+/// strict as the serde parse that already accepted the file.
+struct Parser<'s> {
+    text: &'s str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self) -> Result<Node> {
+        self.skip_ws();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(Node::Array(start..self.pos, items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.skip_ws();
+                    match self.bump() {
+                        Some(b',') => continue,
+                        Some(b']') => return Ok(Node::Array(start..self.pos, items)),
+                        _ => eyre::bail!("malformed JSON array at byte {}", self.pos),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.skip_ws();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(Node::Object(start..self.pos, fields));
+                }
+                loop {
+                    self.skip_ws();
+                    let key = self.string()?;
+                    self.skip_ws();
+                    if self.bump() != Some(b':') {
+                        eyre::bail!("malformed JSON object at byte {}", self.pos);
+                    }
+                    fields.push((key, self.value()?));
+                    self.skip_ws();
+                    match self.bump() {
+                        Some(b',') => continue,
+                        Some(b'}') => return Ok(Node::Object(start..self.pos, fields)),
+                        _ => eyre::bail!("malformed JSON object at byte {}", self.pos),
+                    }
+                }
+            }
+            Some(b'"') => {
+                let value = self.string()?;
+                Ok(Node::String(start..self.pos, value))
+            }
+            Some(_) => {
+                // Numbers, booleans and null: consume up to the next delimiter.
+                while let Some(b) = self.peek() {
+                    if matches!(b, b',' | b']' | b'}') || b.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                Ok(Node::Other(start..self.pos))
+            }
+            None => eyre::bail!("unexpected end of JSON"),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        if self.bump() != Some(b'"') {
+            eyre::bail!("expected JSON string at byte {}", self.pos);
+        }
+        let start = self.pos;
+        while let Some(b) = self.bump() {
+            match b {
+                b'\\' => self.pos += 1,
+                b'"' => return Ok(self.text[start..self.pos - 1].to_string()),
+                _ => {}
+            }
+        }
+        eyre::bail!("unterminated JSON string")
+    }
+
+    fn skip_ws(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hammer_core::ACCESS_LIST_ADDRESS_COST;
+
+    const A: &str = "0x00000000000000000000000000000000000000aa";
+    const K1: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    const K2: &str = "0x0000000000000000000000000000000000000000000000000000000000000002";
+
+    fn declared() -> String {
+        format!(
+            "[\n  {{\n    \"address\": \"{A}\",\n    \"storageKeys\": [\n      \"{K1}\",\n      \"{K2}\",\n      \"{K1}\"\n    ]\n  }}\n]\n"
+        )
+    }
+
+    #[test]
+    fn test_duplicate_points_at_second_occurrence() {
+        let text = declared();
+        let map = SourceMap::new(&text).unwrap();
+        let entries = vec![DiffEntry::Duplicate {
+            address: A.parse().unwrap(),
+            storage_key: K1.parse().unwrap(),
+            gas_waste: ACCESS_LIST_STORAGE_KEY_COST,
+        }];
+        let located = map.locate(&entries);
+        assert_eq!(located.len(), 1);
+        let loc = &located[0].location;
+        assert_eq!(loc.pointer, "/0/storageKeys/2");
+        assert_eq!((loc.line, loc.column), (7, 7));
+        assert_eq!(&text[loc.offset..loc.offset + 3], "\"0x");
+    }
+
+    #[test]
+    fn test_stale_slot_points_at_key_and_whole_address_at_item() {
+        let text = declared();
+        let map = SourceMap::new(&text).unwrap();
+        let address: Address = A.parse().unwrap();
+        let entries = vec![
+            DiffEntry::Stale {
+                address,
+                storage_keys: vec![K2.parse().unwrap()],
+                gas_waste: ACCESS_LIST_STORAGE_KEY_COST,
+            },
+            DiffEntry::Stale {
+                address,
+                storage_keys: vec![K1.parse().unwrap()],
+                gas_waste: ACCESS_LIST_ADDRESS_COST + ACCESS_LIST_STORAGE_KEY_COST,
+            },
+        ];
+        let pointers: Vec<_> = map
+            .locate(&entries)
+            .into_iter()
+            .map(|l| l.location.pointer)
+            .collect();
+        assert_eq!(pointers, vec!["/0/storageKeys/1", "/0/address"]);
+    }
+
+    #[test]
+    fn test_missing_points_at_list() {
+        let text = declared();
+        let map = SourceMap::new(&text).unwrap();
+        let entries = vec![DiffEntry::Missing {
+            address: Address::ZERO,
+            storage_keys: vec![],
+            gas_waste: 0,
+        }];
+        let located = map.locate(&entries);
+        assert_eq!(located[0].location.pointer, "");
+        assert_eq!(
+            (located[0].location.line, located[0].location.column),
+            (1, 1)
+        );
+    }
+
+    fn fixed(items: &[(&str, &[&str])]) -> AccessList {
+        AccessList(
+            items
+                .iter()
+                .map(|(address, keys)| hammer_core::AccessListItem {
+                    address: address.parse().unwrap(),
+                    storage_keys: keys.iter().map(|k| k.parse().unwrap()).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_splice_drops_keys_and_keeps_layout() {
+        // Upper-case hex and a four-space indent survive; the repeat of K1 goes.
+        let text = declared().replace("aa\"", "AA\"").replace("  ", "    ");
+        let spliced = splice_fixes(&text, &fixed(&[(A, &[K1, K2])])).unwrap();
+        assert_eq!(
+            spliced,
+            text.replacen(&format!(",\n            \"{K1}\""), "", 1)
+        );
+        assert!(spliced.contains("00AA\""));
+    }
+
+    #[test]
+    fn test_splice_drops_item_and_appends_indented() {
+        const B: &str = "0x00000000000000000000000000000000000000bb";
+        let text = format!("[\n  {{ \"address\": \"{A}\", \"storageKeys\": [] }}\n]\n");
+        let spliced = splice_fixes(&text, &fixed(&[(B, &[K2])])).unwrap();
+        assert_eq!(
+            spliced,
+            format!(
+                "[\n  {{\n    \"address\": \"{B}\",\n    \"storageKeys\": [\n      \"{K2}\"\n    ]\n  }}\n]\n"
+            )
+        );
+
+        // A one-line list stays on one line; a slot added to an empty array goes in it.
+        let text = format!("[{{\"address\":\"{A}\",\"storageKeys\":[]}}]");
+        let spliced = splice_fixes(&text, &fixed(&[(A, &[K1])])).unwrap();
+        assert_eq!(
+            spliced,
+            format!("[{{\"address\":\"{A}\",\"storageKeys\":[\"{K1}\"]}}]")
+        );
+        assert_eq!(splice_fixes(&text, &fixed(&[])).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_non_array_rejected() {
+        assert!(SourceMap::new("{\"address\": 1}").is_err());
+    }
+}
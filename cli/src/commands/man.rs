@@ -0,0 +1,25 @@
+use clap::Args;
+use eyre::{Context, Result};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ManArgs {
+    /// Write `hammer.1` plus one page per subcommand (`hammer-generate.1`, ...) into this
+    /// directory instead of printing the top-level page to stdout
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+}
+
+/// Render roff manpages from the CLI definition, so they never drift from `--help`.
+pub fn run(args: ManArgs, cmd: clap::Command) -> Result<()> {
+    match args.out_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(&dir)
+                .wrap_err_with(|| format!("failed to create {}", dir.display()))?;
+            clap_mangen::generate_to(cmd, &dir)
+                .wrap_err_with(|| format!("failed to write manpages to {}", dir.display()))?;
+        }
+        None => clap_mangen::Man::new(cmd).render(&mut std::io::stdout())?,
+    }
+    Ok(())
+}
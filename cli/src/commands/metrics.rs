@@ -0,0 +1,131 @@
+//! Usage counters for `serve`, per API key, exposed as Prometheus text (`--metrics`).
+//!
+//! Keys are labelled by an id, the first four bytes of their keccak-256 hash in hex,
+//! never by the key itself, so the page can be scraped without leaking credentials.
+//! Requests without `--api-keys` are labelled `anonymous`, ones with a key not in the
+//! file `unknown`. Labels come from fixed sets, so callers cannot grow the page.
+
+use alloy_primitives::keccak256;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Methods counted by name; any other is counted as `other`.
+const METHODS: &[&str] = &[
+    "hammer_generateAccessList",
+    "hammer_validateAccessList",
+    "hammer_scoreAccessList",
+    "hammer_compareTx",
+];
+
+/// The label of an API key.
+pub fn key_id(key: &str) -> String {
+    hex::encode(&keccak256(key.as_bytes())[..4])
+}
+
+/// How a served call ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Outcome {
+    Ok,
+    Error,
+    /// Refused under a `--max-*` limit.
+    Limited,
+}
+
+impl Outcome {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Error => "error",
+            Self::Limited => "limited",
+        }
+    }
+}
+
+/// Counters since the server started.
+#[derive(Debug, Default)]
+pub struct Usage {
+    /// Calls by key, method and outcome.
+    calls: Mutex<BTreeMap<(String, &'static str, Outcome), u64>>,
+    /// Requests turned away before any call was served, by key and reason.
+    rejected: Mutex<BTreeMap<(String, &'static str), u64>>,
+}
+
+impl Usage {
+    /// Count a call of `method` by `key` (a [`key_id`] or one of the fixed labels).
+    pub fn call(&self, key: &str, method: &str, outcome: Outcome) {
+        let method = METHODS
+            .iter()
+            .find(|m| **m == method)
+            .copied()
+            .unwrap_or("other");
+        let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        *calls.entry((key.to_string(), method, outcome)).or_default() += 1;
+    }
+
+    /// Count a request turned away, with the reason (`unauthorized`, `quota`, or the
+    /// limit's flag).
+    pub fn reject(&self, key: &str, reason: &'static str) {
+        let mut rejected = self.rejected.lock().unwrap_or_else(|e| e.into_inner());
+        *rejected.entry((key.to_string(), reason)).or_default() += 1;
+    }
+
+    /// The counters in Prometheus' text exposition format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "# HELP hammer_calls_total JSON-RPC calls served, by API key, method and outcome.\n",
+        );
+        out.push_str("# TYPE hammer_calls_total counter\n");
+        let calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+        for ((key, method, outcome), count) in calls.iter() {
+            let _ = writeln!(
+                out,
+                "hammer_calls_total{{key=\"{}\",method=\"{}\",outcome=\"{}\"}} {}",
+                key,
+                method,
+                outcome.as_str(),
+                count
+            );
+        }
+        out.push_str("# HELP hammer_rejected_total Requests turned away before being served, by API key and reason.\n");
+        out.push_str("# TYPE hammer_rejected_total counter\n");
+        let rejected = self.rejected.lock().unwrap_or_else(|e| e.into_inner());
+        for ((key, reason), count) in rejected.iter() {
+            let _ = writeln!(
+                out,
+                "hammer_rejected_total{{key=\"{}\",reason=\"{}\"}} {}",
+                key, reason, count
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_render() {
+        let usage = Usage::default();
+        let alice = key_id("alice-secret");
+        assert_eq!(alice.len(), 8);
+        usage.call(&alice, "hammer_compareTx", Outcome::Ok);
+        usage.call(&alice, "hammer_compareTx", Outcome::Ok);
+        usage.call(&alice, "eth_sendRawTransaction", Outcome::Error);
+        usage.reject("unknown", "unauthorized");
+
+        let page = usage.render();
+        assert!(page.contains(&format!(
+            "hammer_calls_total{{key=\"{}\",method=\"hammer_compareTx\",outcome=\"ok\"}} 2\n",
+            alice
+        )));
+        assert!(page.contains(&format!(
+            "hammer_calls_total{{key=\"{}\",method=\"other\",outcome=\"error\"}} 1\n",
+            alice
+        )));
+        assert!(page.contains("hammer_rejected_total{key=\"unknown\",reason=\"unauthorized\"} 1\n"));
+        assert!(!page.contains("alice-secret"));
+    }
+}
@@ -1,5 +1,36 @@
+pub mod attest;
+pub mod bundle;
+pub mod cache;
+pub mod codegen;
 pub mod compare;
+pub mod compare_block;
+pub mod completions;
+pub mod config;
+pub mod deploy;
+pub mod drift;
+pub mod engine;
+pub mod events;
+pub mod examples;
+pub mod fuzz;
 pub mod generate;
+pub mod global;
+pub mod html;
+pub mod layout;
+pub mod limits;
+pub mod locate;
+pub mod man;
+pub mod metrics;
 pub mod prefetch;
+pub mod queue;
+pub mod report_diff;
+pub mod seen;
+pub mod selftest;
+pub mod serve;
+pub mod sink;
+pub mod slot;
+pub mod stale_history;
+pub mod units;
 pub mod util;
 pub mod validate;
+pub mod verify_signature;
+pub mod watch;
@@ -26,7 +26,7 @@ use revm::database::{AlloyDB, CacheDB};
 use revm::database_interface::{WrapDatabaseAsync, WrapDatabaseRef};
 use revm::primitives::KECCAK_EMPTY;
 use revm::state::{AccountInfo, Bytecode};
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet};
 
 pub type PrewarmedDB =
     CacheDB<WrapDatabaseRef<WrapDatabaseAsync<AlloyDB<Ethereum, DynProvider<Ethereum>>>>>;
@@ -43,6 +43,46 @@ pub async fn build(
     tx_req: TransactionRequest,
     declared: &AccessList,
 ) -> eyre::Result<PrewarmedDB> {
+    let mut cache_db = empty(provider.clone(), state_block)?;
+    prewarm(
+        &mut cache_db,
+        provider,
+        state_block,
+        hint_block,
+        tx_req,
+        declared,
+    )
+    .await;
+    Ok(cache_db)
+}
+
+/// An empty `CacheDB` over `state_block`; every access falls through to AlloyDB.
+pub fn empty(provider: DynProvider<Ethereum>, state_block: BlockId) -> eyre::Result<PrewarmedDB> {
+    let alloy_db = AlloyDB::new(provider, state_block);
+    let async_db = WrapDatabaseAsync::new(alloy_db)
+        .ok_or_else(|| eyre::eyre!("WrapDatabaseAsync requires tokio runtime"))?;
+    Ok(CacheDB::new(WrapDatabaseRef::from(async_db)))
+}
+
+/// Add the pre-execution state of `tx_req` to an existing cache built over the same
+/// `state_block`, so several transactions against one block can share a database.
+///
+/// Logs under the `hammer::prefetch` target (`RUST_LOG=hammer::prefetch=debug`).
+#[tracing::instrument(
+    target = "hammer::prefetch",
+    name = "prefetch",
+    level = "debug",
+    skip_all,
+    fields(block = %state_block)
+)]
+pub async fn prewarm(
+    cache_db: &mut PrewarmedDB,
+    provider: DynProvider<Ethereum>,
+    state_block: BlockId,
+    hint_block: BlockId,
+    tx_req: TransactionRequest,
+    declared: &AccessList,
+) {
     use alloy_provider::ext::DebugApi;
 
     let trace_opts = GethDebugTracingCallOptions {
@@ -58,23 +98,29 @@ pub async fn build(
     // One RPC call returns every account + storage slot the tx will touch.
     let pre_state_map: Option<
         BTreeMap<Address, alloy_rpc_types_trace::geth::pre_state::AccountState>,
-    > = provider
+    > = match provider
         .debug_trace_call_prestate(tx_req.clone(), hint_block, trace_opts)
         .await
-        .ok()
-        .and_then(|frame| match frame {
-            PreStateFrame::Default(mode) => Some(mode.0),
-            _ => None,
-        });
-
-    // Build the underlying AlloyDB stack.
-    let alloy_db = AlloyDB::new(provider.clone(), state_block);
-    let async_db = WrapDatabaseAsync::new(alloy_db)
-        .ok_or_else(|| eyre::eyre!("WrapDatabaseAsync requires tokio runtime"))?;
-    let inner = WrapDatabaseRef::from(async_db);
-    let mut cache_db = CacheDB::new(inner);
+    {
+        Ok(PreStateFrame::Default(mode)) => Some(mode.0),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::debug!(
+                target: "hammer::prefetch",
+                error = %e,
+                "debug_traceCall unavailable; falling back to eth_createAccessList"
+            );
+            None
+        }
+    };
 
     if let Some(state) = pre_state_map {
+        tracing::debug!(
+            target: "hammer::prefetch",
+            accounts = state.len(),
+            slots = state.values().map(|a| a.storage.len()).sum::<usize>(),
+            "prestate from debug_traceCall"
+        );
         // Populate the cache directly from the prestate — zero additional RPCs.
         for (addr, account) in state {
             let bytecode = account
@@ -115,7 +161,7 @@ pub async fn build(
 
         let hint_list = merge_access_lists(node_hint.as_ref(), declared);
 
-        let mut addr_slots: HashMap<Address, HashSet<U256>> = HashMap::new();
+        let mut addr_slots: BTreeMap<Address, BTreeSet<U256>> = BTreeMap::new();
         for item in hint_list.0.iter() {
             let entry = addr_slots.entry(item.address).or_default();
             for key in &item.storage_keys {
@@ -132,11 +178,13 @@ pub async fn build(
                 let b = state_block;
                 async move {
                     let (balance, nonce, code) = tokio::join!(
-                        async { p.get_balance(addr).block_id(b).await.unwrap_or(U256::ZERO) },
-                        async { p.get_transaction_count(addr).block_id(b).await.unwrap_or(0) },
-                        async { p.get_code_at(addr).block_id(b).await.unwrap_or_default() },
+                        async { p.get_balance(addr).block_id(b).await },
+                        async { p.get_transaction_count(addr).block_id(b).await },
+                        async { p.get_code_at(addr).block_id(b).await },
                     );
-                    (addr, balance, nonce, code)
+                    // A failed read is left out of the cache rather than guessed, so
+                    // the access falls through to AlloyDB and surfaces its error.
+                    Some((addr, balance.ok()?, nonce.ok()?, code.ok()?))
                 }
             })
             .collect();
@@ -152,20 +200,22 @@ pub async fn build(
                 let p = provider.clone();
                 let b = state_block;
                 async move {
-                    let value = p
-                        .get_storage_at(addr, slot)
-                        .block_id(b)
-                        .await
-                        .unwrap_or(U256::ZERO);
-                    (addr, slot, value)
+                    let value = p.get_storage_at(addr, slot).block_id(b).await.ok()?;
+                    Some((addr, slot, value))
                 }
             })
             .collect();
 
+        tracing::debug!(
+            target: "hammer::prefetch",
+            accounts = account_futs.len(),
+            slots = storage_futs.len(),
+            "fetching hinted state in parallel"
+        );
         let (account_results, storage_results) =
             tokio::join!(join_all(account_futs), join_all(storage_futs));
 
-        for (addr, balance, nonce, code_bytes) in account_results {
+        for (addr, balance, nonce, code_bytes) in account_results.into_iter().flatten() {
             let bytecode = if code_bytes.is_empty() {
                 Bytecode::default()
             } else {
@@ -188,18 +238,16 @@ pub async fn build(
             );
         }
 
-        for (addr, slot, value) in storage_results {
+        for (addr, slot, value) in storage_results.into_iter().flatten() {
             let _ = cache_db.insert_account_storage(addr, slot, value);
         }
     }
-
-    Ok(cache_db)
 }
 
 fn merge_access_lists(a: Option<&AccessList>, b: &AccessList) -> AccessList {
-    let mut map: HashMap<Address, HashSet<alloy_primitives::B256>> = HashMap::new();
+    let mut map: BTreeMap<Address, BTreeSet<alloy_primitives::B256>> = BTreeMap::new();
 
-    let extend = |map: &mut HashMap<Address, HashSet<alloy_primitives::B256>>,
+    let extend = |map: &mut BTreeMap<Address, BTreeSet<alloy_primitives::B256>>,
                   list: &AccessList| {
         for item in list.0.iter() {
             let keys = map.entry(item.address).or_default();
@@ -221,3 +269,30 @@ fn merge_access_lists(a: Option<&AccessList>, b: &AccessList) -> AccessList {
             .collect(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    fn item(byte: u8, slots: &[u8]) -> AccessListItem {
+        AccessListItem {
+            address: Address::repeat_byte(byte),
+            storage_keys: slots.iter().map(|s| B256::with_last_byte(*s)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_merge_access_lists_is_sorted_and_deduplicated() {
+        let hint = AccessList(vec![item(2, &[3, 1]), item(1, &[])]);
+        let declared = AccessList(vec![item(2, &[1, 2])]);
+        let merged = merge_access_lists(Some(&hint), &declared);
+        assert_eq!(merged.0, vec![item(1, &[]), item(2, &[1, 2, 3])]);
+        // Byte-stable regardless of which list contributed first.
+        let swapped = merge_access_lists(Some(&declared), &hint);
+        assert_eq!(
+            serde_json::to_string(&merged).unwrap(),
+            serde_json::to_string(&swapped).unwrap()
+        );
+    }
+}
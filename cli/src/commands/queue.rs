@@ -0,0 +1,331 @@
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::{AccessList, TransactionRequest};
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{access_list_gas_cost, generate_queue_iter, generate_queue_with};
+use revm::context::TxEnv;
+use revm::primitives::TxKind;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::attest;
+use super::config::OptimizerArgs;
+use super::global::GlobalArgs;
+use super::sink::Sink;
+use super::util::{
+    apply_chain_rules, assert_post_berlin, block_env_from_header, fetch_block, parse_block_id,
+    parse_hex_bytes, parse_u256, state_read_error, traced, NEAREST_AVAILABLE_DEFAULT,
+};
+
+/// Gas limit for entries that do not set `gas`, as in `generate`.
+const DEFAULT_GAS: u64 = 30_000_000;
+
+#[derive(Args)]
+pub struct QueueArgs {
+    /// Sender of every transaction in the queue
+    #[arg(long)]
+    pub from: String,
+    /// JSON array of transactions in send order: `{"to", "data"?, "value"?, "gas"?}`
+    #[arg(long)]
+    pub txs: PathBuf,
+    /// Nonce of the first transaction [default: the sender's nonce at --block]
+    #[arg(long)]
+    pub nonce: Option<u64>,
+    #[arg(long, default_value = "latest")]
+    pub block: String,
+    /// If the block's state is pruned, simulate against the nearest block within BLOCKS
+    /// that still has it [default BLOCKS: 64]
+    #[arg(long, value_name = "BLOCKS", num_args = 0..=1, default_missing_value = NEAREST_AVAILABLE_DEFAULT)]
+    pub nearest_available: Option<u64>,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+/// One entry of the `--txs` file.
+#[derive(Debug, PartialEq)]
+struct QueuedTx {
+    to: Address,
+    data: Vec<u8>,
+    value: U256,
+    gas: u64,
+}
+
+/// Generate access lists for the sender's transactions as if sent back to back: nonces
+/// follow on from `--nonce`, and each transaction sees the state the earlier ones left.
+///
+/// With `--output ndjson`, each transaction's entries are written (one JSON object per
+/// line) as soon as it is traced, before the next one runs.
+pub async fn run(args: QueueArgs, global: &GlobalArgs) -> Result<()> {
+    // Validate all local arguments before any network calls.
+    let output = global.output("json", &["json", "human", "ndjson"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    if output == "ndjson" && (sink != Sink::Stdout || signer.is_some()) {
+        eyre::bail!("--output ndjson streams to stdout; --out and --sign-key need --output json");
+    }
+    let mut config = args.optimizer.config()?;
+    let from: Address = args.from.parse().wrap_err("invalid --from")?;
+    let queue = parse_queue(
+        &std::fs::read_to_string(&args.txs)
+            .wrap_err_with(|| format!("failed to read {}", args.txs.display()))?,
+    )
+    .wrap_err_with(|| format!("invalid queue in {}", args.txs.display()))?;
+    let block_id = parse_block_id(&args.block)?;
+
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
+
+    let (block, substitution) = fetch_block(&provider, block_id, args.nearest_available).await?;
+    timer.phase("block");
+    let block_id = match substitution {
+        Some(s) => BlockId::number(s.used),
+        None => block_id,
+    };
+
+    let header = &block.header;
+    // Guard 3: Reject pre-Berlin blocks
+    assert_post_berlin(header.number)?;
+    apply_chain_rules(&mut config, global.chain_id()?, header.number);
+    let block_env = block_env_from_header(header);
+
+    let first_nonce = match args.nonce {
+        Some(nonce) => nonce,
+        None => provider
+            .get_transaction_count(from)
+            .block_id(block_id)
+            .await
+            .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?,
+    };
+
+    let gas_price = block_env.basefee.max(1_000_000_000) as u128;
+    let txs: Vec<TxEnv> = queue
+        .iter()
+        .enumerate()
+        .map(|(i, q)| {
+            TxEnv::builder()
+                .caller(from)
+                .nonce(first_nonce + i as u64)
+                .kind(TxKind::Call(q.to))
+                .gas_limit(q.gas)
+                .gas_price(gas_price)
+                .value(q.value)
+                .data(q.data.clone().into())
+                .build()
+                .unwrap()
+        })
+        .collect();
+
+    // Prefetch every entry against the base state before anything executes: prewarming
+    // after a commit would overwrite state the queue itself wrote.
+    let state_block_id = BlockId::hash(header.hash);
+    let mut db = super::prefetch::empty(provider.clone(), state_block_id)?;
+    for q in &queue {
+        let tx_req = TransactionRequest {
+            from: Some(from),
+            to: Some(TxKind::Call(q.to)),
+            value: Some(q.value),
+            input: alloy_rpc_types_eth::TransactionInput::new(q.data.clone().into()),
+            gas: Some(q.gas),
+            ..Default::default()
+        };
+        super::prefetch::prewarm(
+            &mut db,
+            provider.clone(),
+            state_block_id,
+            state_block_id,
+            tx_req,
+            &AccessList::default(),
+        )
+        .await;
+    }
+    timer.phase("state");
+
+    if let Some(s) = substitution {
+        if output == "human" {
+            println!("{}", s.notice());
+        } else if !global.quiet {
+            eprintln!("{}", s.notice());
+        }
+    }
+
+    if output == "ndjson" {
+        let mut stdout = std::io::stdout().lock();
+        traced(header.number, || -> Result<()> {
+            let lists = generate_queue_iter(db, txs, block_env, &config);
+            for ((i, optimal), q) in lists.enumerate().zip(&queue) {
+                let optimal = optimal.wrap_err("queue simulation failed")?;
+                write_ndjson(&mut stdout, first_nonce + i as u64, q.to, &optimal.list)?;
+            }
+            Ok(())
+        })?;
+        timer.phase("trace");
+        timer.total();
+        return Ok(());
+    }
+
+    let lists = traced(header.number, || {
+        generate_queue_with(db, txs, block_env, &config)
+    })
+    .wrap_err("queue simulation failed")?;
+    timer.phase("trace");
+    timer.total();
+
+    match output {
+        "json" => {
+            let entries = lists
+                .iter()
+                .zip(&queue)
+                .enumerate()
+                .map(|(i, (optimal, q))| {
+                    Ok(serde_json::json!({
+                        "nonce": first_nonce + i as u64,
+                        "to": q.to,
+                        "access_list": serde_json::to_value(&optimal.list)?,
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            attest::write_report(entries.into(), signer.as_ref(), &sink).await?;
+        }
+        "human" => {
+            for (i, (optimal, q)) in lists.iter().zip(&queue).enumerate() {
+                println!(
+                    "#{} nonce {} → {} (cost: {}):",
+                    i,
+                    first_nonce + i as u64,
+                    q.to,
+                    units.gas(access_list_gas_cost(&optimal.list))
+                );
+                for item in &optimal.list.0 {
+                    println!("  {}:", item.address);
+                    for key in &item.storage_keys {
+                        println!("    - {}", key);
+                    }
+                }
+            }
+        }
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Write one line per entry of `list` (`{"nonce", "to", "address", "storage_keys"}`) and flush, so a reader sees each transaction's
+/// entries as soon as it is traced.
+fn write_ndjson(out: &mut impl Write, nonce: u64, to: Address, list: &AccessList) -> Result<()> {
+    for item in &list.0 {
+        let entry = serde_json::json!({
+            "nonce": nonce,
+            "to": to,
+            "address": item.address,
+            "storage_keys": item.storage_keys,
+        });
+        serde_json::to_writer(&mut *out, &entry)?;
+        writeln!(out)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn parse_queue(text: &str) -> Result<Vec<QueuedTx>> {
+    let value: serde_json::Value = serde_json::from_str(text)?;
+    let entries = value
+        .as_array()
+        .ok_or_else(|| eyre::eyre!("expected a JSON array of transactions"))?;
+    if entries.is_empty() {
+        eyre::bail!("queue is empty");
+    }
+    entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let field = |name: &str| entry.get(name).and_then(|v| v.as_str());
+            let to = field("to")
+                .ok_or_else(|| eyre::eyre!("entry {}: missing \"to\"", i))?
+                .parse()
+                .wrap_err_with(|| format!("entry {}: invalid \"to\"", i))?;
+            let data = parse_hex_bytes(field("data").unwrap_or("0x"))
+                .wrap_err_with(|| format!("entry {}: invalid \"data\"", i))?;
+            let value = parse_u256(field("value").unwrap_or("0"))
+                .wrap_err_with(|| format!("entry {}: invalid \"value\"", i))?;
+            let gas = match entry.get("gas") {
+                None => DEFAULT_GAS,
+                Some(gas) => gas
+                    .as_u64()
+                    .ok_or_else(|| eyre::eyre!("entry {}: \"gas\" must be a number", i))?,
+            };
+            Ok(QueuedTx {
+                to,
+                data,
+                value,
+                gas,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_queue_defaults() {
+        let queue = parse_queue(
+            r#"[
+                {"to": "0x0000000000000000000000000000000000000001"},
+                {"to": "0x0000000000000000000000000000000000000002", "data": "0xabcd", "value": "0x10", "gas": 100000}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            queue[0],
+            QueuedTx {
+                to: Address::with_last_byte(1),
+                data: vec![],
+                value: U256::ZERO,
+                gas: DEFAULT_GAS,
+            }
+        );
+        assert_eq!(queue[1].data, vec![0xab, 0xcd]);
+        assert_eq!(queue[1].value, U256::from(16));
+        assert_eq!(queue[1].gas, 100_000);
+    }
+
+    #[test]
+    fn test_write_ndjson_one_line_per_entry() {
+        let list = AccessList(vec![
+            alloy_rpc_types_eth::AccessListItem {
+                address: Address::with_last_byte(0x40),
+                storage_keys: vec![alloy_primitives::B256::with_last_byte(1)],
+            },
+            alloy_rpc_types_eth::AccessListItem {
+                address: Address::with_last_byte(0x41),
+                storage_keys: vec![],
+            },
+        ]);
+        let mut out = Vec::new();
+        write_ndjson(&mut out, 7, Address::with_last_byte(2), &list).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["nonce"], 7);
+        assert_eq!(lines[0]["storage_keys"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            lines[1]["address"],
+            Address::with_last_byte(0x41).to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_queue_errors_name_entry() {
+        let err = parse_queue(r#"[{"to": "0x0000000000000000000000000000000000000001"}, {}]"#)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "entry 1: missing \"to\"");
+        assert!(parse_queue("[]").is_err());
+        assert!(parse_queue("{}").is_err());
+    }
+}
@@ -0,0 +1,466 @@
+use alloy_primitives::{Address, B256};
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{AccessList, DiffEntry, ValidationReport};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use super::attest;
+use super::compare::{execution_penalty, issue_row};
+use super::engine;
+use super::global::GlobalArgs;
+use super::units::Units;
+
+#[derive(Args)]
+pub struct ReportDiffArgs {
+    /// Report before the change: `validate` or `compare --output json` output
+    pub before: PathBuf,
+    /// Report after the change, for the same logical transaction
+    pub after: PathBuf,
+}
+
+/// A gas figure in both reports.
+struct Cost {
+    name: &'static str,
+    before: i64,
+    after: i64,
+}
+
+/// How one finding moved between the reports.
+enum Change<'a> {
+    /// Only in the first report.
+    Resolved(&'a DiffEntry),
+    /// Only in the second report.
+    Introduced(&'a DiffEntry),
+    /// Same kind and address in both, with different slots or gas.
+    Changed {
+        before: &'a DiffEntry,
+        after: &'a DiffEntry,
+    },
+}
+
+/// Everything that differs between two reports.
+struct ReportDiff<'a> {
+    valid: (bool, bool),
+    costs: Vec<Cost>,
+    changes: Vec<Change<'a>>,
+    /// Optimal-list entries only in the second report: an address, or one of its slots.
+    optimal_added: Vec<(Address, Option<B256>)>,
+    /// Optimal-list entries only in the first report.
+    optimal_removed: Vec<(Address, Option<B256>)>,
+}
+
+/// Compare two validation reports for the same logical transaction, e.g. before and
+/// after a contract upgrade.
+pub async fn run(args: ReportDiffArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human", "markdown"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let (before, before_engine) = load(&args.before)?;
+    let (after, after_engine) = load(&args.after)?;
+    if !global.quiet {
+        if let Some(warning) = engine::mismatch(before_engine.as_ref(), after_engine.as_ref()) {
+            eprintln!("{}", warning);
+        }
+    }
+    let diff = diff(&before, &after);
+    match output {
+        "json" => attest::write_report(to_json(&diff)?, signer.as_ref(), &sink).await?,
+        "markdown" => print_markdown(&diff),
+        "human" => print_human(&diff, &units),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// A report and the `engine` it records, if any.
+fn load(path: &Path) -> Result<(ValidationReport, Option<serde_json::Value>)> {
+    let text = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    // A signed report is read as the report it wraps; `verify-signature` checks it.
+    serde_json::from_str::<serde_json::Value>(&text)
+        .map(attest::unwrap_report)
+        .and_then(|value| {
+            let engine = value.get("engine").cloned();
+            serde_json::from_value(value).map(|report| (report, engine))
+        })
+        .wrap_err_with(|| {
+            format!(
+                "invalid report in {}: expected one validation report",
+                path.display()
+            )
+        })
+}
+
+/// Findings are matched by kind and address (and slot, for duplicates); repeats of the
+/// same key pair up in report order.
+fn diff<'a>(before: &'a ValidationReport, after: &'a ValidationReport) -> ReportDiff<'a> {
+    let cost = |name, f: fn(&ValidationReport) -> i64| Cost {
+        name,
+        before: f(before),
+        after: f(after),
+    };
+    let costs = vec![
+        cost("Declared list", |r| r.gas_summary.declared_list_cost as i64),
+        cost("Optimal list", |r| r.gas_summary.optimal_list_cost as i64),
+        cost("Upfront waste", |r| r.gas_summary.waste_per_tx),
+        cost("Runtime penalty", |r| execution_penalty(r) as i64),
+    ];
+
+    let mut keyed: BTreeMap<_, (Vec<&DiffEntry>, Vec<&DiffEntry>)> = BTreeMap::new();
+    for e in &before.entries {
+        keyed.entry(entry_key(e)).or_default().0.push(e);
+    }
+    for e in &after.entries {
+        keyed.entry(entry_key(e)).or_default().1.push(e);
+    }
+    let mut changes = Vec::new();
+    for (_, (b, a)) in keyed {
+        for i in 0..b.len().max(a.len()) {
+            match (b.get(i), a.get(i)) {
+                (Some(b), Some(a)) if b != a => changes.push(Change::Changed {
+                    before: b,
+                    after: a,
+                }),
+                (Some(b), None) => changes.push(Change::Resolved(b)),
+                (None, Some(a)) => changes.push(Change::Introduced(a)),
+                _ => {}
+            }
+        }
+    }
+
+    let before_list = optimal_entries(before);
+    let after_list = optimal_entries(after);
+    ReportDiff {
+        valid: (before.is_valid, after.is_valid),
+        costs,
+        changes,
+        optimal_added: after_list.difference(&before_list).copied().collect(),
+        optimal_removed: before_list.difference(&after_list).copied().collect(),
+    }
+}
+
+fn entry_key(entry: &DiffEntry) -> (&'static str, Address, Option<B256>) {
+    let (kind, address, _) = issue_row(entry);
+    let slot = match entry {
+        DiffEntry::Duplicate { storage_key, .. } => Some(*storage_key),
+        _ => None,
+    };
+    (kind, *address, slot)
+}
+
+fn optimal_entries(report: &ValidationReport) -> BTreeSet<(Address, Option<B256>)> {
+    list_entries(&report.optimal_list)
+}
+
+/// A list's entries: each address, and each of its slots.
+pub(super) fn list_entries(list: &AccessList) -> BTreeSet<(Address, Option<B256>)> {
+    let mut out = BTreeSet::new();
+    for item in &list.0 {
+        out.insert((item.address, None));
+        for key in &item.storage_keys {
+            out.insert((item.address, Some(*key)));
+        }
+    }
+    out
+}
+
+impl ReportDiff<'_> {
+    fn is_empty(&self) -> bool {
+        self.valid.0 == self.valid.1
+            && self.costs.iter().all(|c| c.before == c.after)
+            && self.changes.is_empty()
+            && self.optimal_added.is_empty()
+            && self.optimal_removed.is_empty()
+    }
+}
+
+fn describe(entry: &DiffEntry, units: &Units) -> String {
+    let (kind, address, slots) = issue_row(entry);
+    format!(
+        "{} {} ({} slots, {})",
+        kind,
+        address,
+        slots.len(),
+        units.gas(entry.gas_waste())
+    )
+}
+
+pub(super) fn optimal_line(address: &Address, slot: &Option<B256>) -> String {
+    match slot {
+        Some(slot) => format!("{} slot {}", address, slot),
+        None => address.to_string(),
+    }
+}
+
+fn yes_no(valid: bool) -> &'static str {
+    if valid {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+fn print_human(diff: &ReportDiff, units: &Units) {
+    if diff.is_empty() {
+        println!("No differences.");
+        return;
+    }
+    println!(
+        "Valid:            {} → {}",
+        yes_no(diff.valid.0),
+        yes_no(diff.valid.1)
+    );
+    // Costs can be negative (e.g. savings the declared list forgoes).
+    let amount = |gas: i64| match u64::try_from(gas) {
+        Ok(gas) => units.gas(gas),
+        Err(_) => units.signed(gas),
+    };
+    for c in &diff.costs {
+        println!(
+            "{:<17} {} → {}  ({})",
+            format!("{}:", c.name),
+            amount(c.before),
+            amount(c.after),
+            units.signed(c.after - c.before)
+        );
+    }
+    if !diff.changes.is_empty() {
+        println!("Findings:");
+        for change in &diff.changes {
+            match change {
+                Change::Resolved(e) => println!("  - resolved    {}", describe(e, units)),
+                Change::Introduced(e) => println!("  + introduced  {}", describe(e, units)),
+                Change::Changed { before, after } => {
+                    let (kind, address, slots) = issue_row(before);
+                    println!(
+                        "  ~ changed     {} {}: {} → {} slots, {} → {}",
+                        kind,
+                        address,
+                        slots.len(),
+                        issue_row(after).2.len(),
+                        units.gas(before.gas_waste()),
+                        units.gas(after.gas_waste())
+                    );
+                }
+            }
+        }
+    }
+    if !diff.optimal_added.is_empty() || !diff.optimal_removed.is_empty() {
+        println!("Optimal list:");
+        for (address, slot) in &diff.optimal_added {
+            println!("  + {}", optimal_line(address, slot));
+        }
+        for (address, slot) in &diff.optimal_removed {
+            println!("  - {}", optimal_line(address, slot));
+        }
+    }
+}
+
+fn print_markdown(diff: &ReportDiff) {
+    println!("### Report diff");
+    println!();
+    if diff.is_empty() {
+        println!("No differences.");
+        return;
+    }
+    println!("| | Before | After | Change |");
+    println!("|---|---:|---:|---:|");
+    println!(
+        "| Valid | {} | {} | |",
+        yes_no(diff.valid.0),
+        yes_no(diff.valid.1)
+    );
+    for c in &diff.costs {
+        println!(
+            "| {} | {} | {} | {:+} |",
+            c.name,
+            c.before,
+            c.after,
+            c.after - c.before
+        );
+    }
+    if !diff.changes.is_empty() {
+        println!();
+        println!("| Change | Issue | Address | Slots | Gas |");
+        println!("|---|---|---|---:|---:|");
+        for change in &diff.changes {
+            let (label, entry, previous) = match change {
+                Change::Resolved(e) => ("resolved", *e, None),
+                Change::Introduced(e) => ("introduced", *e, None),
+                Change::Changed { before, after } => ("changed", *after, Some(*before)),
+            };
+            let (kind, address, slots) = issue_row(entry);
+            match previous {
+                Some(previous) => println!(
+                    "| {} | {} | `{}` | {} → {} | {} → {} |",
+                    label,
+                    kind,
+                    address,
+                    issue_row(previous).2.len(),
+                    slots.len(),
+                    previous.gas_waste(),
+                    entry.gas_waste()
+                ),
+                None => println!(
+                    "| {} | {} | `{}` | {} | {} |",
+                    label,
+                    kind,
+                    address,
+                    slots.len(),
+                    entry.gas_waste()
+                ),
+            }
+        }
+    }
+    if !diff.optimal_added.is_empty() || !diff.optimal_removed.is_empty() {
+        println!();
+        println!("Optimal list:");
+        println!();
+        for (address, slot) in &diff.optimal_added {
+            println!("- added `{}`", optimal_line(address, slot));
+        }
+        for (address, slot) in &diff.optimal_removed {
+            println!("- removed `{}`", optimal_line(address, slot));
+        }
+    }
+}
+
+fn to_json(diff: &ReportDiff) -> Result<serde_json::Value> {
+    let costs: serde_json::Map<_, _> = diff
+        .costs
+        .iter()
+        .map(|c| {
+            (
+                c.name.to_lowercase().replace(' ', "_"),
+                json!({ "before": c.before, "after": c.after, "delta": c.after - c.before }),
+            )
+        })
+        .collect();
+    let mut resolved = Vec::new();
+    let mut introduced = Vec::new();
+    let mut changed = Vec::new();
+    for change in &diff.changes {
+        match change {
+            Change::Resolved(e) => resolved.push(serde_json::to_value(e)?),
+            Change::Introduced(e) => introduced.push(serde_json::to_value(e)?),
+            Change::Changed { before, after } => {
+                changed.push(json!({ "before": before, "after": after }))
+            }
+        }
+    }
+    let entries = |list: &[(Address, Option<B256>)]| -> Vec<serde_json::Value> {
+        list.iter()
+            .map(|(address, slot)| json!({ "address": address, "storage_key": slot }))
+            .collect()
+    };
+    Ok(json!({
+        "identical": diff.is_empty(),
+        "is_valid": { "before": diff.valid.0, "after": diff.valid.1 },
+        "costs": costs,
+        "resolved": resolved,
+        "introduced": introduced,
+        "changed": changed,
+        "optimal_list": {
+            "added": entries(&diff.optimal_added),
+            "removed": entries(&diff.optimal_removed),
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_rpc_types_eth::{AccessList, AccessListItem};
+    use hammer_core::GasSummary;
+
+    fn report(entries: Vec<DiffEntry>, optimal: Vec<AccessListItem>) -> ValidationReport {
+        ValidationReport {
+            is_valid: entries.is_empty(),
+            is_valid_lenient: !entries.iter().any(DiffEntry::is_shortfall),
+            gas_summary: GasSummary {
+                declared_list_cost: 0,
+                optimal_list_cost: 0,
+                no_list_cost: 0,
+                waste_per_tx: entries.iter().map(|e| e.gas_waste() as i64).sum(),
+                savings_vs_no_list: 0,
+            },
+            entries,
+            optimal_list: AccessList(optimal),
+            new_accounts: vec![],
+            precompile_calls: Default::default(),
+            fixes: vec![],
+            declared_origin: Default::default(),
+            callbacks: vec![],
+            volatile: Default::default(),
+            near_misses: Vec::new(),
+            budget_exceeded: Vec::new(),
+            gas_sensitive: Vec::new(),
+            transient: Default::default(),
+        }
+    }
+
+    fn stale(byte: u8, slots: u8) -> DiffEntry {
+        DiffEntry::Stale {
+            address: Address::repeat_byte(byte),
+            storage_keys: (0..slots).map(B256::with_last_byte).collect(),
+            gas_waste: 1900 * slots as u64,
+        }
+    }
+
+    #[test]
+    fn test_identical_reports() {
+        let a = report(vec![stale(1, 2)], vec![]);
+        let d = diff(&a, &a);
+        assert!(d.is_empty());
+    }
+
+    #[test]
+    fn test_resolved_introduced_and_changed() {
+        let before = report(vec![stale(1, 2), stale(2, 1)], vec![]);
+        let after = report(vec![stale(1, 3), stale(3, 1)], vec![]);
+        let d = diff(&before, &after);
+        assert!(!d.is_empty());
+        let kinds: Vec<_> = d
+            .changes
+            .iter()
+            .map(|c| match c {
+                Change::Resolved(e) => ("resolved", *issue_row(e).1),
+                Change::Introduced(e) => ("introduced", *issue_row(e).1),
+                Change::Changed { after, .. } => ("changed", *issue_row(after).1),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("changed", Address::repeat_byte(1)),
+                ("resolved", Address::repeat_byte(2)),
+                ("introduced", Address::repeat_byte(3)),
+            ]
+        );
+        let waste = d.costs.iter().find(|c| c.name == "Upfront waste").unwrap();
+        assert_eq!((waste.before, waste.after), (5700, 7600));
+    }
+
+    #[test]
+    fn test_optimal_list_slot_changes() {
+        let item = |slots: Vec<B256>| AccessListItem {
+            address: Address::repeat_byte(9),
+            storage_keys: slots,
+        };
+        let before = report(vec![], vec![item(vec![B256::with_last_byte(1)])]);
+        let after = report(vec![], vec![item(vec![B256::with_last_byte(2)])]);
+        let d = diff(&before, &after);
+        assert_eq!(
+            d.optimal_added,
+            vec![(Address::repeat_byte(9), Some(B256::with_last_byte(2)))]
+        );
+        assert_eq!(
+            d.optimal_removed,
+            vec![(Address::repeat_byte(9), Some(B256::with_last_byte(1)))]
+        );
+        assert!(d.changes.is_empty());
+    }
+}
@@ -0,0 +1,158 @@
+//! A mined transaction replayed against the block it was first seen at, for `compare
+//! --as-of-seen`.
+//!
+//! The list a sender attaches is built from the state when they send, not when the
+//! transaction lands. Between the two, other transactions move pools, rounds and
+//! balances, so even a perfect list at send time can be wrong at inclusion. Replaying at
+//! the seen block splits the gas a list loses into what it lost to that latency and what
+//! it lost to how it was generated.
+
+use alloy::network::Ethereum;
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, B256};
+use alloy_provider::{DynProvider, Provider};
+use eyre::{Context, Result};
+use hammer_core::{
+    score_access_list, validate_replay_at_spec_with, AccessList, ListScore, OptimizedAccessList,
+    OptimizerConfig,
+};
+use serde_json::json;
+
+use super::compare::{replay_env, replay_spec, BlockState, Comparison};
+use super::drift::Drift;
+use super::report_diff::optimal_line;
+use super::units::Units;
+use super::util::{apply_chain_rules, traced};
+
+/// The transaction at the block it was seen at, against the same at inclusion.
+pub(super) struct AsOfSeen {
+    pub(super) seen_block: u64,
+    pub(super) included_block: u64,
+    /// The optimal list at the seen block.
+    pub(super) seen_optimal: AccessList,
+    /// How the optimal list moved from the seen block to inclusion.
+    pub(super) drift: Drift,
+    /// The declared list against the optimum at the seen block: what generation lost.
+    pub(super) at_send: ListScore,
+    /// The declared list against the optimum at inclusion: what it actually lost.
+    pub(super) at_inclusion: ListScore,
+    /// The seen block's optimal list against the optimum at inclusion: what a perfect
+    /// list at send time would still have lost to latency.
+    pub(super) latency: ListScore,
+}
+
+/// Replay `comparison`'s transaction over the state at `seen`, with `seen`'s header, and
+/// set the result against the replay at inclusion. The nonce check is skipped, as in
+/// `compare`: the sender may have had transactions pending ahead of it.
+pub(super) async fn replay(
+    provider: &DynProvider<Ethereum>,
+    comparison: &Comparison,
+    seen: u64,
+) -> Result<AsOfSeen> {
+    if seen >= comparison.block_number {
+        eyre::bail!(
+            "--as-of-seen {} is not before the inclusion block {}",
+            seen,
+            comparison.block_number
+        );
+    }
+    let tx = provider
+        .get_transaction_by_hash(comparison.tx_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Transaction not found"))?;
+    let block = provider
+        .get_block_by_number(seen.into())
+        .await?
+        .ok_or_else(|| eyre::eyre!("block {} not found", seen))?;
+    let mut state = BlockState::at(provider, &block.header)?;
+    let (tx_env, tx_req) = replay_env(&tx, &state.block_env);
+    let state_block_id = BlockId::hash(block.header.hash);
+    super::prefetch::prewarm(
+        &mut state.db,
+        provider.clone(),
+        state_block_id,
+        state_block_id,
+        tx_req,
+        &comparison.declared,
+    )
+    .await;
+    let block_env = state.block_env.clone();
+    let mut config = OptimizerConfig::default();
+    apply_chain_rules(&mut config, Some(comparison.chain_id), seen);
+    let spec = replay_spec(comparison.chain_id, seen);
+    let (report, _) = traced(seen, || {
+        validate_replay_at_spec_with(
+            &mut state.db,
+            tx_env,
+            block_env,
+            comparison.declared.clone(),
+            spec,
+            &config,
+        )
+    })
+    .wrap_err_with(|| format!("replay at seen block {} failed", seen))?;
+
+    let included = OptimizedAccessList::new(comparison.report.optimal_list.clone(), vec![]);
+    Ok(AsOfSeen {
+        seen_block: seen,
+        included_block: comparison.block_number,
+        drift: Drift::between(&report.optimal_list, &included.list),
+        at_send: ListScore::of(&report),
+        at_inclusion: ListScore::of(&comparison.report),
+        latency: score_access_list(&report.optimal_list, &included, &config.schedule),
+        seen_optimal: report.optimal_list,
+    })
+}
+
+pub(super) fn to_json(seen: &AsOfSeen) -> serde_json::Value {
+    let entries = |list: &[(Address, Option<B256>)]| -> Vec<serde_json::Value> {
+        list.iter()
+            .map(|(address, slot)| json!({ "address": address, "storage_key": slot }))
+            .collect()
+    };
+    json!({
+        "seen_block": seen.seen_block,
+        "latency_blocks": seen.included_block - seen.seen_block,
+        "seen_optimal_list": seen.seen_optimal,
+        "stability": seen.drift.stability(),
+        "added": entries(&seen.drift.added),
+        "removed": entries(&seen.drift.removed),
+        "at_send": seen.at_send,
+        "at_inclusion": seen.at_inclusion,
+        "latency": seen.latency,
+    })
+}
+
+pub(super) fn print(seen: &AsOfSeen, units: &Units) {
+    println!(
+        "As of seen block {} ({} blocks before inclusion):",
+        seen.seen_block,
+        seen.included_block - seen.seen_block
+    );
+    if seen.drift.is_stable() {
+        println!("  The optimal list did not change between sending and inclusion.");
+    } else {
+        println!(
+            "  The optimal list drifted: {} entries kept, {} added, {} removed ({:.0}% stable)",
+            seen.drift.kept,
+            seen.drift.added.len(),
+            seen.drift.removed.len(),
+            seen.drift.stability() * 100.0
+        );
+        for (address, slot) in &seen.drift.added {
+            println!("    + {}", optimal_line(address, slot));
+        }
+        for (address, slot) in &seen.drift.removed {
+            println!("    - {}", optimal_line(address, slot));
+        }
+    }
+    println!(
+        "  Declared list: {} extra at send time, {} extra at inclusion",
+        units.signed(seen.at_send.net_cost),
+        units.signed(seen.at_inclusion.net_cost)
+    );
+    println!(
+        "  Lost to latency: {} (the optimal list at send time, at inclusion)",
+        units.signed(seen.latency.net_cost)
+    );
+}
@@ -0,0 +1,223 @@
+//! `hammer selftest`: generate and validate against a built-in fixture, with no network.
+//!
+//! The fixture is a contract that reads its own slot 0 and then STATICCALLs a second
+//! contract reading slots 0 and 1. The optimal list is known: the callee with both slots,
+//! the caller stripped as `tx.to`. A binary whose EVM or optimizer has drifted from that
+//! fails here before it reports on a real transaction.
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+use clap::Args;
+use eyre::Result;
+use hammer_core::{
+    access_list_gas_cost, generate, validate, AccessList, AccessListItem, DiffEntry,
+};
+use revm::context::{BlockEnv, TxEnv};
+use revm::database::InMemoryDB;
+use revm::primitives::TxKind;
+use revm::state::{AccountInfo, Bytecode};
+use serde_json::json;
+
+use super::global::GlobalArgs;
+
+const SENDER: Address = Address::with_last_byte(0xa0);
+const CALLER: Address = Address::with_last_byte(0xa1);
+const CALLEE: Address = Address::with_last_byte(0xa2);
+const COINBASE: Address = Address::with_last_byte(0xa3);
+
+#[derive(Args)]
+pub struct SelftestArgs {}
+
+/// One check and what went wrong, if anything.
+struct Check {
+    name: &'static str,
+    failure: Option<String>,
+}
+
+impl Check {
+    fn new(name: &'static str, failure: Option<String>) -> Self {
+        Self { name, failure }
+    }
+}
+
+/// Run the selftest command. Exits non-zero if any check fails.
+pub fn run(_args: SelftestArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human"])?;
+    let checks = run_checks();
+    let passed = checks.iter().all(|c| c.failure.is_none());
+    match output {
+        "json" => {
+            let report = json!({
+                "passed": passed,
+                "checks": checks
+                    .iter()
+                    .map(|c| json!({
+                        "name": c.name,
+                        "passed": c.failure.is_none(),
+                        "failure": c.failure,
+                    }))
+                    .collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+        "human" => {
+            for check in &checks {
+                match &check.failure {
+                    None => println!("PASS  {}", check.name),
+                    Some(failure) => println!("FAIL  {}: {}", check.name, failure),
+                }
+            }
+            println!(
+                "{}",
+                if passed {
+                    "Selftest passed."
+                } else {
+                    "Selftest FAILED."
+                }
+            );
+        }
+        _ => unreachable!(),
+    }
+    std::process::exit(if passed { 0 } else { 1 });
+}
+
+fn run_checks() -> Vec<Check> {
+    let expected = AccessList(vec![AccessListItem {
+        address: CALLEE,
+        storage_keys: vec![B256::ZERO, B256::with_last_byte(1)],
+    }]);
+    let generated = match generate(fixture(), tx(), block()) {
+        Ok(generated) => generated.list,
+        Err(e) => return vec![Check::new("generate", Some(e.to_string()))],
+    };
+    let mut checks = vec![Check::new(
+        "generate: callee and both slots listed, tx.to stripped",
+        (generated != expected).then(|| format!("got {:?}", generated)),
+    )];
+    checks.push(Check::new(
+        "gas: list costs one address and two slots",
+        (access_list_gas_cost(&expected) != 2_400 + 2 * 1_900)
+            .then(|| format!("got {}", access_list_gas_cost(&expected))),
+    ));
+
+    let validated = |declared: AccessList| validate(fixture(), tx(), block(), declared);
+    checks.push(Check::new(
+        "validate: the generated list is valid",
+        match validated(generated.clone()) {
+            Ok(report) if report.is_valid => None,
+            Ok(report) => Some(format!("{:?}", report.entries)),
+            Err(e) => Some(e.to_string()),
+        },
+    ));
+    checks.push(Check::new(
+        "validate: an empty list misses the callee",
+        match validated(AccessList::default()) {
+            Ok(report)
+                if report.entries.iter().any(
+                    |e| matches!(e, DiffEntry::Missing { address, .. } if *address == CALLEE),
+                ) =>
+            {
+                None
+            }
+            Ok(report) => Some(format!("{:?}", report.entries)),
+            Err(e) => Some(e.to_string()),
+        },
+    ));
+    let mut with_target = generated;
+    with_target.0.push(AccessListItem {
+        address: CALLER,
+        storage_keys: vec![],
+    });
+    checks.push(Check::new(
+        "validate: declaring tx.to is redundant",
+        match validated(with_target) {
+            Ok(report)
+                if report.entries.iter().any(
+                    |e| matches!(e, DiffEntry::Redundant { address, .. } if *address == CALLER),
+                ) =>
+            {
+                None
+            }
+            Ok(report) => Some(format!("{:?}", report.entries)),
+            Err(e) => Some(e.to_string()),
+        },
+    ));
+    checks
+}
+
+/// The sender, funded; the caller, which reads slot 0 and STATICCALLs the callee; the
+/// callee, which reads slots 0 and 1.
+fn fixture() -> InMemoryDB {
+    let mut caller = vec![
+        0x60, 0x00, 0x54, 0x50, // SLOAD(0), POP
+        0x60, 0x00, 0x60, 0x00, // retSize, retOffset
+        0x60, 0x00, 0x60, 0x00, // argsSize, argsOffset
+        0x73, // PUSH20 callee
+    ];
+    caller.extend_from_slice(CALLEE.as_slice());
+    caller.extend_from_slice(&[0x5a, 0xfa, 0x00]); // GAS, STATICCALL, STOP
+    let callee = vec![
+        0x60, 0x00, 0x54, 0x50, // SLOAD(0), POP
+        0x60, 0x01, 0x54, 0x00, // SLOAD(1), STOP
+    ];
+
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        SENDER,
+        AccountInfo {
+            balance: U256::from(10u64).pow(U256::from(18u64)),
+            ..Default::default()
+        },
+    );
+    for (address, code) in [(CALLER, caller), (CALLEE, callee)] {
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(Bytes::from(code))),
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+    }
+    db
+}
+
+fn tx() -> TxEnv {
+    TxEnv::builder()
+        .caller(SENDER)
+        .nonce(0)
+        .kind(TxKind::Call(CALLER))
+        .gas_limit(1_000_000)
+        .gas_price(1_000_000_000u128)
+        .build()
+        .unwrap()
+}
+
+fn block() -> BlockEnv {
+    BlockEnv {
+        number: U256::from(20_000_000u64),
+        beneficiary: COINBASE,
+        timestamp: U256::from(1_700_000_000u64),
+        gas_limit: 30_000_000,
+        basefee: 1_000_000_000,
+        prevrandao: Some(B256::ZERO),
+        blob_excess_gas_and_price: Some(
+            revm::context_interface::block::BlobExcessGasAndPrice::new(
+                0,
+                revm::primitives::eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
+            ),
+        ),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixture_checks_pass() {
+        for check in run_checks() {
+            assert_eq!(check.failure, None, "{}", check.name);
+        }
+    }
+}
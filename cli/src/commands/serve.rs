@@ -0,0 +1,821 @@
+use alloy::network::Ethereum;
+use alloy_eips::BlockId;
+use alloy_primitives::B256;
+use alloy_provider::{DynProvider, Provider};
+use alloy_rpc_types_eth::TransactionRequest;
+use clap::Args;
+use eyre::{Context, Result};
+use hammer_core::{
+    generate_with_strategy, score_access_list, validate_with, OptimizerConfig, Strategy,
+};
+use http_body_util::{BodyExt, Full, LengthLimitError, Limited};
+use hyper::body::{Bytes, Incoming};
+use hyper::header::{HeaderMap, HeaderValue};
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use revm::context::TxEnv;
+use revm::primitives::TxKind;
+use serde_json::{json, Value};
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::net::TcpListener;
+
+use super::compare::{self, prepare_one, BlockState};
+use super::config::OptimizerArgs;
+use super::global::GlobalArgs;
+use super::limits::{ApiKeys, Denied, LimitArgs, LimitExceeded};
+use super::metrics::{key_id, Outcome, Usage};
+use super::util::{
+    access_list_json, apply_chain_rules, assert_post_berlin, fetch_block, state_read_error, traced,
+};
+
+/// Gas limit for calls that do not set `gas`, as in `generate`.
+const DEFAULT_GAS: u64 = 30_000_000;
+
+// JSON-RPC 2.0 error codes.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The request was well-formed but could not be served (RPC failure, revert guard, ...).
+const SERVER_ERROR: i64 = -32000;
+/// Over a `--max-*` limit or the key's quota (EIP-1474's "limit exceeded").
+const LIMIT_EXCEEDED: i64 = -32005;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8546")]
+    pub listen: SocketAddr,
+    /// Blocks whose pre-warmed state is kept between requests; the oldest is dropped first
+    #[arg(long, default_value = "16")]
+    pub cache_blocks: usize,
+    /// Serve usage counters per API key at GET /metrics, in Prometheus text format
+    #[arg(long)]
+    pub metrics: bool,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+    #[command(flatten)]
+    pub limits: LimitArgs,
+}
+
+/// Recent blocks by chain id and hash: each one's number and its pre-warmed state.
+type BlockCache = BTreeMap<(u64, B256), (u64, Arc<Mutex<BlockState>>)>;
+
+/// What every request shares: one provider, and the pre-warmed state of recent blocks.
+struct Server {
+    provider: DynProvider<Ethereum>,
+    chain_id: u64,
+    config: OptimizerConfig,
+    cache_blocks: usize,
+    limits: LimitArgs,
+    /// Keys requests must carry, with their quotas; `None` to serve anyone.
+    api_keys: Option<ApiKeys>,
+    /// Whether GET /metrics serves `usage`.
+    metrics: bool,
+    usage: Usage,
+    /// Each block's number and state, locked while a trace runs over it: traces against
+    /// one block take turns, ones against different blocks run side by side.
+    blocks: Mutex<BlockCache>,
+}
+
+/// A JSON-RPC error object, and the HTTP status to answer a lone request with.
+#[derive(Debug, PartialEq, Eq)]
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+    status: StatusCode,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+            status: StatusCode::OK,
+        }
+    }
+
+    fn server(e: eyre::Report) -> Self {
+        match e.downcast_ref::<LimitExceeded>() {
+            Some(limit) => Self::limit(limit),
+            None => Self::new(SERVER_ERROR, format!("{:#}", e)),
+        }
+    }
+
+    fn limit(limit: &LimitExceeded) -> Self {
+        Self {
+            code: LIMIT_EXCEEDED,
+            message: limit.to_string(),
+            data: Some(limit.data()),
+            status: limit.status(),
+        }
+    }
+}
+
+/// Run the serve command.
+///
+/// Serves `hammer_generateAccessList`, `hammer_validateAccessList`,
+/// `hammer_scoreAccessList` and `hammer_compareTx` as JSON-RPC 2.0 over HTTP POST, batches included, until interrupted. The state each
+/// request fetches stays cached per block, so later requests against the same block only
+/// fetch what the earlier ones did not touch. Requests over a `--max-*` limit or their
+/// key's quota are refused with a 4xx status and a structured error before they are
+/// traced (see [`super::limits`]).
+///
+/// # Test boundary
+///
+/// Serving requires a live RPC connection. Request parsing and error responses are
+/// covered by the unit tests below; the methods themselves are `generate`, `validate`
+/// and `compare`'s.
+pub async fn run(args: ServeArgs, global: &GlobalArgs) -> Result<()> {
+    let config = args.optimizer.config()?;
+    let api_keys = args
+        .limits
+        .api_keys
+        .as_deref()
+        .map(ApiKeys::load)
+        .transpose()?;
+    let provider = global.provider().await?;
+    let chain_id = match global.chain_id()? {
+        Some(id) => id,
+        None => provider
+            .get_chain_id()
+            .await
+            .wrap_err("failed to fetch chain id")?,
+    };
+    let listener = TcpListener::bind(args.listen)
+        .await
+        .wrap_err_with(|| format!("failed to listen on {}", args.listen))?;
+    if !global.quiet {
+        eprintln!("Listening on http://{}", listener.local_addr()?);
+    }
+
+    let server = Arc::new(Server {
+        provider,
+        chain_id,
+        config,
+        cache_blocks: args.cache_blocks.max(1),
+        limits: args.limits,
+        api_keys,
+        metrics: args.metrics,
+        usage: Usage::default(),
+        blocks: Mutex::new(BTreeMap::new()),
+    });
+    loop {
+        let (stream, _) = tokio::select! {
+            accepted = listener.accept() => accepted.wrap_err("failed to accept connection")?,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+        let server = server.clone();
+        tokio::spawn(async move {
+            let service = hyper::service::service_fn(move |req| {
+                let server = server.clone();
+                async move { Ok::<_, std::convert::Infallible>(server.respond(req).await) }
+            });
+            if let Err(e) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::debug!(target: "hammer::serve", error = %e, "connection closed");
+            }
+        });
+    }
+}
+
+impl Server {
+    async fn respond(&self, req: Request<Incoming>) -> Response<Full<Bytes>> {
+        if self.metrics && req.method() == Method::GET && req.uri().path() == "/metrics" {
+            let mut response = Response::new(Full::new(Bytes::from(self.usage.render())));
+            response.headers_mut().insert(
+                hyper::header::CONTENT_TYPE,
+                HeaderValue::from_static("text/plain; version=0.0.4"),
+            );
+            return response;
+        }
+        if req.method() != Method::POST {
+            return http_response(
+                StatusCode::METHOD_NOT_ALLOWED,
+                error_response(Value::Null, RpcError::new(INVALID_REQUEST, "use POST")),
+            );
+        }
+        let key = api_key(req.headers()).map(str::to_string);
+        let label = match (&self.api_keys, key.as_deref()) {
+            (None, _) => "anonymous".to_string(),
+            (Some(keys), Some(key)) if keys.contains(key) => key_id(key),
+            (Some(_), _) => "unknown".to_string(),
+        };
+        // Turn away unknown keys before reading the body; quotas are counted once the
+        // number of requests is known.
+        if let Some(denied) = self.admit(key.as_deref(), &label, 0) {
+            return denied;
+        }
+        let max_body = self.limits.max_body_bytes;
+        let declared_len = req
+            .headers()
+            .get(hyper::header::CONTENT_LENGTH)
+            .and_then(|len| len.to_str().ok()?.parse::<usize>().ok());
+        if let Some(Err(limit)) = declared_len.map(|len| self.limits.check_body(len)) {
+            self.usage.reject(&label, limit.limit);
+            return lone_error(Value::Null, RpcError::limit(&limit));
+        }
+        let body = match Limited::new(req.into_body(), max_body).collect().await {
+            Ok(body) => body.to_bytes(),
+            // A body sent without its length: all that is known is that it is over.
+            Err(e) if e.is::<LengthLimitError>() => {
+                let limit = self.limits.check_body(max_body + 1).unwrap_err();
+                self.usage.reject(&label, limit.limit);
+                return lone_error(Value::Null, RpcError::limit(&limit));
+            }
+            Err(e) => {
+                return http_response(
+                    StatusCode::BAD_REQUEST,
+                    error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())),
+                )
+            }
+        };
+        let (response, status) = match serde_json::from_slice::<Value>(&body) {
+            Err(e) => (
+                error_response(Value::Null, RpcError::new(PARSE_ERROR, e.to_string())),
+                StatusCode::OK,
+            ),
+            Ok(Value::Array(batch)) if !batch.is_empty() => {
+                if let Err(limit) = self.limits.check_batch(batch.len()) {
+                    self.usage.reject(&label, limit.limit);
+                    return lone_error(Value::Null, RpcError::limit(&limit));
+                }
+                if let Some(denied) = self.admit(key.as_deref(), &label, batch.len() as u32) {
+                    return denied;
+                }
+                let mut responses = Vec::with_capacity(batch.len());
+                for request in &batch {
+                    responses.push(self.handle(request, &label).await.0);
+                }
+                (Value::Array(responses), StatusCode::OK)
+            }
+            Ok(request) => {
+                if let Some(denied) = self.admit(key.as_deref(), &label, 1) {
+                    return denied;
+                }
+                self.handle(&request, &label).await
+            }
+        };
+        http_response(status, response)
+    }
+
+    /// Count `requests` against the caller's quota; the response turning them away, if
+    /// they are not admitted.
+    fn admit(
+        &self,
+        key: Option<&str>,
+        label: &str,
+        requests: u32,
+    ) -> Option<Response<Full<Bytes>>> {
+        let api_keys = self.api_keys.as_ref()?;
+        let denied = api_keys.admit(key, requests, Instant::now()).err()?;
+        self.usage.reject(
+            label,
+            match denied {
+                Denied::Unauthorized => "unauthorized",
+                Denied::OverQuota(_) => "quota",
+            },
+        );
+        match denied {
+            Denied::Unauthorized => Some(http_response(
+                StatusCode::UNAUTHORIZED,
+                error_response(
+                    Value::Null,
+                    RpcError::new(INVALID_REQUEST, "missing or unknown API key"),
+                ),
+            )),
+            Denied::OverQuota(renews_in) => {
+                let retry_after = renews_in.as_secs().max(1);
+                let mut error = RpcError::new(LIMIT_EXCEEDED, "API key over its quota");
+                error.data = Some(json!({"limit": "quota", "retry_after": retry_after}));
+                let mut response = http_response(
+                    StatusCode::TOO_MANY_REQUESTS,
+                    error_response(Value::Null, error),
+                );
+                response
+                    .headers_mut()
+                    .insert(hyper::header::RETRY_AFTER, HeaderValue::from(retry_after));
+                Some(response)
+            }
+        }
+    }
+
+    /// Answer one request object from the caller labelled `label`, with the HTTP status to
+    /// answer it with alone.
+    async fn handle(&self, request: &Value, label: &str) -> (Value, StatusCode) {
+        let (id, method, params) = match parse_request(request) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                self.usage.call(label, "", Outcome::Error);
+                return (error_response(id_of(request), e), StatusCode::OK);
+            }
+        };
+        let start = Instant::now();
+        let result = self.dispatch(method, params).await;
+        tracing::info!(
+            target: "hammer::serve",
+            method,
+            ok = result.is_ok(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "request served"
+        );
+        let outcome = match &result {
+            Ok(_) => Outcome::Ok,
+            Err(e) if e.code == LIMIT_EXCEEDED => Outcome::Limited,
+            Err(_) => Outcome::Error,
+        };
+        self.usage.call(label, method, outcome);
+        match result {
+            Ok(result) => (
+                json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                StatusCode::OK,
+            ),
+            Err(e) => {
+                let status = e.status;
+                (error_response(id, e), status)
+            }
+        }
+    }
+
+    async fn dispatch(&self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "hammer_generateAccessList" => {
+                let (tx, block) = call_params(params)?;
+                self.check_call(&tx)?;
+                self.generate(tx, block).await.map_err(RpcError::server)
+            }
+            "hammer_validateAccessList" => {
+                let (tx, block) = call_params(params)?;
+                self.check_call(&tx)?;
+                self.validate(tx, block).await.map_err(RpcError::server)
+            }
+            "hammer_scoreAccessList" => {
+                let (tx, block) = call_params(params)?;
+                self.check_call(&tx)?;
+                self.score(tx, block).await.map_err(RpcError::server)
+            }
+            "hammer_compareTx" => {
+                let tx_hash = hash_params(params)?;
+                self.compare(tx_hash).await.map_err(RpcError::server)
+            }
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("method {} not found", method),
+            )),
+        }
+    }
+
+    fn check_call(&self, tx: &TransactionRequest) -> Result<(), RpcError> {
+        let input_len = tx.input.input().map_or(0, |input| input.len());
+        self.limits
+            .check_call(input_len, tx.gas)
+            .map_err(|limit| RpcError::limit(&limit))
+    }
+
+    /// `generate`'s access list for the call, as `generate --output json` prints it.
+    async fn generate(&self, tx: TransactionRequest, block_id: BlockId) -> Result<Value> {
+        let (key, scratch, tx_env, config) = self.prepare(&tx, block_id).await?;
+        let optimal = self
+            .trace(key, scratch, move |state| {
+                let block_env = state.block_env.clone();
+                traced(state.block_number, || {
+                    generate_with_strategy(
+                        &mut state.db,
+                        tx_env,
+                        block_env,
+                        &config,
+                        Strategy::default(),
+                    )
+                })
+                .wrap_err("access list generation failed")
+            })
+            .await?;
+        self.limits.check_trace(&optimal.list)?;
+        access_list_json(&optimal, Vec::new())
+    }
+
+    /// `validate`'s report for the call's `accessList`.
+    async fn validate(&self, tx: TransactionRequest, block_id: BlockId) -> Result<Value> {
+        let declared = tx.access_list.clone().unwrap_or_default();
+        let (key, scratch, tx_env, config) = self.prepare(&tx, block_id).await?;
+        let report = self
+            .trace(key, scratch, move |state| {
+                let block_env = state.block_env.clone();
+                traced(state.block_number, || {
+                    validate_with(&mut state.db, tx_env, block_env, declared, &config)
+                })
+                .wrap_err("validation failed")
+            })
+            .await?;
+        self.limits.check_trace(&report.optimal_list)?;
+        Ok(serde_json::to_value(&report)?)
+    }
+
+    /// The [`ListScore`](hammer_core::ListScore) of the call's `accessList`: one number
+    /// to rank it by, without `validate`'s full report.
+    async fn score(&self, tx: TransactionRequest, block_id: BlockId) -> Result<Value> {
+        let declared = tx.access_list.clone().unwrap_or_default();
+        let (key, scratch, tx_env, config) = self.prepare(&tx, block_id).await?;
+        let schedule = config.schedule;
+        let optimal = self
+            .trace(key, scratch, move |state| {
+                let block_env = state.block_env.clone();
+                traced(state.block_number, || {
+                    generate_with_strategy(
+                        &mut state.db,
+                        tx_env,
+                        block_env,
+                        &config,
+                        Strategy::default(),
+                    )
+                })
+                .wrap_err("access list generation failed")
+            })
+            .await?;
+        self.limits.check_trace(&optimal.list)?;
+        let score = score_access_list(&declared, &optimal, &schedule);
+        Ok(serde_json::to_value(score)?)
+    }
+
+    /// `compare --output json` for a mined transaction.
+    async fn compare(&self, tx_hash: B256) -> Result<Value> {
+        let mut fetched = BTreeMap::new();
+        let prepared =
+            prepare_one(&self.provider, self.chain_id, tx_hash, &mut fetched, None).await?;
+        let key = (self.chain_id, prepared.block_hash);
+        let scratch = fetched
+            .remove(&key)
+            .ok_or_else(|| eyre::eyre!("no state was fetched for block {}", key.1))?;
+        let chain_id = self.chain_id;
+        let comparison = self
+            .trace(key, scratch, move |state| {
+                prepared.compare(state, chain_id, false, false)
+            })
+            .await?;
+        self.limits.check_trace(&comparison.report.optimal_list)?;
+        compare::to_json(&comparison)
+    }
+
+    /// Run `f` on a blocking thread over the shared state of `scratch`'s block, with what
+    /// `scratch` fetched added to it. A block seen for the first time keeps `scratch` as
+    /// its state, and the oldest blocks past `--cache-blocks` are dropped at once.
+    async fn trace<T, F>(&self, key: (u64, B256), scratch: BlockState, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut BlockState) -> Result<T> + Send + 'static,
+    {
+        let (shared, scratch) = {
+            let mut blocks = self.blocks.lock().unwrap_or_else(|e| e.into_inner());
+            match blocks.entry(key) {
+                Entry::Occupied(entry) => (entry.get().1.clone(), Some(scratch)),
+                Entry::Vacant(entry) => {
+                    let number = scratch.block_number;
+                    let shared = entry
+                        .insert((number, Arc::new(Mutex::new(scratch))))
+                        .1
+                        .clone();
+                    evict(&mut blocks, self.cache_blocks);
+                    (shared, None)
+                }
+            }
+        };
+        tokio::task::spawn_blocking(move || {
+            let mut state = shared.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(scratch) = scratch {
+                state.absorb(scratch);
+            }
+            f(&mut state)
+        })
+        .await
+        .wrap_err("trace task failed")?
+    }
+
+    /// A fresh state for the call's block, pre-warmed for `tx`, with the call's
+    /// environment and the config under the block's gas schedule. Nothing shared is held
+    /// while it is fetched.
+    async fn prepare(
+        &self,
+        tx: &TransactionRequest,
+        block_id: BlockId,
+    ) -> Result<((u64, B256), BlockState, TxEnv, OptimizerConfig)> {
+        let (block, _) = fetch_block(&self.provider, block_id, None).await?;
+        let header = &block.header;
+        assert_post_berlin(header.number)?;
+        let mut config = self.config.clone();
+        apply_chain_rules(&mut config, Some(self.chain_id), header.number);
+
+        let from = tx
+            .from
+            .ok_or_else(|| eyre::eyre!("the call has no `from`"))?;
+        let state_block_id = BlockId::hash(header.hash);
+        let nonce = match tx.nonce {
+            Some(nonce) => nonce,
+            None => self
+                .provider
+                .get_transaction_count(from)
+                .block_id(state_block_id)
+                .await
+                .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?,
+        };
+
+        let mut state = BlockState::at(&self.provider, header)?;
+        let gas_price = state.block_env.basefee.max(1_000_000_000) as u128;
+        let data = tx.input.input().cloned().unwrap_or_default();
+        let mut builder = TxEnv::builder()
+            .caller(from)
+            .nonce(nonce)
+            .kind(tx.to.unwrap_or(TxKind::Create))
+            .gas_limit(tx.gas.unwrap_or(self.limits.default_gas(DEFAULT_GAS)))
+            .gas_price(gas_price)
+            .value(tx.value.unwrap_or_default())
+            .data(data);
+        if let Some(authorizations) = &tx.authorization_list {
+            builder = builder.authorization_list_signed(authorizations.clone());
+        }
+        let tx_env = builder
+            .build()
+            .map_err(|e| eyre::eyre!("invalid call: {:?}", e))?;
+
+        let hint = TransactionRequest {
+            gas: Some(tx.gas.unwrap_or(self.limits.default_gas(DEFAULT_GAS))),
+            access_list: None,
+            ..tx.clone()
+        };
+        let declared = tx.access_list.clone().unwrap_or_default();
+        super::prefetch::prewarm(
+            &mut state.db,
+            self.provider.clone(),
+            state_block_id,
+            state_block_id,
+            hint,
+            &declared,
+        )
+        .await;
+        Ok(((self.chain_id, header.hash), state, tx_env, config))
+    }
+}
+
+/// Drop the oldest blocks' state until at most `keep` remain.
+fn evict(blocks: &mut BlockCache, keep: usize) {
+    while blocks.len() > keep {
+        let oldest = blocks
+            .iter()
+            .min_by_key(|(_, (number, _))| *number)
+            .map(|(key, _)| *key);
+        match oldest {
+            Some(key) => blocks.remove(&key),
+            None => break,
+        };
+    }
+}
+
+/// The id, method and params of a request object.
+fn parse_request(request: &Value) -> Result<(Value, &str, &[Value]), RpcError> {
+    let fields = request
+        .as_object()
+        .ok_or_else(|| RpcError::new(INVALID_REQUEST, "request must be an object"))?;
+    if fields.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\""));
+    }
+    let method = fields
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_REQUEST, "missing method"))?;
+    let params = match fields.get("params") {
+        None => &[][..],
+        Some(Value::Array(params)) => params.as_slice(),
+        Some(_) => return Err(RpcError::new(INVALID_PARAMS, "params must be an array")),
+    };
+    Ok((id_of(request), method, params))
+}
+
+fn id_of(request: &Value) -> Value {
+    request.get("id").cloned().unwrap_or(Value::Null)
+}
+
+/// `[call, block?]`, as `eth_call` takes them; the block defaults to latest.
+fn call_params(params: &[Value]) -> Result<(TransactionRequest, BlockId), RpcError> {
+    if params.is_empty() || params.len() > 2 {
+        return Err(RpcError::new(INVALID_PARAMS, "expected [call, block?]"));
+    }
+    let tx: TransactionRequest = serde_json::from_value(params[0].clone())
+        .map_err(|e| RpcError::new(INVALID_PARAMS, format!("invalid call: {}", e)))?;
+    if tx.from.is_none() {
+        return Err(RpcError::new(INVALID_PARAMS, "the call has no `from`"));
+    }
+    let block = match params.get(1) {
+        None | Some(Value::Null) => BlockId::latest(),
+        Some(block) => serde_json::from_value(block.clone())
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("invalid block: {}", e)))?,
+    };
+    Ok((tx, block))
+}
+
+/// `[txHash]`.
+fn hash_params(params: &[Value]) -> Result<B256, RpcError> {
+    match params {
+        [hash] => serde_json::from_value(hash.clone())
+            .map_err(|e| RpcError::new(INVALID_PARAMS, format!("invalid transaction hash: {}", e))),
+        _ => Err(RpcError::new(INVALID_PARAMS, "expected [txHash]")),
+    }
+}
+
+/// The key from `x-api-key`, or else `Authorization: Bearer`.
+fn api_key(headers: &HeaderMap) -> Option<&str> {
+    if let Some(key) = headers.get("x-api-key") {
+        return key.to_str().ok();
+    }
+    headers
+        .get(hyper::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+fn error_response(id: Value, error: RpcError) -> Value {
+    let mut fields = json!({"code": error.code, "message": error.message});
+    if let Some(data) = error.data {
+        fields["data"] = data;
+    }
+    json!({"jsonrpc": "2.0", "id": id, "error": fields})
+}
+
+/// An error answering a whole request, with its own HTTP status.
+fn lone_error(id: Value, error: RpcError) -> Response<Full<Bytes>> {
+    let status = error.status;
+    http_response(status, error_response(id, error))
+}
+
+fn http_response(status: StatusCode, body: Value) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body.to_string())));
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        hyper::header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::Address;
+
+    fn server(cache_blocks: usize) -> Server {
+        let provider = alloy_provider::ProviderBuilder::new()
+            .disable_recommended_fillers()
+            .connect_http("http://127.0.0.1:1".parse().unwrap())
+            .erased();
+        Server {
+            provider,
+            chain_id: 1,
+            config: OptimizerConfig::default(),
+            cache_blocks,
+            limits: LimitArgs {
+                max_body_bytes: 1 << 20,
+                max_batch: None,
+                max_calldata_bytes: None,
+                max_gas: None,
+                max_trace_entries: None,
+                api_keys: None,
+            },
+            api_keys: None,
+            metrics: false,
+            usage: Usage::default(),
+            blocks: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn block(server: &Server, number: u64) -> ((u64, B256), BlockState) {
+        let header = alloy_rpc_types_eth::Header {
+            hash: B256::with_last_byte(number as u8),
+            inner: alloy::consensus::Header {
+                number,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let state = BlockState::at(&server.provider, &header).unwrap();
+        ((1, header.hash), state)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_failed_traces_do_not_grow_the_cache() {
+        let server = server(2);
+        for number in 1..=4 {
+            let (key, state) = block(&server, number);
+            let failed = server
+                .trace(key, state, |_| -> Result<()> {
+                    eyre::bail!("trace failed")
+                })
+                .await;
+            assert!(failed.is_err());
+        }
+        let blocks = server.blocks.lock().unwrap();
+        let numbers: Vec<u64> = blocks.values().map(|(number, _)| *number).collect();
+        assert_eq!(numbers.len(), 2);
+        assert!(numbers.contains(&3) && numbers.contains(&4));
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_traces_of_different_blocks_overlap() {
+        let server = server(4);
+        // Each trace waits for the other to start: run one at a time, they never finish.
+        let barrier = Arc::new(std::sync::Barrier::new(2));
+        let run = |number| {
+            let (key, state) = block(&server, number);
+            let barrier = barrier.clone();
+            server.trace(key, state, move |_| {
+                barrier.wait();
+                Ok(())
+            })
+        };
+        let both = async { tokio::join!(run(1), run(2)) };
+        let (a, b) = tokio::time::timeout(std::time::Duration::from_secs(10), both)
+            .await
+            .expect("traces ran one at a time");
+        assert!(a.is_ok() && b.is_ok());
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": "hammer_compareTx",
+            "params": [B256::ZERO],
+        });
+        let (id, method, params) = parse_request(&request).unwrap();
+        assert_eq!((id, method), (json!(7), "hammer_compareTx"));
+        assert_eq!(hash_params(params), Ok(B256::ZERO));
+
+        let no_version = json!({"id": 1, "method": "hammer_compareTx"});
+        assert_eq!(
+            parse_request(&no_version).unwrap_err().code,
+            INVALID_REQUEST
+        );
+        let named = json!({"jsonrpc": "2.0", "id": 1, "method": "m", "params": {}});
+        assert_eq!(parse_request(&named).unwrap_err().code, INVALID_PARAMS);
+        assert_eq!(hash_params(&[]).unwrap_err().code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_call_params() {
+        let from = Address::with_last_byte(0x40);
+        let to = Address::with_last_byte(0x41);
+        let call = json!({"from": from, "to": to, "input": "0x12", "accessList": []});
+
+        let (tx, block) = call_params(std::slice::from_ref(&call)).unwrap();
+        assert_eq!((tx.from, tx.to), (Some(from), Some(TxKind::Call(to))));
+        assert_eq!(tx.access_list.map(|list| list.0.len()), Some(0));
+        assert_eq!(block, BlockId::latest());
+
+        let (_, block) = call_params(&[call.clone(), json!("0x10")]).unwrap();
+        assert_eq!(block, BlockId::number(16));
+
+        let no_from = json!({"to": to});
+        assert_eq!(call_params(&[no_from]).unwrap_err().code, INVALID_PARAMS);
+        assert_eq!(call_params(&[]).unwrap_err().code, INVALID_PARAMS);
+        let bad_block = call_params(&[call, json!("soon")]).unwrap_err();
+        assert_eq!(bad_block.code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_limit_errors_are_structured() {
+        let limit = LimitExceeded {
+            limit: "max-gas",
+            max: 5_000_000,
+            actual: 30_000_000,
+        };
+        let error = RpcError::server(eyre::Report::new(limit.clone()).wrap_err("traced"));
+        assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+        let response = error_response(json!(1), error);
+        assert_eq!(response["error"]["code"], LIMIT_EXCEEDED);
+        assert_eq!(response["error"]["data"], limit.data());
+
+        let plain = error_response(json!(1), RpcError::server(eyre::eyre!("reverted")));
+        assert_eq!(plain["error"]["code"], SERVER_ERROR);
+        assert!(plain["error"].get("data").is_none());
+    }
+
+    #[test]
+    fn test_api_key_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(api_key(&headers), None);
+        headers.insert(
+            hyper::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer k1"),
+        );
+        assert_eq!(api_key(&headers), Some("k1"));
+        headers.insert("x-api-key", HeaderValue::from_static("k2"));
+        assert_eq!(api_key(&headers), Some("k2"));
+    }
+}
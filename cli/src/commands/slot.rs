@@ -0,0 +1,99 @@
+use alloy_primitives::B256;
+use clap::{Args, Subcommand};
+use eyre::{Context, Result};
+use hammer_core::{array_element_slot, nested_mapping_slot};
+
+use super::global::GlobalArgs;
+use super::util::parse_u256;
+
+#[derive(Args)]
+pub struct SlotArgs {
+    #[command(subcommand)]
+    pub kind: SlotKind,
+}
+
+#[derive(Subcommand)]
+pub enum SlotKind {
+    /// Slot of mapping[key] (repeat --key for nested mappings, outermost first)
+    Mapping {
+        /// Slot the mapping is declared at (decimal or 0x-hex)
+        #[arg(long)]
+        base: String,
+        /// Mapping key as a value type: address, uint, or bytes32 (decimal or 0x-hex)
+        #[arg(long = "key", required = true)]
+        keys: Vec<String>,
+    },
+    /// Slot of array[index] for a dynamic array
+    Array {
+        /// Slot the array is declared at (decimal or 0x-hex)
+        #[arg(long)]
+        base: String,
+        /// Element index (decimal or 0x-hex)
+        #[arg(long)]
+        index: String,
+        /// Number of slots each element occupies (structs/static arrays span several)
+        #[arg(long, default_value = "1")]
+        element_slots: u64,
+    },
+}
+
+pub fn run(args: SlotArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human"])?;
+    let slot = match args.kind {
+        SlotKind::Mapping { base, keys } => {
+            let base = parse_word(&base).wrap_err("invalid --base")?;
+            let keys = keys
+                .iter()
+                .map(|k| parse_word(k).wrap_err_with(|| format!("invalid --key {}", k)))
+                .collect::<Result<Vec<_>>>()?;
+            nested_mapping_slot(base, &keys)
+        }
+        SlotKind::Array {
+            base,
+            index,
+            element_slots,
+        } => {
+            let base = parse_word(&base).wrap_err("invalid --base")?;
+            let index = parse_u256(&index).wrap_err("invalid --index")?;
+            if element_slots == 0 {
+                eyre::bail!("--element-slots must be at least 1");
+            }
+            array_element_slot(base, index, element_slots)
+        }
+    };
+    match output {
+        "json" => println!("{}", serde_json::to_string(&slot)?),
+        "human" => println!("{}", slot),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
+
+/// Parse a decimal or 0x-hex number into a left-padded 32-byte word.
+fn parse_word(s: &str) -> Result<B256> {
+    Ok(B256::from(parse_u256(s)?.to_be_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_word_address_left_padded() {
+        let word = parse_word("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045").unwrap();
+        assert_eq!(&word[..12], &[0u8; 12]);
+        assert_eq!(word[12], 0xd8);
+    }
+
+    #[test]
+    fn test_parse_word_decimal() {
+        let word = parse_word("3").unwrap();
+        assert_eq!(word[31], 3);
+    }
+
+    #[test]
+    fn test_parse_word_too_wide_rejected() {
+        let wide = format!("0x1{}", "0".repeat(64));
+        assert!(parse_word(&wide).is_err());
+    }
+}
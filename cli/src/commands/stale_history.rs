@@ -0,0 +1,259 @@
+//! When stale entries were last needed, from tracing the same call at nearby blocks.
+//!
+//! A declared entry that execution no longer touches is either left over from a contract
+//! that moved (an upgrade, a migrated pool, a new oracle round) or was never right. Tracing
+//! the call again at earlier blocks tells the two apart: an entry touched a few hundred
+//! blocks ago went stale at a block the trace can name.
+
+use alloy::network::Ethereum;
+use alloy_eips::BlockId;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_provider::DynProvider;
+use alloy_rpc_types_eth::{AccessList, TransactionRequest};
+use eyre::{Context, Result};
+use hammer_core::{generate_access_list, DiffEntry, ValidationReport};
+use revm::context::TxEnv;
+use revm::primitives::TxKind;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::report_diff::optimal_line;
+use super::util::{block_env_from_header, fetch_block, traced};
+
+/// An address (`slot` absent) or one of its slots.
+type Entry = (Address, Option<B256>);
+
+/// The call probed at each block.
+pub struct Call {
+    pub from: Address,
+    pub to: Address,
+    pub data: Bytes,
+    pub value: U256,
+    pub gas_limit: u64,
+}
+
+/// Where one stale entry was last touched, within the window searched.
+#[derive(Debug, PartialEq, Eq)]
+pub struct StaleHistory {
+    pub address: Address,
+    pub slot: Option<B256>,
+    /// Latest block before the validated one at which the call touched it: stale since
+    /// the block after. `None` if the call does not touch it at the window's far edge.
+    pub accessed_until: Option<u64>,
+    /// Earliest block after the validated one at which the call touches it again, when
+    /// validating against a past block.
+    pub accessed_from: Option<u64>,
+}
+
+/// Binary search between a block where an entry is not touched and one where it is, for
+/// the block next to the flip.
+#[derive(Debug, Clone, Copy)]
+struct Bisect {
+    absent: u64,
+    present: u64,
+}
+
+impl Bisect {
+    fn next(&self) -> Option<u64> {
+        (self.absent.abs_diff(self.present) > 1)
+            .then(|| self.absent.min(self.present) + self.absent.abs_diff(self.present) / 2)
+    }
+
+    fn record(&mut self, block: u64, touched: bool) {
+        match touched {
+            true => self.present = block,
+            false => self.absent = block,
+        }
+    }
+}
+
+/// The stale entries of `report`: whole addresses the optimal list lacks, slots otherwise.
+pub fn stale_entries(report: &ValidationReport) -> Vec<Entry> {
+    let listed: BTreeSet<Address> = report.optimal_list.0.iter().map(|i| i.address).collect();
+    report
+        .entries
+        .iter()
+        .flat_map(|e| match e {
+            DiffEntry::Stale {
+                address,
+                storage_keys,
+                ..
+            } if listed.contains(address) => {
+                storage_keys.iter().map(|k| (*address, Some(*k))).collect()
+            }
+            DiffEntry::Stale { address, .. } => vec![(*address, None)],
+            _ => vec![],
+        })
+        .collect()
+}
+
+/// Search `window` blocks either side of `block` (not past `latest`) for when the call
+/// last touched each of `entries`. Assumes one flip per side: an entry touched at the
+/// window's edge is searched for the block nearest `block` that touched it.
+pub async fn search(
+    provider: &DynProvider<Ethereum>,
+    call: &Call,
+    entries: &[Entry],
+    block: u64,
+    latest: u64,
+    window: u64,
+) -> Result<Vec<StaleHistory>> {
+    let mut probes: BTreeMap<u64, BTreeSet<Entry>> = BTreeMap::new();
+    let mut found = Vec::with_capacity(entries.len());
+    for &(address, slot) in entries {
+        let nearest = |edge: u64| (edge != block).then_some(edge);
+        let (before, after) = (
+            nearest(block.saturating_sub(window)),
+            nearest(block.saturating_add(window).min(latest)),
+        );
+        let mut sides = [None, None];
+        for (side, edge) in [before, after].into_iter().enumerate() {
+            let Some(edge) = edge else { continue };
+            if !touched_at(provider, call, &mut probes, edge, (address, slot)).await? {
+                continue;
+            }
+            let mut bisect = Bisect {
+                absent: block,
+                present: edge,
+            };
+            while let Some(probe) = bisect.next() {
+                let touched =
+                    touched_at(provider, call, &mut probes, probe, (address, slot)).await?;
+                bisect.record(probe, touched);
+            }
+            sides[side] = Some(bisect.present);
+        }
+        found.push(StaleHistory {
+            address,
+            slot,
+            accessed_until: sides[0],
+            accessed_from: sides[1],
+        });
+    }
+    Ok(found)
+}
+
+/// Whether the call touches `entry` at `block`, tracing it there once for all entries.
+async fn touched_at(
+    provider: &DynProvider<Ethereum>,
+    call: &Call,
+    probes: &mut BTreeMap<u64, BTreeSet<Entry>>,
+    block: u64,
+    entry: Entry,
+) -> Result<bool> {
+    if let std::collections::btree_map::Entry::Vacant(slot) = probes.entry(block) {
+        let touched = probe(provider, call, block)
+            .await
+            .wrap_err_with(|| format!("stale history probe at block {} failed", block))?;
+        slot.insert(touched);
+    }
+    Ok(probes[&block].contains(&entry))
+}
+
+/// Everything the call touches at `block`: each address, and each slot with its address.
+async fn probe(
+    provider: &DynProvider<Ethereum>,
+    call: &Call,
+    block: u64,
+) -> Result<BTreeSet<Entry>> {
+    let (fetched, _) = fetch_block(provider, BlockId::number(block), None).await?;
+    let header = &fetched.header;
+    let block_env = block_env_from_header(header);
+    let state_block_id = BlockId::hash(header.hash);
+    let tx_env = TxEnv::builder()
+        .caller(call.from)
+        .kind(TxKind::Call(call.to))
+        .gas_limit(call.gas_limit)
+        .gas_price(block_env.basefee.max(1_000_000_000) as u128)
+        .value(call.value)
+        .data(call.data.clone())
+        .build()
+        .unwrap();
+    let tx_req = TransactionRequest {
+        from: Some(call.from),
+        to: Some(TxKind::Call(call.to)),
+        value: Some(call.value),
+        input: alloy_rpc_types_eth::TransactionInput::new(call.data.clone()),
+        gas: Some(call.gas_limit),
+        ..Default::default()
+    };
+    let db = super::prefetch::build(
+        provider.clone(),
+        state_block_id,
+        state_block_id,
+        tx_req,
+        &AccessList::default(),
+    )
+    .await?;
+    // The sender's nonce at each probed block is beside the point: skip the check.
+    let raw = traced(block, || generate_access_list(db, tx_env, block_env, true))?;
+    Ok(raw
+        .access_list
+        .0
+        .iter()
+        .flat_map(|item| {
+            std::iter::once((item.address, None))
+                .chain(item.storage_keys.iter().map(|k| (item.address, Some(*k))))
+        })
+        .collect())
+}
+
+pub fn to_json(history: &[StaleHistory], window: u64) -> serde_json::Value {
+    json!({
+        "window": window,
+        "entries": history
+            .iter()
+            .map(|h| json!({
+                "address": h.address,
+                "storage_key": h.slot,
+                "accessed_until": h.accessed_until,
+                "accessed_from": h.accessed_from,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub fn print(history: &[StaleHistory], window: u64) {
+    if history.is_empty() {
+        return;
+    }
+    println!("Stale entries, traced within {} blocks:", window);
+    for h in history {
+        let when = match (h.accessed_until, h.accessed_from) {
+            (Some(until), Some(from)) => format!(
+                "accessed until block {}, and again from block {}",
+                until, from
+            ),
+            (Some(until), None) => format!("accessed until block {}", until),
+            (None, Some(from)) => format!("accessed from block {}", from),
+            (None, None) => format!("not accessed {} blocks away either", window),
+        };
+        println!("  {}  {}", optimal_line(&h.address, &h.slot), when);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run the bisection against a known history: touched at blocks in `touched`.
+    fn flip(absent: u64, present: u64, touched: impl Fn(u64) -> bool) -> (u64, usize) {
+        let mut bisect = Bisect { absent, present };
+        let mut probes = 0;
+        while let Some(block) = bisect.next() {
+            bisect.record(block, touched(block));
+            probes += 1;
+        }
+        (bisect.present, probes)
+    }
+
+    #[test]
+    fn test_bisect_finds_flip_either_side() {
+        // Validating at 1,000; the slot was read until 873, searched back 256 blocks.
+        assert_eq!(flip(1_000, 744, |b| b <= 873), (873, 8));
+        // Validating at 1,000 in the past; the slot is read again from 1,100.
+        assert_eq!(flip(1_000, 1_256, |b| b >= 1_100).0, 1_100);
+        // Adjacent blocks need no probe.
+        assert_eq!(flip(1_000, 999, |_| true), (999, 0));
+    }
+}
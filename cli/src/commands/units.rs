@@ -0,0 +1,183 @@
+//! How human output shows gas amounts (`--units`, `--gas-price`, `--eth-price`,
+//! `--separators`).
+//!
+//! Amounts stay in gas unless asked otherwise. Converting to gwei or ETH needs the gas
+//! price to charge, and to USD also the ETH price; hammer fetches neither, so both are
+//! given on the command line and the conversion is only as current as they are.
+
+use eyre::Result;
+
+use super::global::GlobalArgs;
+
+/// Unit names accepted by `--units`.
+pub const UNITS: &[&str] = &["gas", "gwei", "eth", "usd"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Unit {
+    #[default]
+    Gas,
+    Gwei,
+    Eth,
+    Usd,
+}
+
+/// Formatter for gas amounts in human output, built from the global options.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Units {
+    unit: Unit,
+    /// Gas price in gwei, for every unit but gas.
+    gas_price: f64,
+    /// ETH price in USD, for `usd`.
+    eth_price: f64,
+    separators: bool,
+}
+
+impl Units {
+    /// The formatter for `--units`, checking that the prices it needs were given.
+    pub fn from_global(global: &GlobalArgs) -> Result<Self> {
+        let unit = match global.units.as_deref().unwrap_or("gas") {
+            "gas" => Unit::Gas,
+            "gwei" => Unit::Gwei,
+            "eth" => Unit::Eth,
+            "usd" => Unit::Usd,
+            other => eyre::bail!(
+                "unknown --units {}: expected one of {}",
+                other,
+                UNITS.join(", ")
+            ),
+        };
+        let gas_price = match (unit, global.gas_price) {
+            (Unit::Gas, _) => 0.0,
+            (_, Some(price)) => price,
+            (_, None) => eyre::bail!(
+                "--units {} needs --gas-price GWEI",
+                global.units.as_deref().unwrap_or_default()
+            ),
+        };
+        let eth_price = match (unit, global.eth_price) {
+            (Unit::Usd, Some(price)) => price,
+            (Unit::Usd, None) => eyre::bail!("--units usd needs --eth-price USD"),
+            _ => 0.0,
+        };
+        Ok(Self {
+            unit,
+            gas_price,
+            eth_price,
+            separators: global.separators,
+        })
+    }
+
+    /// `gas` in the chosen unit, with the unit: `1900137 gas`, `1,900,137 gas`,
+    /// `0.038003 ETH`, `$114.01`.
+    pub fn gas(&self, gas: u64) -> String {
+        self.format(gas as f64, "")
+    }
+
+    /// A gas delta, always signed: `+2400 gas`, `-0.000048 ETH`.
+    pub fn signed(&self, gas: i64) -> String {
+        let sign = if gas < 0 { "-" } else { "+" };
+        self.format(gas.unsigned_abs() as f64, sign)
+    }
+
+    fn format(&self, gas: f64, sign: &str) -> String {
+        let gwei = gas * self.gas_price;
+        match self.unit {
+            Unit::Gas => format!("{}{} gas", sign, self.group(format!("{:.0}", gas))),
+            Unit::Gwei => format!("{}{} gwei", sign, self.group(trim(format!("{:.2}", gwei)))),
+            Unit::Eth => format!("{}{} ETH", sign, self.group(format!("{:.6}", gwei / 1e9))),
+            Unit::Usd => format!(
+                "{}${}",
+                sign,
+                self.group(format!("{:.2}", gwei / 1e9 * self.eth_price))
+            ),
+        }
+    }
+
+    /// Insert thousands separators into the integer part, if enabled.
+    fn group(&self, number: String) -> String {
+        if !self.separators {
+            return number;
+        }
+        let (int, frac) = match number.split_once('.') {
+            Some((int, frac)) => (int, Some(frac)),
+            None => (number.as_str(), None),
+        };
+        let mut grouped = String::new();
+        for (i, digit) in int.chars().enumerate() {
+            if i > 0 && (int.len() - i) % 3 == 0 {
+                grouped.push(',');
+            }
+            grouped.push(digit);
+        }
+        match frac {
+            Some(frac) => format!("{}.{}", grouped, frac),
+            None => grouped,
+        }
+    }
+}
+
+/// Drop trailing zeros of a fixed-point number, and the point if nothing follows it.
+fn trim(number: String) -> String {
+    if !number.contains('.') {
+        return number;
+    }
+    number
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(unit: &str, f: impl FnOnce(&mut GlobalArgs)) -> Result<Units> {
+        let mut global = GlobalArgs {
+            units: Some(unit.to_string()),
+            ..Default::default()
+        };
+        f(&mut global);
+        Units::from_global(&global)
+    }
+
+    #[test]
+    fn test_default_is_plain_gas() {
+        let units = Units::from_global(&GlobalArgs::default()).unwrap();
+        assert_eq!(units.gas(1_900_137), "1900137 gas");
+        assert_eq!(units.signed(-2400), "-2400 gas");
+        assert_eq!(units.signed(0), "+0 gas");
+    }
+
+    #[test]
+    fn test_separators_and_conversions() {
+        let gas = units("gas", |g| g.separators = true).unwrap();
+        assert_eq!(gas.gas(1_900_137), "1,900,137 gas");
+        assert_eq!(gas.gas(900), "900 gas");
+
+        let gwei = units("gwei", |g| {
+            g.gas_price = Some(1.5);
+            g.separators = true;
+        })
+        .unwrap();
+        assert_eq!(gwei.gas(1_900_137), "2,850,205.5 gwei");
+
+        let eth = units("eth", |g| g.gas_price = Some(20.0)).unwrap();
+        assert_eq!(eth.gas(1_900_137), "0.038003 ETH");
+        assert_eq!(eth.signed(-2400), "-0.000048 ETH");
+
+        let usd = units("usd", |g| {
+            g.gas_price = Some(20.0);
+            g.eth_price = Some(3000.0);
+        })
+        .unwrap();
+        assert_eq!(usd.gas(1_900_137), "$114.01");
+    }
+
+    #[test]
+    fn test_missing_prices_rejected() {
+        let err = units("eth", |_| {}).unwrap_err();
+        assert!(err.to_string().contains("--gas-price"), "{err}");
+        let err = units("usd", |g| g.gas_price = Some(1.0)).unwrap_err();
+        assert!(err.to_string().contains("--eth-price"), "{err}");
+    }
+}
@@ -1,6 +1,43 @@
+use alloy::network::Ethereum;
 use alloy_eips::BlockId;
-use alloy_primitives::U256;
+use alloy_primitives::{Address, B256, U256};
+use alloy_provider::{DynProvider, Provider};
+use alloy_rpc_types_eth::Block;
 use eyre::{Context, Result};
+use hammer_core::{is_state_unavailable, GasSchedule, HammerError, Hardfork, OptimizerConfig};
+use revm::context::BlockEnv;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::units::Units;
+
+/// Wrap an RPC error from a state read at `block`: pruned history becomes
+/// [`HammerError::StateUnavailable`] with its archive hint, anything else gets `context`.
+pub fn state_read_error<E>(e: E, block: u64, context: &'static str) -> eyre::Report
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let message = e.to_string();
+    if is_state_unavailable(&message) {
+        HammerError::StateUnavailable { block, message }.into()
+    } else {
+        eyre::Report::new(e).wrap_err(context)
+    }
+}
+
+/// Run the EVM trace inside a span labelled with the block number, logging under the
+/// `hammer::trace` target (`RUST_LOG=hammer::trace=debug`).
+pub fn traced<T>(block: u64, f: impl FnOnce() -> T) -> T {
+    tracing::debug_span!(target: "hammer::trace", "trace", block).in_scope(|| {
+        let start = std::time::Instant::now();
+        let out = f();
+        tracing::debug!(
+            target: "hammer::trace",
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "trace finished"
+        );
+        out
+    })
+}
 
 pub fn parse_block_id(s: &str) -> Result<BlockId> {
     if s.eq_ignore_ascii_case("latest") {
@@ -14,6 +51,172 @@ pub fn parse_block_id(s: &str) -> Result<BlockId> {
     }
 }
 
+/// Default search distance for `--nearest-available` given without a value.
+pub const NEAREST_AVAILABLE_DEFAULT: &str = "64";
+
+/// A block simulated in place of the requested one, whose state the node had pruned.
+#[derive(Debug, Clone, Copy)]
+pub struct BlockSubstitution {
+    pub requested: u64,
+    pub used: u64,
+}
+
+impl BlockSubstitution {
+    /// The notice printed wherever the substitution could otherwise go unnoticed.
+    pub fn notice(&self) -> String {
+        format!(
+            "Block:      {} (requested {}: state pruned; nearest available)",
+            self.used, self.requested
+        )
+    }
+
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({ "requested": self.requested, "used": self.used })
+    }
+}
+
+/// Seconds `--block future:+N` advances the clock per block: mainnet's slot time.
+pub const SLOT_SECONDS: u64 = 12;
+
+/// Parse `--block`: `latest`, `pending`, a number, or `future:+N`. A future block reads
+/// the latest state; its distance past the latest comes back alongside.
+pub fn parse_block_arg(s: &str) -> Result<(BlockId, Option<u64>)> {
+    let Some(ahead) = s.strip_prefix("future:") else {
+        return Ok((parse_block_id(s)?, None));
+    };
+    match ahead.strip_prefix('+').and_then(|n| n.parse::<u64>().ok()) {
+        Some(ahead) if ahead > 0 => Ok((BlockId::latest(), Some(ahead))),
+        _ => eyre::bail!("invalid block: expected future:+N with N at least 1"),
+    }
+}
+
+/// A block simulated `ahead` blocks past the latest, over the latest state
+/// (`--block future:+N`). Its number, timestamp and basefee are estimates; everything
+/// else is the latest block's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FutureBlock {
+    /// The latest block, whose state and header the synthetic block builds on.
+    pub base: u64,
+    pub ahead: u64,
+    pub number: u64,
+    pub timestamp: u64,
+    pub basefee: u64,
+}
+
+impl FutureBlock {
+    /// The block `ahead` past `header`, one slot per block. The basefee moves once by
+    /// how full `header`'s block was, then holds, as if the blocks between hit the target.
+    pub fn after(header: &alloy_rpc_types_eth::Header, ahead: u64) -> Self {
+        let basefee = header.base_fee_per_gas.map_or(0, |basefee| {
+            alloy_eips::eip1559::calc_next_block_base_fee(
+                header.gas_used,
+                header.gas_limit,
+                basefee,
+                alloy_eips::eip1559::BaseFeeParams::ethereum(),
+            )
+        });
+        Self {
+            base: header.number,
+            ahead,
+            number: header.number + ahead,
+            timestamp: header.timestamp + ahead * SLOT_SECONDS,
+            basefee,
+        }
+    }
+
+    pub fn apply(&self, block: &mut BlockEnv) {
+        block.number = U256::from(self.number);
+        block.timestamp = U256::from(self.timestamp);
+        block.basefee = self.basefee;
+    }
+
+    /// The notice printed wherever the synthetic basis could otherwise go unnoticed.
+    pub fn notice(&self) -> String {
+        format!(
+            "Block:      {} (synthetic: {} past latest {}, over its state; estimated timestamp {} and basefee {} wei)",
+            self.number, self.ahead, self.base, self.timestamp, self.basefee
+        )
+    }
+
+    pub fn to_json(self) -> serde_json::Value {
+        serde_json::json!({
+            "synthetic": true,
+            "state_block": self.base,
+            "ahead": self.ahead,
+            "number": self.number,
+            "timestamp": self.timestamp,
+            "estimated_basefee": self.basefee,
+        })
+    }
+}
+
+/// Fetch the block to simulate against. With `nearest_available = Some(range)`, a block
+/// whose state the node has pruned is swapped for the nearest block within `range` that
+/// still has state, preferring the newer block at equal distance.
+pub async fn fetch_block(
+    provider: &DynProvider<Ethereum>,
+    block_id: BlockId,
+    nearest_available: Option<u64>,
+) -> Result<(Block, Option<BlockSubstitution>)> {
+    let block = provider
+        .get_block(block_id)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block not found"))?;
+    let Some(range) = nearest_available else {
+        return Ok((block, None));
+    };
+    let requested = block.header.number;
+    if state_available(provider, requested).await? {
+        return Ok((block, None));
+    }
+    for candidate in probe_order(requested, range) {
+        if !state_available(provider, candidate).await? {
+            continue;
+        }
+        if let Some(block) = provider.get_block(BlockId::number(candidate)).await? {
+            return Ok((
+                block,
+                Some(BlockSubstitution {
+                    requested,
+                    used: candidate,
+                }),
+            ));
+        }
+    }
+    Err(HammerError::StateUnavailable {
+        block: requested,
+        message: format!("no block within {} has state either", range),
+    }
+    .into())
+}
+
+/// Candidate blocks by increasing distance from `requested`: newer first, then older.
+fn probe_order(requested: u64, range: u64) -> impl Iterator<Item = u64> {
+    (1..=range)
+        .flat_map(move |d| [requested.checked_add(d), requested.checked_sub(d)])
+        .flatten()
+}
+
+/// Whether the node can still serve state at `number`. Blocks past the head count as
+/// unavailable; any other RPC failure is an error.
+async fn state_available(provider: &DynProvider<Ethereum>, number: u64) -> Result<bool> {
+    match provider
+        .get_balance(Address::ZERO)
+        .block_id(BlockId::number(number))
+        .await
+    {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            let message = e.to_string();
+            if is_state_unavailable(&message) || message.contains("header not found") {
+                Ok(false)
+            } else {
+                Err(e).wrap_err_with(|| format!("failed to probe state at block {}", number))
+            }
+        }
+    }
+}
+
 pub fn parse_u256(s: &str) -> Result<U256> {
     if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
         U256::from_str_radix(hex, 16).wrap_err("invalid hex number")
@@ -30,6 +233,454 @@ pub fn parse_hex_bytes(s: &str) -> Result<Vec<u8>> {
     hex::decode(s).wrap_err("invalid hex data")
 }
 
+/// Read contract creation bytecode: inline hex, a file containing hex, or a forge/hardhat
+/// artifact JSON (`bytecode.object` or `bytecode` string).
+pub fn read_bytecode(s: &str) -> Result<Vec<u8>> {
+    let path = std::path::Path::new(s);
+    if !path.is_file() {
+        return parse_hex_bytes(s);
+    }
+    let text = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read bytecode from {}", s))?;
+    let text = text.trim();
+    if !text.starts_with('{') {
+        return parse_hex_bytes(text);
+    }
+    let artifact: serde_json::Value =
+        serde_json::from_str(text).wrap_err_with(|| format!("invalid artifact JSON in {}", s))?;
+    let object = artifact["bytecode"]["object"]
+        .as_str()
+        .or_else(|| artifact["bytecode"].as_str())
+        .ok_or_else(|| eyre::eyre!("no bytecode found in artifact {}", s))?;
+    parse_hex_bytes(object)
+}
+
+/// Build the simulated block environment from a fetched block header.
+pub fn block_env_from_header(header: &alloy_rpc_types_eth::Header) -> BlockEnv {
+    BlockEnv {
+        number: U256::from(header.number),
+        beneficiary: header.beneficiary,
+        timestamp: U256::from(header.timestamp),
+        gas_limit: header.gas_limit,
+        basefee: header.base_fee_per_gas.unwrap_or(0),
+        difficulty: header.difficulty,
+        prevrandao: Some(header.mix_hash),
+        blob_excess_gas_and_price: header.excess_blob_gas.map(|excess| {
+            revm::context_interface::block::BlobExcessGasAndPrice::new(
+                excess,
+                revm::primitives::eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
+            )
+        }),
+    }
+}
+
+/// Print which precompiles ran and how often. Precompiles are always warm, so this
+/// explains why a declared precompile is reported Redundant even though it was called.
+pub fn print_precompile_calls(calls: &std::collections::BTreeMap<alloy_primitives::Address, u64>) {
+    if calls.is_empty() {
+        return;
+    }
+    println!("Precompiles called (always warm, never need listing):");
+    for (addr, count) in calls {
+        let name = hammer_core::warm::precompile_name(addr).unwrap_or("?");
+        println!("  {} {} ×{}", addr, name, count);
+    }
+}
+
+/// Print the accounts created by value transfers, with the surcharge they paid.
+/// Shown for context only — an access list cannot avoid this cost.
+pub fn print_new_accounts(new_accounts: &[alloy_primitives::Address], units: &Units) {
+    if new_accounts.is_empty() {
+        return;
+    }
+    println!(
+        "New accounts: {}  →  {} surcharge (not avoidable by an access list)",
+        new_accounts.len(),
+        units.gas(new_accounts.len() as u64 * hammer_core::NEW_ACCOUNT_COST),
+    );
+    for addr in new_accounts {
+        println!("  {}", addr);
+    }
+}
+
+/// Print the lists the transaction's gas limit cannot pay for next to its execution.
+/// Shown even with --quiet: such a transaction is rejected or runs out of gas.
+pub fn print_budget(exceeded: &[hammer_core::BudgetExceeded], units: &Units) {
+    for e in exceeded {
+        let verdict = if e.upfront_exceeds() {
+            "exceeds the gas limit before execution"
+        } else {
+            "crowds out execution"
+        };
+        println!(
+            "Budget:     {} list {}: {} intrinsic + {} list + {} execution > {} limit  ({} short)",
+            e.list.as_str(),
+            verdict,
+            units.gas(e.intrinsic_gas),
+            units.gas(e.list_cost),
+            units.gas(e.execution_gas),
+            units.gas(e.gas_limit),
+            units.gas(e.shortfall),
+        );
+    }
+}
+
+/// Addresses dropped from the list as warm, each with its reason and the gas declaring it
+/// would waste, then the warm ones kept for their cold slots.
+pub fn print_removed(optimal: &hammer_core::OptimizedAccessList, units: &Units) {
+    if !optimal.removed.is_empty() {
+        println!("Removed (warm):");
+    }
+    for entry in &optimal.removed {
+        println!(
+            "  {}: {}; declaring it would waste {}",
+            entry.address,
+            entry.reason.describe(),
+            units.gas(entry.gas_waste)
+        );
+        if entry.cold_slots > 0 {
+            println!(
+                "    its {} slots are still read cold; declaring them pays off from {}",
+                entry.cold_slots,
+                hammer_core::WARM_ADDRESS_MIN_SLOTS
+            );
+        }
+    }
+    for address in &optimal.warm_kept {
+        println!(
+            "Kept (warm): {}: the account is warm, but its slots are read cold and repay the address charge",
+            address
+        );
+    }
+}
+
+/// Print the advisory notes on access patterns the list can only partly pay for.
+pub fn print_advice(advice: &[hammer_core::Advice], units: &Units) {
+    if advice.is_empty() {
+        return;
+    }
+    println!("Advice:");
+    for note in advice {
+        println!(
+            "  {} {} save only {} each and still cost {} apiece; {}",
+            note.addresses.len(),
+            note.kind.describe(),
+            units.gas(note.saving_each),
+            units.gas(note.cost_each),
+            note.kind.suggestion()
+        );
+    }
+}
+
+/// Warning for traces that ran third-party callbacks, whose list entries may vary.
+pub fn callback_warning(callbacks: &[hammer_core::Callback]) -> Option<String> {
+    if callbacks.is_empty() {
+        return None;
+    }
+    let names: BTreeSet<&str> = callbacks.iter().map(|c| c.function.as_str()).collect();
+    Some(format!(
+        "warning: trace ran {} callback(s) ({}); entries first touched inside depend on \
+         third-party state and the list may need padding",
+        callbacks.len(),
+        names.into_iter().collect::<Vec<_>>().join(", ")
+    ))
+}
+
+/// Warning that the trace depends on the gas left, so attaching the list may change it.
+pub fn gas_sensitive_warning(sensitive: &[hammer_core::GasSensitivity]) -> Option<String> {
+    if sensitive.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "warning: execution depends on the gas left in {} place(s); attaching the list \
+         changes it and could change what the transaction does (check with generate --self-check)",
+        sensitive.len()
+    ))
+}
+
+/// Print the gas-sensitivity warning with the frame of each place.
+pub fn print_gas_sensitive(sensitive: &[hammer_core::GasSensitivity]) {
+    let Some(warning) = gas_sensitive_warning(sensitive) else {
+        return;
+    };
+    println!("{}", warning);
+    for s in sensitive {
+        println!("  {}", s.describe());
+    }
+}
+
+/// ` [in <callback>]` for a list entry first touched inside a callback, else empty.
+pub fn callback_tag(
+    callbacks: &[hammer_core::Callback],
+    address: &Address,
+    slot: Option<&B256>,
+) -> String {
+    callbacks
+        .iter()
+        .find(|c| c.touched(address, slot))
+        .map(|c| format!(" [in {}]", c.function))
+        .unwrap_or_default()
+}
+
+/// ` [volatile]` for a listed slot expected to move as a price feed updates, else empty.
+pub fn volatile_tag(
+    volatile: &BTreeMap<Address, BTreeSet<B256>>,
+    address: &Address,
+    slot: &B256,
+) -> &'static str {
+    match volatile.get(address) {
+        Some(slots) if slots.contains(slot) => " [volatile]",
+        _ => "",
+    }
+}
+
+/// Print the listed slots expected to move as price feeds update.
+pub fn print_volatile(volatile: &BTreeMap<Address, BTreeSet<B256>>) {
+    if volatile.is_empty() {
+        return;
+    }
+    println!(
+        "Volatile slots: {} (price-feed state; a precomputed list goes stale when the feed updates)",
+        volatile.values().map(BTreeSet::len).sum::<usize>()
+    );
+    for (address, slots) in volatile {
+        println!("  {}:", address);
+        for slot in slots {
+            println!("    - {}", slot);
+        }
+    }
+}
+
+pub fn print_transient(transient: &BTreeMap<Address, BTreeSet<B256>>) {
+    if transient.is_empty() {
+        return;
+    }
+    println!(
+        "Transient slots: {} (EIP-1153; never cold, so never listed)",
+        transient.values().map(BTreeSet::len).sum::<usize>()
+    );
+    for (address, slots) in transient {
+        println!("  {}:", address);
+        for slot in slots {
+            println!("    - {}", slot);
+        }
+    }
+}
+
+/// Print each callback and the list entries first touched inside it.
+pub fn print_callbacks(callbacks: &[hammer_core::Callback]) {
+    let Some(warning) = callback_warning(callbacks) else {
+        return;
+    };
+    println!("{}", warning);
+    for c in callbacks {
+        println!(
+            "  {} ({}): {} → {}",
+            c.function,
+            c.kind.as_str(),
+            c.caller,
+            c.target
+        );
+        for (address, slots) in &c.touched {
+            println!("    {}: {} slots", address, slots.len());
+        }
+    }
+}
+
+/// Print how the run with the list compared to the run without it.
+pub fn print_self_check(check: &hammer_core::SelfCheck) {
+    if check.is_consistent() {
+        println!("Self-check: same calls and state with the list declared");
+        return;
+    }
+    println!("Self-check: the transaction behaves differently with the list declared");
+    if check.success_without != check.success_with {
+        let outcome = |success: bool| if success { "succeeds" } else { "fails" };
+        println!(
+            "  {} without the list, {} with it",
+            outcome(check.success_without),
+            outcome(check.success_with)
+        );
+    }
+    if let Some(path) = &check.diverging_call {
+        println!("  calls diverge at {}", path);
+    }
+    for (label, touched) in [
+        ("only without the list", &check.only_without),
+        ("only with the list", &check.only_with),
+    ] {
+        for (address, slots) in touched {
+            println!("  touched {}: {} ({} slots)", label, address, slots.len());
+        }
+    }
+}
+
+/// Print the call tree, indented by depth, with the path `--entry-call` takes.
+pub fn print_calls(calls: &[hammer_core::CallFrame]) {
+    println!("Calls ({}):", calls.len());
+    for call in calls {
+        let selector = call.selector.map(|s| format!(" {}", s)).unwrap_or_default();
+        println!(
+            "  {}{} {} {} → {}{}",
+            "  ".repeat(call.path.0.len() - 1),
+            call.path,
+            call.kind.as_str(),
+            call.caller,
+            call.target,
+            selector
+        );
+    }
+}
+
+/// Parse `--split` points: comma-separated indices of the top-level calls that start a new
+/// transaction, e.g. `3,7`.
+pub fn parse_split_points(s: &str) -> Result<Vec<usize>> {
+    s.split(',')
+        .map(|point| point.trim().parse::<usize>())
+        .collect::<std::result::Result<_, _>>()
+        .wrap_err_with(|| format!("invalid --split {}: expected call indices like 3,7", s))
+}
+
+/// Print each split plan with its parts and what it costs upfront.
+pub fn print_split_plans(plans: &[hammer_core::SplitPlan], max_size: Option<usize>, units: &Units) {
+    println!("Split plans:");
+    for plan in plans {
+        let points: Vec<String> = plan.split_points.iter().map(usize::to_string).collect();
+        let name = if points.is_empty() {
+            "unsplit".to_string()
+        } else {
+            format!("split at {}", points.join(","))
+        };
+        let fits = match (max_size, plan.fits) {
+            (Some(max), false) => format!("  (exceeds {} entries)", max),
+            _ => String::new(),
+        };
+        println!(
+            "  {}: {} tx, list cost {}, upfront {}{}",
+            name,
+            plan.parts.len(),
+            units.gas(plan.list_cost),
+            units.gas(plan.upfront_cost),
+            fits
+        );
+        for part in &plan.parts {
+            println!(
+                "    calls {}..{}: {} entries, list cost {}",
+                part.calls.start,
+                part.calls.end,
+                part.size,
+                units.gas(part.list_cost)
+            );
+        }
+    }
+}
+
+/// JSON output of `generate`/`deploy`: the bare list, or, when optional sections such as
+/// `--state-diff` are requested, an object carrying the list and those sections.
+pub fn access_list_json(
+    optimal: &hammer_core::OptimizedAccessList,
+    sections: Vec<(&str, serde_json::Value)>,
+) -> Result<serde_json::Value> {
+    let list = serde_json::to_value(&optimal.list)?;
+    if sections.is_empty() {
+        return Ok(list);
+    }
+    let mut out = serde_json::Map::new();
+    out.insert("access_list".to_string(), list);
+    for (name, value) in sections {
+        out.insert(name.to_string(), value);
+    }
+    Ok(out.into())
+}
+
+/// Net balance change of each `(role, address)` participant, for `--balances`. Roles are
+/// kept even when two coincide (e.g. a self-call), so each is reported.
+pub fn participant_balances(
+    optimal: &hammer_core::OptimizedAccessList,
+    participants: &[(&'static str, alloy_primitives::Address)],
+) -> Vec<(&'static str, hammer_core::BalanceChange)> {
+    participants
+        .iter()
+        .filter_map(|(role, address)| {
+            hammer_core::balance_changes(&[*address], &optimal.state_diff, &optimal.logs)
+                .pop()
+                .map(|change| (*role, change))
+        })
+        .collect()
+}
+
+/// JSON for `--balances`: each change tagged with the participant's role.
+pub fn balances_json(
+    balances: &[(&'static str, hammer_core::BalanceChange)],
+) -> Result<serde_json::Value> {
+    balances
+        .iter()
+        .map(|(role, change)| {
+            let mut entry = serde_json::to_value(change)?;
+            entry["role"] = (*role).into();
+            Ok(entry)
+        })
+        .collect()
+}
+
+/// Print net ETH and ERC-20 changes per participant. Token amounts are raw units: the
+/// decimals are not known from the trace.
+pub fn print_balances(balances: &[(&'static str, hammer_core::BalanceChange)]) {
+    println!("Balance changes:");
+    for (role, change) in balances {
+        println!(
+            "  {:<9} {}: {} ETH",
+            role,
+            change.address,
+            alloy_primitives::utils::format_ether(change.eth)
+        );
+        for (token, delta) in &change.tokens {
+            println!("    token {}: {}", token, delta);
+        }
+    }
+}
+
+/// Print the post-execution state diff: one block per changed account.
+pub fn print_state_diff(diff: &[hammer_core::AccountDiff]) {
+    println!("State diff ({} accounts):", diff.len());
+    for account in diff {
+        println!("  {}:", account.address);
+        if let Some(c) = &account.balance {
+            println!("    balance:   {} → {}", c.from, c.to);
+        }
+        if let Some(c) = &account.nonce {
+            println!("    nonce:     {} → {}", c.from, c.to);
+        }
+        if let Some(c) = &account.code_hash {
+            println!("    code hash: {} → {}", c.from, c.to);
+        }
+        for (slot, c) in &account.storage {
+            println!("    {}: {} → {}", slot, c.from, c.to);
+        }
+    }
+}
+
+/// The fork a block ran under: by block number on mainnet (or when no `--chain` is
+/// given), the latest fork elsewhere, as other chains activate forks at their own heights.
+pub fn fork_at(chain_id: Option<u64>, block_number: u64) -> Hardfork {
+    match chain_id {
+        None | Some(1) => Hardfork::at_block(block_number).unwrap_or(Hardfork::Berlin),
+        Some(_) => Hardfork::default(),
+    }
+}
+
+/// Gas constants of the fork a block ran under (see [`fork_at`]).
+pub fn gas_schedule(chain_id: Option<u64>, block_number: u64) -> GasSchedule {
+    fork_at(chain_id, block_number).schedule()
+}
+
+/// Set `config` up for a block of `chain_id` (mainnet when not given): the fork's gas
+/// schedule, and the addresses the chain keeps warm on top of Ethereum's precompiles.
+pub fn apply_chain_rules(config: &mut OptimizerConfig, chain_id: Option<u64>, block_number: u64) {
+    config.schedule = gas_schedule(chain_id, block_number);
+    config.chain_warm = hammer_core::chain_warm_addresses(chain_id.unwrap_or(1));
+}
+
 /// Assert that the block number is post-Berlin fork (where EIP-2930 access lists exist).
 ///
 /// Berlin fork activated at block 12,244,000 on mainnet.
@@ -46,25 +697,12 @@ pub fn assert_post_berlin(block_number: u64) -> Result<()> {
     Ok(())
 }
 
-/// Reject contract creation transactions (CREATE/CREATE2).
-///
-/// `to` is `None` for creation transactions; access list analysis requires a call target.
-pub fn assert_not_create(to: Option<alloy_primitives::Address>) -> Result<()> {
-    if to.is_none() {
-        eyre::bail!(
-            "contract creation transactions (CREATE/CREATE2) are not supported \
-             — access list analysis requires a call target"
-        );
-    }
-    Ok(())
-}
-
 /// Reject blob transactions (EIP-4844, Type 3).
 ///
 /// Blob data (versioned hashes, KZG commitments/proofs) is not replayed, making
 /// access list comparison meaningless for these transactions.
 pub fn assert_not_blob(blob_hashes: Option<&[alloy_primitives::B256]>) -> Result<()> {
-    if blob_hashes.map_or(false, |h| !h.is_empty()) {
+    if blob_hashes.is_some_and(|h| !h.is_empty()) {
         eyre::bail!(
             "blob transactions (EIP-4844, Type 3) are not supported \
              — blob data is not replayed"
@@ -76,7 +714,36 @@ pub fn assert_not_blob(blob_hashes: Option<&[alloy_primitives::B256]>) -> Result
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_primitives::{Address, B256};
+    use alloy_primitives::B256;
+
+    #[test]
+    fn test_state_read_error_recognizes_pruned_state() {
+        let err = state_read_error(
+            std::io::Error::other("missing trie node 0xabc"),
+            17_000_000,
+            "failed to fetch nonce",
+        );
+        assert!(matches!(
+            err.downcast_ref::<HammerError>(),
+            Some(HammerError::StateUnavailable {
+                block: 17_000_000,
+                ..
+            })
+        ));
+        assert!(err.to_string().contains("requires archive state"));
+
+        let err = state_read_error(std::io::Error::other("timeout"), 1, "failed to fetch nonce");
+        assert_eq!(err.to_string(), "failed to fetch nonce");
+    }
+
+    #[test]
+    fn test_probe_order_nearest_first_newer_on_ties() {
+        let order: Vec<u64> = probe_order(100, 3).collect();
+        assert_eq!(order, vec![101, 99, 102, 98, 103, 97]);
+        // Never below genesis.
+        let order: Vec<u64> = probe_order(1, 2).collect();
+        assert_eq!(order, vec![2, 0, 3]);
+    }
 
     // --- parse_u256 edge cases ---
 
@@ -159,6 +826,16 @@ mod tests {
         assert_eq!(id, BlockId::pending());
     }
 
+    // --- gas_schedule ---
+
+    #[test]
+    fn test_gas_schedule_by_mainnet_block() {
+        assert!(!gas_schedule(None, 17_034_869).warm_coinbase);
+        assert!(gas_schedule(Some(1), 17_034_870).warm_coinbase);
+        // Other chains' block numbers say nothing about mainnet forks.
+        assert_eq!(gas_schedule(Some(8453), 1), GasSchedule::default());
+    }
+
     // --- assert_post_berlin ---
 
     #[test]
@@ -185,20 +862,6 @@ mod tests {
         assert!(err.to_string().contains("12243999"));
     }
 
-    // --- assert_not_create ---
-
-    #[test]
-    fn test_assert_not_create_with_call_target() {
-        let addr = Address::from_slice(&[0u8; 20]);
-        assert!(assert_not_create(Some(addr)).is_ok());
-    }
-
-    #[test]
-    fn test_assert_not_create_with_none() {
-        let err = assert_not_create(None).unwrap_err();
-        assert!(err.to_string().contains("CREATE"));
-    }
-
     // --- assert_not_blob ---
 
     #[test]
@@ -219,6 +882,51 @@ mod tests {
         assert!(err.to_string().contains("EIP-4844"));
     }
 
+    // --- parse_block_arg / FutureBlock ---
+
+    #[test]
+    fn test_parse_block_arg_future() {
+        assert_eq!(
+            parse_block_arg("future:+3").unwrap(),
+            (BlockId::latest(), Some(3))
+        );
+        assert_eq!(
+            parse_block_arg("12345").unwrap(),
+            (BlockId::number(12345), None)
+        );
+        for invalid in ["future:3", "future:+0", "future:+x", "future:-1"] {
+            assert!(parse_block_arg(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_future_block_estimates_from_latest() {
+        let mut header: alloy_rpc_types_eth::Header = Default::default();
+        header.inner.number = 100;
+        header.inner.timestamp = 1_000;
+        header.inner.gas_limit = 30_000_000;
+        // A full block raises the basefee by the maximum 1/8.
+        header.inner.gas_used = 30_000_000;
+        header.inner.base_fee_per_gas = Some(8_000_000_000);
+        let future = FutureBlock::after(&header, 3);
+        assert_eq!(
+            future,
+            FutureBlock {
+                base: 100,
+                ahead: 3,
+                number: 103,
+                timestamp: 1_036,
+                basefee: 9_000_000_000,
+            }
+        );
+
+        let mut block = block_env_from_header(&header);
+        future.apply(&mut block);
+        assert_eq!(block.number, U256::from(103));
+        assert_eq!(block.basefee, 9_000_000_000);
+        assert_eq!(block.gas_limit, 30_000_000);
+    }
+
     // --- parse_block_id ---
 
     #[test]
@@ -275,6 +983,33 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_read_bytecode_inline_hex() {
+        assert_eq!(read_bytecode("0x6000").unwrap(), vec![0x60, 0x00]);
+    }
+
+    #[test]
+    fn test_read_bytecode_forge_artifact() {
+        let path = std::env::temp_dir().join("hammer_util_artifact.json");
+        std::fs::write(
+            &path,
+            r#"{"abi": [], "bytecode": {"object": "0x60016000"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            read_bytecode(path.to_str().unwrap()).unwrap(),
+            vec![0x60, 0x01, 0x60, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_read_bytecode_artifact_without_bytecode() {
+        let path = std::env::temp_dir().join("hammer_util_artifact_empty.json");
+        std::fs::write(&path, r#"{"abi": []}"#).unwrap();
+        let err = read_bytecode(path.to_str().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("no bytecode"));
+    }
+
     #[test]
     fn test_parse_hex_bytes_invalid() {
         assert!(parse_hex_bytes("0xgg").is_err());
@@ -1,20 +1,31 @@
-use alloy_primitives::U256;
+use alloy_eips::BlockId;
 use alloy_provider::Provider;
 use alloy_rpc_types_eth::AccessList;
-use clap::Args;
+use clap::{Args, ValueEnum};
 use eyre::{Context, Result};
-use hammer_core::validate;
-use reqwest::Url;
-use revm::context::{BlockEnv, TxEnv};
+use hammer_core::{apply_fixes, validate_candidates, FixEdit, ListScore, ValidationReport};
+use revm::context::TxEnv;
+use revm::primitives::hardfork::SpecId;
 use revm::primitives::TxKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use super::util::{assert_post_berlin, parse_block_id, parse_hex_bytes, parse_u256};
+use super::attest;
+use super::config::{BlockOverrideArgs, OptimizerArgs, StateOverrideArgs};
+use super::engine;
+use super::global::GlobalArgs;
+use super::layout;
+use super::locate::{self, SourceMap};
+use super::stale_history;
+use super::units::Units;
+use super::util::{
+    apply_chain_rules, assert_post_berlin, block_env_from_header, fetch_block, parse_block_arg,
+    parse_hex_bytes, parse_u256, print_budget, print_callbacks, print_gas_sensitive,
+    print_new_accounts, print_precompile_calls, print_transient, print_volatile, state_read_error,
+    traced, FutureBlock, NEAREST_AVAILABLE_DEFAULT,
+};
 
 #[derive(Args)]
 pub struct ValidateArgs {
-    #[arg(long, default_value = "https://eth.llamarpc.com")]
-    pub rpc_url: String,
     #[arg(long)]
     pub from: String,
     #[arg(long)]
@@ -23,94 +34,592 @@ pub struct ValidateArgs {
     pub data: String,
     #[arg(long, default_value = "0")]
     pub value: String,
-    #[arg(long)]
-    pub access_list: PathBuf,
+    /// Gas limit of the transaction; a declared or optimal list that leaves execution too
+    /// little of it is reported as `budget_exceeded`
+    #[arg(long, default_value_t = 30_000_000)]
+    pub gas_limit: u64,
+    /// Declared list to check (JSON). Repeat it to rank several candidate lists, e.g.
+    /// from a wallet, a node and by hand, against the one traced optimum
+    #[arg(long, required = true)]
+    pub access_list: Vec<PathBuf>,
+    /// Block to simulate against: latest, pending, a number, or future:+N for a synthetic
+    /// block N past the latest (estimated number, timestamp and basefee over the latest state)
     #[arg(long, default_value = "latest")]
     pub block: String,
-    #[arg(long, default_value = "json", value_parser = ["json", "human"])]
-    pub output: String,
+    /// If the block's state is pruned, simulate against the nearest block within BLOCKS
+    /// that still has it [default BLOCKS: 64]
+    #[arg(long, value_name = "BLOCKS", num_args = 0..=1, default_missing_value = NEAREST_AVAILABLE_DEFAULT)]
+    pub nearest_available: Option<u64>,
+    /// Solc storage layout for a contract, as ADDRESS=PATH (repeatable); labels slots in human
+    /// output and under `slot_labels` in JSON
+    #[arg(long = "storage-layout", visible_alias = "labels")]
+    pub storage_layouts: Vec<String>,
+    /// Report each finding at its line, column and JSON pointer in the --access-list file
+    /// (`file:line:col: kind: message`, or JSON with --output json) for editor tooling
+    #[arg(long)]
+    pub locations: bool,
+    /// Write the declared list with the suggested fixes applied to this path, editing
+    /// only the entries that change: the file's order, layout and hex case are kept
+    #[arg(long, value_name = "PATH")]
+    pub fix: Option<PathBuf>,
+    /// On a failed validation, rewrite the --access-list file with the fixes applied,
+    /// keeping the original as `<file>.bak`
+    #[arg(long, conflicts_with = "fix")]
+    pub fix_in_place: bool,
+    /// For each stale entry, trace the call again up to BLOCKS blocks away to find when
+    /// it was last touched: stale since an upgrade, or never right [default BLOCKS: 256]
+    #[arg(long, value_name = "BLOCKS", num_args = 0..=1, default_missing_value = "256")]
+    pub stale_history: Option<u64>,
+    /// Which verdict sets the exit code
+    #[arg(long, value_enum, default_value_t = Verdict::Strict)]
+    pub verdict: Verdict,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+    #[command(flatten)]
+    pub block_overrides: BlockOverrideArgs,
+    #[command(flatten)]
+    pub state_overrides: StateOverrideArgs,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Verdict {
+    /// The declared list must match the optimal one exactly
+    Strict,
+    /// Nothing execution needs may be missing; extra declared entries are tolerated
+    Lenient,
+}
+
+impl Verdict {
+    fn passes(self, report: &ValidationReport) -> bool {
+        match self {
+            Self::Strict => report.is_valid,
+            Self::Lenient => report.is_valid_lenient,
+        }
+    }
 }
 
-pub async fn run(args: ValidateArgs) -> Result<()> {
+pub async fn run(args: ValidateArgs, global: &GlobalArgs) -> Result<()> {
     // Validate all local arguments before any network calls.
+    let output = global.output("json", &["json", "human"])?;
+    let units = global.units()?;
+    let signer = global.signer()?;
+    let sink = global.sink()?;
+    let mut config = args.optimizer.config()?;
+    let overrides = args.state_overrides.load()?;
     let from: alloy_primitives::Address = args.from.parse().wrap_err("invalid --from")?;
     let to: alloy_primitives::Address = args.to.parse().wrap_err("invalid --to")?;
     let value = parse_u256(&args.value)?;
     let data = parse_hex_bytes(&args.data)?;
-    let block_id = parse_block_id(&args.block)?;
-    let declared: AccessList =
-        serde_json::from_str(&std::fs::read_to_string(&args.access_list)?)
-            .wrap_err_with(|| format!("invalid access list in {}", args.access_list.display()))?;
-
-    let url = Url::parse(&args.rpc_url).wrap_err("invalid RPC URL")?;
-    let provider = alloy_provider::ProviderBuilder::new()
-        .disable_recommended_fillers()
-        .connect_http(url)
-        .erased();
-
-    let block = provider
-        .get_block(block_id)
-        .await?
-        .ok_or_else(|| eyre::eyre!("Block not found"))?;
+    let (block_id, ahead) = parse_block_arg(&args.block)?;
+    if args.access_list.len() > 1
+        && (args.locations
+            || args.fix.is_some()
+            || args.fix_in_place
+            || args.stale_history.is_some())
+    {
+        eyre::bail!(
+            "--locations, --fix, --fix-in-place and --stale-history take a single --access-list"
+        );
+    }
+    let candidates = args
+        .access_list
+        .iter()
+        .map(|path| read_list(path))
+        .collect::<Result<Vec<_>>>()?;
+    let list_path = &args.access_list[0];
+    let (declared_text, declared) = candidates[0].clone();
+    let source_map = if args.locations {
+        Some(SourceMap::new(&declared_text)?)
+    } else {
+        None
+    };
+    let layouts = layout::load(&args.storage_layouts)?;
+
+    let mut timer = global.timer();
+    let provider = global.provider().await?;
+
+    let (block, substitution) = fetch_block(&provider, block_id, args.nearest_available).await?;
+    timer.phase("block");
+    // Every later state read must target the block actually simulated.
+    let block_id = match substitution {
+        Some(s) => BlockId::number(s.used),
+        None => block_id,
+    };
 
     let header = &block.header;
     // Guard 3: Reject pre-Berlin blocks
     assert_post_berlin(header.number)?;
-    let block_env = BlockEnv {
-        number: U256::from(header.number),
-        beneficiary: header.beneficiary,
-        timestamp: U256::from(header.timestamp),
-        gas_limit: header.gas_limit,
-        basefee: header.base_fee_per_gas.unwrap_or(0),
-        difficulty: header.difficulty,
-        prevrandao: Some(header.mix_hash),
-        blob_excess_gas_and_price: header.excess_blob_gas.map(|excess| {
-            revm::context_interface::block::BlobExcessGasAndPrice::new(
-                excess,
-                revm::primitives::eip4844::BLOB_BASE_FEE_UPDATE_FRACTION_PRAGUE,
-            )
-        }),
-    };
+    let future = ahead.map(|ahead| FutureBlock::after(header, ahead));
+    let number = future.map_or(header.number, |f| f.number);
+    apply_chain_rules(&mut config, global.chain_id()?, number);
+    let mut block_env = block_env_from_header(header);
+    if let Some(future) = &future {
+        future.apply(&mut block_env);
+    }
+    args.block_overrides.apply(&mut block_env);
 
-    let nonce = provider
-        .get_transaction_count(from)
-        .block_id(block_id)
-        .await
-        .wrap_err("failed to fetch nonce")?;
+    let nonce = match overrides.0.get(&from).and_then(|o| o.nonce) {
+        Some(nonce) => nonce,
+        None => provider
+            .get_transaction_count(from)
+            .block_id(block_id)
+            .await
+            .map_err(|e| state_read_error(e, header.number, "failed to fetch nonce"))?,
+    };
 
     let gas_price = block_env.basefee.max(1_000_000_000) as u128;
     let tx_env = TxEnv::builder()
         .caller(from)
         .nonce(nonce)
         .kind(TxKind::Call(to))
-        .gas_limit(30_000_000)
+        .gas_limit(args.gas_limit)
         .gas_price(gas_price)
         .value(value)
-        .data(data.into())
+        .data(data.clone().into())
         .build()
         .unwrap();
 
-    let alloy_db = revm::database::AlloyDB::new(provider, block_id);
+    let alloy_db = revm::database::AlloyDB::new(provider.clone(), block_id);
     let async_db = revm::database_interface::WrapDatabaseAsync::new(alloy_db)
         .ok_or_else(|| eyre::eyre!("WrapDatabaseAsync requires tokio runtime"))?;
-    let db = revm::database_interface::WrapDatabaseRef::from(async_db);
+    let mut db =
+        revm::database::CacheDB::new(revm::database_interface::WrapDatabaseRef::from(async_db));
+    overrides
+        .apply(&mut db)
+        .wrap_err("failed to apply state overrides")?;
 
-    let report = validate(db, tx_env, block_env, declared).wrap_err("validation failed")?;
+    let coinbase = block_env.beneficiary;
+    let lists: Vec<AccessList> = candidates.into_iter().map(|(_, list)| list).collect();
+    let mut reports = traced(header.number, || {
+        validate_candidates(db, tx_env, block_env, &lists, &config)
+    })
+    .wrap_err("validation failed")?;
+    timer.phase("trace");
+    timer.total();
 
-    match args.output.as_str() {
-        "json" => println!("{}", serde_json::to_string_pretty(&report)?),
+    if reports.len() > 1 {
+        let ranking = rank(&args.access_list, &reports);
+        match output {
+            "json" => {
+                let mut value = ranking_json(&ranking, &reports[0])?;
+                engine::stamp(&mut value, SpecId::default());
+                if let (Some(s), Some(fields)) = (substitution, value.as_object_mut()) {
+                    fields.insert("block_substitution".into(), s.to_json());
+                }
+                if let (Some(f), Some(fields)) = (future, value.as_object_mut()) {
+                    fields.insert("synthetic_block".into(), f.to_json());
+                }
+                attest::write_report(value, signer.as_ref(), &sink).await?;
+            }
+            "human" => {
+                if let Some(s) = substitution {
+                    println!("{}", s.notice());
+                }
+                if let Some(f) = future {
+                    println!("{}", f.notice());
+                }
+                print_ranking(&ranking, &reports[0], &units);
+            }
+            _ => unreachable!(),
+        }
+        let any_passes = reports.iter().any(|report| args.verdict.passes(report));
+        std::process::exit(if any_passes { 0 } else { 1 });
+    }
+    let report = reports.remove(0);
+    let stale = stale_history::stale_entries(&report);
+    let history = match args.stale_history {
+        Some(window) if !stale.is_empty() => {
+            let latest = provider
+                .get_block_number()
+                .await
+                .wrap_err("failed to fetch latest block number")?;
+            let call = stale_history::Call {
+                from,
+                to,
+                data: data.into(),
+                value,
+                gas_limit: args.gas_limit,
+            };
+            let history =
+                stale_history::search(&provider, &call, &stale, header.number, latest, window)
+                    .await?;
+            Some((history, window))
+        }
+        _ => None,
+    };
+
+    let fix_target = match (&args.fix, args.fix_in_place) {
+        (Some(path), _) => Some(path.clone()),
+        (None, true) => Some(list_path.clone()),
+        (None, false) => None,
+    };
+    if let Some(path) = fix_target.filter(|_| !report.fixes.is_empty()) {
+        if args.fix_in_place {
+            let backup = backup_path(&path);
+            std::fs::write(&backup, &declared_text)
+                .wrap_err_with(|| format!("failed to write backup {}", backup.display()))?;
+        }
+        let fixed = apply_fixes(&declared, &report.fixes);
+        let text = match locate::splice_fixes(&declared_text, &fixed) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::debug!(target: "hammer::fix", error = %e, "fixes not spliced, writing the list afresh");
+                serde_json::to_string_pretty(&fixed)? + "\n"
+            }
+        };
+        std::fs::write(&path, text)
+            .wrap_err_with(|| format!("failed to write {}", path.display()))?;
+        if !global.quiet {
+            eprintln!("Wrote fixed access list to {}", path.display());
+        }
+    }
+
+    if let Some(source_map) = source_map {
+        if !global.quiet {
+            if let Some(s) = substitution {
+                eprintln!("{}", s.notice());
+            }
+            if let Some(f) = future {
+                eprintln!("{}", f.notice());
+            }
+        }
+        print_locations(&source_map, &report, list_path, output, &units)?;
+        std::process::exit(if args.verdict.passes(&report) { 0 } else { 1 });
+    }
+
+    match output {
+        "json" => {
+            let mut value = serde_json::to_value(&report)?;
+            engine::stamp(&mut value, SpecId::default());
+            if let (Some(s), Some(fields)) = (substitution, value.as_object_mut()) {
+                fields.insert("block_substitution".into(), s.to_json());
+            }
+            if let (Some(f), Some(fields)) = (future, value.as_object_mut()) {
+                fields.insert("synthetic_block".into(), f.to_json());
+            }
+            if let (Some((history, window)), Some(fields)) = (&history, value.as_object_mut()) {
+                fields.insert(
+                    "stale_history".into(),
+                    stale_history::to_json(history, *window),
+                );
+            }
+            let labels = layout::to_json(
+                &layouts,
+                &[&declared, &report.optimal_list],
+                &[from, to, coinbase],
+            );
+            if let (Some(labels), Some(fields)) = (labels, value.as_object_mut()) {
+                fields.insert("slot_labels".into(), labels);
+            }
+            attest::write_report(value, signer.as_ref(), &sink).await?;
+        }
         "human" => {
+            if let Some(s) = substitution {
+                println!("{}", s.notice());
+            }
+            if let Some(f) = future {
+                println!("{}", f.notice());
+            }
             if report.is_valid {
                 println!("Valid: access list matches execution trace.");
             } else {
+                if report.is_valid_lenient {
+                    println!("Nothing required is missing (lenient verdict passes), but:");
+                }
                 println!("Issues found:");
                 for e in &report.entries {
                     println!("  {:?}", e);
                 }
-                println!("Gas summary: {:?}", report.gas_summary);
+                for hint in &report.near_misses {
+                    println!(
+                        "  did you mean {} instead of {} at {}?",
+                        hint.actual, hint.declared, hint.address
+                    );
+                }
+                let s = &report.gas_summary;
+                println!(
+                    "Gas summary: {} declared, {} optimal, {} without a list  ({} upfront waste)",
+                    units.gas(s.declared_list_cost),
+                    units.gas(s.optimal_list_cost),
+                    units.gas(s.no_list_cost),
+                    units.signed(s.waste_per_tx),
+                );
+                print_fixes(&report.fixes);
+            }
+            if let Some((history, window)) = &history {
+                stale_history::print(history, *window);
+            }
+            print_budget(&report.budget_exceeded, &units);
+            if !global.quiet {
+                print_new_accounts(&report.new_accounts, &units);
+                print_precompile_calls(&report.precompile_calls);
+                print_callbacks(&report.callbacks);
+                print_gas_sensitive(&report.gas_sensitive);
+                print_volatile(&report.volatile);
+                print_transient(&report.transient);
+            }
+            layout::print_labels(
+                &layouts,
+                &[&declared, &report.optimal_list],
+                &[from, to, coinbase],
+            );
+        }
+        _ => unreachable!(),
+    }
+    std::process::exit(if args.verdict.passes(&report) { 0 } else { 1 });
+}
+
+fn print_locations(
+    source_map: &SourceMap,
+    report: &ValidationReport,
+    path: &Path,
+    output: &str,
+    units: &Units,
+) -> Result<()> {
+    let located = source_map.locate(&report.entries);
+    match output {
+        "json" => {
+            let findings = located
+                .iter()
+                .map(|l| {
+                    Ok(serde_json::json!({
+                        "pointer": l.location.pointer,
+                        "line": l.location.line,
+                        "column": l.location.column,
+                        "offset": l.location.offset,
+                        "message": locate::message(l.entry),
+                        "entry": serde_json::to_value(l.entry)?,
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            println!("{}", serde_json::to_string_pretty(&findings)?);
+        }
+        "human" => {
+            for l in &located {
+                let kind = serde_json::to_value(l.entry)?["kind"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string();
+                println!(
+                    "{}:{}:{}: {}: {} ({})",
+                    path.display(),
+                    l.location.line,
+                    l.location.column,
+                    kind,
+                    locate::message(l.entry),
+                    units.gas(l.entry.gas_waste())
+                );
             }
         }
         _ => unreachable!(),
     }
-    std::process::exit(if report.is_valid { 0 } else { 1 });
+    Ok(())
+}
+
+/// `list.json` → `list.json.bak`.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// The edit script as a diff: `+` adds to the declared list, `-` removes from it.
+fn print_fixes(fixes: &[FixEdit]) {
+    println!("Suggested fixes:");
+    for fix in fixes {
+        match fix {
+            FixEdit::AddAddress {
+                address,
+                storage_keys,
+            } => {
+                println!("  + {}", address);
+                for key in storage_keys {
+                    println!("  +   {}", key);
+                }
+            }
+            FixEdit::AddSlots {
+                address,
+                storage_keys,
+            } => {
+                for key in storage_keys {
+                    println!("  + {} {}", address, key);
+                }
+            }
+            FixEdit::RemoveAddress { address } => println!("  - {}", address),
+            FixEdit::RemoveSlots {
+                address,
+                storage_keys,
+            } => {
+                for key in storage_keys {
+                    println!("  - {} {}", address, key);
+                }
+            }
+            FixEdit::RemoveDuplicate {
+                address,
+                storage_key,
+            } => println!("  - {} {} (repeat)", address, storage_key),
+        }
+    }
+}
+
+fn read_list(path: &Path) -> Result<(String, AccessList)> {
+    let text = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+    let list = serde_json::from_str(&text)
+        .wrap_err_with(|| format!("invalid access list in {}", path.display()))?;
+    Ok((text, list))
+}
+
+/// How one candidate list fared against the traced optimum.
+#[derive(Debug)]
+struct Standing<'a> {
+    path: &'a Path,
+    report: &'a ValidationReport,
+    /// Entries execution needed that the list misses or lists without all their slots.
+    missing: usize,
+    /// Gas the list costs beyond the optimal one: upfront waste plus runtime penalty.
+    score: ListScore,
+}
+
+/// The candidates from least to most extra gas, fewer missing entries first on a tie,
+/// then in the order given.
+fn rank<'a>(paths: &'a [PathBuf], reports: &'a [ValidationReport]) -> Vec<Standing<'a>> {
+    let mut standings: Vec<Standing> = paths
+        .iter()
+        .zip(reports)
+        .map(|(path, report)| Standing {
+            path,
+            report,
+            missing: report.entries.iter().filter(|e| e.is_shortfall()).count(),
+            score: ListScore::of(report),
+        })
+        .collect();
+    standings.sort_by_key(|s| (s.score.net_cost, s.missing));
+    standings
+}
+
+fn ranking_json(ranking: &[Standing], any: &ValidationReport) -> Result<serde_json::Value> {
+    let rows: Vec<serde_json::Value> = ranking
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            serde_json::json!({
+                "rank": i + 1,
+                "file": s.path.display().to_string(),
+                "declared_origin": s.report.declared_origin.as_str(),
+                "list_cost": s.report.gas_summary.declared_list_cost,
+                "upfront_waste": s.score.upfront_waste,
+                "missing": s.missing,
+                "runtime_penalty": s.score.runtime_penalty,
+                "extra_gas": s.score.net_cost,
+                "issues": s.report.entries.len(),
+                "is_valid": s.report.is_valid,
+                "is_valid_lenient": s.report.is_valid_lenient,
+            })
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "optimal_list": serde_json::to_value(&any.optimal_list)?,
+        "optimal_list_cost": any.gas_summary.optimal_list_cost,
+        "ranking": rows,
+    }))
+}
+
+fn print_ranking(ranking: &[Standing], any: &ValidationReport, units: &Units) {
+    println!(
+        "Ranked against the optimal list (cost: {}):",
+        units.gas(any.gas_summary.optimal_list_cost)
+    );
+    for (i, s) in ranking.iter().enumerate() {
+        let verdict = match (s.report.is_valid, s.report.is_valid_lenient) {
+            (true, _) => "valid",
+            (false, true) => "valid (lenient)",
+            (false, false) => "invalid",
+        };
+        println!(
+            "  {}. {}  {} extra  ({} upfront, {} missing, {} at runtime; {} issues)  {}, {}",
+            i + 1,
+            s.path.display(),
+            units.signed(s.score.net_cost),
+            units.signed(s.score.upfront_waste),
+            s.missing,
+            units.gas(s.score.runtime_penalty),
+            s.report.entries.len(),
+            s.report.declared_origin.as_str(),
+            verdict,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hammer_core::DiffEntry;
+
+    fn report(waste_per_tx: i64, entries: Vec<DiffEntry>) -> ValidationReport {
+        ValidationReport {
+            entries,
+            gas_summary: hammer_core::GasSummary {
+                declared_list_cost: 0,
+                optimal_list_cost: 0,
+                no_list_cost: 0,
+                waste_per_tx,
+                savings_vs_no_list: 0,
+            },
+            optimal_list: Default::default(),
+            is_valid: false,
+            is_valid_lenient: false,
+            new_accounts: vec![],
+            precompile_calls: Default::default(),
+            fixes: vec![],
+            declared_origin: Default::default(),
+            callbacks: vec![],
+            volatile: Default::default(),
+            near_misses: vec![],
+            budget_exceeded: vec![],
+            gas_sensitive: vec![],
+            transient: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rank_by_extra_gas_then_missing() {
+        let missing = DiffEntry::Missing {
+            address: alloy_primitives::Address::with_last_byte(0x40),
+            storage_keys: vec![],
+            gas_waste: 2_500,
+        };
+        let paths = ["wallet.json", "node.json", "hand.json", "empty.json"].map(PathBuf::from);
+        let reports = [
+            // Cheaper upfront, but misses an address: 2,500 lost at runtime.
+            report(-2_400, vec![missing.clone()]),
+            // Keeps a warm address: 2,400 wasted upfront.
+            report(2_400, vec![]),
+            report(0, vec![]),
+            // Same net as hand.json, but only because it misses something.
+            report(-2_500, vec![missing]),
+        ];
+        let ranking = rank(&paths, &reports);
+        let order: Vec<(&Path, i64, usize)> = ranking
+            .iter()
+            .map(|s| (s.path, s.score.net_cost, s.missing))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (Path::new("hand.json"), 0, 0),
+                (Path::new("empty.json"), 0, 1),
+                (Path::new("wallet.json"), 100, 1),
+                (Path::new("node.json"), 2_400, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backup_path_appends_suffix() {
+        assert_eq!(
+            backup_path(Path::new("lists/swap.json")),
+            PathBuf::from("lists/swap.json.bak")
+        );
+    }
 }
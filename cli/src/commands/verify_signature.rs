@@ -0,0 +1,62 @@
+//! `hammer verify-signature`: check a report signed with `--sign-key` (see `attest`).
+
+use alloy_primitives::Address;
+use clap::Args;
+use eyre::{Context, Result};
+use std::path::PathBuf;
+
+use super::attest;
+use super::global::GlobalArgs;
+
+#[derive(Args)]
+pub struct VerifySignatureArgs {
+    /// Signed report: JSON output of a command run with --sign-key
+    pub report: PathBuf,
+    /// Also require the report to be signed by this address
+    #[arg(long)]
+    pub signer: Option<String>,
+}
+
+/// Verify the report's hash and signature; fails unless both hold (and the signer
+/// matches `--signer`, when given).
+pub fn run(args: VerifySignatureArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("human", &["json", "human"])?;
+    let expected: Option<Address> = args
+        .signer
+        .as_deref()
+        .map(str::parse)
+        .transpose()
+        .wrap_err("invalid --signer")?;
+    let text = std::fs::read_to_string(&args.report)
+        .wrap_err_with(|| format!("failed to read {}", args.report.display()))?;
+    let envelope: serde_json::Value = serde_json::from_str(&text)
+        .wrap_err_with(|| format!("invalid JSON in {}", args.report.display()))?;
+    let attestation = attest::verify(&envelope)
+        .wrap_err_with(|| format!("{} failed verification", args.report.display()))?;
+    if let Some(expected) = expected {
+        if attestation.signer != expected {
+            eyre::bail!(
+                "{} is signed by {}, not {}",
+                args.report.display(),
+                attestation.signer,
+                expected
+            );
+        }
+    }
+    match output {
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "valid": true,
+                "signer": attestation.signer,
+                "hash": attestation.hash,
+            }))?
+        ),
+        "human" => println!(
+            "Valid signature by {} over report hash {}",
+            attestation.signer, attestation.hash
+        ),
+        _ => unreachable!(),
+    }
+    Ok(())
+}
@@ -0,0 +1,221 @@
+//! `hammer watch`: the declared list of each pending transaction to a watched contract,
+//! validated as it arrives.
+
+use alloy::network::Ethereum;
+use alloy::network::TransactionResponse;
+use alloy_eips::{BlockId, BlockNumberOrTag};
+use alloy_primitives::{Address, TxKind, B256};
+use alloy_provider::{DynProvider, Provider, ProviderBuilder, WsConnect};
+use alloy_rpc_types_eth::{Transaction, TransactionTrait};
+use clap::Args;
+use eyre::{Context, Result};
+use futures::StreamExt;
+use hammer_core::{validate_with_tracer, ListScore, OptimizerConfig, RevmTracer, ValidationReport};
+use serde_json::json;
+use std::collections::BTreeSet;
+
+use super::compare::{replay_env, BlockState};
+use super::config::OptimizerArgs;
+use super::global::GlobalArgs;
+use super::sink;
+use super::units::Units;
+use super::util::{apply_chain_rules, assert_not_blob, traced, FutureBlock};
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// WebSocket endpoint to subscribe to pending transactions on (ws:// or wss://)
+    #[arg(long)]
+    pub ws_url: String,
+    /// Contract to watch (repeatable); pending transactions calling it are validated
+    #[arg(long = "to", required = true)]
+    pub to: Vec<String>,
+    /// Stop after this many watched transactions
+    #[arg(long)]
+    pub limit: Option<u64>,
+    #[command(flatten)]
+    pub optimizer: OptimizerArgs,
+}
+
+/// A pending transaction's declared list against the optimum at the next block.
+struct Watched {
+    tx: Transaction,
+    /// The latest block when the transaction was seen; its state is simulated over.
+    state_block: u64,
+    report: ValidationReport,
+}
+
+/// Run the watch command.
+///
+/// Subscribes to pending transaction hashes, fetches each, and validates the list of
+/// every one that calls a watched contract, simulated over the latest state as the next
+/// block. Prints one line per transaction as it is checked (to `--out` with ndjson),
+/// until `--limit` or the subscription ends. A transaction that cannot be fetched or
+/// simulated is reported on stderr and skipped: pending transactions are routinely
+/// replaced or mined first.
+///
+/// # Test boundary
+///
+/// This function requires a live WebSocket connection. Validation is core's
+/// (`validate_with_tracer`); the replay environment is `compare`'s.
+pub async fn run(args: WatchArgs, global: &GlobalArgs) -> Result<()> {
+    let output = global.output("ndjson", &["ndjson", "human"])?;
+    let units = global.units()?;
+    let config = args.optimizer.config()?;
+    let sink = global.sink()?;
+    let watched: BTreeSet<Address> = args
+        .to
+        .iter()
+        .map(|to| to.parse().wrap_err_with(|| format!("invalid --to {}", to)))
+        .collect::<Result<_>>()?;
+    if !(args.ws_url.starts_with("ws://") || args.ws_url.starts_with("wss://")) {
+        eyre::bail!("invalid --ws-url {}: expected ws:// or wss://", args.ws_url);
+    }
+    let chain_id = global.chain_id()?;
+
+    let provider = ProviderBuilder::new()
+        .disable_recommended_fillers()
+        .connect_ws(WsConnect::new(args.ws_url.as_str()))
+        .await
+        .wrap_err("failed to connect to --ws-url")?
+        .erased();
+    if let Some(expected) = chain_id {
+        let actual = provider
+            .get_chain_id()
+            .await
+            .wrap_err("failed to fetch chain id")?;
+        if actual != expected {
+            eyre::bail!(
+                "WebSocket endpoint is on chain {} but --chain expects {}",
+                actual,
+                expected
+            );
+        }
+    }
+    let mut pending = provider
+        .subscribe_pending_transactions()
+        .await
+        .wrap_err("failed to subscribe to pending transactions")?
+        .into_stream();
+
+    let mut state: Option<(B256, BlockState)> = None;
+    let mut seen = 0;
+    let mut written = 0;
+    while let Some(hash) = pending.next().await {
+        let tx = match provider.get_transaction_by_hash(hash).await {
+            Ok(Some(tx)) => tx,
+            // Replaced, dropped or already mined: nothing to check.
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("{}: failed to fetch: {}", hash, e);
+                continue;
+            }
+        };
+        if !matches!(tx.inner.kind(), TxKind::Call(to) if watched.contains(&to)) {
+            continue;
+        }
+        seen += 1;
+        match check(&provider, tx, &mut state, chain_id, &config).await {
+            Ok(watched) => match output {
+                "ndjson" => {
+                    sink.append(written, to_json(&watched).to_string(), sink::JSON)
+                        .await?;
+                    written += 1;
+                }
+                "human" => print(&watched, &units),
+                _ => unreachable!(),
+            },
+            Err(e) => eprintln!("{}: {:#}", hash, e),
+        }
+        if args.limit.is_some_and(|limit| seen >= limit) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Validate `tx`'s list over the latest state, as if mined in the next block. The state
+/// cache is kept while the latest block stays the same.
+async fn check(
+    provider: &DynProvider<Ethereum>,
+    tx: Transaction,
+    state: &mut Option<(B256, BlockState)>,
+    chain_id: Option<u64>,
+    config: &OptimizerConfig,
+) -> Result<Watched> {
+    assert_not_blob(tx.inner.blob_versioned_hashes())?;
+    let latest = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .ok_or_else(|| eyre::eyre!("latest block not found"))?;
+    let header = &latest.header;
+    if state.as_ref().is_none_or(|(hash, _)| *hash != header.hash) {
+        let mut fresh = BlockState::at(provider, header)?;
+        FutureBlock::after(header, 1).apply(&mut fresh.block_env);
+        *state = Some((header.hash, fresh));
+    }
+    let Some((_, block_state)) = state.as_mut() else {
+        unreachable!()
+    };
+    let mut config = config.clone();
+    apply_chain_rules(&mut config, chain_id, header.number + 1);
+
+    let declared = tx.inner.access_list().cloned().unwrap_or_default();
+    let block_env = block_state.block_env.clone();
+    let (tx_env, tx_req) = replay_env(&tx, &block_env);
+    let state_block_id = BlockId::hash(header.hash);
+    super::prefetch::prewarm(
+        &mut block_state.db,
+        provider.clone(),
+        state_block_id,
+        state_block_id,
+        tx_req,
+        &declared,
+    )
+    .await;
+    // The sender may have transactions pending ahead of this one: skip the nonce check.
+    let mut tracer = RevmTracer::replay(&mut block_state.db);
+    let report = traced(header.number, || {
+        validate_with_tracer(&mut tracer, tx_env, block_env, declared, &config)
+    })
+    .wrap_err("simulation failed")?;
+    Ok(Watched {
+        tx,
+        state_block: header.number,
+        report,
+    })
+}
+
+fn to_json(watched: &Watched) -> serde_json::Value {
+    let report = &watched.report;
+    json!({
+        "tx_hash": watched.tx.tx_hash(),
+        "from": watched.tx.from(),
+        "to": watched.tx.inner.to(),
+        "state_block": watched.state_block,
+        "is_valid": report.is_valid,
+        "issues": report.entries.len(),
+        "score": ListScore::of(report),
+        "optimal_list": report.optimal_list,
+    })
+}
+
+fn print(watched: &Watched, units: &Units) {
+    let report = &watched.report;
+    let verdict = if report.is_valid {
+        "valid".to_string()
+    } else {
+        format!("{} issues", report.entries.len())
+    };
+    println!(
+        "{} {} → {}: {}, {} against the optimal list",
+        watched.tx.tx_hash(),
+        watched.tx.from(),
+        watched
+            .tx
+            .inner
+            .to()
+            .map_or("?".to_string(), |to| to.to_string()),
+        verdict,
+        units.signed(ListScore::of(report).net_cost)
+    );
+}
@@ -1,38 +1,138 @@
-use clap::Parser;
-use commands::{compare, generate, validate};
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use commands::{
+    bundle, cache, codegen, compare, compare_block, completions, deploy, drift, examples, generate,
+    global, man, queue, report_diff, selftest, serve, slot, validate, verify_signature, watch,
+};
 use eyre::Result;
 use tracing_subscriber::EnvFilter;
 
+mod build_info;
 mod commands;
 
 #[derive(Parser)]
 #[command(name = "hammer")]
 #[command(about = "Hammer — EIP-2930 access list generation and validation")]
+#[command(disable_version_flag = true)]
 struct Cli {
+    #[command(flatten)]
+    global: global::GlobalArgs,
+    /// Print version
+    #[arg(short = 'V', long)]
+    version: bool,
+    /// With --version, also print the git commit, target, profile and enabled features
+    #[arg(long, requires = "version")]
+    build_info: bool,
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(clap::Subcommand)]
 enum Commands {
     /// Generate optimized access list for a transaction
     Generate(generate::GenerateArgs),
+    /// Generate one access list covering every transaction of a bundle
+    GenerateBundle(bundle::BundleArgs),
     /// Validate declared access list against execution trace
     Validate(validate::ValidateArgs),
     /// Compare mined transaction's access list to optimal
     Compare(compare::CompareArgs),
+    /// Compare every transaction of a block and total the gas their lists waste
+    CompareBlock(compare_block::CompareBlockArgs),
+    /// Generate the list for the same call at two blocks and show what changed between them
+    Drift(drift::DriftArgs),
+    /// Generate the access list for a contract deployment (creation bytecode + constructor args)
+    Deploy(deploy::DeployArgs),
+    /// Generate access lists for a sender's queue of transactions, each on the state the earlier ones left
+    Queue(queue::QueueArgs),
+    /// Show what changed between two validation reports for the same transaction
+    ReportDiff(report_diff::ReportDiffArgs),
+    /// Serve generate, validate and compare as JSON-RPC over HTTP
+    Serve(serve::ServeArgs),
+    /// Compute storage slots for mapping keys and dynamic array elements
+    Slot(slot::SlotArgs),
+    /// Check the hash and signature of a report signed with --sign-key
+    VerifySignature(verify_signature::VerifySignatureArgs),
+    /// Validate the access list of each pending transaction to a contract as it arrives
+    Watch(watch::WatchArgs),
+    /// Generate Solidity or TypeScript source embedding an access list
+    #[command(name = "access-list-to-sol")]
+    AccessListToSol(codegen::CodegenArgs),
+    /// Manage the on-disk state cache (clear)
+    Cache(cache::CacheArgs),
+    /// Run generate and validate against a built-in fixture, without network, and report pass/fail
+    Selftest(selftest::SelftestArgs),
+    /// Print copy-pasteable invocations for common workflows
+    Examples(examples::ExamplesArgs),
+    /// Print a shell completion script (bash, zsh, fish, elvish, powershell)
+    Completions(completions::CompletionsArgs),
+    /// Print the manpage, or write one per subcommand with --out-dir
+    Man(man::ManArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    if cli.version {
+        match cli.build_info {
+            true => println!("{}", build_info::build_info()),
+            false => println!("{}", build_info::version()),
+        }
+        return Ok(());
+    }
+    let Some(command) = cli.command else {
+        Cli::command()
+            .error(ErrorKind::MissingSubcommand, "a subcommand is required")
+            .exit();
+    };
+    // Logs always go to stderr so stdout carries only the result, e.g. for `| jq`.
+    // `--quiet` also ignores RUST_LOG.
+    let filter = if cli.global.quiet {
+        EnvFilter::new("hammer=error")
+    } else {
+        EnvFilter::from_default_env().add_directive("hammer=info".parse()?)
+    };
     tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env().add_directive("hammer=info".parse()?))
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
         .init();
 
-    let cli = Cli::parse();
-    match cli.command {
-        Commands::Generate(args) => generate::run(args).await,
-        Commands::Validate(args) => validate::run(args).await,
-        Commands::Compare(args) => compare::run(args).await,
+    let global = &cli.global;
+    // (writes JSON reports, signs them)
+    let reports = match &command {
+        Commands::Generate(_)
+        | Commands::GenerateBundle(_)
+        | Commands::Validate(_)
+        | Commands::Compare(_)
+        | Commands::CompareBlock(_)
+        | Commands::Drift(_)
+        | Commands::Deploy(_)
+        | Commands::Queue(_)
+        | Commands::ReportDiff(_) => (true, true),
+        // A stream of records, each sent on its own.
+        Commands::Watch(_) => (true, false),
+        _ => (false, false),
+    };
+    global.report_flags(reports.0, reports.1)?;
+    match command {
+        Commands::Generate(args) => generate::run(args, global).await,
+        Commands::GenerateBundle(args) => bundle::run(args, global).await,
+        Commands::Validate(args) => validate::run(args, global).await,
+        Commands::Compare(args) => compare::run(args, global).await,
+        Commands::CompareBlock(args) => compare_block::run(args, global).await,
+        Commands::Drift(args) => drift::run(args, global).await,
+        Commands::Deploy(args) => deploy::run(args, global).await,
+        Commands::Queue(args) => queue::run(args, global).await,
+        Commands::ReportDiff(args) => report_diff::run(args, global).await,
+        Commands::Serve(args) => serve::run(args, global).await,
+        Commands::Slot(args) => slot::run(args, global),
+        Commands::VerifySignature(args) => verify_signature::run(args, global),
+        Commands::Watch(args) => watch::run(args, global).await,
+        Commands::AccessListToSol(args) => codegen::run(args),
+        Commands::Cache(args) => cache::run(args, global),
+        Commands::Selftest(args) => selftest::run(args, global),
+        Commands::Examples(args) => examples::run(args, global),
+        Commands::Completions(args) => completions::run(args, Cli::command()),
+        Commands::Man(args) => man::run(args, Cli::command()),
     }
 }
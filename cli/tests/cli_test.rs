@@ -55,6 +55,63 @@ fn test_generate_invalid_from_address() {
         .stderr(predicate::str::contains("invalid --from"));
 }
 
+#[test]
+fn test_generate_invalid_prevrandao() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--prevrandao",
+            "0x12",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--prevrandao"));
+}
+
+#[test]
+fn test_generate_invalid_future_block() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--block",
+            "future:+0",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("future:+N"));
+}
+
+#[test]
+fn test_generate_missing_state_override_file() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--state-override",
+            "/nonexistent/overrides.json",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("/nonexistent/overrides.json"));
+}
+
 #[test]
 fn test_generate_invalid_hex_data() {
     cmd()
@@ -134,7 +191,7 @@ fn test_compare_invalid_tx_hash() {
         .stderr(predicate::str::contains("invalid tx hash"));
 }
 
-// Guards 1 (CREATE), 2 (blob), 3 (pre-Berlin block), and 4 (reverted) all require a live
+// Guards 1 (blob), 2 (pre-Berlin block), and 3 (reverted) all require a live
 // transaction from RPC and cannot be exercised in offline CLI tests. Their logic lives in
 // pure helper functions in cli/src/commands/util.rs and is covered by unit tests there.
 
@@ -269,6 +326,27 @@ fn test_generate_invalid_value() {
         .failure();
 }
 
+#[test]
+fn test_generate_invalid_fuzz_arg() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--fuzz-sig",
+            "transfer(address,uint256)",
+            "--fuzz-arg",
+            "0=1..5",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --fuzz-arg"));
+}
+
 #[test]
 fn test_generate_invalid_output_format() {
     cmd()
@@ -288,8 +366,194 @@ fn test_generate_invalid_output_format() {
         .stderr(predicate::str::contains("output"));
 }
 
+#[test]
+fn test_generate_emit_tx_needs_json() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--emit",
+            "tx",
+            "--output",
+            "human",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--emit tx writes JSON"));
+}
+
+// --- compare-block subcommand ---
+
+#[test]
+fn test_compare_block_missing_block_arg() {
+    cmd()
+        .args(["compare-block"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--block"));
+}
+
+#[test]
+fn test_serve_invalid_listen_address() {
+    cmd()
+        .args(["serve", "--listen", "localhost"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--listen"));
+}
+
+#[test]
+fn test_validate_fix_rejects_several_lists() {
+    cmd()
+        .args([
+            "validate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--access-list",
+            "a.json",
+            "--access-list",
+            "b.json",
+            "--fix-in-place",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("single --access-list"));
+}
+
+#[test]
+fn test_drift_missing_before_arg() {
+    cmd()
+        .args([
+            "drift",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--before"));
+}
+
+#[test]
+fn test_generate_bundle_missing_file() {
+    cmd()
+        .args(["generate-bundle", "--bundle", "/nonexistent/bundle.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to read"));
+}
+
+#[test]
+fn test_selftest_passes_offline() {
+    cmd()
+        .args(["selftest"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Selftest passed."));
+}
+
+#[test]
+fn test_cache_clear_removes_cached_blocks() {
+    let dir = std::env::temp_dir().join("hammer_test_cache");
+    std::fs::create_dir_all(dir.join("1")).unwrap();
+    std::fs::write(dir.join("1").join("0xab.json"), "{}").unwrap();
+    cmd()
+        .env("HAMMER_CACHE_DIR", &dir)
+        .args(["cache", "clear"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 cached blocks"));
+    assert!(!dir.join("1").exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_cache_stats_and_gc() {
+    let dir = std::env::temp_dir().join("hammer_test_cache_gc");
+    std::fs::remove_dir_all(&dir).ok();
+    let now = std::time::SystemTime::now();
+    for (chain, block, age) in [("1", "0x01", 300), ("1", "0x02", 200), ("10", "0x03", 100)] {
+        std::fs::create_dir_all(dir.join(chain)).unwrap();
+        let path = dir.join(chain).join(format!("{}.json", block));
+        std::fs::write(&path, [b' '; 100]).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(now - std::time::Duration::from_secs(age))
+            .unwrap();
+    }
+    cmd()
+        .env("HAMMER_CACHE_DIR", &dir)
+        .args(["cache", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Chain 1: 2 blocks, 200 bytes"))
+        .stdout(predicate::str::contains("Chain 10: 1 blocks, 100 bytes"))
+        .stdout(predicate::str::contains("Total: 3 blocks, 300 bytes"));
+    cmd()
+        .env("HAMMER_CACHE_DIR", &dir)
+        .args(["cache", "gc", "--max-size", "150"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Removed 2 cached blocks (200 bytes); 100 bytes remain",
+        ));
+    // The oldest written go first.
+    assert!(!dir.join("1").join("0x01.json").exists());
+    assert!(!dir.join("1").join("0x02.json").exists());
+    assert!(dir.join("10").join("0x03.json").exists());
+    cmd()
+        .env("HAMMER_CACHE_DIR", &dir)
+        .args(["cache", "gc", "--max-size", "1X"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid size"));
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_watch_rejects_http_url() {
+    cmd()
+        .args([
+            "watch",
+            "--ws-url",
+            "https://eth.llamarpc.com",
+            "--to",
+            "0x0000000000000000000000000000000000000001",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected ws:// or wss://"));
+}
+
 // --- compare additional error paths ---
 
+#[test]
+fn test_compare_as_of_seen_single_transaction() {
+    cmd()
+        .args([
+            "compare",
+            "--tx-hash",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "--tx-hash",
+            "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+            "--as-of-seen",
+            "19000000",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("single transaction"));
+}
+
 #[test]
 fn test_compare_invalid_rpc_url() {
     cmd()
@@ -394,3 +658,944 @@ fn test_validate_rpc_network_failure_is_user_friendly() {
         .failure()
         .stderr(predicate::str::is_empty().not());
 }
+
+// --- slot subcommand (pure computation, no RPC) ---
+
+#[test]
+fn test_slot_mapping_known_vector() {
+    // keccak256(pad32(0) ++ pad32(0)): first entry of a mapping declared at slot 0.
+    cmd()
+        .args(["slot", "mapping", "--base", "0", "--key", "0"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "0xad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5",
+        ));
+}
+
+#[test]
+fn test_slot_mapping_requires_key() {
+    cmd()
+        .args(["slot", "mapping", "--base", "3"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--key"));
+}
+
+#[test]
+fn test_slot_array_invalid_index() {
+    cmd()
+        .args(["slot", "array", "--base", "3", "--index", "xyz"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --index"));
+}
+
+// --- --storage-layout is parsed before any RPC call ---
+
+#[test]
+fn test_generate_storage_layout_missing_separator() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--storage-layout",
+            "layout.json",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected ADDRESS=PATH"));
+}
+
+#[test]
+fn test_compare_storage_layout_invalid_json() {
+    let tmp = std::env::temp_dir().join("hammer_test_bad_layout.json");
+    std::fs::write(&tmp, "{\"storage\": 3}").unwrap();
+    let spec = format!(
+        "0x0000000000000000000000000000000000000005={}",
+        tmp.to_str().unwrap()
+    );
+
+    cmd()
+        .args([
+            "compare",
+            "--tx-hash",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "--storage-layout",
+            &spec,
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid storage layout"));
+}
+
+#[test]
+fn test_compare_tx_hashes_file_missing() {
+    cmd()
+        .args([
+            "compare",
+            "--tx-hashes",
+            "/nonexistent/hammer_tx_hashes.txt",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("failed to read"));
+}
+
+/// Every hash is parsed before any network call, so one bad hash fails the whole batch.
+#[test]
+fn test_compare_batch_invalid_second_hash() {
+    cmd()
+        .args([
+            "compare",
+            "--tx-hash",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "--tx-hash",
+            "0xbb",
+            "--rpc-url",
+            "not-a-url",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid tx hash 0xbb"));
+}
+
+#[test]
+fn test_compare_invalid_output_format() {
+    cmd()
+        .args([
+            "compare",
+            "--tx-hash",
+            "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "--output",
+            "yaml",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("markdown"));
+}
+
+// --- deploy subcommand ---
+
+#[test]
+fn test_deploy_missing_bytecode_arg() {
+    cmd()
+        .args([
+            "deploy",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--bytecode"));
+}
+
+#[test]
+fn test_deploy_empty_bytecode_rejected() {
+    cmd()
+        .args([
+            "deploy",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--bytecode",
+            "0x",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("creation bytecode is empty"));
+}
+
+#[test]
+fn test_deploy_invalid_constructor_args() {
+    cmd()
+        .args([
+            "deploy",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--bytecode",
+            "0x6000",
+            "--args",
+            "0xzz",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --args"));
+}
+
+#[test]
+fn test_deploy_salt_requires_create2_factory() {
+    cmd()
+        .args([
+            "deploy",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--bytecode",
+            "0x6000",
+            "--salt",
+            "0x01",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--create2-factory"));
+}
+
+#[test]
+fn test_deploy_invalid_create2_factory() {
+    cmd()
+        .args([
+            "deploy",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--bytecode",
+            "0x6000",
+            "--create2-factory",
+            "nope",
+            "--salt",
+            "0x01",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --create2-factory"));
+}
+
+/// --locations indexes the declared file before any network call; a well-formed list
+/// must get through to the RPC step.
+#[test]
+fn test_validate_locations_reaches_rpc() {
+    let tmp = std::env::temp_dir().join("hammer_test_locations.json");
+    std::fs::write(&tmp, "[\n  {\"address\": \"0x0000000000000000000000000000000000000005\", \"storageKeys\": []}\n]\n").unwrap();
+    cmd()
+        .args([
+            "validate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--access-list",
+            tmp.to_str().unwrap(),
+            "--locations",
+            "--rpc-url",
+            "not-a-url",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid RPC URL"));
+}
+
+#[test]
+fn test_validate_fix_in_place_conflicts_with_fix() {
+    cmd()
+        .args([
+            "validate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--access-list",
+            "list.json",
+            "--fix",
+            "fixed.json",
+            "--fix-in-place",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+// --- global options ---
+
+/// Global options may precede the subcommand; a value after it overrides that one.
+#[test]
+fn test_global_output_before_subcommand_overridden_after() {
+    cmd()
+        .args([
+            "--output", "json", "slot", "array", "--base", "0", "--index", "0",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("\"0x"));
+    cmd()
+        .args([
+            "--output", "json", "slot", "array", "--base", "0", "--index", "0", "--output", "human",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("0x"));
+}
+
+#[test]
+fn test_global_output_unsupported_by_subcommand() {
+    cmd()
+        .args([
+            "--output",
+            "markdown",
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--output markdown is not supported",
+        ));
+}
+
+#[test]
+fn test_html_output_only_for_compare() {
+    // The output format is checked before the access list file is read.
+    cmd()
+        .args([
+            "validate",
+            "--access-list",
+            "/nonexistent.json",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--output",
+            "html",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--output html is not supported"));
+}
+
+#[test]
+fn test_nearest_available_rejects_non_numeric_range() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--nearest-available",
+            "soon",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--nearest-available"));
+}
+
+#[test]
+fn test_global_unknown_chain() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--chain",
+            "nope",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown --chain nope"));
+}
+
+/// Without --rpc-url there is only a default endpoint for mainnet.
+#[test]
+fn test_global_chain_without_rpc_url_rejected() {
+    cmd()
+        .args([
+            "--chain",
+            "sepolia",
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("pass --rpc-url"));
+}
+
+// --- access-list-to-sol (pure, no RPC) ---
+
+#[test]
+fn test_access_list_to_sol_solidity() {
+    let tmp = std::env::temp_dir().join("hammer_test_codegen.json");
+    std::fs::write(
+        &tmp,
+        r#"[{"address":"0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48","storageKeys":["0x0000000000000000000000000000000000000000000000000000000000000001"]}]"#,
+    )
+    .unwrap();
+    cmd()
+        .args([
+            "access-list-to-sol",
+            "--access-list",
+            tmp.to_str().unwrap(),
+            "--name",
+            "Swap",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("library Swap {").and(predicate::str::contains(
+                "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48",
+            )),
+        );
+}
+
+#[test]
+fn test_access_list_to_sol_typescript() {
+    let tmp = std::env::temp_dir().join("hammer_test_codegen_ts.json");
+    std::fs::write(&tmp, "[]").unwrap();
+    cmd()
+        .args([
+            "access-list-to-sol",
+            "--access-list",
+            tmp.to_str().unwrap(),
+            "--lang",
+            "typescript",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("export const accessList = ["));
+}
+
+#[test]
+fn test_access_list_to_sol_invalid_list() {
+    let tmp = std::env::temp_dir().join("hammer_test_codegen_bad.json");
+    std::fs::write(&tmp, "{").unwrap();
+    cmd()
+        .args(["access-list-to-sol", "--access-list", tmp.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid access list"));
+}
+
+// --- completions / man (pure, no RPC) ---
+
+#[test]
+fn test_completions_bash_mentions_subcommands() {
+    cmd()
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_hammer").and(predicate::str::contains("compare")));
+}
+
+#[test]
+fn test_completions_unknown_shell() {
+    cmd()
+        .args(["completions", "tcsh"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("zsh"));
+}
+
+#[test]
+fn test_man_prints_roff() {
+    cmd()
+        .args(["man"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(".TH hammer"));
+}
+
+#[test]
+fn test_man_out_dir_writes_page_per_subcommand() {
+    let dir = std::env::temp_dir().join("hammer_test_man");
+    let _ = std::fs::remove_dir_all(&dir);
+    cmd()
+        .args(["man", "--out-dir", dir.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(dir.join("hammer.1").exists());
+    assert!(dir.join("hammer-generate.1").exists());
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+// --- report-diff subcommand ---
+
+fn report_json(entries: &str, waste: i64) -> String {
+    format!(
+        r#"{{"entries":[{entries}],"gas_summary":{{"declared_list_cost":0,"optimal_list_cost":0,"no_list_cost":0,"waste_per_tx":{waste},"savings_vs_no_list":0}},"optimal_list":[],"is_valid":{}}}"#,
+        entries.is_empty()
+    )
+}
+
+const STALE_ENTRY: &str = r#"{"kind":"stale","address":"0x00000000000000000000000000000000000000aa","storage_keys":[],"gas_waste":2400}"#;
+
+#[test]
+fn test_report_diff_identical() {
+    let a = std::env::temp_dir().join("hammer_test_diff_same.json");
+    std::fs::write(&a, report_json(STALE_ENTRY, 2400)).unwrap();
+    cmd()
+        .args(["report-diff", a.to_str().unwrap(), a.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No differences."));
+}
+
+#[test]
+fn test_report_diff_units() {
+    let before = std::env::temp_dir().join("hammer_test_diff_units_before.json");
+    let after = std::env::temp_dir().join("hammer_test_diff_units_after.json");
+    std::fs::write(&before, report_json(STALE_ENTRY, 2_400_000)).unwrap();
+    std::fs::write(&after, report_json("", 0)).unwrap();
+    let args = [
+        "report-diff",
+        before.to_str().unwrap(),
+        after.to_str().unwrap(),
+        "--units",
+        "eth",
+    ];
+    cmd()
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--units eth needs --gas-price GWEI",
+        ));
+    cmd()
+        .args(args)
+        .args(["--gas-price", "20"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(-0.048000 ETH)"));
+    cmd()
+        .args([
+            "report-diff",
+            before.to_str().unwrap(),
+            after.to_str().unwrap(),
+        ])
+        .arg("--separators")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(-2,400,000 gas)"));
+}
+
+#[test]
+fn test_report_diff_warns_on_engine_change() {
+    let before = std::env::temp_dir().join("hammer_test_diff_engine_before.json");
+    let after = std::env::temp_dir().join("hammer_test_diff_engine_after.json");
+    let with_engine = |revm: &str| {
+        let mut report: serde_json::Value = serde_json::from_str(&report_json("", 0)).unwrap();
+        report["engine"] = serde_json::json!({"hammer": "0.1.0", "revm": revm, "spec": "Prague"});
+        report.to_string()
+    };
+    std::fs::write(&before, with_engine("27.0.0")).unwrap();
+    std::fs::write(&after, with_engine("34.0.0")).unwrap();
+    let args = [
+        "report-diff",
+        before.to_str().unwrap(),
+        after.to_str().unwrap(),
+    ];
+    cmd()
+        .args(args)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "before: revm 27.0.0 under Prague; after: revm 34.0.0 under Prague",
+        ));
+    cmd()
+        .args(args)
+        .arg("--quiet")
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn test_report_diff_resolved_finding() {
+    let before = std::env::temp_dir().join("hammer_test_diff_before.json");
+    let after = std::env::temp_dir().join("hammer_test_diff_after.json");
+    std::fs::write(&before, report_json(STALE_ENTRY, 2400)).unwrap();
+    std::fs::write(&after, report_json("", 0)).unwrap();
+    cmd()
+        .args([
+            "report-diff",
+            before.to_str().unwrap(),
+            after.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("Valid:            no → yes")
+                .and(predicate::str::contains("(-2400 gas)"))
+                .and(predicate::str::contains("- resolved    stale")),
+        );
+
+    let out = cmd()
+        .args([
+            "report-diff",
+            before.to_str().unwrap(),
+            after.to_str().unwrap(),
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(json["costs"]["upfront_waste"]["delta"], -2400);
+    assert_eq!(json["resolved"][0]["kind"], "stale");
+}
+
+#[test]
+fn test_report_written_to_out_file() {
+    let report = std::env::temp_dir().join("hammer_test_out_report.json");
+    let out = std::env::temp_dir().join("hammer_test_out_written.json");
+    std::fs::write(&report, report_json(STALE_ENTRY, 2400)).unwrap();
+    std::fs::remove_file(&out).ok();
+    let args = [
+        "report-diff",
+        report.to_str().unwrap(),
+        report.to_str().unwrap(),
+        "--output",
+        "json",
+    ];
+    cmd()
+        .args(args)
+        .args(["--out", out.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+    assert!(json["resolved"].as_array().unwrap().is_empty());
+
+    cmd()
+        .args(args)
+        .args(["--out", "ftp://example.com/report.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --out"));
+}
+
+#[test]
+fn test_report_flags_rejected_without_reports() {
+    cmd()
+        .args(["selftest", "--out", "/tmp/hammer_test_selftest_out.json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--out is not supported by this command",
+        ));
+    cmd()
+        .args([
+            "watch",
+            "--ws-url",
+            "ws://127.0.0.1:1",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--sign-key",
+            "/nonexistent/key",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--sign-key is not supported by this command",
+        ));
+}
+
+#[test]
+fn test_queue_ndjson_rejects_out() {
+    cmd()
+        .args([
+            "queue",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--txs",
+            "/nonexistent/queue.json",
+            "--output",
+            "ndjson",
+            "--out",
+            "/tmp/hammer_test_queue_out.ndjson",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--output ndjson streams to stdout",
+        ));
+}
+
+// --- verify-signature subcommand ---
+
+#[test]
+fn test_signed_report_round_trip() {
+    let dir = std::env::temp_dir();
+    let key = dir.join("hammer_test_sign.key");
+    let report = dir.join("hammer_test_sign_report.json");
+    let signed = dir.join("hammer_test_sign_signed.json");
+    std::fs::write(
+        &key,
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318\n",
+    )
+    .unwrap();
+    std::fs::write(&report, report_json(STALE_ENTRY, 2400)).unwrap();
+    let out = cmd()
+        .args([
+            "report-diff",
+            report.to_str().unwrap(),
+            report.to_str().unwrap(),
+        ])
+        .args(["--output", "json", "--sign-key", key.to_str().unwrap()])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    let envelope: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    let signer = envelope["attestation"]["signer"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    std::fs::write(&signed, &out.stdout).unwrap();
+
+    cmd()
+        .args([
+            "verify-signature",
+            signed.to_str().unwrap(),
+            "--signer",
+            &signer,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Valid signature by"));
+    cmd()
+        .args(["verify-signature", signed.to_str().unwrap()])
+        .args(["--signer", "0x0000000000000000000000000000000000000040"])
+        .assert()
+        .failure();
+
+    let tampered = String::from_utf8(out.stdout)
+        .unwrap()
+        .replacen("true", "false", 1);
+    std::fs::write(&signed, tampered).unwrap();
+    cmd()
+        .args(["verify-signature", signed.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("modified after signing"));
+}
+
+#[test]
+fn test_report_diff_json_stdout_clean_with_logging_enabled() {
+    let a = std::env::temp_dir().join("hammer_test_diff_log.json");
+    std::fs::write(&a, report_json(STALE_ENTRY, 2400)).unwrap();
+    let out = cmd()
+        .env("RUST_LOG", "trace")
+        .args([
+            "report-diff",
+            a.to_str().unwrap(),
+            a.to_str().unwrap(),
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(out.status.success());
+    serde_json::from_slice::<serde_json::Value>(&out.stdout).unwrap();
+}
+
+#[test]
+fn test_report_diff_rejects_batch_array() {
+    let a = std::env::temp_dir().join("hammer_test_diff_array.json");
+    std::fs::write(&a, format!("[{}]", report_json("", 0))).unwrap();
+    cmd()
+        .args(["report-diff", a.to_str().unwrap(), a.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected one validation report"));
+}
+
+// --- queue subcommand ---
+
+#[test]
+fn test_queue_invalid_entry_rejected_before_rpc() {
+    let tmp = std::env::temp_dir().join("hammer_test_queue_bad.json");
+    std::fs::write(&tmp, r#"[{"data": "0x"}]"#).unwrap();
+    cmd()
+        .args([
+            "queue",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--txs",
+            tmp.to_str().unwrap(),
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("entry 0: missing \"to\""));
+}
+
+#[test]
+fn test_generate_invalid_abi_spec_rejected_before_rpc() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--logs",
+            "--abi",
+            "token.json",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid --abi token.json: expected ADDRESS=PATH",
+        ));
+}
+
+#[test]
+fn test_generate_abi_requires_logs() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--abi",
+            "0x0000000000000000000000000000000000000003=token.json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--logs"));
+}
+
+#[test]
+fn test_validate_invalid_assume_warm_rejected_before_rpc() {
+    cmd()
+        .args([
+            "validate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--access-list",
+            "/nonexistent/list.json",
+            "--assume-warm",
+            "0x12",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --assume-warm 0x12"));
+}
+
+#[test]
+fn test_generate_invalid_entry_call_rejected_before_rpc() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--entry-call",
+            "0/1",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid --entry-call"))
+        .stderr(predicate::str::contains(
+            "expected dot-separated call indices",
+        ));
+}
+
+#[test]
+fn test_generate_entry_call_conflicts_with_state_diff() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--entry-call",
+            "0",
+            "--state-diff",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_generate_invalid_split_rejected_before_rpc() {
+    cmd()
+        .args([
+            "generate",
+            "--from",
+            "0x0000000000000000000000000000000000000001",
+            "--to",
+            "0x0000000000000000000000000000000000000002",
+            "--split",
+            "3;7",
+            "--rpc-url",
+            "http://127.0.0.1:1",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid --split 3;7: expected call indices like 3,7",
+        ));
+}
+
+#[test]
+fn test_examples_unknown_topic_lists_topics() {
+    cmd()
+        .args(["examples", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unknown example nope"))
+        .stderr(predicate::str::contains("validate-wallet"));
+}
+
+#[test]
+fn test_examples_fill_rpc_url() {
+    cmd()
+        .args(["--rpc-url", "http://localhost:8545", "examples", "compare"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "hammer compare --tx-hash <TX_HASH> --output human --rpc-url http://localhost:8545",
+        ));
+}
+
+#[test]
+fn test_version_build_info() {
+    cmd()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("hammer {}\n", env!("CARGO_PKG_VERSION")));
+    cmd()
+        .args(["--version", "--build-info"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("commit:")
+                .and(predicate::str::contains("target:"))
+                .and(predicate::str::contains("features: rustls")),
+        );
+}
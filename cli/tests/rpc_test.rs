@@ -175,6 +175,156 @@ fn test_generate_json_output_is_valid_json() {
     }
 }
 
+/// With every log target enabled, stdout must still be exactly the JSON document:
+/// logs go to stderr.
+#[test]
+fn test_generate_json_stdout_clean_with_logging_enabled() {
+    require_rpc!(url);
+
+    let output = hammer()
+        .env("RUST_LOG", "trace")
+        .args([
+            "generate",
+            "--from",
+            VITALIK,
+            "--to",
+            UNISWAP_V3_ROUTER,
+            "--block",
+            PINNED_BLOCK,
+            "--rpc-url",
+            &url,
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    serde_json::from_slice::<serde_json::Value>(&output.stdout)
+        .expect("stdout must be only the JSON document");
+}
+
+/// `--state-diff` wraps the list in an object alongside the diff; the sender's nonce is
+/// always bumped, so the diff is never empty.
+#[test]
+fn test_generate_state_diff_json() {
+    require_rpc!(url);
+
+    let output = hammer()
+        .args([
+            "generate",
+            "--from",
+            VITALIK,
+            "--to",
+            UNISWAP_V3_ROUTER,
+            "--block",
+            PINNED_BLOCK,
+            "--rpc-url",
+            &url,
+            "--state-diff",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(parsed["access_list"].is_array());
+    let diff = parsed["state_diff"].as_array().expect("state_diff array");
+    let sender = diff
+        .iter()
+        .find(|d| d["address"].as_str().unwrap().eq_ignore_ascii_case(VITALIK))
+        .expect("sender in state diff");
+    assert!(sender["nonce"]["to"].is_number());
+}
+
+/// `--balances` reports the sender, target and coinbase by role; the sender pays gas, so
+/// its ETH change is negative.
+#[test]
+fn test_generate_balances_json() {
+    require_rpc!(url);
+
+    let output = hammer()
+        .args([
+            "generate",
+            "--from",
+            VITALIK,
+            "--to",
+            UNISWAP_V3_ROUTER,
+            "--block",
+            PINNED_BLOCK,
+            "--rpc-url",
+            &url,
+            "--balances",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let balances = parsed["balance_changes"]
+        .as_array()
+        .expect("balance_changes");
+    let roles: Vec<&str> = balances
+        .iter()
+        .map(|b| b["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["from", "to", "coinbase"]);
+    assert!(balances[0]["eth"].as_str().unwrap().starts_with('-'));
+}
+
+/// `RUST_LOG=hammer::prefetch=debug` enables prefetch logs, labelled with the block,
+/// without enabling trace logs.
+#[test]
+fn test_prefetch_log_target_is_selectable() {
+    require_rpc!(url);
+
+    let output = hammer()
+        .env("RUST_LOG", "hammer::prefetch=debug")
+        .args([
+            "generate",
+            "--from",
+            VITALIK,
+            "--to",
+            UNISWAP_V3_ROUTER,
+            "--block",
+            PINNED_BLOCK,
+            "--rpc-url",
+            &url,
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("prefetch{block="), "stderr: {}", stderr);
+    assert!(!stderr.contains("trace finished"), "stderr: {}", stderr);
+}
+
+/// On a node that has the pinned block's state, `--nearest-available` changes nothing.
+#[test]
+fn test_nearest_available_keeps_block_with_state() {
+    require_rpc!(url);
+
+    hammer()
+        .args([
+            "generate",
+            "--from",
+            VITALIK,
+            "--to",
+            UNISWAP_V3_ROUTER,
+            "--block",
+            PINNED_BLOCK,
+            "--nearest-available",
+            "--rpc-url",
+            &url,
+            "--output",
+            "human",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("state pruned").not());
+}
+
 /// Asserts the --output human branch runs and produces the expected header line.
 #[test]
 fn test_generate_human_output_format() {
@@ -196,7 +346,7 @@ fn test_generate_human_output_format() {
         ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Access list (gas cost:"));
+        .stdout(predicate::str::contains("Access list (cost:"));
 }
 
 /// Asserts --block <number> works as an alternative to `latest`.
@@ -412,6 +562,40 @@ fn test_validate_exit_1_on_stale_list_for_plain_transfer() {
     );
 }
 
+/// --fix-in-place rewrites the stale list to the fixed (empty) list and keeps a backup.
+#[test]
+fn test_validate_fix_in_place_rewrites_stale_list() {
+    require_rpc!(url);
+
+    let stale_list =
+        r#"[{"address":"0x1234567890123456789012345678901234567890","storageKeys":[]}]"#;
+    let list_path = temp_file("hammer_rpc_fix_in_place_al.json", stale_list);
+
+    let output = hammer()
+        .args([
+            "validate",
+            "--from",
+            VITALIK,
+            "--to",
+            PLAIN_EOA,
+            "--block",
+            PINNED_BLOCK,
+            "--access-list",
+            &list_path,
+            "--fix-in-place",
+            "--rpc-url",
+            &url,
+        ])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    let fixed = std::fs::read_to_string(&list_path).unwrap();
+    assert_eq!(fixed.trim(), "[]");
+    let backup = std::fs::read_to_string(format!("{}.bak", list_path)).unwrap();
+    assert_eq!(backup, stale_list);
+}
+
 /// The --output human branch for a valid report must print the exact success string.
 #[test]
 fn test_validate_human_output_valid_report() {
@@ -538,6 +722,70 @@ fn test_compare_valid_tx_produces_gas_summary() {
         );
 }
 
+/// `--output json` emits the full report plus the compare metadata as one JSON object.
+#[test]
+fn test_compare_json_output_includes_metadata() {
+    require_rpc!(url);
+
+    let Some(tx_hash) = find_successful_tx(&url) else {
+        eprintln!("SKIP: could not find a suitable successful tx in block 17_000_000 (node may not have that state)");
+        return;
+    };
+
+    let out = hammer()
+        .args([
+            "compare",
+            "--tx-hash",
+            &tx_hash,
+            "--rpc-url",
+            &url,
+            "--output",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        out.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&out.stderr)
+    );
+    let json: serde_json::Value = serde_json::from_slice(&out.stdout).expect("stdout must be JSON");
+    assert_eq!(json["tx_hash"].as_str(), Some(tx_hash.as_str()));
+    assert!(json["miner"].is_string());
+    assert!(json["gas_summary"].is_object());
+    assert!(json["is_valid"].is_boolean());
+}
+
+/// `--output html` emits one standalone document for the compared transaction.
+#[test]
+fn test_compare_html_output_is_standalone_document() {
+    require_rpc!(url);
+
+    let Some(tx_hash) = find_successful_tx(&url) else {
+        eprintln!("SKIP: could not find a suitable successful tx in block 17_000_000 (node may not have that state)");
+        return;
+    };
+
+    hammer()
+        .args([
+            "compare",
+            "--tx-hash",
+            &tx_hash,
+            "--rpc-url",
+            &url,
+            "--output",
+            "html",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::starts_with("<!DOCTYPE html>")
+                .and(predicate::str::contains(format!("id=\"{}\"", tx_hash)))
+                .and(predicate::str::contains("Declared list origin"))
+                .and(predicate::str::ends_with("</html>\n")),
+        );
+}
+
 /// The first-ever EIP-4844 blob tx must be rejected by the blob guard.
 #[test]
 fn test_compare_blob_tx_rejected() {
@@ -0,0 +1,128 @@
+//! Advisory notes on access patterns an access list can only partly pay for.
+//!
+//! A list turns a cold access into an upfront charge slightly below it, so the saving per
+//! entry is small and fixed. When a trace spreads its reads over many accounts, most of
+//! the cold cost remains however the list is built; only restructuring the calls (going
+//! through a contract that is warm anyway, keeping related state together) removes it.
+
+use alloy_eip2930::AccessList;
+use alloy_primitives::Address;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::gas::{
+    ACCESS_LIST_ADDRESS_COST, ACCESS_LIST_STORAGE_KEY_COST, NET_SAVINGS_PER_ACCESSED_ADDRESS,
+    NET_SAVINGS_PER_ACCESSED_SLOT, WARM_STORAGE_READ_COST,
+};
+
+/// Fewest entries of one pattern worth a note.
+pub const ADVICE_MIN_ENTRIES: usize = 5;
+
+/// Pattern an [`Advice`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AdviceKind {
+    /// Accounts touched without reading their storage (balance checks, plain transfers).
+    AddressOnly,
+    /// Contracts touched for a single storage slot each.
+    SingleSlot,
+}
+
+impl AdviceKind {
+    /// What the entries are, for human output.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::AddressOnly => "address-only entries",
+            Self::SingleSlot => "contracts read for a single slot",
+        }
+    }
+
+    /// The restructuring that would remove the cost, for human output.
+    pub fn suggestion(self) -> &'static str {
+        match self {
+            Self::AddressOnly => "consider batching through a router you already warm",
+            Self::SingleSlot => "consider keeping that state in fewer contracts",
+        }
+    }
+}
+
+/// One note: the entries showing a pattern and what the list does for each.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Advice {
+    pub kind: AdviceKind,
+    pub addresses: Vec<Address>,
+    /// Net gas the list saves per entry.
+    pub saving_each: u64,
+    /// Gas each entry still costs with the list: its upfront charge and warm reads.
+    pub cost_each: u64,
+}
+
+/// Notes on `list` for every pattern with at least [`ADVICE_MIN_ENTRIES`] entries.
+pub fn advise(list: &AccessList) -> Vec<Advice> {
+    let mut address_only = Vec::new();
+    let mut single_slot = Vec::new();
+    for item in &list.0 {
+        match item.storage_keys.len() {
+            0 => address_only.push(item.address),
+            1 => single_slot.push(item.address),
+            _ => {}
+        }
+    }
+
+    let mut notes = Vec::new();
+    if address_only.len() >= ADVICE_MIN_ENTRIES {
+        notes.push(Advice {
+            kind: AdviceKind::AddressOnly,
+            addresses: address_only,
+            saving_each: NET_SAVINGS_PER_ACCESSED_ADDRESS as u64,
+            cost_each: ACCESS_LIST_ADDRESS_COST,
+        });
+    }
+    if single_slot.len() >= ADVICE_MIN_ENTRIES {
+        notes.push(Advice {
+            kind: AdviceKind::SingleSlot,
+            addresses: single_slot,
+            saving_each: (NET_SAVINGS_PER_ACCESSED_ADDRESS + NET_SAVINGS_PER_ACCESSED_SLOT) as u64,
+            cost_each: ACCESS_LIST_ADDRESS_COST
+                + ACCESS_LIST_STORAGE_KEY_COST
+                + WARM_STORAGE_READ_COST,
+        });
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eip2930::AccessListItem;
+    use alloy_primitives::B256;
+
+    fn list(slots: &[usize]) -> AccessList {
+        AccessList(
+            slots
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| AccessListItem {
+                    address: Address::with_last_byte(0x40 + i as u8),
+                    storage_keys: (0..n).map(|s| B256::with_last_byte(s as u8)).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_patterns_noted_from_threshold() {
+        assert!(advise(&list(&[0, 0, 0, 0, 1, 1, 1, 1, 2])).is_empty());
+
+        let notes = advise(&list(&[0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 3]));
+        assert_eq!(notes.len(), 2);
+        assert_eq!(notes[0].kind, AdviceKind::AddressOnly);
+        assert_eq!(notes[0].addresses.len(), 5);
+        assert_eq!((notes[0].saving_each, notes[0].cost_each), (100, 2400));
+        assert_eq!(notes[1].kind, AdviceKind::SingleSlot);
+        assert_eq!(notes[1].addresses[0], Address::with_last_byte(0x45));
+        assert_eq!((notes[1].saving_each, notes[1].cost_each), (200, 4400));
+    }
+}
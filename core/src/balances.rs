@@ -0,0 +1,160 @@
+//! Net balance changes of the transaction's participants, read off the trace: ETH from the
+//! state diff, ERC-20 tokens from `Transfer` logs.
+
+use alloy_primitives::{b256, Address, Log, B256, I256, U256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::state_diff::AccountDiff;
+
+/// `keccak256("Transfer(address,address,uint256)")`, shared by ERC-20 and ERC-721.
+pub const TRANSFER_TOPIC: B256 =
+    b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+
+/// Net change in one account's holdings. Positive values were received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BalanceChange {
+    pub address: Address,
+    /// Wei, including gas paid when the account is the sender.
+    pub eth: I256,
+    /// Raw token units by token contract; tokens that netted to zero are omitted.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub tokens: BTreeMap<Address, I256>,
+}
+
+/// ERC-20 transfer `(token, from, to, amount)` if `log` has that shape.
+///
+/// Heuristic: any `Transfer` with two indexed addresses and a 32-byte body. ERC-721
+/// indexes the token id as a third topic, so it does not match.
+pub fn erc20_transfer(log: &Log) -> Option<(Address, Address, Address, U256)> {
+    match (log.topics(), log.data.data.len()) {
+        ([topic, from, to], 32) if *topic == TRANSFER_TOPIC => Some((
+            log.address,
+            Address::from_word(*from),
+            Address::from_word(*to),
+            U256::from_be_slice(&log.data.data),
+        )),
+        _ => None,
+    }
+}
+
+/// Balance changes of each of `participants` (deduplicated, in the order given).
+pub fn balance_changes(
+    participants: &[Address],
+    state_diff: &[AccountDiff],
+    logs: &[Log],
+) -> Vec<BalanceChange> {
+    let mut seen = Vec::new();
+    for address in participants {
+        if !seen.contains(address) {
+            seen.push(*address);
+        }
+    }
+    seen.into_iter()
+        .map(|address| {
+            let eth = state_diff
+                .iter()
+                .find(|d| d.address == address)
+                .and_then(|d| d.balance)
+                .map_or(I256::ZERO, |c| I256::from_raw(c.to.wrapping_sub(c.from)));
+            let mut tokens: BTreeMap<Address, I256> = BTreeMap::new();
+            for (token, from, to, amount) in logs.iter().filter_map(erc20_transfer) {
+                let amount = I256::from_raw(amount);
+                if from == address {
+                    *tokens.entry(token).or_default() -= amount;
+                }
+                if to == address {
+                    *tokens.entry(token).or_default() += amount;
+                }
+            }
+            tokens.retain(|_, delta| !delta.is_zero());
+            BalanceChange {
+                address,
+                eth,
+                tokens,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_diff::Change;
+    use alloy_primitives::keccak256;
+
+    fn transfer(token: Address, from: Address, to: Address, amount: u64) -> Log {
+        Log::new_unchecked(
+            token,
+            vec![TRANSFER_TOPIC, from.into_word(), to.into_word()],
+            B256::from(U256::from(amount)).to_vec().into(),
+        )
+    }
+
+    #[test]
+    fn test_transfer_topic_matches_signature() {
+        assert_eq!(
+            TRANSFER_TOPIC,
+            keccak256("Transfer(address,address,uint256)")
+        );
+    }
+
+    #[test]
+    fn test_erc721_transfer_ignored() {
+        let log = Log::new_unchecked(
+            Address::repeat_byte(9),
+            vec![
+                TRANSFER_TOPIC,
+                B256::ZERO,
+                B256::ZERO,
+                B256::with_last_byte(1),
+            ],
+            Default::default(),
+        );
+        assert_eq!(erc20_transfer(&log), None);
+    }
+
+    #[test]
+    fn test_eth_and_token_net_changes() {
+        let alice = Address::repeat_byte(1);
+        let pool = Address::repeat_byte(2);
+        let token = Address::repeat_byte(0xaa);
+        let diff = vec![AccountDiff {
+            address: alice,
+            balance: Some(Change {
+                from: U256::from(100),
+                to: U256::from(60),
+            }),
+            nonce: None,
+            code_hash: None,
+            storage: BTreeMap::new(),
+        }];
+        let logs = vec![
+            transfer(token, pool, alice, 500),
+            transfer(token, alice, pool, 200),
+        ];
+
+        let changes = balance_changes(&[alice, pool, alice], &diff, &logs);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].eth, I256::try_from(-40).unwrap());
+        assert_eq!(changes[0].tokens[&token], I256::try_from(300).unwrap());
+        assert_eq!(changes[1].eth, I256::ZERO);
+        assert_eq!(changes[1].tokens[&token], I256::try_from(-300).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip_token_omitted() {
+        let alice = Address::repeat_byte(1);
+        let token = Address::repeat_byte(0xaa);
+        let logs = vec![
+            transfer(token, alice, Address::ZERO, 5),
+            transfer(token, Address::ZERO, alice, 5),
+        ];
+        assert!(balance_changes(&[alice], &[], &logs)[0].tokens.is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+//! Whether a list leaves the transaction enough gas to execute.
+//!
+//! A list's upfront cost comes out of the same gas limit as execution. A list that does
+//! not fit next to intrinsic gas makes the transaction invalid; one that fits but eats
+//! into what execution needs makes it run out of gas partway. Execution is measured by a
+//! trace without a list, where every first access is cold; each touched entry a list
+//! declares takes its cold surcharge off that.
+
+use alloy_eip2930::AccessList;
+use alloy_primitives::{Address, B256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::gas::{
+    access_list_gas_cost, COLD_ACCOUNT_ACCESS_COST, COLD_SLOAD_COST, WARM_STORAGE_READ_COST,
+};
+use crate::types::ValidationReport;
+
+/// The gas a transaction has, and what it needs besides its list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasBudget {
+    pub gas_limit: u64,
+    /// Base cost and calldata, without any list (see [`crate::gas::intrinsic_gas`]).
+    pub intrinsic_gas: u64,
+    /// Gas execution spent with no list, refunds included. A trace that ran out of gas
+    /// measures what the limit allowed.
+    pub execution_gas: u64,
+}
+
+/// Which of a report's lists a [`BudgetExceeded`] is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum BudgetList {
+    Declared,
+    Optimal,
+}
+
+impl BudgetList {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Declared => "declared",
+            Self::Optimal => "optimal",
+        }
+    }
+}
+
+/// A list the transaction's gas limit cannot pay for alongside its execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BudgetExceeded {
+    pub list: BudgetList,
+    pub gas_limit: u64,
+    pub intrinsic_gas: u64,
+    /// Upfront cost of the list.
+    pub list_cost: u64,
+    /// Gas execution needs with the list's entries warm.
+    pub execution_gas: u64,
+    /// Gas missing: intrinsic gas, list and execution less the limit.
+    pub shortfall: u64,
+}
+
+impl BudgetExceeded {
+    /// Whether intrinsic gas and the list alone exceed the limit, so the transaction is
+    /// rejected before it runs rather than running out of gas.
+    pub fn upfront_exceeds(&self) -> bool {
+        self.intrinsic_gas + self.list_cost > self.gas_limit
+    }
+}
+
+/// The report's declared and optimal lists that do not fit in `budget`, declared first.
+pub fn check_budget(
+    declared: &AccessList,
+    report: &ValidationReport,
+    budget: &GasBudget,
+) -> Vec<BudgetExceeded> {
+    let touched = slots_of(&report.optimal_list);
+    [
+        (BudgetList::Declared, declared),
+        (BudgetList::Optimal, &report.optimal_list),
+    ]
+    .into_iter()
+    .filter_map(|(which, list)| {
+        let list_cost = access_list_gas_cost(list);
+        let execution_gas = budget
+            .execution_gas
+            .saturating_sub(warm_savings(&touched, &slots_of(list)));
+        let required = budget.intrinsic_gas + list_cost + execution_gas;
+        (required > budget.gas_limit).then(|| BudgetExceeded {
+            list: which,
+            gas_limit: budget.gas_limit,
+            intrinsic_gas: budget.intrinsic_gas,
+            list_cost,
+            execution_gas,
+            shortfall: required - budget.gas_limit,
+        })
+    })
+    .collect()
+}
+
+fn slots_of(list: &AccessList) -> BTreeMap<Address, BTreeSet<B256>> {
+    let mut slots: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    for item in &list.0 {
+        slots
+            .entry(item.address)
+            .or_default()
+            .extend(item.storage_keys.iter().copied());
+    }
+    slots
+}
+
+/// Cold surcharges execution skips for the touched accounts and slots `list` declares.
+fn warm_savings(
+    touched: &BTreeMap<Address, BTreeSet<B256>>,
+    list: &BTreeMap<Address, BTreeSet<B256>>,
+) -> u64 {
+    touched
+        .iter()
+        .filter_map(|(address, slots)| Some((slots, list.get(address)?)))
+        .map(|(slots, declared)| {
+            COLD_ACCOUNT_ACCESS_COST
+                + slots.intersection(declared).count() as u64
+                    * (COLD_SLOAD_COST - WARM_STORAGE_READ_COST)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GasSummary;
+    use alloy_eip2930::AccessListItem;
+
+    fn list(items: &[(u8, &[u8])]) -> AccessList {
+        AccessList(
+            items
+                .iter()
+                .map(|&(a, slots)| AccessListItem {
+                    address: Address::with_last_byte(a),
+                    storage_keys: slots.iter().map(|&s| B256::with_last_byte(s)).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    fn report(optimal: AccessList) -> ValidationReport {
+        ValidationReport {
+            entries: vec![],
+            gas_summary: GasSummary {
+                declared_list_cost: 0,
+                optimal_list_cost: 0,
+                no_list_cost: 0,
+                waste_per_tx: 0,
+                savings_vs_no_list: 0,
+            },
+            optimal_list: optimal,
+            is_valid: true,
+            is_valid_lenient: true,
+            new_accounts: vec![],
+            precompile_calls: Default::default(),
+            fixes: vec![],
+            declared_origin: Default::default(),
+            callbacks: vec![],
+            volatile: Default::default(),
+            near_misses: vec![],
+            budget_exceeded: vec![],
+            gas_sensitive: vec![],
+            transient: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_bloated_declared_list_crowds_out_execution() {
+        // Optimal: one contract, one slot (4,300 upfront), saving 4,600 at runtime.
+        let report = report(list(&[(0x40, &[1])]));
+        let declared = list(&[(0x40, &[1]), (0x41, &[1, 2, 3]), (0x42, &[])]);
+        let budget = GasBudget {
+            gas_limit: 61_000,
+            intrinsic_gas: 21_000,
+            execution_gas: 40_000,
+        };
+        let exceeded = check_budget(&declared, &report, &budget);
+        assert_eq!(
+            exceeded,
+            vec![BudgetExceeded {
+                list: BudgetList::Declared,
+                gas_limit: 61_000,
+                intrinsic_gas: 21_000,
+                list_cost: 4_300 + 2_400 + 3 * 1_900 + 2_400,
+                execution_gas: 35_400,
+                shortfall: 21_000 + 14_800 + 35_400 - 61_000,
+            }]
+        );
+        assert!(!exceeded[0].upfront_exceeds());
+
+        let tight = GasBudget {
+            gas_limit: 30_000,
+            ..budget
+        };
+        let exceeded = check_budget(&declared, &report, &tight);
+        assert_eq!(exceeded.len(), 2);
+        assert!(exceeded[0].upfront_exceeds());
+        assert_eq!(exceeded[1].list, BudgetList::Optimal);
+        assert!(!exceeded[1].upfront_exceeds());
+    }
+}
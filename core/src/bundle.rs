@@ -0,0 +1,129 @@
+//! One list for a bundle: the lists of its transactions merged.
+//!
+//! Warmth does not carry from one transaction to the next, so a bundle sent as separate
+//! transactions still needs a list on each. Sent as one transaction through a multicall or
+//! executor contract, what an earlier call touched is warm for the later ones: the bundle
+//! needs every entry any of its transactions needs, once. The merge also sizes what the
+//! bundle as a whole pays for state access.
+
+use alloy_eip2930::{AccessList, AccessListItem};
+use alloy_primitives::{Address, B256};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::types::OptimizedAccessList;
+
+/// Merge the lists of a bundle's transactions, given in bundle order. An entry an earlier
+/// transaction lists is not repeated for a later one; entries keep the order they first
+/// appear in. An address one transaction strips as warm stays listed if another needs it.
+///
+/// Per-transaction details (calls, logs, callbacks, state diff, gas-sensitive branches)
+/// refer to one transaction's execution and are left out; the lists passed in keep them.
+pub fn merge_bundle(lists: &[OptimizedAccessList]) -> OptimizedAccessList {
+    let mut items: Vec<AccessListItem> = Vec::new();
+    let mut index: BTreeMap<Address, usize> = BTreeMap::new();
+    let mut seen: BTreeSet<(Address, B256)> = BTreeSet::new();
+    for item in lists.iter().flat_map(|l| &l.list.0) {
+        let i = *index.entry(item.address).or_insert_with(|| {
+            items.push(AccessListItem {
+                address: item.address,
+                storage_keys: Vec::new(),
+            });
+            items.len() - 1
+        });
+        for key in &item.storage_keys {
+            if seen.insert((item.address, *key)) {
+                items[i].storage_keys.push(*key);
+            }
+        }
+    }
+
+    let mut merged = OptimizedAccessList::new(AccessList(items), Vec::new());
+    for optimized in lists {
+        for removed in &optimized.removed {
+            let listed = index.contains_key(&removed.address);
+            if !listed && !merged.removed_addresses.contains(&removed.address) {
+                merged.removed_addresses.push(removed.address);
+                merged.removed.push(removed.clone());
+            }
+        }
+        union(&mut merged.warm_kept, &optimized.warm_kept);
+        union(&mut merged.authorities, &optimized.authorities);
+        union(&mut merged.new_accounts, &optimized.new_accounts);
+        for (&precompile, &calls) in &optimized.precompile_calls {
+            *merged.precompile_calls.entry(precompile).or_default() += calls;
+        }
+        for (&address, slots) in &optimized.transient {
+            merged
+                .transient
+                .entry(address)
+                .or_default()
+                .extend(slots.iter().copied());
+        }
+        for (&address, slots) in &optimized.volatile {
+            merged
+                .volatile
+                .entry(address)
+                .or_default()
+                .extend(slots.iter().copied());
+        }
+    }
+    merged
+}
+
+fn union(into: &mut Vec<Address>, from: &[Address]) {
+    for address in from {
+        if !into.contains(address) {
+            into.push(*address);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{RemovalReason, RemovedAddress};
+
+    fn optimized(items: &[(u8, &[u8])], removed: &[u8]) -> OptimizedAccessList {
+        let mut optimized = OptimizedAccessList::new(
+            AccessList(
+                items
+                    .iter()
+                    .map(|&(a, slots)| AccessListItem {
+                        address: Address::with_last_byte(a),
+                        storage_keys: slots.iter().map(|&s| B256::with_last_byte(s)).collect(),
+                    })
+                    .collect(),
+            ),
+            removed
+                .iter()
+                .map(|&a| Address::with_last_byte(a))
+                .collect(),
+        );
+        optimized.removed = removed
+            .iter()
+            .map(|&a| RemovedAddress {
+                address: Address::with_last_byte(a),
+                reason: RemovalReason::TxTo,
+                gas_waste: 2_400,
+                cold_slots: 0,
+            })
+            .collect();
+        optimized
+    }
+
+    #[test]
+    fn test_merge_dedups_across_transactions() {
+        // Both read slot 1 of 0x40. The first strips 0x41 as its target, but the second
+        // needs it, so it stays listed.
+        let first = optimized(&[(0x40, &[1])], &[0x41]);
+        let second = optimized(&[(0x40, &[1, 2]), (0x41, &[7]), (0x42, &[])], &[0x43]);
+        let merged = merge_bundle(&[first, second]);
+        let expected = optimized(&[(0x40, &[1, 2]), (0x41, &[7]), (0x42, &[])], &[]);
+        assert_eq!(merged.list, expected.list);
+        assert_eq!(
+            merged.removed_addresses,
+            vec![Address::with_last_byte(0x43)]
+        );
+        assert_eq!(merge_bundle(&[]).list, AccessList::default());
+    }
+}
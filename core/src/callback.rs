@@ -0,0 +1,127 @@
+//! Recognition of well-known callback entry points in a trace.
+//!
+//! A callback hands control to code the transaction's sender does not control (or does
+//! not control the inputs of): a token's ERC-777 hook, a flash-loan receiver, a pool's
+//! swap callback. What that code touches can change with state, so list entries first
+//! touched inside one are less certain than the rest of the trace.
+
+use alloy_primitives::{Address, B256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Callback interfaces hammer recognizes by selector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CallbackKind {
+    /// ERC-777 `tokensToSend` / `tokensReceived` hooks.
+    Erc777Hook,
+    /// ERC-3156, Aave, Balancer and Uniswap V2 flash-loan/flash-swap receivers.
+    FlashLoan,
+    /// Uniswap V3 swap, mint and flash callbacks.
+    UniswapV3,
+}
+
+impl CallbackKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Erc777Hook => "erc777_hook",
+            Self::FlashLoan => "flash_loan",
+            Self::UniswapV3 => "uniswap_v3",
+        }
+    }
+}
+
+/// Known callback selectors: `(selector, function name, kind)`.
+pub const CALLBACK_SELECTORS: &[(u32, &str, CallbackKind)] = &[
+    (0x75ab9782, "tokensToSend", CallbackKind::Erc777Hook),
+    (0x0023de29, "tokensReceived", CallbackKind::Erc777Hook),
+    (0x23e30c8b, "onFlashLoan", CallbackKind::FlashLoan),
+    (0x920f5c84, "executeOperation", CallbackKind::FlashLoan),
+    (0x1b11d0ff, "executeOperation", CallbackKind::FlashLoan),
+    (0xf04f2707, "receiveFlashLoan", CallbackKind::FlashLoan),
+    (0x10d1e85c, "uniswapV2Call", CallbackKind::FlashLoan),
+    (0xfa461e33, "uniswapV3SwapCallback", CallbackKind::UniswapV3),
+    (0xd3487997, "uniswapV3MintCallback", CallbackKind::UniswapV3),
+    (
+        0xe9cbafb0,
+        "uniswapV3FlashCallback",
+        CallbackKind::UniswapV3,
+    ),
+];
+
+/// The callback a call with this selector enters, if any: `(function name, kind)`.
+pub fn classify(selector: [u8; 4]) -> Option<(&'static str, CallbackKind)> {
+    CALLBACK_SELECTORS
+        .iter()
+        .find(|(s, _, _)| *s == u32::from_be_bytes(selector))
+        .map(|(_, name, kind)| (*name, *kind))
+}
+
+/// One callback frame in the trace and the state first touched while it ran (including
+/// its subcalls).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Callback {
+    pub kind: CallbackKind,
+    /// Callback function name, e.g. `uniswapV3SwapCallback`.
+    pub function: String,
+    /// Contract that made the callback (the pool, lender or token).
+    pub caller: Address,
+    /// Contract whose callback ran.
+    pub target: Address,
+    /// Addresses and slots not touched before the callback started.
+    pub touched: BTreeMap<Address, BTreeSet<B256>>,
+}
+
+impl Callback {
+    /// Whether `address` (and `slot`, if given) was first touched inside this callback.
+    pub fn touched(&self, address: &Address, slot: Option<&B256>) -> bool {
+        match (self.touched.get(address), slot) {
+            (Some(slots), Some(slot)) => slots.contains(slot),
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn test_selectors_match_signatures() {
+        let signatures = [
+            "tokensToSend(address,address,address,uint256,bytes,bytes)",
+            "tokensReceived(address,address,address,uint256,bytes,bytes)",
+            "onFlashLoan(address,address,uint256,uint256,bytes)",
+            "executeOperation(address[],uint256[],uint256[],address,bytes)",
+            "executeOperation(address,uint256,uint256,address,bytes)",
+            "receiveFlashLoan(address[],uint256[],uint256[],bytes)",
+            "uniswapV2Call(address,uint256,uint256,bytes)",
+            "uniswapV3SwapCallback(int256,int256,bytes)",
+            "uniswapV3MintCallback(uint256,uint256,bytes)",
+            "uniswapV3FlashCallback(uint256,uint256,bytes)",
+        ];
+        for ((selector, name, _), signature) in CALLBACK_SELECTORS.iter().zip(signatures) {
+            assert!(signature.starts_with(name));
+            assert_eq!(
+                selector.to_be_bytes(),
+                keccak256(signature)[..4],
+                "selector of {}",
+                signature
+            );
+        }
+    }
+
+    #[test]
+    fn test_classify_unknown_selector() {
+        assert_eq!(classify([0xa9, 0x05, 0x9c, 0xbb]), None);
+        assert_eq!(
+            classify([0xfa, 0x46, 0x1e, 0x33]),
+            Some(("uniswapV3SwapCallback", CallbackKind::UniswapV3))
+        );
+    }
+}
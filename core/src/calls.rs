@@ -0,0 +1,142 @@
+//! The call tree of a trace, and paths that pick one call out of it.
+//!
+//! A call's path lists child indices from the top-level frame down: `2` is the third call
+//! the transaction's target made, `2.0` the first call made inside that one. CREATE and
+//! CREATE2 frames are numbered alongside calls, so paths from a listing stay stable.
+
+use alloy_primitives::{Address, FixedBytes};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::HammerError;
+
+/// Position of an internal call in the call tree, e.g. `0.1.2`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CallPath(pub Vec<usize>);
+
+impl fmt::Display for CallPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self.0.iter().map(usize::to_string).collect();
+        f.write_str(&parts.join("."))
+    }
+}
+
+impl FromStr for CallPath {
+    type Err = HammerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('.')
+            .map(|part| part.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map(CallPath)
+            .map_err(|_| {
+                HammerError::InvalidCallPath(format!(
+                    "{:?}: expected dot-separated call indices, e.g. 0.1",
+                    s
+                ))
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CallPath {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CallPath {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// How a frame was entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CallKind {
+    Call,
+    StaticCall,
+    DelegateCall,
+    CallCode,
+    Create,
+    Create2,
+}
+
+impl CallKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::StaticCall => "static_call",
+            Self::DelegateCall => "delegate_call",
+            Self::CallCode => "call_code",
+            Self::Create => "create",
+            Self::Create2 => "create2",
+        }
+    }
+
+    /// Whether the frame runs in its caller's storage context, so it cannot stand alone as
+    /// a transaction.
+    pub fn borrows_context(self) -> bool {
+        matches!(self, Self::DelegateCall | Self::CallCode)
+    }
+}
+
+/// One internal call (or creation) in the trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CallFrame {
+    pub path: CallPath,
+    pub kind: CallKind,
+    /// Account that made the call.
+    pub caller: Address,
+    /// Account whose storage the frame runs against; for creations, the new contract.
+    pub target: Address,
+    /// Account whose code ran, when it differs from `target` (DELEGATECALL, CALLCODE).
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code_address: Option<Address>,
+    /// First four bytes of the calldata, if it had that many.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub selector: Option<FixedBytes<4>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_round_trip() {
+        let path: CallPath = "0.12.3".parse().unwrap();
+        assert_eq!(path, CallPath(vec![0, 12, 3]));
+        assert_eq!(path.to_string(), "0.12.3");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_path_serializes_as_string() {
+        let path = CallPath(vec![0, 12, 3]);
+        assert_eq!(
+            serde_json::to_value(&path).unwrap(),
+            serde_json::json!("0.12.3")
+        );
+    }
+
+    #[test]
+    fn test_invalid_paths_rejected() {
+        for bad in ["", "0.", "a", "0/1", "-1"] {
+            assert!(bad.parse::<CallPath>().is_err(), "{:?}", bad);
+        }
+    }
+}
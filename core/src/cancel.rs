@@ -0,0 +1,25 @@
+//! Cooperative cancellation of a running trace.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Flag a frontend sets, from any thread, to stop a trace early (e.g. when its client
+/// disconnects). The tracer checks it before every opcode and the traced call fails with
+/// [`crate::HammerError::Cancelled`]. Clones share the flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every trace holding a clone of this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
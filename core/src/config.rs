@@ -0,0 +1,28 @@
+//! Caller-supplied knowledge that a single trace cannot see.
+
+use alloy_primitives::{Address, B256};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::gas::GasSchedule;
+
+/// Adjustments to optimization and validation for state outside the traced transaction.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OptimizerConfig {
+    /// Addresses the caller knows are already warm, e.g. warmed earlier in their own
+    /// bundle. The optimizer strips them like `tx.from`; declaring one is Redundant.
+    pub assume_warm: BTreeSet<Address>,
+    /// Addresses the chain keeps warm beyond Ethereum's precompiles, such as Arbitrum's
+    /// ArbOS precompiles (see [`crate::system::chain_warm_addresses`]). Stripped and
+    /// judged Redundant like precompiles.
+    pub chain_warm: BTreeSet<Address>,
+    /// Entries always kept in the generated list, whatever the trace shows: state the
+    /// caller pre-warms for later subcalls hammer cannot see (e.g. callbacks triggered by
+    /// other actors). An address with no slots is kept on its own.
+    pub force_include: BTreeMap<Address, BTreeSet<B256>>,
+    /// Slots known to move as feeds update, flagged volatile when listed. An address with
+    /// no slots flags all of its listed slots.
+    pub volatile: BTreeMap<Address, BTreeSet<B256>>,
+    /// Gas constants of the fork the transaction runs under: what entries cost and save,
+    /// and whether the coinbase is warm. Defaults to the latest fork.
+    pub schedule: GasSchedule,
+}
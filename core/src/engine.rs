@@ -0,0 +1,238 @@
+//! A configured engine that services can share across threads and tasks.
+
+use alloy_eip2930::AccessList;
+use alloy_primitives::{Address, B256};
+use revm::context::{BlockEnv, TxEnv};
+use revm::database::{Database, DatabaseCommit};
+use std::sync::Arc;
+
+use crate::cancel::CancellationToken;
+use crate::config::OptimizerConfig;
+use crate::error::HammerError;
+use crate::gas::Hardfork;
+use crate::optimizer::Strategy;
+use crate::system::chain_warm_addresses;
+use crate::tracer::RevmTracer;
+use crate::types::{OptimizedAccessList, ValidationReport};
+
+/// Access list engine applying one [`OptimizerConfig`] to every call. Set it up once,
+/// with [`Hammer::new`] or [`Hammer::builder`], instead of passing the config to each
+/// free function.
+///
+/// Cloning is cheap (the configuration is shared behind an `Arc`) and the engine is
+/// `Send + Sync`, so an async service can build one at startup and hand clones to its
+/// tasks. It holds no mutable state: each call brings its own database and runs its own
+/// trace, so concurrent calls never wait on each other.
+#[derive(Debug, Clone, Default)]
+pub struct Hammer {
+    config: Arc<OptimizerConfig>,
+    strategy: Strategy,
+}
+
+impl Hammer {
+    pub fn new(config: OptimizerConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            strategy: Strategy::default(),
+        }
+    }
+
+    /// A builder that sets up the config from the chain, fork and strategy in use.
+    pub fn builder() -> HammerBuilder {
+        HammerBuilder::default()
+    }
+
+    pub fn config(&self) -> &OptimizerConfig {
+        &self.config
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    /// [`crate::generate_with_strategy`] under this engine's config and strategy.
+    pub fn generate<DB>(
+        &self,
+        db: DB,
+        tx: TxEnv,
+        block: BlockEnv,
+    ) -> Result<OptimizedAccessList, HammerError>
+    where
+        DB: Database,
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        crate::generate_with_strategy(db, tx, block, &self.config, self.strategy)
+    }
+
+    /// [`crate::generate_cancellable`] under this engine's config.
+    pub fn generate_cancellable<DB>(
+        &self,
+        db: DB,
+        tx: TxEnv,
+        block: BlockEnv,
+        cancel: &CancellationToken,
+    ) -> Result<OptimizedAccessList, HammerError>
+    where
+        DB: Database,
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        crate::generate_cancellable(db, tx, block, &self.config, cancel)
+    }
+
+    /// [`crate::generate_queue_with`] under this engine's config.
+    pub fn generate_queue<DB>(
+        &self,
+        db: DB,
+        txs: Vec<TxEnv>,
+        block: BlockEnv,
+    ) -> Result<Vec<OptimizedAccessList>, HammerError>
+    where
+        DB: Database + DatabaseCommit,
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        crate::generate_queue_with(db, txs, block, &self.config)
+    }
+
+    /// [`crate::validate_with`] under this engine's config.
+    pub fn validate<DB>(
+        &self,
+        db: DB,
+        tx: TxEnv,
+        block: BlockEnv,
+        declared: AccessList,
+    ) -> Result<ValidationReport, HammerError>
+    where
+        DB: Database,
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        crate::validate_with(db, tx, block, declared, &self.config)
+    }
+
+    /// [`crate::validate_replay`] under this engine's config: the nonce check is skipped,
+    /// so mined transactions can be replayed.
+    pub fn validate_replay<DB>(
+        &self,
+        db: DB,
+        tx: TxEnv,
+        block: BlockEnv,
+        declared: AccessList,
+    ) -> Result<ValidationReport, HammerError>
+    where
+        DB: Database,
+        DB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        crate::validate_with_tracer(
+            &mut RevmTracer::replay(db),
+            tx,
+            block,
+            declared,
+            &self.config,
+        )
+    }
+}
+
+/// Builds a [`Hammer`] one setting at a time. Unset settings keep their defaults:
+/// Ethereum's warm rules, the latest fork's gas schedule, [`Strategy::Complete`].
+#[derive(Debug, Clone, Default)]
+pub struct HammerBuilder {
+    config: OptimizerConfig,
+    strategy: Strategy,
+}
+
+impl HammerBuilder {
+    /// Start from `config`; later calls adjust it.
+    pub fn config(mut self, config: OptimizerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Strip the addresses `chain_id` keeps warm on top of Ethereum's precompiles (see
+    /// [`crate::system`]).
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.config.chain_warm = chain_warm_addresses(chain_id);
+        self
+    }
+
+    /// Price lists and state access with `hardfork`'s gas schedule. The trace itself runs
+    /// under the latest EVM rules, as with the free functions.
+    pub fn hardfork(mut self, hardfork: Hardfork) -> Self {
+        self.config.schedule = hardfork.schedule();
+        self
+    }
+
+    /// Which touched entries [`Hammer::generate`] keeps.
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Treat `addresses` as already warm (see [`OptimizerConfig::assume_warm`]).
+    pub fn assume_warm(mut self, addresses: impl IntoIterator<Item = Address>) -> Self {
+        self.config.assume_warm.extend(addresses);
+        self
+    }
+
+    /// Always list `address` with `slots` (see [`OptimizerConfig::force_include`]).
+    pub fn force_include(
+        mut self,
+        address: Address,
+        slots: impl IntoIterator<Item = B256>,
+    ) -> Self {
+        self.config
+            .force_include
+            .entry(address)
+            .or_default()
+            .extend(slots);
+        self
+    }
+
+    pub fn build(self) -> Hammer {
+        Hammer {
+            config: Arc::new(self.config),
+            strategy: self.strategy,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engine_is_shareable() {
+        fn assert_shareable<T: Send + Sync + Clone + 'static>() {}
+        assert_shareable::<Hammer>();
+    }
+
+    #[test]
+    fn test_clones_share_config() {
+        let engine = Hammer::new(OptimizerConfig {
+            assume_warm: [alloy_primitives::Address::with_last_byte(0x40)].into(),
+            ..Default::default()
+        });
+        let clone = engine.clone();
+        assert!(std::ptr::eq(engine.config(), clone.config()));
+    }
+
+    #[test]
+    fn test_builder_sets_config() {
+        let arb_sys = alloy_primitives::Address::with_last_byte(0x64);
+        let engine = Hammer::builder()
+            .chain_id(42161)
+            .hardfork(Hardfork::Berlin)
+            .strategy(Strategy::NetSavings)
+            .build();
+        assert!(engine.config().chain_warm.contains(&arb_sys));
+        assert_eq!(engine.config().schedule, Hardfork::Berlin.schedule());
+        assert_eq!(engine.strategy(), Strategy::NetSavings);
+
+        // Settings compose with a starting config, in call order.
+        let engine = Hammer::builder()
+            .assume_warm([arb_sys])
+            .config(OptimizerConfig::default())
+            .hardfork(Hardfork::London)
+            .build();
+        assert!(engine.config().assume_warm.is_empty());
+        assert_eq!(engine.config().schedule, Hardfork::London.schedule());
+    }
+}
@@ -19,6 +19,55 @@ pub enum HammerError {
 
     #[error("Unsupported transaction: {0}")]
     UnsupportedTransaction(String),
+
+    #[error("Invalid call path: {0}")]
+    InvalidCallPath(String),
+
+    #[error("Invalid split: {0}")]
+    InvalidSplit(String),
+
+    #[error("Invalid state override: {0}")]
+    InvalidStateOverride(String),
+
+    #[error("Trace cancelled")]
+    Cancelled,
+
+    #[error(
+        "State unavailable: block {block} requires archive state; try a newer block or an \
+         archive endpoint ({message})"
+    )]
+    StateUnavailable { block: u64, message: String },
+}
+
+/// Fragments of node errors meaning the requested historical state was pruned
+/// (geth, erigon, reth, nethermind and hosted providers word it differently).
+const STATE_UNAVAILABLE_PATTERNS: &[&str] = &[
+    "missing trie node",
+    "state not available",
+    "state is not available",
+    "historical state",
+    "state histories haven't been fully indexed",
+    "pruned",
+    "distance to target block exceeds maximum",
+];
+
+/// Whether a node or state-backend error message means historical state is unavailable.
+pub fn is_state_unavailable(message: &str) -> bool {
+    let message = message.to_ascii_lowercase();
+    STATE_UNAVAILABLE_PATTERNS
+        .iter()
+        .any(|pattern| message.contains(pattern))
+}
+
+impl HammerError {
+    /// Classify an EVM failure at `block`, recognizing pruned state behind it.
+    pub fn evm(message: String, block: u64) -> Self {
+        if is_state_unavailable(&message) {
+            Self::StateUnavailable { block, message }
+        } else {
+            Self::EvmExecution(message)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -44,4 +93,37 @@ mod tests {
             "Unsupported transaction: z"
         );
     }
+
+    #[test]
+    fn test_state_unavailable_detection() {
+        assert!(is_state_unavailable("missing trie node 1a2b (path )"));
+        assert!(is_state_unavailable(
+            "database error: server returned an error response: error code -32000: Missing Trie Node"
+        ));
+        assert!(is_state_unavailable(
+            "required historical state unavailable"
+        ));
+        assert!(!is_state_unavailable("execution reverted"));
+    }
+
+    #[test]
+    fn test_evm_error_classification() {
+        let err = HammerError::evm("missing trie node abc".into(), 17_000_000);
+        assert!(matches!(
+            err,
+            HammerError::StateUnavailable {
+                block: 17_000_000,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "State unavailable: block 17000000 requires archive state; try a newer block or \
+             an archive endpoint (missing trie node abc)"
+        );
+        assert!(matches!(
+            HammerError::evm("out of gas".into(), 1),
+            HammerError::EvmExecution(_)
+        ));
+    }
 }
@@ -0,0 +1,243 @@
+//! Minimal edit scripts that turn a declared access list into a valid one.
+//!
+//! Rather than replacing the user's list with the optimal one, each finding becomes an
+//! edit against the declared list, so untouched entries keep their position and order.
+
+use alloy_eip2930::{AccessList, AccessListItem};
+use alloy_primitives::{Address, B256};
+use std::collections::BTreeSet;
+
+use crate::types::{DiffEntry, FixEdit};
+
+/// Derive the edit script for a report's findings. `optimal` tells whole-address
+/// stale entries (address never touched) apart from stale slots.
+pub fn fix_edits(entries: &[DiffEntry], optimal: &AccessList) -> Vec<FixEdit> {
+    let optimal_addresses: BTreeSet<Address> = optimal.0.iter().map(|i| i.address).collect();
+    let mut duplicates: BTreeSet<(Address, B256)> = BTreeSet::new();
+    let mut edits = Vec::new();
+
+    for entry in entries {
+        match entry {
+            DiffEntry::Missing {
+                address,
+                storage_keys,
+                ..
+            } => edits.push(FixEdit::AddAddress {
+                address: *address,
+                storage_keys: storage_keys.clone(),
+            }),
+            DiffEntry::Incomplete {
+                address,
+                missing_slots,
+                ..
+            } => edits.push(FixEdit::AddSlots {
+                address: *address,
+                storage_keys: missing_slots.clone(),
+            }),
+            DiffEntry::Stale {
+                address,
+                storage_keys,
+                ..
+            } => {
+                if optimal_addresses.contains(address) {
+                    edits.push(FixEdit::RemoveSlots {
+                        address: *address,
+                        storage_keys: storage_keys.clone(),
+                    });
+                } else {
+                    edits.push(FixEdit::RemoveAddress { address: *address });
+                }
+            }
+            DiffEntry::Redundant { address, .. } => {
+                edits.push(FixEdit::RemoveAddress { address: *address })
+            }
+            DiffEntry::Duplicate {
+                address,
+                storage_key,
+                ..
+            } => {
+                // One edit per repeated slot; it removes every repeat at once.
+                if duplicates.insert((*address, *storage_key)) {
+                    edits.push(FixEdit::RemoveDuplicate {
+                        address: *address,
+                        storage_key: *storage_key,
+                    });
+                }
+            }
+        }
+    }
+    edits
+}
+
+/// Apply `edits` to `declared`. Entries and slots not named by an edit keep their
+/// position; additions go to the end of the matching entry or of the list.
+pub fn apply_fixes(declared: &AccessList, edits: &[FixEdit]) -> AccessList {
+    let mut items = declared.0.clone();
+    for edit in edits {
+        match edit {
+            FixEdit::RemoveAddress { address } => items.retain(|i| i.address != *address),
+            FixEdit::RemoveSlots {
+                address,
+                storage_keys,
+            } => {
+                for item in items.iter_mut().filter(|i| i.address == *address) {
+                    item.storage_keys.retain(|k| !storage_keys.contains(k));
+                }
+            }
+            FixEdit::RemoveDuplicate {
+                address,
+                storage_key,
+            } => {
+                let mut seen = false;
+                for item in items.iter_mut().filter(|i| i.address == *address) {
+                    item.storage_keys.retain(|k| {
+                        if k != storage_key {
+                            return true;
+                        }
+                        !std::mem::replace(&mut seen, true)
+                    });
+                }
+            }
+            FixEdit::AddSlots {
+                address,
+                storage_keys,
+            } => match items.iter_mut().find(|i| i.address == *address) {
+                Some(item) => {
+                    for key in storage_keys {
+                        if !item.storage_keys.contains(key) {
+                            item.storage_keys.push(*key);
+                        }
+                    }
+                }
+                None => items.push(AccessListItem {
+                    address: *address,
+                    storage_keys: storage_keys.clone(),
+                }),
+            },
+            FixEdit::AddAddress {
+                address,
+                storage_keys,
+            } => items.push(AccessListItem {
+                address: *address,
+                storage_keys: storage_keys.clone(),
+            }),
+        }
+    }
+    AccessList(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OptimizedAccessList;
+    use crate::validator::validate;
+
+    fn addr(n: u8) -> Address {
+        Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
+    }
+
+    fn slot(n: u8) -> B256 {
+        B256::from([n; 32])
+    }
+
+    fn list(items: Vec<(Address, Vec<B256>)>) -> AccessList {
+        AccessList(
+            items
+                .into_iter()
+                .map(|(address, storage_keys)| AccessListItem {
+                    address,
+                    storage_keys,
+                })
+                .collect(),
+        )
+    }
+
+    const FROM: u8 = 200;
+    const TO: u8 = 201;
+    const COINBASE: u8 = 202;
+
+    fn check(declared: &AccessList, optimal: &AccessList) -> crate::types::ValidationReport {
+        let optimal = OptimizedAccessList::new(optimal.clone(), vec![]);
+        validate(declared, &optimal, addr(FROM), addr(TO), addr(COINBASE))
+    }
+
+    #[test]
+    fn test_fixes_make_every_finding_kind_valid() {
+        let optimal = list(vec![
+            (addr(20), vec![slot(1), slot(2)]),
+            (addr(21), vec![slot(3)]),
+            (addr(22), vec![slot(5)]),
+        ]);
+        let declared = list(vec![
+            (addr(FROM), vec![]),               // redundant
+            (addr(20), vec![slot(1), slot(1)]), // duplicate + incomplete (slot 2)
+            (addr(23), vec![slot(9)]),          // stale address
+            (addr(22), vec![slot(5), slot(6)]), // stale slot
+        ]);
+        let report = check(&declared, &optimal);
+        assert!(!report.is_valid);
+
+        let fixed = apply_fixes(&declared, &report.fixes);
+        let recheck = check(&fixed, &optimal);
+        assert!(recheck.is_valid, "remaining: {:?}", recheck.entries);
+        assert!(recheck.fixes.is_empty());
+    }
+
+    #[test]
+    fn test_apply_fixes_preserves_order_and_appends() {
+        let optimal = list(vec![
+            (addr(22), vec![slot(5)]),
+            (addr(20), vec![slot(1), slot(2)]),
+            (addr(21), vec![slot(3)]),
+        ]);
+        let declared = list(vec![(addr(22), vec![slot(5)]), (addr(20), vec![slot(1)])]);
+        let report = check(&declared, &optimal);
+        let fixed = apply_fixes(&declared, &report.fixes);
+        assert_eq!(
+            fixed,
+            list(vec![
+                (addr(22), vec![slot(5)]),
+                (addr(20), vec![slot(1), slot(2)]),
+                (addr(21), vec![slot(3)]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_duplicate_keeps_first_occurrence_across_items() {
+        let declared = list(vec![
+            (addr(20), vec![slot(1), slot(2)]),
+            (addr(20), vec![slot(1)]),
+        ]);
+        let edits = vec![FixEdit::RemoveDuplicate {
+            address: addr(20),
+            storage_key: slot(1),
+        }];
+        assert_eq!(
+            apply_fixes(&declared, &edits),
+            list(vec![(addr(20), vec![slot(1), slot(2)]), (addr(20), vec![])])
+        );
+    }
+
+    #[test]
+    fn test_stale_slot_under_used_address_removes_slot_only() {
+        let optimal = list(vec![(addr(20), vec![slot(1)])]);
+        let entries = vec![DiffEntry::Stale {
+            address: addr(20),
+            storage_keys: vec![slot(2)],
+            gas_waste: 1900,
+        }];
+        assert_eq!(
+            fix_edits(&entries, &optimal),
+            vec![FixEdit::RemoveSlots {
+                address: addr(20),
+                storage_keys: vec![slot(2)],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_valid_report_has_no_fixes() {
+        assert!(fix_edits(&[], &AccessList::default()).is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! Declared addresses that look copied from another chain's deployment.
+//!
+//! Protocols deploy to several chains, often at different addresses, and a list built for
+//! one chain is easily pasted into a transaction on another. Such entries are stale like
+//! any other, but they point at accounts with no code here, usually no account at all.
+//! Telling them apart from ordinary stale entries says which fix is needed: rebuilding
+//! the list for this chain, not trimming it.
+
+use alloy_primitives::Address;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::types::{DiffEntry, ValidationReport};
+
+/// A stale declared address with no code on the chain the transaction ran on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ForeignAddress {
+    pub address: Address,
+    /// Whether the account exists at all (has a balance or nonce). An account that does
+    /// not exist is the stronger sign of a list from another chain.
+    pub exists: bool,
+}
+
+/// Addresses of the report's stale entries: declared, never touched.
+pub fn stale_addresses(report: &ValidationReport) -> Vec<Address> {
+    report
+        .entries
+        .iter()
+        .filter_map(|entry| match entry {
+            DiffEntry::Stale { address, .. } => Some(*address),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The report's stale addresses that have no code in `db`, in report order.
+#[cfg(feature = "evm")]
+pub fn foreign_addresses<DB>(
+    db: &mut DB,
+    report: &ValidationReport,
+) -> Result<Vec<ForeignAddress>, crate::HammerError>
+where
+    DB: revm::Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let mut foreign = Vec::new();
+    for address in stale_addresses(report) {
+        let account = db
+            .basic(address)
+            .map_err(|e| crate::HammerError::RpcError(Box::new(e)))?;
+        match account {
+            Some(info) if !info.is_empty_code_hash() => {}
+            Some(info) => foreign.push(ForeignAddress {
+                address,
+                exists: info.nonce > 0 || !info.balance.is_zero(),
+            }),
+            None => foreign.push(ForeignAddress {
+                address,
+                exists: false,
+            }),
+        }
+    }
+    Ok(foreign)
+}
+
+#[cfg(all(test, feature = "evm"))]
+mod tests {
+    use super::*;
+    use crate::types::GasSummary;
+    use alloy_primitives::{Bytes, U256};
+    use revm::bytecode::Bytecode;
+    use revm::database::{CacheDB, EmptyDB};
+    use revm::state::AccountInfo;
+
+    fn stale(address: Address) -> DiffEntry {
+        DiffEntry::Stale {
+            address,
+            storage_keys: vec![],
+            gas_waste: 2400,
+        }
+    }
+
+    #[test]
+    fn test_only_codeless_stale_addresses_are_foreign() {
+        let (contract, holder, missing) = (
+            Address::with_last_byte(0x40),
+            Address::with_last_byte(0x41),
+            Address::with_last_byte(0x42),
+        );
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(
+            contract,
+            AccountInfo::default().with_code(Bytecode::new_raw(Bytes::from_static(&[0x00]))),
+        );
+        db.insert_account_info(holder, AccountInfo::from_balance(U256::from(1)));
+        let report = ValidationReport {
+            entries: vec![stale(contract), stale(holder), stale(missing)],
+            gas_summary: GasSummary {
+                declared_list_cost: 0,
+                optimal_list_cost: 0,
+                no_list_cost: 0,
+                waste_per_tx: 0,
+                savings_vs_no_list: 0,
+            },
+            optimal_list: Default::default(),
+            is_valid: false,
+            is_valid_lenient: true,
+            new_accounts: vec![],
+            precompile_calls: Default::default(),
+            fixes: vec![],
+            declared_origin: Default::default(),
+            callbacks: vec![],
+            volatile: Default::default(),
+            near_misses: vec![],
+            budget_exceeded: vec![],
+            gas_sensitive: vec![],
+            transient: Default::default(),
+        };
+        assert_eq!(
+            foreign_addresses(&mut db, &report).unwrap(),
+            vec![
+                ForeignAddress {
+                    address: holder,
+                    exists: true
+                },
+                ForeignAddress {
+                    address: missing,
+                    exists: false
+                },
+            ]
+        );
+    }
+}
@@ -1,6 +1,27 @@
 //! EIP-2929 and EIP-2930 gas constants and calculations.
 
-use alloy_rpc_types_eth::AccessList;
+use alloy_eip2930::AccessList;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Intrinsic cost every transaction pays before calldata and access list.
+pub const TX_BASE_COST: u64 = 21000;
+
+/// Intrinsic cost per zero byte of calldata.
+pub const TX_DATA_ZERO_COST: u64 = 4;
+
+/// Intrinsic cost per non-zero byte of calldata (EIP-2028).
+pub const TX_DATA_NONZERO_COST: u64 = 16;
+
+/// Intrinsic surcharge of a contract-creation transaction (EIP-2).
+pub const TX_CREATE_COST: u64 = 32000;
+
+/// Intrinsic cost per 32-byte word of init code (EIP-3860).
+pub const INITCODE_WORD_COST: u64 = 2;
+
+/// Intrinsic cost per authorization of a set-code transaction (EIP-7702
+/// `PER_EMPTY_ACCOUNT_COST`); part is refunded for authorities that already exist.
+pub const AUTHORIZATION_COST: u64 = 25000;
 
 /// Cost to include an address in the access list (EIP-2930).
 pub const ACCESS_LIST_ADDRESS_COST: u64 = 2400;
@@ -17,6 +38,10 @@ pub const COLD_SLOAD_COST: u64 = 2100;
 /// Cost of subsequent (warm) storage read (EIP-2929).
 pub const WARM_STORAGE_READ_COST: u64 = 100;
 
+/// Surcharge for a value-bearing CALL to an empty account (EIP-161 new-account cost).
+/// Charged regardless of access lists — warming an address does not make it exist.
+pub const NEW_ACCOUNT_COST: u64 = 25000;
+
 /// Net gas saved per slot when including an accessed slot in the access list.
 /// Cold read costs 2100, warm costs 100. Upfront cost is 1900. Net: 2000 - 1900 = 100.
 pub const NET_SAVINGS_PER_ACCESSED_SLOT: i64 = (COLD_SLOAD_COST as i64)
@@ -24,22 +49,258 @@ pub const NET_SAVINGS_PER_ACCESSED_SLOT: i64 = (COLD_SLOAD_COST as i64)
     - (ACCESS_LIST_STORAGE_KEY_COST as i64);
 
 /// Net gas saved per address when including an accessed address in the access list.
-/// Cold account costs 2600, warm is free. Upfront cost is 2400. Net: 2600 - 2400 = 200.
-pub const NET_SAVINGS_PER_ACCESSED_ADDRESS: i64 =
-    (COLD_ACCOUNT_ACCESS_COST as i64) - (ACCESS_LIST_ADDRESS_COST as i64);
+/// Cold account access costs 2600, warm costs 100. Upfront cost is 2400. Net: 2500 - 2400 = 100.
+pub const NET_SAVINGS_PER_ACCESSED_ADDRESS: i64 = (COLD_ACCOUNT_ACCESS_COST as i64)
+    - (WARM_STORAGE_READ_COST as i64)
+    - (ACCESS_LIST_ADDRESS_COST as i64);
 
-/// Compute the total gas cost of an access list (address + storage key costs).
-pub fn access_list_gas_cost(list: &AccessList) -> u64 {
-    let mut cost = 0u64;
-    let mut seen_addresses = std::collections::HashSet::new();
+/// Fewest slots for which declaring a warm-by-default address pays off. Warmth covers the
+/// account, not its storage, so its slots are still read cold (e.g. the sender's own storage
+/// under EIP-7702 delegated code); from this many, their savings repay the address charge.
+pub const WARM_ADDRESS_MIN_SLOTS: usize =
+    (ACCESS_LIST_ADDRESS_COST / NET_SAVINGS_PER_ACCESSED_SLOT as u64) as usize + 1;
+
+/// The refund may cover at most gas spent / quotient: 5 since London (EIP-3529).
+pub const MAX_REFUND_QUOTIENT: u64 = 5;
+
+/// The refund cap before London: half the gas spent, which gas tokens relied on.
+pub const MAX_REFUND_QUOTIENT_PRE_LONDON: u64 = 2;
+
+/// How a transaction's refund was paid, for netting gas savings against the refund cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Refund {
+    /// Gas spent before the refund.
+    pub gas_spent: u64,
+    /// Refund accrued during execution (SSTORE clears, pre-London SELFDESTRUCT).
+    pub counter: u64,
+    /// Refund cap divisor of the fork the transaction ran under.
+    pub quotient: u64,
+}
+
+impl Refund {
+    /// Refund actually paid: the counter, capped at `gas_spent / quotient`.
+    pub fn paid(&self) -> u64 {
+        self.counter.min(self.gas_spent / self.quotient)
+    }
+
+    /// Whether the cap cut the refund short.
+    pub fn is_capped(&self) -> bool {
+        self.paid() < self.counter
+    }
+
+    /// Gas the sender pays less when `saved` gas of the spend is avoided. A capped refund
+    /// shrinks with the spend, so it claws back part of the saving.
+    pub fn net_savings(&self, saved: u64) -> u64 {
+        let saved = saved.min(self.gas_spent);
+        let after = Refund {
+            gas_spent: self.gas_spent - saved,
+            ..*self
+        };
+        (self.gas_spent - self.paid()) - (after.gas_spent - after.paid())
+    }
+}
+
+/// Mainnet hardforks since access lists exist (EIP-2930, Berlin).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Hardfork {
+    Berlin,
+    London,
+    Shanghai,
+    Cancun,
+    #[default]
+    Prague,
+}
+
+impl Hardfork {
+    /// Every fork, oldest first.
+    pub const ALL: [Hardfork; 5] = [
+        Self::Berlin,
+        Self::London,
+        Self::Shanghai,
+        Self::Cancun,
+        Self::Prague,
+    ];
 
-    for item in list.0.iter() {
-        if seen_addresses.insert(item.address) {
-            cost += ACCESS_LIST_ADDRESS_COST;
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Berlin => "berlin",
+            Self::London => "london",
+            Self::Shanghai => "shanghai",
+            Self::Cancun => "cancun",
+            Self::Prague => "prague",
+        }
+    }
+
+    /// First mainnet block the fork applied to.
+    pub fn activation_block(self) -> u64 {
+        match self {
+            Self::Berlin => 12_244_000,
+            Self::London => 12_965_000,
+            Self::Shanghai => 17_034_870,
+            Self::Cancun => 19_426_587,
+            Self::Prague => 22_431_084,
+        }
+    }
+
+    /// The fork a mainnet block ran under, or `None` before Berlin.
+    pub fn at_block(block_number: u64) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|fork| block_number >= fork.activation_block())
+    }
+
+    /// The latest of these forks that `spec` includes, or `None` before Berlin.
+    #[cfg(feature = "evm")]
+    pub fn from_spec(spec: revm::primitives::hardfork::SpecId) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .rev()
+            .find(|fork| spec.is_enabled_in(fork.spec()))
+    }
+
+    /// The revm spec that executes transactions under the fork's rules.
+    #[cfg(feature = "evm")]
+    pub fn spec(self) -> revm::primitives::hardfork::SpecId {
+        use revm::primitives::hardfork::SpecId;
+        match self {
+            Self::Berlin => SpecId::BERLIN,
+            Self::London => SpecId::LONDON,
+            Self::Shanghai => SpecId::SHANGHAI,
+            Self::Cancun => SpecId::CANCUN,
+            Self::Prague => SpecId::PRAGUE,
+        }
+    }
+
+    /// The gas constants the fork prices access lists and state access with.
+    pub fn schedule(self) -> GasSchedule {
+        match self {
+            Self::Berlin => GasSchedule::BERLIN,
+            Self::London => GasSchedule::LONDON,
+            Self::Shanghai => GasSchedule::SHANGHAI,
+            Self::Cancun => GasSchedule::CANCUN,
+            Self::Prague => GasSchedule::PRAGUE,
         }
-        cost += (item.storage_keys.len() as u64) * ACCESS_LIST_STORAGE_KEY_COST;
     }
-    cost
+}
+
+/// Gas constants of one hardfork, as far as access lists are concerned. The optimizer and
+/// validator take one through [`crate::OptimizerConfig`]; the default is the latest fork.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasSchedule {
+    /// Cost to include an address in the access list.
+    pub access_list_address_cost: u64,
+    /// Cost to include a storage key in the access list.
+    pub access_list_storage_key_cost: u64,
+    /// Cost of first (cold) access to an account.
+    pub cold_account_access_cost: u64,
+    /// Cost of first (cold) SLOAD of a storage slot.
+    pub cold_sload_cost: u64,
+    /// Cost of subsequent (warm) storage read.
+    pub warm_storage_read_cost: u64,
+    /// Whether the coinbase is warm from the start (EIP-3651).
+    pub warm_coinbase: bool,
+    /// Refund cap divisor.
+    pub max_refund_quotient: u64,
+}
+
+impl GasSchedule {
+    /// EIP-2929 and EIP-2930 as introduced.
+    pub const BERLIN: Self = Self {
+        access_list_address_cost: ACCESS_LIST_ADDRESS_COST,
+        access_list_storage_key_cost: ACCESS_LIST_STORAGE_KEY_COST,
+        cold_account_access_cost: COLD_ACCOUNT_ACCESS_COST,
+        cold_sload_cost: COLD_SLOAD_COST,
+        warm_storage_read_cost: WARM_STORAGE_READ_COST,
+        warm_coinbase: false,
+        max_refund_quotient: MAX_REFUND_QUOTIENT_PRE_LONDON,
+    };
+
+    /// Refunds capped at a fifth of the gas spent (EIP-3529).
+    pub const LONDON: Self = Self {
+        max_refund_quotient: MAX_REFUND_QUOTIENT,
+        ..Self::BERLIN
+    };
+
+    /// The coinbase starts warm (EIP-3651).
+    pub const SHANGHAI: Self = Self {
+        warm_coinbase: true,
+        ..Self::LONDON
+    };
+
+    /// Unchanged from Shanghai.
+    pub const CANCUN: Self = Self::SHANGHAI;
+
+    /// Unchanged from Cancun.
+    pub const PRAGUE: Self = Self::CANCUN;
+
+    /// Total gas cost of an access list (address + storage key costs).
+    pub fn access_list_cost(&self, list: &AccessList) -> u64 {
+        let mut cost = 0u64;
+        let mut seen_addresses = std::collections::HashSet::new();
+
+        for item in list.0.iter() {
+            if seen_addresses.insert(item.address) {
+                cost += self.access_list_address_cost;
+            }
+            cost += (item.storage_keys.len() as u64) * self.access_list_storage_key_cost;
+        }
+        cost
+    }
+
+    /// Gas a cold storage read costs over a warm one.
+    pub fn cold_sload_surcharge(&self) -> u64 {
+        self.cold_sload_cost - self.warm_storage_read_cost
+    }
+
+    /// Gas a cold account access costs over a warm one.
+    pub fn cold_account_surcharge(&self) -> u64 {
+        self.cold_account_access_cost - self.warm_storage_read_cost
+    }
+
+    /// Net gas saved per slot when including an accessed slot in the access list.
+    pub fn net_savings_per_slot(&self) -> i64 {
+        self.cold_sload_surcharge() as i64 - self.access_list_storage_key_cost as i64
+    }
+
+    /// Net gas saved per address when including an accessed address in the access list.
+    pub fn net_savings_per_address(&self) -> i64 {
+        self.cold_account_surcharge() as i64 - self.access_list_address_cost as i64
+    }
+
+    /// Fewest slots for which declaring a warm-by-default address pays off (see
+    /// [`WARM_ADDRESS_MIN_SLOTS`]), or `None` if slots never repay it.
+    pub fn warm_address_min_slots(&self) -> Option<usize> {
+        let per_slot = u64::try_from(self.net_savings_per_slot())
+            .ok()
+            .filter(|&saving| saving > 0)?;
+        Some((self.access_list_address_cost / per_slot) as usize + 1)
+    }
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Hardfork::default().schedule()
+    }
+}
+
+/// Compute the total gas cost of an access list (address + storage key costs) under the
+/// latest fork's schedule (see [`GasSchedule::access_list_cost`]).
+pub fn access_list_gas_cost(list: &AccessList) -> u64 {
+    GasSchedule::default().access_list_cost(list)
+}
+
+/// Intrinsic gas of a transaction with calldata (or init code) `data`, before any access
+/// list: the base cost, calldata bytes and, for a creation, its surcharge and init code words.
+pub fn intrinsic_gas(data: &[u8], create: bool) -> u64 {
+    let zeros = data.iter().filter(|&&b| b == 0).count() as u64;
+    let nonzeros = data.len() as u64 - zeros;
+    let mut gas = TX_BASE_COST + zeros * TX_DATA_ZERO_COST + nonzeros * TX_DATA_NONZERO_COST;
+    if create {
+        gas += TX_CREATE_COST + (data.len() as u64).div_ceil(32) * INITCODE_WORD_COST;
+    }
+    gas
 }
 
 /// Convert gas amount to ETH at given gas price (in gwei).
@@ -51,8 +312,8 @@ pub fn gas_to_eth(gas: u64, gas_price_gwei: u64) -> f64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_eip2930::{AccessList, AccessListItem};
     use alloy_primitives::{Address, B256};
-    use alloy_rpc_types_eth::{AccessList, AccessListItem};
 
     fn addr(n: u8) -> Address {
         Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
@@ -62,6 +323,17 @@ mod tests {
         B256::from_slice(&[0u8; 31].iter().chain(&[n]).copied().collect::<Vec<_>>())
     }
 
+    #[test]
+    fn test_intrinsic_gas() {
+        assert_eq!(intrinsic_gas(&[], false), 21_000);
+        assert_eq!(intrinsic_gas(&[0, 0, 1], false), 21_000 + 4 + 4 + 16);
+        // 33 bytes of init code are two words.
+        assert_eq!(
+            intrinsic_gas(&[1; 33], true),
+            21_000 + 33 * 16 + 32_000 + 2 * 2
+        );
+    }
+
     #[test]
     fn test_empty_list_cost() {
         assert_eq!(access_list_gas_cost(&AccessList::default()), 0);
@@ -132,12 +404,75 @@ mod tests {
         assert_eq!(gas_to_eth(0, 30), 0.0);
     }
 
+    #[test]
+    fn test_hardfork_at_block() {
+        assert_eq!(Hardfork::at_block(12_243_999), None);
+        assert_eq!(Hardfork::at_block(12_244_000), Some(Hardfork::Berlin));
+        assert_eq!(Hardfork::at_block(12_964_999), Some(Hardfork::Berlin));
+        assert_eq!(Hardfork::at_block(12_965_000), Some(Hardfork::London));
+        assert_eq!(Hardfork::at_block(17_034_870), Some(Hardfork::Shanghai));
+        assert_eq!(Hardfork::at_block(19_426_587), Some(Hardfork::Cancun));
+        assert_eq!(Hardfork::at_block(u64::MAX), Some(Hardfork::Prague));
+    }
+
+    #[test]
+    fn test_schedules_by_fork() {
+        let berlin = Hardfork::Berlin.schedule();
+        assert!(!berlin.warm_coinbase);
+        assert_eq!(berlin.max_refund_quotient, MAX_REFUND_QUOTIENT_PRE_LONDON);
+        assert_eq!(
+            Hardfork::London.schedule().max_refund_quotient,
+            MAX_REFUND_QUOTIENT
+        );
+        assert!(Hardfork::Shanghai.schedule().warm_coinbase);
+        assert_eq!(GasSchedule::default(), GasSchedule::PRAGUE);
+        for fork in Hardfork::ALL {
+            let schedule = fork.schedule();
+            assert_eq!(schedule.cold_account_surcharge(), 2_500);
+            assert_eq!(
+                schedule.net_savings_per_slot(),
+                NET_SAVINGS_PER_ACCESSED_SLOT
+            );
+            assert_eq!(
+                schedule.net_savings_per_address(),
+                NET_SAVINGS_PER_ACCESSED_ADDRESS
+            );
+            assert_eq!(
+                schedule.warm_address_min_slots(),
+                Some(WARM_ADDRESS_MIN_SLOTS)
+            );
+        }
+
+        // A schedule where slots cost as much upfront as they save never repays an address.
+        let flat = GasSchedule {
+            access_list_storage_key_cost: 2000,
+            ..GasSchedule::default()
+        };
+        assert_eq!(flat.warm_address_min_slots(), None);
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn test_hardfork_from_spec() {
+        use revm::primitives::hardfork::SpecId;
+        assert_eq!(Hardfork::from_spec(SpecId::ISTANBUL), None);
+        assert_eq!(Hardfork::from_spec(SpecId::BERLIN), Some(Hardfork::Berlin));
+        assert_eq!(Hardfork::from_spec(SpecId::MERGE), Some(Hardfork::London));
+        assert_eq!(
+            Hardfork::from_spec(SpecId::default()),
+            Some(Hardfork::Prague)
+        );
+        for fork in Hardfork::ALL {
+            assert_eq!(Hardfork::from_spec(fork.spec()), Some(fork));
+        }
+    }
+
     #[test]
     fn test_constants() {
         // Net savings per slot: cold SLOAD (2100) - warm read (100) - slot upfront (1900) = 100
         assert_eq!(NET_SAVINGS_PER_ACCESSED_SLOT, 100);
-        // Net savings per address: cold account (2600) - address upfront (2400) = 200
-        assert_eq!(NET_SAVINGS_PER_ACCESSED_ADDRESS, 200);
+        // Net savings per address: cold account (2600) - warm (100) - address upfront (2400) = 100
+        assert_eq!(NET_SAVINGS_PER_ACCESSED_ADDRESS, 100);
     }
 
     // gas_to_eth edge cases
@@ -211,4 +546,32 @@ mod tests {
         let result = gas_to_eth(21_000, u64::MAX);
         assert!(result.is_finite(), "expected finite result, got {}", result);
     }
+
+    #[test]
+    fn test_net_savings_against_refund_cap() {
+        let uncapped = Refund {
+            gas_spent: 100_000,
+            counter: 4_800,
+            quotient: MAX_REFUND_QUOTIENT,
+        };
+        assert!(!uncapped.is_capped());
+        assert_eq!(uncapped.net_savings(2_000), 2_000);
+
+        // A gas-token burn before London: the refund is capped at half the spend, so half
+        // of every gas saved is lost with it.
+        let gas_token = Refund {
+            gas_spent: 100_000,
+            counter: 240_000,
+            quotient: MAX_REFUND_QUOTIENT_PRE_LONDON,
+        };
+        assert!(gas_token.is_capped());
+        assert_eq!(gas_token.paid(), 50_000);
+        assert_eq!(gas_token.net_savings(2_000), 1_000);
+
+        let london = Refund {
+            quotient: MAX_REFUND_QUOTIENT,
+            ..gas_token
+        };
+        assert_eq!(london.net_savings(2_000), 1_600);
+    }
 }
@@ -0,0 +1,65 @@
+//! Execution that depends on how much gas is left.
+//!
+//! A list moves gas from execution to the upfront charge: cold accesses get cheaper, but
+//! the gas available when execution starts drops by the list's cost. Code that reads
+//! `gasleft()` into a condition, or a call that runs out of the 63/64 of the remaining gas
+//! it was given, may then behave differently once the list is attached. The tracer flags
+//! both, with the frame they happened in; `generate --self-check` shows whether it does.
+
+use alloy_primitives::Address;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::calls::CallPath;
+
+/// Most opcodes between reading `GAS` and a conditional jump for the jump to count as
+/// branching on it; `gasleft() < x` compiles to about half as many.
+pub const GAS_BRANCH_WINDOW: usize = 8;
+
+/// A place in the trace where the gas left could change what happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "snake_case"))]
+pub enum GasSensitivity {
+    /// A conditional jump shortly after the frame read `GAS`, as `gasleft()` in a
+    /// condition compiles to. Reported once per jump site.
+    Branch {
+        /// Frame of the first time the jump was reached; empty for the top-level frame.
+        path: CallPath,
+        /// Account whose code holds the jump.
+        code_address: Address,
+        pc: usize,
+    },
+    /// An internal call that ran out of gas. It was given at most 63/64 of what its caller
+    /// had left, so with more or less gas it may complete, or fail elsewhere.
+    StarvedCall { path: CallPath, target: Address },
+}
+
+impl GasSensitivity {
+    /// The frame the sensitivity is attributed to.
+    pub fn path(&self) -> &CallPath {
+        match self {
+            Self::Branch { path, .. } | Self::StarvedCall { path, .. } => path,
+        }
+    }
+
+    /// One line for human output.
+    pub fn describe(&self) -> String {
+        let frame = if self.path().0.is_empty() {
+            "the top-level call".to_string()
+        } else {
+            format!("call {}", self.path())
+        };
+        match self {
+            Self::Branch {
+                code_address, pc, ..
+            } => format!(
+                "{} branches on gasleft() (code {} at pc {})",
+                frame, code_address, pc
+            ),
+            Self::StarvedCall { target, .. } => {
+                format!("{} to {} ran out of gas", frame, target)
+            }
+        }
+    }
+}
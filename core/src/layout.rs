@@ -0,0 +1,373 @@
+//! Reverse slot resolution against a solc storage layout (`forge inspect <C> storageLayout`).
+//!
+//! Builds a table of every slot reachable from the layout's variables — including mapping
+//! entries for a set of candidate keys (typically the transaction participants and every
+//! address in the access lists) — so report slots can be shown as `balanceOf[0xabc…]`
+//! instead of raw 32-byte words.
+
+use alloy_primitives::{keccak256, Address, B256, U256};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::slot::mapping_slot;
+
+/// Maximum mapping nesting depth expanded against candidate keys.
+const MAX_MAPPING_DEPTH: usize = 3;
+
+/// Maximum static array length expanded element by element.
+const MAX_STATIC_ELEMENTS: u64 = 4096;
+
+/// Maximum element offset searched inside dynamic array and long bytes/string data regions.
+const MAX_REGION_SLOTS: u64 = 1 << 24;
+
+/// Solidity storage layout as emitted by solc's `storageLayout` output selection.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageVariable>,
+    #[serde(default)]
+    pub types: Option<BTreeMap<String, StorageType>>,
+}
+
+/// A state variable (or struct member) in the layout.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageVariable {
+    pub label: String,
+    #[serde(default)]
+    pub offset: u64,
+    /// Slot as a decimal string (solc emits strings to avoid precision loss).
+    pub slot: String,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+/// A type entry from the layout's `types` table.
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageType {
+    pub encoding: String,
+    pub label: String,
+    #[serde(rename = "numberOfBytes")]
+    pub number_of_bytes: String,
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub base: Option<String>,
+    #[serde(default)]
+    pub members: Option<Vec<StorageVariable>>,
+}
+
+impl StorageType {
+    fn bytes(&self) -> u64 {
+        self.number_of_bytes.parse().unwrap_or(32)
+    }
+
+    /// Number of whole slots the type occupies.
+    fn slots(&self) -> u64 {
+        self.bytes().div_ceil(32).max(1)
+    }
+}
+
+/// Contiguous data region whose slots are derived from a keccak start (dynamic arrays, long bytes).
+#[derive(Debug, Clone)]
+struct Region {
+    start: U256,
+    label: String,
+    /// Element type for arrays; `None` for bytes/string data.
+    element: Option<String>,
+}
+
+/// Resolves storage slots of a single contract to variable expressions.
+#[derive(Debug, Clone)]
+pub struct SlotResolver {
+    types: BTreeMap<String, StorageType>,
+    labels: BTreeMap<B256, String>,
+    regions: Vec<Region>,
+}
+
+impl SlotResolver {
+    /// Build a resolver for `layout`, expanding mappings against the given candidate keys.
+    pub fn new(layout: &StorageLayout, candidate_keys: &[Address]) -> Self {
+        let mut resolver = Self {
+            types: layout.types.clone().unwrap_or_default(),
+            labels: BTreeMap::new(),
+            regions: Vec::new(),
+        };
+        for var in &layout.storage {
+            let Ok(slot) = U256::from_str_radix(&var.slot, 10) else {
+                continue;
+            };
+            resolver.expand(&var.ty, slot, var.label.clone(), candidate_keys, 0);
+        }
+        resolver
+    }
+
+    /// Human-readable expression for `slot`, e.g. `_balances[0xd8dA…6045]` or `_owner | _paused`.
+    pub fn resolve(&self, slot: B256) -> Option<String> {
+        if let Some(label) = self.labels.get(&slot) {
+            return Some(label.clone());
+        }
+        let target = U256::from_be_bytes(slot.0);
+        self.regions.iter().find_map(|region| {
+            let delta = target.wrapping_sub(region.start);
+            if delta >= U256::from(MAX_REGION_SLOTS) {
+                return None;
+            }
+            let delta = delta.to::<u64>();
+            let Some(element) = region.element.as_ref().and_then(|e| self.types.get(e)) else {
+                return Some(format!("{} (data +{})", region.label, delta));
+            };
+            if element.bytes() < 32 {
+                let per_slot = 32 / element.bytes().max(1);
+                let first = delta * per_slot;
+                return Some(format!(
+                    "{}[{}..{}]",
+                    region.label,
+                    first,
+                    first + per_slot - 1
+                ));
+            }
+            let stride = element.slots();
+            let label = format!("{}[{}]", region.label, delta / stride);
+            Some(self.member_label(element, delta % stride, label))
+        })
+    }
+
+    fn insert(&mut self, slot: U256, label: String) {
+        self.labels
+            .entry(B256::from(slot.to_be_bytes()))
+            .and_modify(|existing| {
+                // Packed variables share a slot.
+                existing.push_str(" | ");
+                existing.push_str(&label);
+            })
+            .or_insert(label);
+    }
+
+    fn expand(&mut self, ty: &str, slot: U256, label: String, keys: &[Address], depth: usize) {
+        let Some(info) = self.types.get(ty).cloned() else {
+            self.insert(slot, label);
+            return;
+        };
+        match info.encoding.as_str() {
+            "mapping" => {
+                if depth >= MAX_MAPPING_DEPTH || !is_word_key(info.key.as_deref()) {
+                    return;
+                }
+                let Some(value) = info.value.as_deref() else {
+                    return;
+                };
+                let base = B256::from(slot.to_be_bytes());
+                for key in keys {
+                    let entry = U256::from_be_bytes(mapping_slot(base, key.into_word()).0);
+                    let entry_label = format!("{}[{}]", label, key);
+                    self.expand(value, entry, entry_label, keys, depth + 1);
+                }
+            }
+            "dynamic_array" => {
+                self.insert(slot, format!("{}.length", label));
+                self.regions.push(Region {
+                    start: data_start(slot),
+                    label,
+                    element: info.base.clone(),
+                });
+            }
+            "bytes" => {
+                self.insert(slot, label.clone());
+                self.regions.push(Region {
+                    start: data_start(slot),
+                    label,
+                    element: None,
+                });
+            }
+            _ => {
+                if let Some(members) = &info.members {
+                    for member in members {
+                        let Ok(offset) = U256::from_str_radix(&member.slot, 10) else {
+                            continue;
+                        };
+                        let member_label = format!("{}.{}", label, member.label);
+                        self.expand(&member.ty, slot + offset, member_label, keys, depth);
+                    }
+                } else if let Some(base) = info.base.as_deref() {
+                    self.expand_static_array(&info, base, slot, &label, keys, depth);
+                } else {
+                    self.insert(slot, label);
+                }
+            }
+        }
+    }
+
+    fn expand_static_array(
+        &mut self,
+        info: &StorageType,
+        base: &str,
+        slot: U256,
+        label: &str,
+        keys: &[Address],
+        depth: usize,
+    ) {
+        let element_bytes = self.types.get(base).map(|t| t.bytes()).unwrap_or(32);
+        if element_bytes < 32 {
+            let per_slot = 32 / element_bytes.max(1);
+            for i in 0..info.slots().min(MAX_STATIC_ELEMENTS) {
+                let first = i * per_slot;
+                let slot_label = format!("{}[{}..{}]", label, first, first + per_slot - 1);
+                self.insert(slot + U256::from(i), slot_label);
+            }
+            return;
+        }
+        let stride = element_bytes.div_ceil(32);
+        let count = (info.bytes() / (stride * 32)).min(MAX_STATIC_ELEMENTS);
+        for i in 0..count {
+            let element_slot = slot + U256::from(i * stride);
+            self.expand(base, element_slot, format!("{}[{}]", label, i), keys, depth);
+        }
+    }
+
+    /// Append the struct member at `offset` (in slots) to `label`, if the type is a struct.
+    fn member_label(&self, ty: &StorageType, offset: u64, label: String) -> String {
+        let Some(members) = &ty.members else {
+            return label;
+        };
+        let names: Vec<&str> = members
+            .iter()
+            .filter(|m| m.slot.parse::<u64>().ok() == Some(offset))
+            .map(|m| m.label.as_str())
+            .collect();
+        if names.is_empty() {
+            label
+        } else {
+            format!("{}.{}", label, names.join("|"))
+        }
+    }
+}
+
+/// Only value-type keys are hashed as 32-byte words; string/bytes keys are not expandable.
+fn is_word_key(key: Option<&str>) -> bool {
+    key.is_some_and(|k| !k.starts_with("t_string") && !k.starts_with("t_bytes_"))
+}
+
+fn data_start(slot: U256) -> U256 {
+    U256::from_be_bytes(keccak256(slot.to_be_bytes::<32>()).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(n: u8) -> Address {
+        Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
+    }
+
+    fn word(n: u64) -> B256 {
+        B256::from(U256::from(n).to_be_bytes())
+    }
+
+    // A trimmed-down `forge inspect Token storageLayout --json`.
+    const LAYOUT: &str = r#"{
+      "storage": [
+        {"astId": 1, "contract": "T", "label": "_balances", "offset": 0, "slot": "0", "type": "t_mapping(t_address,t_uint256)"},
+        {"astId": 2, "contract": "T", "label": "_allowances", "offset": 0, "slot": "1", "type": "t_mapping(t_address,t_mapping(t_address,t_uint256))"},
+        {"astId": 3, "contract": "T", "label": "_totalSupply", "offset": 0, "slot": "2", "type": "t_uint256"},
+        {"astId": 4, "contract": "T", "label": "_owner", "offset": 0, "slot": "3", "type": "t_address"},
+        {"astId": 5, "contract": "T", "label": "_paused", "offset": 20, "slot": "3", "type": "t_bool"},
+        {"astId": 6, "contract": "T", "label": "holders", "offset": 0, "slot": "4", "type": "t_array(t_struct(Pos)1_storage)dyn_storage"},
+        {"astId": 7, "contract": "T", "label": "cfg", "offset": 0, "slot": "5", "type": "t_struct(Pos)1_storage"},
+        {"astId": 8, "contract": "T", "label": "name", "offset": 0, "slot": "7", "type": "t_string_storage"}
+      ],
+      "types": {
+        "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+        "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+        "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+        "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+        "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+        "t_mapping(t_address,t_mapping(t_address,t_uint256))": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => mapping(address => uint256))", "numberOfBytes": "32", "value": "t_mapping(t_address,t_uint256)"},
+        "t_array(t_struct(Pos)1_storage)dyn_storage": {"encoding": "dynamic_array", "label": "struct Pos[]", "numberOfBytes": "32", "base": "t_struct(Pos)1_storage"},
+        "t_struct(Pos)1_storage": {"encoding": "inplace", "label": "struct Pos", "numberOfBytes": "64", "members": [
+          {"astId": 9, "contract": "T", "label": "amount", "offset": 0, "slot": "0", "type": "t_uint256"},
+          {"astId": 10, "contract": "T", "label": "owner", "offset": 0, "slot": "1", "type": "t_address"}
+        ]}
+      }
+    }"#;
+
+    fn resolver(keys: &[Address]) -> SlotResolver {
+        let layout: StorageLayout = serde_json::from_str(LAYOUT).unwrap();
+        SlotResolver::new(&layout, keys)
+    }
+
+    #[test]
+    fn test_resolves_plain_variable() {
+        assert_eq!(
+            resolver(&[]).resolve(word(2)).as_deref(),
+            Some("_totalSupply")
+        );
+    }
+
+    #[test]
+    fn test_packed_variables_share_label() {
+        assert_eq!(
+            resolver(&[]).resolve(word(3)).as_deref(),
+            Some("_owner | _paused")
+        );
+    }
+
+    #[test]
+    fn test_resolves_mapping_entry_for_candidate_key() {
+        let holder = addr(0x42);
+        let slot = mapping_slot(word(0), holder.into_word());
+        assert_eq!(
+            resolver(&[holder]).resolve(slot),
+            Some(format!("_balances[{}]", holder))
+        );
+    }
+
+    #[test]
+    fn test_resolves_nested_mapping_entry() {
+        let (owner, spender) = (addr(0x42), addr(0x43));
+        let slot = mapping_slot(
+            mapping_slot(word(1), owner.into_word()),
+            spender.into_word(),
+        );
+        assert_eq!(
+            resolver(&[owner, spender]).resolve(slot),
+            Some(format!("_allowances[{}][{}]", owner, spender))
+        );
+    }
+
+    #[test]
+    fn test_mapping_entry_unknown_key_unresolved() {
+        let slot = mapping_slot(word(0), addr(0x99).into_word());
+        assert!(resolver(&[addr(0x42)]).resolve(slot).is_none());
+    }
+
+    #[test]
+    fn test_resolves_struct_members() {
+        let r = resolver(&[]);
+        assert_eq!(r.resolve(word(5)).as_deref(), Some("cfg.amount"));
+        assert_eq!(r.resolve(word(6)).as_deref(), Some("cfg.owner"));
+    }
+
+    #[test]
+    fn test_resolves_dynamic_array_of_structs() {
+        let r = resolver(&[]);
+        assert_eq!(r.resolve(word(4)).as_deref(), Some("holders.length"));
+        let start = data_start(U256::from(4));
+        // Element 3, member `owner` (offset 1, stride 2) → start + 7.
+        let slot = B256::from((start + U256::from(7)).to_be_bytes());
+        assert_eq!(r.resolve(slot).as_deref(), Some("holders[3].owner"));
+    }
+
+    #[test]
+    fn test_resolves_long_string_data() {
+        let r = resolver(&[]);
+        assert_eq!(r.resolve(word(7)).as_deref(), Some("name"));
+        let slot = B256::from((data_start(U256::from(7)) + U256::from(1)).to_be_bytes());
+        assert_eq!(r.resolve(slot).as_deref(), Some("name (data +1)"));
+    }
+
+    #[test]
+    fn test_unrelated_slot_unresolved() {
+        assert!(resolver(&[]).resolve(word(1000)).is_none());
+    }
+}
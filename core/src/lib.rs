@@ -1,88 +1,108 @@
 //! Hammer core library — EIP-2930 access list generation, optimization, and validation.
+//!
+//! # Features
+//!
+//! - `evm` (default): tracing with revm — [`tracer`], the [`replay`] entry points
+//!   ([`generate`], [`validate`], ...), [`parallel`] replays and the [`Hammer`] engine.
+//! - `serde` (default): `Serialize`/`Deserialize` for lists and reports, and solc storage
+//!   layouts ([`layout`]).
+//!
+//! With neither, the crate is the validator, optimizer and gas math over lists and trace
+//! results produced elsewhere, on top of `alloy-primitives` and `alloy-eip2930` only.
 
-use alloy_primitives::Address;
-use alloy_rpc_types_eth::AccessList;
-use revm::context::{BlockEnv, TxEnv};
-use revm::database::Database;
-
+pub mod advice;
+pub mod balances;
+pub mod budget;
+pub mod bundle;
+pub mod callback;
+pub mod calls;
+pub mod cancel;
+pub mod config;
+#[cfg(feature = "evm")]
+pub mod engine;
 pub mod error;
+pub mod fix;
+pub mod foreign;
 pub mod gas;
+pub mod gas_sensitive;
+#[cfg(feature = "serde")]
+pub mod layout;
+pub mod near_miss;
 pub mod optimizer;
+pub mod oracle;
+pub mod origin;
+pub mod pad;
+#[cfg(feature = "evm")]
+pub mod parallel;
+#[cfg(feature = "evm")]
+pub mod replay;
+pub mod score;
+pub mod self_check;
+pub mod slot;
+pub mod split;
+pub mod state_diff;
+pub mod state_override;
+pub mod system;
+#[cfg(feature = "evm")]
 pub mod tracer;
 pub mod types;
 pub mod validator;
 pub mod warm;
 
-pub use error::HammerError;
+pub use advice::{advise, Advice, AdviceKind};
+pub use alloy_eip2930::{AccessList, AccessListItem};
+pub use balances::{balance_changes, BalanceChange};
+pub use budget::{check_budget, BudgetExceeded, BudgetList, GasBudget};
+pub use bundle::merge_bundle;
+pub use callback::{Callback, CallbackKind};
+pub use calls::{CallFrame, CallKind, CallPath};
+pub use cancel::CancellationToken;
+pub use config::OptimizerConfig;
+#[cfg(feature = "evm")]
+pub use engine::{Hammer, HammerBuilder};
+pub use error::{is_state_unavailable, HammerError};
+pub use fix::{apply_fixes, fix_edits};
+#[cfg(feature = "evm")]
+pub use foreign::foreign_addresses;
+pub use foreign::{stale_addresses, ForeignAddress};
 pub use gas::{
-    access_list_gas_cost, gas_to_eth, ACCESS_LIST_ADDRESS_COST, ACCESS_LIST_STORAGE_KEY_COST,
+    access_list_gas_cost, gas_to_eth, intrinsic_gas, GasSchedule, Hardfork, Refund,
+    ACCESS_LIST_ADDRESS_COST, ACCESS_LIST_STORAGE_KEY_COST, AUTHORIZATION_COST,
+    MAX_REFUND_QUOTIENT, MAX_REFUND_QUOTIENT_PRE_LONDON, NEW_ACCOUNT_COST, TX_BASE_COST,
+    WARM_ADDRESS_MIN_SLOTS,
+};
+pub use gas_sensitive::{GasSensitivity, GAS_BRANCH_WINDOW};
+#[cfg(feature = "serde")]
+pub use layout::{SlotResolver, StorageLayout};
+pub use near_miss::{near_misses, NearMiss};
+pub use optimizer::{optimize, optimize_with, optimize_with_strategy, Strategy};
+pub use origin::ListOrigin;
+pub use pad::{pad, EntryHits, PaddedList};
+#[cfg(feature = "evm")]
+pub use parallel::{generate_many_parallel, replay_parallel};
+#[cfg(feature = "evm")]
+pub use replay::{
+    generate, generate_batch, generate_bundle, generate_bundle_with, generate_cancellable,
+    generate_entry_call, generate_padded, generate_queue, generate_queue_iter, generate_queue_with,
+    generate_self_checked, generate_split_plans, generate_with, generate_with_strategy,
+    generate_with_tracer, tx_target, validate, validate_candidates,
+    validate_candidates_with_tracer, validate_replay, validate_replay_at_spec,
+    validate_replay_at_spec_with, validate_with, validate_with_tracer,
+};
+pub use score::{score_access_list, ListScore};
+pub use self_check::{self_check, SelfCheck};
+pub use slot::{array_element_slot, mapping_slot, nested_mapping_slot};
+pub use split::{list_size, SplitPart, SplitPlan};
+pub use state_diff::{AccountDiff, Change};
+pub use state_override::{AccountOverride, StateOverrides};
+pub use system::{chain_warm_addresses, system_contract, SystemContract};
+#[cfg(feature = "evm")]
+pub use tracer::{
+    generate_access_list, generate_access_list_at_spec, generate_access_list_cancellable,
+    generate_access_list_for_calls, generate_access_list_per_call, generate_access_list_queue,
+    QueueTraces, RevmTracer, Tracer,
+};
+pub use types::{
+    AccessCount, AddressAccesses, DiffEntry, FixEdit, GasSummary, OptimizedAccessList,
+    RawTraceResult, RemovalReason, RemovedAddress, ValidationReport,
 };
-pub use optimizer::optimize;
-pub use tracer::generate_access_list;
-pub use types::{DiffEntry, GasSummary, OptimizedAccessList, RawTraceResult, ValidationReport};
-
-/// Generate an optimized access list for the given transaction.
-pub fn generate<DB>(db: DB, tx: TxEnv, block: BlockEnv) -> Result<OptimizedAccessList, HammerError>
-where
-    DB: Database,
-    DB::Error: std::error::Error + Send + Sync + 'static,
-{
-    let tx_from = tx.caller;
-    let tx_to = match tx.kind {
-        revm::primitives::TxKind::Call(addr) => addr,
-        revm::primitives::TxKind::Create => Address::ZERO,
-    };
-    let coinbase = block.beneficiary;
-    let raw = generate_access_list(db, tx, block, false)?;
-    Ok(optimize(raw, tx_from, tx_to, coinbase))
-}
-
-/// Validate a declared access list against the optimal one from execution trace.
-pub fn validate<DB>(
-    db: DB,
-    tx: TxEnv,
-    block: BlockEnv,
-    declared: AccessList,
-) -> Result<ValidationReport, HammerError>
-where
-    DB: Database,
-    DB::Error: std::error::Error + Send + Sync + 'static,
-{
-    let tx_from = tx.caller;
-    let tx_to = match tx.kind {
-        revm::primitives::TxKind::Call(addr) => addr,
-        revm::primitives::TxKind::Create => Address::ZERO,
-    };
-    let coinbase = block.beneficiary;
-    let raw = generate_access_list(db, tx, block, false)?;
-    let optimal = optimize(raw, tx_from, tx_to, coinbase);
-
-    Ok(validator::validate(
-        &declared, &optimal, tx_from, tx_to, coinbase,
-    ))
-}
-
-/// Validate for replay (e.g. compare): skips nonce check so mined txs can be replayed.
-pub fn validate_replay<DB>(
-    db: DB,
-    tx: TxEnv,
-    block: BlockEnv,
-    declared: AccessList,
-) -> Result<ValidationReport, HammerError>
-where
-    DB: Database,
-    DB::Error: std::error::Error + Send + Sync + 'static,
-{
-    let tx_from = tx.caller;
-    let tx_to = match tx.kind {
-        revm::primitives::TxKind::Call(addr) => addr,
-        revm::primitives::TxKind::Create => Address::ZERO,
-    };
-    let coinbase = block.beneficiary;
-    let raw = generate_access_list(db, tx, block, true)?;
-    let optimal = optimize(raw, tx_from, tx_to, coinbase);
-
-    Ok(validator::validate(
-        &declared, &optimal, tx_from, tx_to, coinbase,
-    ))
-}
@@ -0,0 +1,156 @@
+//! "Did you mean" hints: a stale declared slot paired with a missing slot of the same
+//! contract that differs only in its final bytes.
+//!
+//! Off-by-one mistakes land there: a plain variable one slot over, or the wrong element of
+//! a dynamic array (`keccak256(base) + index`). Mapping entries hash their key, so a
+//! wrong key is not near anything and gets no hint.
+
+use alloy_primitives::{Address, B256, U256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::types::DiffEntry;
+
+/// Number of trailing bytes two slots may differ in and still be a near miss.
+pub const NEAR_MISS_BYTES: usize = 4;
+
+/// A declared slot the trace never touched, and the touched slot it was probably meant to be.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NearMiss {
+    pub address: Address,
+    /// Stale slot in the declared list.
+    pub declared: B256,
+    /// Missing slot it most likely stands for.
+    pub actual: B256,
+}
+
+/// Pair each stale slot with the closest missing slot of the same address that shares all
+/// but the last [`NEAR_MISS_BYTES`] bytes. Each missing slot is used at most once.
+pub fn near_misses(entries: &[DiffEntry]) -> Vec<NearMiss> {
+    let mut stale: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    let mut missing: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    for entry in entries {
+        match entry {
+            DiffEntry::Stale {
+                address,
+                storage_keys,
+                ..
+            } => stale
+                .entry(*address)
+                .or_default()
+                .extend(storage_keys.iter().copied()),
+            DiffEntry::Incomplete {
+                address,
+                missing_slots,
+                ..
+            } => missing
+                .entry(*address)
+                .or_default()
+                .extend(missing_slots.iter().copied()),
+            _ => {}
+        }
+    }
+
+    let mut hints = Vec::new();
+    for (address, declared_slots) in stale {
+        let Some(candidates) = missing.get_mut(&address) else {
+            continue;
+        };
+        for declared in declared_slots {
+            let closest = candidates
+                .iter()
+                .filter(|actual| is_near(&declared, actual))
+                .min_by_key(|actual| distance(&declared, actual))
+                .copied();
+            if let Some(actual) = closest {
+                candidates.remove(&actual);
+                hints.push(NearMiss {
+                    address,
+                    declared,
+                    actual,
+                });
+            }
+        }
+    }
+    hints
+}
+
+fn is_near(a: &B256, b: &B256) -> bool {
+    let prefix = 32 - NEAR_MISS_BYTES;
+    a[..prefix] == b[..prefix]
+}
+
+fn distance(a: &B256, b: &B256) -> U256 {
+    let (a, b) = (U256::from_be_bytes(a.0), U256::from_be_bytes(b.0));
+    a.max(b) - a.min(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    fn stale(address: Address, slots: Vec<B256>) -> DiffEntry {
+        DiffEntry::Stale {
+            address,
+            storage_keys: slots,
+            gas_waste: 0,
+        }
+    }
+
+    fn incomplete(address: Address, slots: Vec<B256>) -> DiffEntry {
+        DiffEntry::Incomplete {
+            address,
+            missing_slots: slots,
+            gas_waste: 0,
+        }
+    }
+
+    fn element(base: u8, index: u64) -> B256 {
+        let start = U256::from_be_bytes(keccak256(B256::with_last_byte(base)).0);
+        B256::from(start + U256::from(index))
+    }
+
+    #[test]
+    fn test_array_off_by_one_paired_with_closest() {
+        let token = Address::with_last_byte(0x40);
+        let entries = vec![
+            stale(token, vec![element(3, 5)]),
+            incomplete(token, vec![element(3, 9), element(3, 6)]),
+        ];
+        assert_eq!(
+            near_misses(&entries),
+            vec![NearMiss {
+                address: token,
+                declared: element(3, 5),
+                actual: element(3, 6),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_hint_across_addresses_or_far_slots() {
+        let a = Address::with_last_byte(0x40);
+        let b = Address::with_last_byte(0x41);
+        let entries = vec![
+            stale(a, vec![B256::with_last_byte(1), element(3, 0)]),
+            incomplete(b, vec![B256::with_last_byte(2)]),
+            incomplete(a, vec![element(4, 0)]),
+        ];
+        assert!(near_misses(&entries).is_empty());
+    }
+
+    #[test]
+    fn test_each_missing_slot_used_once() {
+        let a = Address::with_last_byte(0x40);
+        let entries = vec![
+            stale(a, vec![B256::with_last_byte(1), B256::with_last_byte(3)]),
+            incomplete(a, vec![B256::with_last_byte(2)]),
+        ];
+        let hints = near_misses(&entries);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].declared, B256::with_last_byte(1));
+    }
+}
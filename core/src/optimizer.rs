@@ -1,55 +1,189 @@
 //! Warm-address stripping — remove entries that are already warm by default.
 
+use alloy_eip2930::{AccessList, AccessListItem};
 use alloy_primitives::{Address, B256};
-use alloy_rpc_types_eth::{AccessList, AccessListItem};
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::types::{OptimizedAccessList, RawTraceResult};
+use crate::config::OptimizerConfig;
+use crate::gas::GasSchedule;
+use crate::types::{
+    AddressAccesses, OptimizedAccessList, RawTraceResult, RemovalReason, RemovedAddress,
+};
 use crate::warm::precompile_addresses;
 
+/// Which touched entries the optimizer keeps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Every touched entry that is not warm by default.
+    #[default]
+    Complete,
+    /// Only entries whose cold surcharges, as counted by the tracer, exceed their upfront
+    /// cost. Under EIP-2929 one cold touch already repays an entry, so this drops only
+    /// what the trace touched without paying for it cold. Traces without counts (per-call
+    /// and split traces) keep everything.
+    NetSavings,
+}
+
 /// Optimize access list by removing warm-by-default addresses.
 ///
-/// Removes: tx.from, tx.to (EIP-2929), block.coinbase (EIP-3651), precompiles,
-/// contracts created during execution. Deduplicates/sorts for deterministic output.
+/// Removes: tx.from, tx.to (EIP-2929), block.coinbase (EIP-3651), EIP-7702 authorities,
+/// precompiles, contracts created during execution. Warmth covers an account, not its storage, so a
+/// warm address whose storage starts cold (see [`storage_starts_cold`]) is kept after all,
+/// with its slots, when it has at least [`crate::WARM_ADDRESS_MIN_SLOTS`] of them. The
+/// coinbase is warm only from Shanghai on (EIP-3651). Deduplicates/sorts for deterministic output:
+/// the list, its slots and the removed addresses are all in ascending order, whatever
+/// order the trace reported them in.
 pub fn optimize(
     raw: RawTraceResult,
     tx_from: Address,
     tx_to: Address,
     coinbase: Address,
 ) -> OptimizedAccessList {
+    optimize_with(raw, tx_from, tx_to, coinbase, &OptimizerConfig::default())
+}
+
+/// [`optimize`], also stripping the config's `assume_warm` and `chain_warm` addresses and
+/// then adding its `force_include` entries, which are kept even if they would otherwise be
+/// stripped. Gas is weighed with the config's [`GasSchedule`].
+pub fn optimize_with(
+    raw: RawTraceResult,
+    tx_from: Address,
+    tx_to: Address,
+    coinbase: Address,
+    config: &OptimizerConfig,
+) -> OptimizedAccessList {
+    optimize_with_strategy(raw, tx_from, tx_to, coinbase, config, Strategy::Complete)
+}
+
+/// [`optimize_with`], keeping the touched entries `strategy` picks. Entries dropped as a
+/// net loss are reported in `removed`; forced entries are kept whatever they save.
+pub fn optimize_with_strategy(
+    raw: RawTraceResult,
+    tx_from: Address,
+    tx_to: Address,
+    coinbase: Address,
+    config: &OptimizerConfig,
+    strategy: Strategy,
+) -> OptimizedAccessList {
+    let schedule = &config.schedule;
     let precompiles = precompile_addresses();
+    let access_counts = raw.access_counts;
     let created_set: BTreeSet<Address> = raw.created_contracts.into_iter().collect();
+    let authorities = raw.authorities;
+    let new_accounts = raw.new_accounts;
+    let precompile_calls = raw.precompile_calls;
+    let state_diff = raw.state_diff;
+    let mut callbacks = raw.callbacks;
+    let oracle_reads = raw.oracle_reads;
+    let logs = raw.logs;
+    let calls = raw.calls;
+    let gas_sensitive = raw.gas_sensitive;
+    let transient = raw.transient;
+
+    let participants = [
+        (tx_from, RemovalReason::TxFrom),
+        (tx_to, RemovalReason::TxTo),
+        (coinbase, RemovalReason::Coinbase),
+    ];
+    let reason_for = |addr: &Address| {
+        if let Some((_, reason)) = participants.iter().find(|(a, reason)| {
+            a == addr
+                && *a != Address::ZERO
+                && (*reason != RemovalReason::Coinbase || schedule.warm_coinbase)
+        }) {
+            Some(*reason)
+        } else if authorities.contains(addr) {
+            Some(RemovalReason::Authority)
+        } else if config.assume_warm.contains(addr) {
+            Some(RemovalReason::AssumedWarm)
+        } else if precompiles.contains(addr) {
+            Some(RemovalReason::Precompile)
+        } else if config.chain_warm.contains(addr) {
+            Some(RemovalReason::SystemContract)
+        } else if created_set.contains(addr) {
+            Some(RemovalReason::CreatedContract)
+        } else {
+            None
+        }
+    };
 
-    let warm_by_default: BTreeSet<Address> = [tx_from, tx_to, coinbase]
-        .into_iter()
-        .filter(|a| *a != Address::ZERO)
-        .collect();
-
-    let mut removed = Vec::new();
+    let mut removed: BTreeMap<Address, (RemovalReason, BTreeSet<B256>)> = BTreeMap::new();
     let mut optimized: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    let mut warm_kept = Vec::new();
 
     for item in raw.access_list.0.into_iter() {
         let addr = item.address;
-
-        if warm_by_default.contains(&addr) {
-            removed.push(addr);
-            continue;
-        }
-        if precompiles.contains(&addr) {
-            removed.push(addr);
-            continue;
-        }
-        if created_set.contains(&addr) {
-            removed.push(addr);
-            continue;
+        let mut slots: BTreeSet<B256> = item.storage_keys.into_iter().collect();
+
+        if let Some(reason) = reason_for(&addr) {
+            let pays_for_address = schedule
+                .warm_address_min_slots()
+                .is_some_and(|min| slots.len() >= min);
+            if storage_starts_cold(reason) && pays_for_address {
+                warm_kept.push(addr);
+            } else {
+                removed
+                    .entry(addr)
+                    .or_insert((reason, BTreeSet::new()))
+                    .1
+                    .extend(slots);
+                continue;
+            }
         }
 
-        let slots: BTreeSet<B256> = item.storage_keys.into_iter().collect();
+        if let (Strategy::NetSavings, Some(counts)) = (strategy, access_counts.get(&addr)) {
+            slots.retain(|slot| {
+                slot_saving(schedule, counts, slot) > schedule.access_list_storage_key_cost
+            });
+            if !pays_off(schedule, counts, &slots) {
+                removed
+                    .entry(addr)
+                    .or_insert((RemovalReason::NetLoss, BTreeSet::new()))
+                    .1
+                    .extend(slots);
+                continue;
+            }
+        }
         if !slots.is_empty() || !optimized.contains_key(&addr) {
             optimized.entry(addr).or_default().extend(slots);
         }
     }
 
+    for (addr, slots) in &config.force_include {
+        removed.remove(addr);
+        optimized
+            .entry(*addr)
+            .or_default()
+            .extend(slots.iter().copied());
+    }
+
+    // Keep only what the final list declares.
+    for callback in &mut callbacks {
+        callback.touched = std::mem::take(&mut callback.touched)
+            .into_iter()
+            .filter_map(|(addr, slots)| {
+                let kept = optimized.get(&addr)?;
+                Some((addr, slots.intersection(kept).copied().collect()))
+            })
+            .collect();
+    }
+
+    let mut volatile: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    for (addr, kept) in &optimized {
+        let mut flagged: BTreeSet<B256> = oracle_reads
+            .get(addr)
+            .map(|slots| slots.intersection(kept).copied().collect())
+            .unwrap_or_default();
+        match config.volatile.get(addr) {
+            Some(slots) if slots.is_empty() => flagged.extend(kept.iter().copied()),
+            Some(slots) => flagged.extend(slots.intersection(kept).copied()),
+            None => {}
+        }
+        if !flagged.is_empty() {
+            volatile.insert(*addr, flagged);
+        }
+    }
+
     let list = AccessList(
         optimized
             .into_iter()
@@ -60,13 +194,74 @@ pub fn optimize(
             .collect(),
     );
 
-    OptimizedAccessList::new(list, removed)
+    let removed: Vec<RemovedAddress> = removed
+        .into_iter()
+        .map(|(address, (reason, slots))| RemovedAddress {
+            address,
+            reason,
+            gas_waste: schedule.access_list_address_cost
+                + slots.len() as u64 * schedule.access_list_storage_key_cost,
+            cold_slots: if storage_starts_cold(reason) {
+                slots.len() as u64
+            } else {
+                0
+            },
+        })
+        .collect();
+    warm_kept.retain(|addr| !removed.iter().any(|r| r.address == *addr));
+    let mut optimized = OptimizedAccessList::new(list, removed.iter().map(|r| r.address).collect());
+    optimized.removed = removed;
+    optimized.warm_kept = warm_kept;
+    optimized.authorities = authorities;
+    optimized.new_accounts = new_accounts;
+    optimized.precompile_calls = precompile_calls;
+    optimized.callbacks = callbacks;
+    optimized.volatile = volatile;
+    optimized.state_diff = state_diff;
+    optimized.logs = logs;
+    optimized.calls = calls;
+    optimized.gas_sensitive = gas_sensitive;
+    optimized.transient = transient;
+    optimized
+}
+
+/// Whether the address is warm only as an account, so its slots are read cold: the
+/// sender, target and coinbase, EIP-7702 authorities, and contracts created during
+/// execution, whose fresh storage is cold like any other. Precompiles, the chain's
+/// system contracts included, have no storage; for `--assume-warm` addresses the
+/// caller's knowledge covers the account only, and is taken as is.
+fn storage_starts_cold(reason: RemovalReason) -> bool {
+    matches!(
+        reason,
+        RemovalReason::TxFrom
+            | RemovalReason::TxTo
+            | RemovalReason::Coinbase
+            | RemovalReason::Authority
+            | RemovalReason::CreatedContract
+    )
+}
+
+/// Gas declaring `slot` saves: the cold read surcharge of each cold touch.
+fn slot_saving(schedule: &GasSchedule, counts: &AddressAccesses, slot: &B256) -> u64 {
+    counts.slots.get(slot).map_or(0, |count| count.cold) * schedule.cold_sload_surcharge()
+}
+
+/// Whether declaring the address with `slots` saves more than it costs upfront.
+fn pays_off(schedule: &GasSchedule, counts: &AddressAccesses, slots: &BTreeSet<B256>) -> bool {
+    let saving = counts.account.cold * schedule.cold_account_surcharge()
+        + slots
+            .iter()
+            .map(|slot| slot_saving(schedule, counts, slot))
+            .sum::<u64>();
+    let cost = schedule.access_list_address_cost
+        + slots.len() as u64 * schedule.access_list_storage_key_cost;
+    saving > cost
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use alloy_rpc_types_eth::AccessListItem;
+    use alloy_eip2930::AccessListItem;
 
     fn addr(n: u8) -> Address {
         Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
@@ -82,7 +277,20 @@ mod tests {
         RawTraceResult {
             access_list: AccessList(items),
             created_contracts: created,
+            authorities: vec![],
+            new_accounts: vec![],
+            precompile_calls: Default::default(),
+            callbacks: vec![],
+            oracle_reads: Default::default(),
+            state_diff: vec![],
+            logs: vec![],
+            calls: vec![],
+            access_counts: Default::default(),
+            gas_sensitive: vec![],
+            transient: Default::default(),
             gas_used: 21000,
+            gas_refunded: 0,
+            refund_counter: 0,
             success: true,
         }
     }
@@ -94,6 +302,118 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_net_savings_drops_entries_never_touched_cold() {
+        let (from, to, coinbase) = (addr(1), addr(2), addr(3));
+        let (paid, free) = (addr(0x40), addr(0x41));
+        let mut traced = raw(
+            vec![
+                item(paid, vec![slot(1), slot(2)]),
+                item(free, vec![slot(1)]),
+            ],
+            vec![],
+        );
+        let count = |accesses, cold| crate::types::AccessCount { accesses, cold };
+        traced.access_counts = BTreeMap::from([
+            (
+                paid,
+                AddressAccesses {
+                    account: count(1, 1),
+                    // Slot 2 was only touched once already warm.
+                    slots: BTreeMap::from([(slot(1), count(3, 1)), (slot(2), count(1, 0))]),
+                },
+            ),
+            (
+                free,
+                AddressAccesses {
+                    account: count(1, 0),
+                    slots: BTreeMap::from([(slot(1), count(1, 1))]),
+                },
+            ),
+        ]);
+
+        let complete = optimize(traced.clone(), from, to, coinbase);
+        assert_eq!(complete.list.0.len(), 2);
+
+        let net = optimize_with_strategy(
+            traced,
+            from,
+            to,
+            coinbase,
+            &OptimizerConfig::default(),
+            Strategy::NetSavings,
+        );
+        assert_eq!(net.list.0, vec![item(paid, vec![slot(1)])]);
+        assert_eq!(net.removed.len(), 1);
+        assert_eq!(net.removed[0].address, free);
+        assert_eq!(net.removed[0].reason, RemovalReason::NetLoss);
+    }
+
+    #[test]
+    fn test_net_savings_counts_address_saving_over_a_warm_access() {
+        let (from, to, coinbase) = (addr(1), addr(2), addr(3));
+        let other = addr(0x40);
+        let mut traced = raw(vec![item(other, vec![])], vec![]);
+        traced.access_counts = BTreeMap::from([(
+            other,
+            AddressAccesses {
+                account: crate::types::AccessCount {
+                    accesses: 1,
+                    cold: 1,
+                },
+                slots: BTreeMap::new(),
+            },
+        )]);
+        // Declaring saves 2600 - 100: the access still pays the warm price.
+        let config = |address_cost| OptimizerConfig {
+            schedule: GasSchedule {
+                access_list_address_cost: address_cost,
+                ..GasSchedule::default()
+            },
+            ..Default::default()
+        };
+        let net = |address_cost| {
+            optimize_with_strategy(
+                traced.clone(),
+                from,
+                to,
+                coinbase,
+                &config(address_cost),
+                Strategy::NetSavings,
+            )
+        };
+        assert_eq!(net(2_499).list.0, vec![item(other, vec![])]);
+        assert!(net(2_500).list.0.is_empty());
+        assert_eq!(net(2_500).removed[0].reason, RemovalReason::NetLoss);
+    }
+
+    #[test]
+    fn test_warm_participant_kept_when_its_cold_slots_pay_off() {
+        let (from, to, coinbase) = (addr(1), addr(2), addr(3));
+        let slots = |n: u8| (0..n).map(slot).collect::<Vec<_>>();
+
+        let result = optimize(raw(vec![item(to, slots(24))], vec![]), from, to, coinbase);
+        assert!(result.list.0.is_empty());
+        assert_eq!(result.removed[0].cold_slots, 24);
+
+        // The sender's own storage, e.g. read by EIP-7702 delegated code.
+        let result = optimize(raw(vec![item(from, slots(25))], vec![]), from, to, coinbase);
+        assert_eq!(result.list.0, vec![item(from, slots(25))]);
+        assert_eq!(result.warm_kept, vec![from]);
+        assert!(result.removed.is_empty());
+
+        // A new contract's storage is cold too.
+        let created = addr(0x40);
+        let result = optimize(
+            raw(vec![item(created, slots(30))], vec![created]),
+            from,
+            to,
+            coinbase,
+        );
+        assert_eq!(result.warm_kept, vec![created]);
+        assert_eq!(result.list.0[0].storage_keys.len(), 30);
+    }
+
     #[test]
     fn test_removes_tx_from() {
         let from = addr(1);
@@ -129,6 +449,114 @@ mod tests {
         assert!(result.removed_addresses.contains(&coinbase));
     }
 
+    #[test]
+    fn test_coinbase_kept_before_shanghai() {
+        let (from, to, coinbase) = (addr(0x40), addr(0x41), addr(0x42));
+        let config = OptimizerConfig {
+            schedule: crate::Hardfork::London.schedule(),
+            ..Default::default()
+        };
+        let result = optimize_with(
+            raw(vec![item(coinbase, vec![slot(1)])], vec![]),
+            from,
+            to,
+            coinbase,
+            &config,
+        );
+        assert_eq!(result.list.0, vec![item(coinbase, vec![slot(1)])]);
+        assert!(result.removed.is_empty());
+    }
+
+    #[test]
+    fn test_removes_eip7702_authority() {
+        let authority = addr(0x43);
+        let mut trace = raw(
+            vec![item(authority, vec![slot(1)]), item(addr(0x44), vec![])],
+            vec![],
+        );
+        trace.authorities = vec![authority];
+        let result = optimize(trace, addr(0x40), addr(0x41), addr(0x42));
+        assert_eq!(result.list.0, vec![item(addr(0x44), vec![])]);
+        assert_eq!(result.removed[0].address, authority);
+        assert_eq!(result.removed[0].reason, RemovalReason::Authority);
+        // Delegated code runs in the authority's storage, which starts cold.
+        assert_eq!(result.removed[0].cold_slots, 1);
+        assert_eq!(result.authorities, vec![authority]);
+    }
+
+    #[test]
+    fn test_removes_assumed_warm() {
+        let extra = addr(40);
+        let config = OptimizerConfig {
+            assume_warm: BTreeSet::from([extra]),
+            ..Default::default()
+        };
+        let result = optimize_with(
+            raw(
+                vec![item(extra, vec![slot(1)]), item(addr(41), vec![])],
+                vec![],
+            ),
+            addr(1),
+            addr(2),
+            addr(3),
+            &config,
+        );
+        assert_eq!(result.list.0.len(), 1);
+        assert_eq!(result.list.0[0].address, addr(41));
+        assert_eq!(result.removed_addresses, vec![extra]);
+    }
+
+    #[test]
+    fn test_force_include_kept_and_merged() {
+        let from = addr(1);
+        let other = addr(41);
+        let config = OptimizerConfig {
+            force_include: BTreeMap::from([
+                (from, BTreeSet::new()),
+                (other, BTreeSet::from([slot(2)])),
+            ]),
+            ..Default::default()
+        };
+        let result = optimize_with(
+            raw(vec![item(from, vec![]), item(other, vec![slot(1)])], vec![]),
+            from,
+            addr(2),
+            addr(3),
+            &config,
+        );
+        assert_eq!(
+            result.list.0,
+            vec![item(from, vec![]), item(other, vec![slot(1), slot(2)])]
+        );
+        assert!(result.removed_addresses.is_empty());
+    }
+
+    #[test]
+    fn test_volatile_from_oracle_reads_and_config() {
+        let feed = addr(40);
+        let other = addr(41);
+        let mut trace = raw(
+            vec![
+                item(feed, vec![slot(1), slot(2)]),
+                item(other, vec![slot(3), slot(4)]),
+            ],
+            vec![],
+        );
+        trace.oracle_reads = BTreeMap::from([(feed, BTreeSet::from([slot(2), slot(9)]))]);
+        let config = OptimizerConfig {
+            volatile: BTreeMap::from([(other, BTreeSet::new())]),
+            ..Default::default()
+        };
+        let result = optimize_with(trace, addr(1), addr(2), addr(3), &config);
+        assert_eq!(
+            result.volatile,
+            BTreeMap::from([
+                (feed, BTreeSet::from([slot(2)])),
+                (other, BTreeSet::from([slot(3), slot(4)])),
+            ])
+        );
+    }
+
     #[test]
     fn test_removes_precompiles() {
         let from = addr(20);
@@ -256,6 +684,66 @@ mod tests {
         assert_eq!(result.list.0.len(), 1);
     }
 
+    #[test]
+    fn test_removed_addresses_explained() {
+        let from = addr(100);
+        let to = addr(101);
+        let coinbase = addr(102);
+        let created = addr(103);
+        let assumed = addr(104);
+        let items = vec![
+            item(from, vec![]),
+            item(to, vec![slot(1), slot(2)]),
+            item(coinbase, vec![]),
+            item(addr(5), vec![]),
+            item(created, vec![slot(1)]),
+            item(assumed, vec![]),
+        ];
+        let config = OptimizerConfig {
+            assume_warm: BTreeSet::from([assumed]),
+            ..Default::default()
+        };
+        let result = optimize_with(raw(items, vec![created]), from, to, coinbase, &config);
+        let reasons: Vec<_> = result
+            .removed
+            .iter()
+            .map(|r| (r.address, r.reason, r.gas_waste))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (addr(5), RemovalReason::Precompile, 2400),
+                (from, RemovalReason::TxFrom, 2400),
+                (to, RemovalReason::TxTo, 2400 + 2 * 1900),
+                (coinbase, RemovalReason::Coinbase, 2400),
+                (created, RemovalReason::CreatedContract, 2400 + 1900),
+                (assumed, RemovalReason::AssumedWarm, 2400),
+            ]
+        );
+        assert_eq!(
+            result.removed_addresses,
+            result.removed.iter().map(|r| r.address).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_chain_warm_stripped_as_system_contract() {
+        // ArbSys is a precompile on Arbitrum, so warm there and cold on mainnet.
+        let arb_sys = addr(0x64);
+        let (from, to, coinbase) = (addr(0xa0), addr(0xa1), addr(0xa2));
+        let items = vec![item(arb_sys, vec![])];
+        let mainnet = optimize(raw(items.clone(), vec![]), from, to, coinbase);
+        assert_eq!(mainnet.list.0.len(), 1);
+
+        let config = OptimizerConfig {
+            chain_warm: crate::system::chain_warm_addresses(42161),
+            ..Default::default()
+        };
+        let arbitrum = optimize_with(raw(items, vec![]), from, to, coinbase, &config);
+        assert!(arbitrum.list.0.is_empty());
+        assert_eq!(arbitrum.removed[0].reason, RemovalReason::SystemContract);
+    }
+
     // --- additional coverage ---
 
     #[test]
@@ -373,4 +861,29 @@ mod tests {
         assert_eq!(result.list.0[0].address, just_outside);
         assert!(result.removed_addresses.contains(&boundary_precompile));
     }
+
+    #[test]
+    fn test_output_independent_of_trace_order() {
+        let (from, to, coinbase) = (addr(1), addr(2), addr(3));
+        let items = vec![
+            item(addr(60), vec![slot(2), slot(1)]),
+            item(from, vec![]),
+            item(addr(50), vec![slot(3)]),
+            item(addr(70), vec![]),
+            item(to, vec![]),
+            item(addr(60), vec![slot(1)]),
+        ];
+        let created = vec![addr(70), addr(40)];
+        let mut reversed_items = items.clone();
+        reversed_items.reverse();
+        let mut reversed_created = created.clone();
+        reversed_created.reverse();
+
+        let a = optimize(raw(items, created), from, to, coinbase);
+        let b = optimize(raw(reversed_items, reversed_created), from, to, coinbase);
+        assert_eq!(a.list, b.list);
+        assert_eq!(a.removed_addresses, b.removed_addresses);
+        assert_eq!(a.removed_addresses, vec![from, to, addr(70)]);
+        assert_eq!(a.list.0[1].storage_keys, vec![slot(1), slot(2)]);
+    }
 }
@@ -0,0 +1,58 @@
+//! Recognition of price-feed reads whose slots move as the feed updates.
+//!
+//! A Chainlink aggregator stores each round under its round id, and a Uniswap V3 pool
+//! stores observations in a ring buffer indexed by the latest write. The slots a read
+//! touches therefore change with every update, so a precomputed list that declares them
+//! goes stale as soon as the feed moves. Reads with a fixed slot (e.g. `getReserves`,
+//! `slot0`) only change value, which does not matter to an access list.
+
+/// Feed reads whose slots depend on the latest round: `(selector, function name)`.
+pub const ORACLE_SELECTORS: &[(u32, &str)] = &[
+    (0xfeaf968c, "latestRoundData"),
+    (0x50d25bcd, "latestAnswer"),
+    (0x8205bf6a, "latestTimestamp"),
+    (0x668a0f02, "latestRound"),
+    (0x9a6fc8f5, "getRoundData"),
+    (0x883bdbfd, "observe"),
+];
+
+/// Whether a call with this selector reads a feed whose slots move (see module docs).
+pub fn is_oracle_read(selector: [u8; 4]) -> bool {
+    let selector = u32::from_be_bytes(selector);
+    ORACLE_SELECTORS.iter().any(|(s, _)| *s == selector)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::keccak256;
+
+    #[test]
+    fn test_selectors_match_signatures() {
+        let signatures = [
+            "latestRoundData()",
+            "latestAnswer()",
+            "latestTimestamp()",
+            "latestRound()",
+            "getRoundData(uint80)",
+            "observe(uint32[])",
+        ];
+        for ((selector, name), signature) in ORACLE_SELECTORS.iter().zip(signatures) {
+            assert!(signature.starts_with(name));
+            assert_eq!(
+                selector.to_be_bytes(),
+                keccak256(signature)[..4],
+                "selector of {}",
+                signature
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixed_slot_reads_not_flagged() {
+        // getReserves() and slot0() read fixed slots.
+        assert!(!is_oracle_read([0x09, 0x02, 0xf1, 0xac]));
+        assert!(!is_oracle_read([0x38, 0x50, 0xc7, 0xbd]));
+        assert!(is_oracle_read([0xfe, 0xaf, 0x96, 0x8c]));
+    }
+}
@@ -0,0 +1,126 @@
+//! Heuristic classification of where a declared access list came from.
+//!
+//! Node `eth_createAccessList` output records every address the trace touched,
+//! including ones that are warm anyway (the sender or recipient on some clients, the
+//! coinbase, precompiles), but it never repeats a slot or lists something untouched.
+//! Lists that drift from the trace or repeat slots were assembled some other way.
+
+use alloy_eip2930::AccessList;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::types::DiffEntry;
+
+/// Likely origin of a declared access list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ListOrigin {
+    /// No access list was declared.
+    #[default]
+    None,
+    /// Matches the optimal list exactly, as an optimizer such as hammer produces.
+    Optimal,
+    /// Matches the trace but keeps warm-by-default entries: the shape of raw
+    /// `eth_createAccessList` output.
+    NodeGenerated,
+    /// Stale, incomplete or duplicated entries: written by hand, cached from an older
+    /// state, or assembled by a tool that does not trace.
+    HandWritten,
+}
+
+impl ListOrigin {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Optimal => "optimal",
+            Self::NodeGenerated => "node_generated",
+            Self::HandWritten => "hand_written",
+        }
+    }
+}
+
+/// Classify `declared` from the findings a validation produced for it.
+pub fn classify(declared: &AccessList, entries: &[DiffEntry]) -> ListOrigin {
+    if declared.0.is_empty() {
+        return ListOrigin::None;
+    }
+    if entries.is_empty() {
+        return ListOrigin::Optimal;
+    }
+    if entries
+        .iter()
+        .all(|e| matches!(e, DiffEntry::Redundant { .. }))
+    {
+        return ListOrigin::NodeGenerated;
+    }
+    ListOrigin::HandWritten
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_eip2930::AccessListItem;
+    use alloy_primitives::{Address, B256};
+
+    fn declared() -> AccessList {
+        AccessList(vec![AccessListItem {
+            address: Address::repeat_byte(1),
+            storage_keys: vec![],
+        }])
+    }
+
+    fn redundant() -> DiffEntry {
+        DiffEntry::Redundant {
+            address: Address::repeat_byte(1),
+            gas_waste: 2400,
+        }
+    }
+
+    #[test]
+    fn test_empty_list_is_none() {
+        assert_eq!(classify(&AccessList::default(), &[]), ListOrigin::None);
+        // Missing entries do not change that nothing was declared.
+        let missing = DiffEntry::Missing {
+            address: Address::repeat_byte(2),
+            storage_keys: vec![],
+            gas_waste: 100,
+        };
+        assert_eq!(
+            classify(&AccessList::default(), &[missing]),
+            ListOrigin::None
+        );
+    }
+
+    #[test]
+    fn test_exact_list_is_optimal() {
+        assert_eq!(classify(&declared(), &[]), ListOrigin::Optimal);
+    }
+
+    #[test]
+    fn test_only_redundant_is_node_generated() {
+        assert_eq!(
+            classify(&declared(), &[redundant(), redundant()]),
+            ListOrigin::NodeGenerated
+        );
+    }
+
+    #[test]
+    fn test_duplicate_or_stale_is_hand_written() {
+        let duplicate = DiffEntry::Duplicate {
+            address: Address::repeat_byte(1),
+            storage_key: B256::ZERO,
+            gas_waste: 1900,
+        };
+        assert_eq!(
+            classify(&declared(), &[redundant(), duplicate]),
+            ListOrigin::HandWritten
+        );
+        let stale = DiffEntry::Stale {
+            address: Address::repeat_byte(1),
+            storage_keys: vec![],
+            gas_waste: 2400,
+        };
+        assert_eq!(classify(&declared(), &[stale]), ListOrigin::HandWritten);
+    }
+}
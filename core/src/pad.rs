@@ -0,0 +1,146 @@
+//! Padding a list with the state other inputs to the same call reach.
+//!
+//! A list built from one trace covers one path through the contract. Tracing the call
+//! again with perturbed calldata finds slots that are only touched on other branches
+//! (a fee tier, a second pool, a different holder). The padded list declares everything
+//! any run touched, and each entry's hit rate says how often it was needed.
+
+use alloy_eip2930::{AccessList, AccessListItem};
+use alloy_primitives::{Address, B256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How many runs touched one entry: an address (`slot` absent) or one of its slots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EntryHits {
+    pub address: Address,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub slot: Option<B256>,
+    pub hits: usize,
+}
+
+/// The union of several runs' lists, with per-entry hit counts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PaddedList {
+    pub list: AccessList,
+    /// Runs whose lists were merged.
+    pub runs: usize,
+    /// Runs left out because the call reverted.
+    pub reverted: usize,
+    /// Every entry of `list`, in list order.
+    pub hits: Vec<EntryHits>,
+}
+
+impl PaddedList {
+    /// Hits of `address` (or of its `slot`), if it is in the list.
+    pub fn hits_of(&self, address: &Address, slot: Option<&B256>) -> Option<usize> {
+        self.hits
+            .iter()
+            .find(|h| h.address == *address && h.slot.as_ref() == slot)
+            .map(|h| h.hits)
+    }
+}
+
+/// Merge the lists of the runs that succeeded; `reverted` only records how many did not.
+pub fn pad(lists: &[AccessList], reverted: usize) -> PaddedList {
+    let mut addresses: BTreeMap<Address, usize> = BTreeMap::new();
+    let mut slots: BTreeMap<(Address, B256), usize> = BTreeMap::new();
+    for list in lists {
+        let mut seen: BTreeSet<Address> = BTreeSet::new();
+        let mut seen_slots: BTreeSet<(Address, B256)> = BTreeSet::new();
+        for item in &list.0 {
+            if seen.insert(item.address) {
+                *addresses.entry(item.address).or_default() += 1;
+            }
+            for key in &item.storage_keys {
+                if seen_slots.insert((item.address, *key)) {
+                    *slots.entry((item.address, *key)).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    let mut items = Vec::new();
+    let mut hits = Vec::new();
+    for (address, count) in addresses {
+        hits.push(EntryHits {
+            address,
+            slot: None,
+            hits: count,
+        });
+        let keys: Vec<B256> = slots
+            .range((address, B256::ZERO)..=(address, B256::repeat_byte(0xff)))
+            .map(|(&(_, key), &count)| {
+                hits.push(EntryHits {
+                    address,
+                    slot: Some(key),
+                    hits: count,
+                });
+                key
+            })
+            .collect();
+        items.push(AccessListItem {
+            address,
+            storage_keys: keys,
+        });
+    }
+    PaddedList {
+        list: AccessList(items),
+        runs: lists.len(),
+        reverted,
+        hits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: &[(u8, &[u8])]) -> AccessList {
+        AccessList(
+            items
+                .iter()
+                .map(|(address, slots)| AccessListItem {
+                    address: Address::with_last_byte(*address),
+                    storage_keys: slots.iter().map(|s| B256::with_last_byte(*s)).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_union_with_hit_counts() {
+        let padded = pad(
+            &[
+                list(&[(0x40, &[1])]),
+                list(&[(0x40, &[1, 2]), (0x41, &[])]),
+                list(&[(0x40, &[1])]),
+            ],
+            1,
+        );
+        assert_eq!(padded.list, list(&[(0x40, &[1, 2]), (0x41, &[])]));
+        assert_eq!((padded.runs, padded.reverted), (3, 1));
+        let a = Address::with_last_byte(0x40);
+        assert_eq!(padded.hits_of(&a, None), Some(3));
+        assert_eq!(padded.hits_of(&a, Some(&B256::with_last_byte(1))), Some(3));
+        assert_eq!(padded.hits_of(&a, Some(&B256::with_last_byte(2))), Some(1));
+        assert_eq!(
+            padded.hits_of(&Address::with_last_byte(0x41), None),
+            Some(1)
+        );
+        assert_eq!(padded.hits.len(), 4);
+    }
+
+    #[test]
+    fn test_duplicates_within_a_run_count_once() {
+        let padded = pad(&[list(&[(0x40, &[1, 1]), (0x40, &[1])])], 0);
+        assert_eq!(padded.list, list(&[(0x40, &[1])]));
+        assert!(padded.hits.iter().all(|h| h.hits == 1));
+    }
+}
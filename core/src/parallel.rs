@@ -0,0 +1,78 @@
+//! Independent replays over one state, sharded across threads.
+//!
+//! Transactions traced against the same state without committing between them (a
+//! block's transactions replayed over one snapshot, or candidate calls) do not depend on
+//! each other, so they can run side by side. Each shard layers its own [`CacheDB`] over
+//! the shared state: what one trace loads stays cached for the next in that shard, and
+//! the shared state is only read.
+
+use revm::context::{BlockEnv, TxEnv};
+use revm::database::{CacheDB, DatabaseRef};
+
+use crate::config::OptimizerConfig;
+use crate::error::HammerError;
+use crate::replay::generate_with;
+use crate::types::OptimizedAccessList;
+
+/// Apply `f` to each of `items` over `db`, on up to `jobs` threads, and return the
+/// results in input order. Items are split into contiguous shards, one per thread; with
+/// `jobs` at most 1 they run on the calling thread.
+pub fn replay_parallel<'db, DB, T, R, F>(db: &'db DB, items: Vec<T>, jobs: usize, f: F) -> Vec<R>
+where
+    DB: DatabaseRef + Sync,
+    T: Send,
+    R: Send,
+    F: Fn(&mut CacheDB<&'db DB>, T) -> R + Sync,
+{
+    let shard_len = items.len().div_ceil(jobs.max(1)).max(1);
+    if shard_len >= items.len() {
+        let mut layer = CacheDB::new(db);
+        return items.into_iter().map(|item| f(&mut layer, item)).collect();
+    }
+    let mut shards: Vec<Vec<T>> = Vec::new();
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        shards.push(items.by_ref().take(shard_len).collect());
+    }
+    let f = &f;
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = shards
+            .into_iter()
+            .map(|shard| {
+                scope.spawn(move || {
+                    let mut layer = CacheDB::new(db);
+                    shard
+                        .into_iter()
+                        .map(|item| f(&mut layer, item))
+                        .collect::<Vec<R>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| match handle.join() {
+                Ok(results) => results,
+                Err(panic) => std::panic::resume_unwind(panic),
+            })
+            .collect()
+    })
+}
+
+/// [`crate::generate_with`] for each of `txs` over the same state, on up to `jobs`
+/// threads. Nothing is committed between transactions: each sees `db` as it is, so use
+/// [`crate::generate_queue_with`] when later transactions depend on earlier ones.
+pub fn generate_many_parallel<DB>(
+    db: &DB,
+    txs: Vec<TxEnv>,
+    block: &BlockEnv,
+    config: &OptimizerConfig,
+    jobs: usize,
+) -> Vec<Result<OptimizedAccessList, HammerError>>
+where
+    DB: DatabaseRef + Sync,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    replay_parallel(db, txs, jobs, |layer, tx| {
+        generate_with(layer, tx, block.clone(), config)
+    })
+}
@@ -0,0 +1,533 @@
+//! Tracing a transaction and turning the trace into a list or a report: the entry points
+//! most callers want. Needs the `evm` feature.
+
+use alloy_eip2930::AccessList;
+use alloy_primitives::{Address, Bytes};
+use revm::context::{BlockEnv, TxEnv};
+use revm::context_interface::transaction::TransactionType;
+use revm::database::{Database, DatabaseCommit};
+use revm::primitives::hardfork::SpecId;
+
+use crate::budget::{check_budget, GasBudget};
+use crate::bundle::merge_bundle;
+use crate::calls::{CallFrame, CallPath};
+use crate::cancel::CancellationToken;
+use crate::config::OptimizerConfig;
+use crate::error::HammerError;
+use crate::gas::{access_list_gas_cost, intrinsic_gas, Hardfork, Refund, AUTHORIZATION_COST};
+use crate::optimizer::{optimize, optimize_with, optimize_with_strategy, Strategy};
+use crate::pad::{self, PaddedList};
+use crate::self_check::{self_check, SelfCheck};
+use crate::split::{self, SplitPlan};
+use crate::tracer::{
+    generate_access_list, generate_access_list_at_spec, generate_access_list_cancellable,
+    generate_access_list_for_calls, generate_access_list_per_call, QueueTraces, RevmTracer, Tracer,
+};
+use crate::types::{OptimizedAccessList, RawTraceResult, ValidationReport};
+use crate::validator;
+
+/// The address a transaction executes at: the call target, or for CREATE transactions
+/// the address the contract deploys to (`keccak256(rlp([sender, nonce]))`), which is warm
+/// for the whole transaction just like a call target.
+pub fn tx_target(tx: &TxEnv) -> Address {
+    match tx.kind {
+        revm::primitives::TxKind::Call(addr) => addr,
+        revm::primitives::TxKind::Create => tx.caller.create(tx.nonce),
+    }
+}
+
+/// Generate an optimized access list for the given transaction.
+pub fn generate<DB>(db: DB, tx: TxEnv, block: BlockEnv) -> Result<OptimizedAccessList, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_with(db, tx, block, &OptimizerConfig::default())
+}
+
+/// [`generate`] with caller-supplied warmth (see [`OptimizerConfig`]).
+pub fn generate_with<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+) -> Result<OptimizedAccessList, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_with_strategy(db, tx, block, config, Strategy::Complete)
+}
+
+/// [`generate_with`], keeping the touched entries `strategy` picks (see [`Strategy`]).
+pub fn generate_with_strategy<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+    strategy: Strategy,
+) -> Result<OptimizedAccessList, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_traced(&mut RevmTracer::new(db), tx, block, config, strategy)
+}
+
+/// [`generate_with`] over the trace `tracer` produces, for a backend other than the
+/// built-in EVM (see [`Tracer`]).
+pub fn generate_with_tracer<T: Tracer>(
+    tracer: &mut T,
+    tx: TxEnv,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+) -> Result<OptimizedAccessList, HammerError> {
+    generate_traced(tracer, tx, block, config, Strategy::Complete)
+}
+
+fn generate_traced<T: Tracer>(
+    tracer: &mut T,
+    tx: TxEnv,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+    strategy: Strategy,
+) -> Result<OptimizedAccessList, HammerError> {
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let raw = tracer.trace(tx, block)?;
+    Ok(optimize_with_strategy(
+        raw, tx_from, tx_to, coinbase, config, strategy,
+    ))
+}
+
+/// [`generate_with_strategy`], then the transaction traced again with the list declared,
+/// to check that the list does not change what it does (see [`self_check`]). Both runs
+/// read the same state; nothing is committed between them.
+pub fn generate_self_checked<DB>(
+    mut db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+    strategy: Strategy,
+) -> Result<(OptimizedAccessList, SelfCheck), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let without = generate_access_list(&mut db, tx.clone(), block.clone(), false)?;
+    let optimized =
+        optimize_with_strategy(without.clone(), tx_from, tx_to, coinbase, config, strategy);
+    let mut listed = TxEnv {
+        access_list: optimized.list.clone(),
+        ..tx
+    };
+    // revm applies a list only to typed transactions.
+    if listed.tx_type == TransactionType::Legacy as u8 {
+        listed.tx_type = TransactionType::Eip2930 as u8;
+    }
+    let with = generate_access_list(&mut db, listed, block, false)?;
+    let check = self_check(&without, &with);
+    Ok((optimized, check))
+}
+
+/// Generate an optimized access list for one internal call of the transaction, as if it
+/// were sent as a transaction of its own: from the call's caller to its target, with
+/// nothing warmed by the rest of the transaction (see [`generate_access_list_for_calls`]).
+pub fn generate_entry_call<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    path: &CallPath,
+    config: &OptimizerConfig,
+) -> Result<(CallFrame, OptimizedAccessList), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let coinbase = block.beneficiary;
+    let (frame, raw) =
+        generate_access_list_for_calls(db, tx, block, std::slice::from_ref(path))?.remove(0);
+    let optimized = optimize_with(raw, frame.caller, frame.target, coinbase, config);
+    Ok((frame, optimized))
+}
+
+/// [`generate_with`], plus one plan per entry of `split_points` for sending the
+/// transaction's top-level calls as several transactions (see [`split`]). Parts whose
+/// list has more than `max_size` entries make their plan not fit.
+pub fn generate_split_plans<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    split_points: &[Vec<usize>],
+    max_size: Option<usize>,
+    config: &OptimizerConfig,
+) -> Result<(OptimizedAccessList, Vec<SplitPlan>), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let (raw, calls) = generate_access_list_per_call(db, tx, block)?;
+    let plans = split_points
+        .iter()
+        .map(|points| split::plan_split(&calls, points, tx_from, tx_to, coinbase, config, max_size))
+        .collect::<Result<_, _>>()?;
+    Ok((optimize_with(raw, tx_from, tx_to, coinbase, config), plans))
+}
+
+/// [`generate_with`], plus the list padded with what the same call touches with each of
+/// `calldata` instead of its own (see [`pad`]). Runs that revert are left out of the
+/// padding. Every run reads the same state; nothing is committed between them.
+pub fn generate_padded<DB>(
+    mut db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    calldata: &[Bytes],
+    config: &OptimizerConfig,
+) -> Result<(OptimizedAccessList, PaddedList), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let optimized = optimize_with(
+        generate_access_list(&mut db, tx.clone(), block.clone(), false)?,
+        tx_from,
+        tx_to,
+        coinbase,
+        config,
+    );
+    let mut lists = vec![optimized.list.clone()];
+    let mut reverted = 0;
+    for data in calldata {
+        let variant = TxEnv {
+            data: data.clone(),
+            ..tx.clone()
+        };
+        let raw = generate_access_list(&mut db, variant, block.clone(), false)?;
+        if !raw.success {
+            reverted += 1;
+            continue;
+        }
+        lists.push(optimize_with(raw, tx_from, tx_to, coinbase, config).list);
+    }
+    Ok((optimized, pad::pad(&lists, reverted)))
+}
+
+/// [`generate_with`] that stops early, failing with [`HammerError::Cancelled`], once
+/// `cancel` is cancelled.
+pub fn generate_cancellable<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+    cancel: &CancellationToken,
+) -> Result<OptimizedAccessList, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let raw = generate_access_list_cancellable(db, tx, block, false, cancel)?;
+    Ok(optimize_with(raw, tx_from, tx_to, coinbase, config))
+}
+
+/// Generate optimized access lists for a queue of transactions sent back to back (e.g.
+/// one sender's pending burst, nonces in order). Each transaction runs on the state the
+/// previous ones left, so its list reflects slots they wrote and contracts they deployed.
+pub fn generate_queue<DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+) -> Result<Vec<OptimizedAccessList>, HammerError>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_queue_with(db, txs, block, &OptimizerConfig::default())
+}
+
+/// Generate access lists for an ordered batch of transactions, e.g. a bundle's, each on
+/// the state the earlier ones left. The same as [`generate_queue`], under the name bundle
+/// builders look for.
+pub fn generate_batch<DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+) -> Result<Vec<OptimizedAccessList>, HammerError>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_queue(db, txs, block)
+}
+
+/// [`generate_queue`] with caller-supplied warmth (see [`OptimizerConfig`]).
+pub fn generate_queue_with<DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+) -> Result<Vec<OptimizedAccessList>, HammerError>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_queue_iter(db, txs, block, config).collect()
+}
+
+/// [`generate_queue_with`] as an iterator: each transaction is traced and optimized only
+/// when reached, so block-scale consumers can stream lists out as they are produced
+/// instead of holding every one in memory. Ends after the first error.
+pub fn generate_queue_iter<'a, DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+    config: &'a OptimizerConfig,
+) -> impl Iterator<Item = Result<OptimizedAccessList, HammerError>> + 'a
+where
+    DB: Database + DatabaseCommit + 'a,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let coinbase = block.beneficiary;
+    let participants: Vec<(Address, Address)> =
+        txs.iter().map(|tx| (tx.caller, tx_target(tx))).collect();
+    QueueTraces::new(db, txs, block)
+        .zip(participants)
+        .map(move |(raw, (tx_from, tx_to))| {
+            raw.map(|raw| optimize_with(raw, tx_from, tx_to, coinbase, config))
+        })
+}
+
+/// One access list for a bundle (e.g. a Flashbots bundle): its transactions run back to
+/// back as in [`generate_queue`], and their lists are merged so that an entry an earlier
+/// transaction lists is not repeated for a later one (see [`merge_bundle`]).
+pub fn generate_bundle<DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+) -> Result<OptimizedAccessList, HammerError>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    generate_bundle_with(db, txs, block, &OptimizerConfig::default())
+}
+
+/// [`generate_bundle`] with caller-supplied warmth (see [`OptimizerConfig`]).
+pub fn generate_bundle_with<DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+    config: &OptimizerConfig,
+) -> Result<OptimizedAccessList, HammerError>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    Ok(merge_bundle(&generate_queue_with(db, txs, block, config)?))
+}
+
+/// Validate a declared access list against the optimal one from execution trace.
+pub fn validate<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    declared: AccessList,
+) -> Result<ValidationReport, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    validate_with(db, tx, block, declared, &OptimizerConfig::default())
+}
+
+/// [`validate`] with caller-supplied warmth (see [`OptimizerConfig`]).
+pub fn validate_with<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    declared: AccessList,
+    config: &OptimizerConfig,
+) -> Result<ValidationReport, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    validate_candidates(db, tx, block, std::slice::from_ref(&declared), config)
+        .map(|mut reports| reports.remove(0))
+}
+
+/// [`validate_with`] for several declared lists of the same transaction, e.g. from
+/// different generators: one trace, one report per list, in order.
+pub fn validate_candidates<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    candidates: &[AccessList],
+    config: &OptimizerConfig,
+) -> Result<Vec<ValidationReport>, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    validate_candidates_with_tracer(&mut RevmTracer::new(db), tx, block, candidates, config)
+}
+
+/// [`validate_with`] over the trace `tracer` produces (see [`Tracer`]).
+pub fn validate_with_tracer<T: Tracer>(
+    tracer: &mut T,
+    tx: TxEnv,
+    block: BlockEnv,
+    declared: AccessList,
+    config: &OptimizerConfig,
+) -> Result<ValidationReport, HammerError> {
+    validate_candidates_with_tracer(tracer, tx, block, std::slice::from_ref(&declared), config)
+        .map(|mut reports| reports.remove(0))
+}
+
+/// [`validate_candidates`] over the trace `tracer` produces (see [`Tracer`]).
+pub fn validate_candidates_with_tracer<T: Tracer>(
+    tracer: &mut T,
+    tx: TxEnv,
+    block: BlockEnv,
+    candidates: &[AccessList],
+    config: &OptimizerConfig,
+) -> Result<Vec<ValidationReport>, HammerError> {
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let gas = TxGas::of(&tx);
+    let raw = tracer.trace(tx, block)?;
+    let budget = gas.budget(&raw);
+    let optimal = optimize_with(raw, tx_from, tx_to, coinbase, config);
+
+    Ok(candidates
+        .iter()
+        .map(|declared| {
+            let mut report =
+                validator::validate_with(declared, &optimal, tx_from, tx_to, coinbase, config);
+            report.budget_exceeded = check_budget(declared, &report, &budget);
+            report
+        })
+        .collect())
+}
+
+/// Validate for replay (e.g. compare): skips nonce check so mined txs can be replayed.
+pub fn validate_replay<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    declared: AccessList,
+) -> Result<ValidationReport, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let gas = TxGas::of(&tx);
+    let raw = RevmTracer::replay(db).trace(tx, block)?;
+    let budget = gas.budget(&raw);
+    let optimal = optimize(raw, tx_from, tx_to, coinbase);
+
+    let mut report = validator::validate(&declared, &optimal, tx_from, tx_to, coinbase);
+    report.budget_exceeded = check_budget(&declared, &report, &budget);
+    Ok(report)
+}
+
+/// [`validate_replay`] under the rules of fork `spec` (e.g. Berlin for a transaction mined
+/// before London), priced with that fork's [`crate::GasSchedule`] and also returning how its
+/// refund was paid under the fork's cap.
+pub fn validate_replay_at_spec<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    declared: AccessList,
+    spec: SpecId,
+) -> Result<(ValidationReport, Refund), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let config = OptimizerConfig {
+        schedule: Hardfork::from_spec(spec)
+            .unwrap_or(Hardfork::Berlin)
+            .schedule(),
+        ..Default::default()
+    };
+    validate_replay_at_spec_with(db, tx, block, declared, spec, &config)
+}
+
+/// [`validate_replay_at_spec`] with `config` (e.g. a chain's warm addresses) in place of
+/// the fork's defaults. Its schedule should be the fork's.
+pub fn validate_replay_at_spec_with<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    declared: AccessList,
+    spec: SpecId,
+    config: &OptimizerConfig,
+) -> Result<(ValidationReport, Refund), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let tx_from = tx.caller;
+    let tx_to = tx_target(&tx);
+    let coinbase = block.beneficiary;
+    let gas = TxGas::of(&tx);
+    let raw = generate_access_list_at_spec(db, tx, block, true, spec)?;
+    let budget = gas.budget(&raw);
+    let refund = Refund {
+        gas_spent: raw.gas_used + raw.gas_refunded,
+        counter: raw.refund_counter,
+        quotient: config.schedule.max_refund_quotient,
+    };
+    let optimal = optimize_with(raw, tx_from, tx_to, coinbase, config);
+
+    let mut report =
+        validator::validate_with(&declared, &optimal, tx_from, tx_to, coinbase, config);
+    report.budget_exceeded = check_budget(&declared, &report, &budget);
+    Ok((report, refund))
+}
+
+/// A transaction's gas limit and upfront gas, taken before tracing consumes it.
+struct TxGas {
+    gas_limit: u64,
+    intrinsic_gas: u64,
+    /// Intrinsic gas plus the cost of the list the trace ran with, if any.
+    upfront: u64,
+}
+
+impl TxGas {
+    fn of(tx: &TxEnv) -> Self {
+        let intrinsic_gas = intrinsic_gas(&tx.data, tx.kind.is_create())
+            + tx.authorization_list.len() as u64 * AUTHORIZATION_COST;
+        Self {
+            gas_limit: tx.gas_limit,
+            intrinsic_gas,
+            upfront: intrinsic_gas + access_list_gas_cost(&tx.access_list),
+        }
+    }
+
+    /// The budget, with execution measured by `raw`, a trace of the transaction.
+    fn budget(&self, raw: &RawTraceResult) -> GasBudget {
+        GasBudget {
+            gas_limit: self.gas_limit,
+            intrinsic_gas: self.intrinsic_gas,
+            execution_gas: (raw.gas_used + raw.gas_refunded).saturating_sub(self.upfront),
+        }
+    }
+}
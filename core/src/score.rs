@@ -0,0 +1,153 @@
+//! One comparable number for how much a declared list costs beyond the optimal one.
+//!
+//! A list can lose gas two ways: upfront, by declaring more than execution needs, and at
+//! runtime, by missing what execution reads cold. The two trade off (a list that misses
+//! an address is cheaper upfront), so neither alone ranks lists. Their sum does: lower is
+//! better, and the optimal list scores zero.
+
+use alloy_eip2930::AccessList;
+use alloy_primitives::{Address, B256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::gas::GasSchedule;
+use crate::types::{OptimizedAccessList, ValidationReport};
+
+/// What a declared list costs against the optimal one, and the parts that add up to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ListScore {
+    pub declared_list_cost: u64,
+    pub optimal_list_cost: u64,
+    /// Declared list cost less the optimal one; negative when the declared list is cheaper
+    /// because it misses entries.
+    pub upfront_waste: i64,
+    /// Runtime gas lost to slots execution reads cold because the list misses them.
+    pub runtime_penalty: u64,
+    /// `upfront_waste + runtime_penalty`: the expected cost delta of sending the declared
+    /// list instead of the optimal one.
+    pub net_cost: i64,
+}
+
+impl ListScore {
+    fn new(declared_list_cost: u64, optimal_list_cost: u64, runtime_penalty: u64) -> Self {
+        let upfront_waste = declared_list_cost as i64 - optimal_list_cost as i64;
+        Self {
+            declared_list_cost,
+            optimal_list_cost,
+            upfront_waste,
+            runtime_penalty,
+            net_cost: upfront_waste + runtime_penalty as i64,
+        }
+    }
+
+    /// The score of the list a validation report was made for.
+    pub fn of(report: &ValidationReport) -> Self {
+        let runtime_penalty = report
+            .entries
+            .iter()
+            .filter(|e| e.is_shortfall())
+            .map(|e| e.gas_waste())
+            .sum();
+        let summary = &report.gas_summary;
+        Self {
+            declared_list_cost: summary.declared_list_cost,
+            optimal_list_cost: summary.optimal_list_cost,
+            upfront_waste: summary.waste_per_tx,
+            runtime_penalty,
+            net_cost: summary.waste_per_tx + runtime_penalty as i64,
+        }
+    }
+}
+
+/// Score `declared` against the traced optimum, priced with `schedule`. Agrees with
+/// [`ListScore::of`] the report [`crate::validator::validate_with`] makes for the same lists.
+pub fn score_access_list(
+    declared: &AccessList,
+    optimal: &OptimizedAccessList,
+    schedule: &GasSchedule,
+) -> ListScore {
+    let mut declared_slots: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    for item in &declared.0 {
+        declared_slots
+            .entry(item.address)
+            .or_default()
+            .extend(item.storage_keys.iter().copied());
+    }
+    let missed: usize = optimal
+        .list
+        .0
+        .iter()
+        .map(|item| {
+            let declared = declared_slots.get(&item.address);
+            item.storage_keys
+                .iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .filter(|slot| !declared.is_some_and(|d| d.contains(*slot)))
+                .count()
+        })
+        .sum();
+    ListScore::new(
+        schedule.access_list_cost(declared),
+        schedule.access_list_cost(&optimal.list),
+        missed as u64 * schedule.cold_sload_surcharge(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validator::validate;
+    use alloy_eip2930::AccessListItem;
+
+    fn list(items: &[(u8, &[u8])]) -> AccessList {
+        AccessList(
+            items
+                .iter()
+                .map(|&(a, slots)| AccessListItem {
+                    address: Address::with_last_byte(a),
+                    storage_keys: slots.iter().map(|&s| B256::with_last_byte(s)).collect(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_score_trades_upfront_against_runtime() {
+        let optimal = OptimizedAccessList::new(list(&[(0x40, &[1, 2]), (0x41, &[])]), vec![]);
+        let schedule = GasSchedule::default();
+
+        let exact = score_access_list(&optimal.list, &optimal, &schedule);
+        assert_eq!(exact.net_cost, 0);
+
+        // Misses slot 2 (1,900 cheaper upfront, 2,000 lost at runtime) and declares an
+        // untouched address (2,400 wasted).
+        let declared = list(&[(0x40, &[1]), (0x41, &[]), (0x42, &[])]);
+        let score = score_access_list(&declared, &optimal, &schedule);
+        assert_eq!(
+            score,
+            ListScore {
+                declared_list_cost: 3 * 2_400 + 1_900,
+                optimal_list_cost: 2 * 2_400 + 2 * 1_900,
+                upfront_waste: 2_400 - 1_900,
+                runtime_penalty: 2_000,
+                net_cost: 2_400 - 1_900 + 2_000,
+            }
+        );
+
+        let (from, to, coinbase) = (
+            Address::with_last_byte(0x50),
+            Address::with_last_byte(0x51),
+            Address::with_last_byte(0x52),
+        );
+        for declared in [declared, list(&[]), list(&[(0x40, &[1, 1, 2, 3])])] {
+            let report = validate(&declared, &optimal, from, to, coinbase);
+            assert_eq!(
+                ListScore::of(&report),
+                score_access_list(&declared, &optimal, &schedule)
+            );
+        }
+    }
+}
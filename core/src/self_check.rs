@@ -0,0 +1,172 @@
+//! Tracing again with the list applied, to catch gas-dependent behavior.
+//!
+//! A list changes what the transaction pays for state access, and code can branch on gas:
+//! `gasleft()` checks, calls that forward a share of the remaining gas, loops that stop
+//! when gas runs low. A list built from a run without it may then describe a path the
+//! transaction no longer takes once the list is sent. A second run with the list declared
+//! shows whether the calls made and the state touched held.
+
+use alloy_primitives::{Address, B256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::calls::CallPath;
+use crate::types::RawTraceResult;
+
+/// How the run with the list compared to the run without it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SelfCheck {
+    pub success_without: bool,
+    pub success_with: bool,
+    /// First internal call that differs between the runs, or that only one of them made.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub diverging_call: Option<CallPath>,
+    /// State only the run without the list touched. An address the other run never
+    /// touched comes with all of its slots.
+    pub only_without: BTreeMap<Address, BTreeSet<B256>>,
+    /// State only the run with the list touched.
+    pub only_with: BTreeMap<Address, BTreeSet<B256>>,
+}
+
+impl SelfCheck {
+    /// Whether both runs ended the same way, made the same calls and touched the same state.
+    pub fn is_consistent(&self) -> bool {
+        self.success_without == self.success_with
+            && self.diverging_call.is_none()
+            && self.only_without.is_empty()
+            && self.only_with.is_empty()
+    }
+}
+
+/// Compare a trace without the list to one of the same transaction with it.
+pub fn self_check(without: &RawTraceResult, with: &RawTraceResult) -> SelfCheck {
+    let diverging_call = (0..without.calls.len().max(with.calls.len())).find_map(|i| {
+        match (without.calls.get(i), with.calls.get(i)) {
+            (Some(a), Some(b)) if a == b => None,
+            (Some(frame), _) | (None, Some(frame)) => Some(frame.path.clone()),
+            (None, None) => None,
+        }
+    });
+    let (touched_without, touched_with) = (touched(without), touched(with));
+    SelfCheck {
+        success_without: without.success,
+        success_with: with.success,
+        diverging_call,
+        only_without: difference(&touched_without, &touched_with),
+        only_with: difference(&touched_with, &touched_without),
+    }
+}
+
+fn touched(raw: &RawTraceResult) -> BTreeMap<Address, BTreeSet<B256>> {
+    let mut touched: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    for item in &raw.access_list.0 {
+        touched
+            .entry(item.address)
+            .or_default()
+            .extend(item.storage_keys.iter().copied());
+    }
+    touched
+}
+
+fn difference(
+    a: &BTreeMap<Address, BTreeSet<B256>>,
+    b: &BTreeMap<Address, BTreeSet<B256>>,
+) -> BTreeMap<Address, BTreeSet<B256>> {
+    a.iter()
+        .filter_map(|(address, slots)| match b.get(address) {
+            None => Some((*address, slots.clone())),
+            Some(other) => {
+                let only: BTreeSet<B256> = slots.difference(other).copied().collect();
+                (!only.is_empty()).then_some((*address, only))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calls::{CallFrame, CallKind};
+    use alloy_eip2930::{AccessList, AccessListItem};
+
+    fn raw(items: &[(u8, &[u8])], targets: &[u8], success: bool) -> RawTraceResult {
+        RawTraceResult {
+            access_list: AccessList(
+                items
+                    .iter()
+                    .map(|&(a, slots)| AccessListItem {
+                        address: Address::with_last_byte(a),
+                        storage_keys: slots.iter().map(|&s| B256::with_last_byte(s)).collect(),
+                    })
+                    .collect(),
+            ),
+            created_contracts: vec![],
+            authorities: vec![],
+            new_accounts: vec![],
+            precompile_calls: Default::default(),
+            callbacks: vec![],
+            oracle_reads: Default::default(),
+            state_diff: Default::default(),
+            logs: vec![],
+            calls: targets
+                .iter()
+                .enumerate()
+                .map(|(i, &t)| CallFrame {
+                    path: CallPath(vec![i]),
+                    kind: CallKind::Call,
+                    caller: Address::with_last_byte(0x40),
+                    target: Address::with_last_byte(t),
+                    code_address: None,
+                    selector: None,
+                })
+                .collect(),
+            access_counts: Default::default(),
+            gas_sensitive: vec![],
+            transient: Default::default(),
+            gas_used: 0,
+            gas_refunded: 0,
+            refund_counter: 0,
+            success,
+        }
+    }
+
+    #[test]
+    fn test_identical_runs_are_consistent() {
+        let run = raw(&[(0x41, &[1, 2])], &[0x41], true);
+        assert!(self_check(&run, &run.clone()).is_consistent());
+    }
+
+    #[test]
+    fn test_gas_dependent_branch_diverges() {
+        // With the list, enough gas is left for a second call that reads another pool.
+        let without = raw(&[(0x41, &[1, 2])], &[0x41], true);
+        let with = raw(&[(0x41, &[1]), (0x42, &[7])], &[0x41, 0x42], true);
+        let check = self_check(&without, &with);
+        assert!(!check.is_consistent());
+        assert_eq!(check.diverging_call, Some(CallPath(vec![1])));
+        assert_eq!(
+            check.only_without,
+            BTreeMap::from([(
+                Address::with_last_byte(0x41),
+                BTreeSet::from([B256::with_last_byte(2)])
+            )])
+        );
+        assert_eq!(
+            check.only_with,
+            BTreeMap::from([(
+                Address::with_last_byte(0x42),
+                BTreeSet::from([B256::with_last_byte(7)])
+            )])
+        );
+
+        let reverted = raw(&[(0x41, &[1, 2])], &[0x41], false);
+        let check = self_check(&without, &reverted);
+        assert!(check.diverging_call.is_none());
+        assert!(!check.is_consistent());
+    }
+}
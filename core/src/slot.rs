@@ -0,0 +1,101 @@
+//! Solidity storage slot arithmetic — mapping and dynamic array key derivation.
+
+use alloy_primitives::{keccak256, B256, U256};
+
+/// Storage slot of `mapping[key]` for a mapping declared at slot `base`.
+///
+/// Solidity computes `keccak256(pad32(key) ++ pad32(base))` for value-type keys.
+pub fn mapping_slot(base: B256, key: B256) -> B256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(key.as_slice());
+    buf[32..].copy_from_slice(base.as_slice());
+    keccak256(buf)
+}
+
+/// Storage slot of `mapping[k0][k1]...[kn]` for a nested mapping declared at slot `base`.
+///
+/// Each level hashes the next key against the slot produced by the previous level.
+pub fn nested_mapping_slot(base: B256, keys: &[B256]) -> B256 {
+    keys.iter().fold(base, |slot, key| mapping_slot(slot, *key))
+}
+
+/// Storage slot of `array[index]` for a dynamic array declared at slot `base`.
+///
+/// Elements start at `keccak256(pad32(base))` and occupy `element_slots` slots each
+/// (1 for value types up to 32 bytes; larger for structs and static arrays). Packed
+/// elements smaller than 32 bytes are not supported — pass `element_slots = 1` and
+/// divide the index by the packing factor yourself.
+pub fn array_element_slot(base: B256, index: U256, element_slots: u64) -> B256 {
+    let start = U256::from_be_bytes(keccak256(base.as_slice()).0);
+    let offset = index.wrapping_mul(U256::from(element_slots));
+    B256::from(start.wrapping_add(offset).to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{address, b256};
+
+    fn word(n: u64) -> B256 {
+        B256::from(U256::from(n).to_be_bytes())
+    }
+
+    #[test]
+    fn test_mapping_slot_address_key() {
+        // balanceOf[0xd8dA...6045] with balanceOf at slot 0 (standard OpenZeppelin ERC20 layout).
+        let holder = address!("d8dA6BF26964aF9D7eEd9e03E53415D37aA96045");
+        let slot = mapping_slot(word(0), holder.into_word());
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(holder.as_slice());
+        assert_eq!(slot, keccak256(preimage));
+    }
+
+    #[test]
+    fn test_mapping_slot_known_vector() {
+        // keccak256(pad32(0) ++ pad32(0)) — the first entry of a mapping at slot 0.
+        assert_eq!(
+            mapping_slot(word(0), word(0)),
+            b256!("ad3228b676f7d3cd4284a5443f17f1962b36e491b30a40b2405849e597ba5fb5")
+        );
+    }
+
+    #[test]
+    fn test_nested_mapping_slot_folds_keys() {
+        let base = word(3);
+        let (k1, k2) = (word(7), word(9));
+        assert_eq!(
+            nested_mapping_slot(base, &[k1, k2]),
+            mapping_slot(mapping_slot(base, k1), k2)
+        );
+    }
+
+    #[test]
+    fn test_nested_mapping_slot_no_keys_is_base() {
+        assert_eq!(nested_mapping_slot(word(3), &[]), word(3));
+    }
+
+    #[test]
+    fn test_array_element_slot_first_element() {
+        // keccak256(pad32(2)) is the data start for a dynamic array declared at slot 2.
+        let start = keccak256(word(2).as_slice());
+        assert_eq!(array_element_slot(word(2), U256::ZERO, 1), start);
+    }
+
+    #[test]
+    fn test_array_element_slot_struct_stride() {
+        let start = U256::from_be_bytes(keccak256(word(2).as_slice()).0);
+        let slot = array_element_slot(word(2), U256::from(4), 3);
+        assert_eq!(U256::from_be_bytes(slot.0), start + U256::from(12));
+    }
+
+    #[test]
+    fn test_array_element_slot_wraps_at_max() {
+        // Slot arithmetic is modulo 2^256, exactly like the EVM.
+        let slot = array_element_slot(word(2), U256::MAX, 2);
+        let start = U256::from_be_bytes(keccak256(word(2).as_slice()).0);
+        assert_eq!(
+            U256::from_be_bytes(slot.0),
+            start.wrapping_sub(U256::from(2))
+        );
+    }
+}
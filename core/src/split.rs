@@ -0,0 +1,228 @@
+//! Splitting one batch transaction into several along the calls its target makes.
+//!
+//! A batch (multicall, router, airdrop) whose access list is too large can be sent as
+//! several transactions to the same target, each carrying a contiguous run of the
+//! original top-level calls. A part's list is what its calls touched in the original
+//! trace plus the accounts they called, optimized as if the part were sent alone. The
+//! parts are not re-simulated against each other's state, so the lists are exact only
+//! when no call depends on state an earlier part writes.
+
+use alloy_eip2930::{AccessList, AccessListItem};
+use alloy_primitives::{Address, B256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use crate::calls::CallFrame;
+use crate::config::OptimizerConfig;
+use crate::error::HammerError;
+use crate::gas::{access_list_gas_cost, TX_BASE_COST};
+use crate::optimizer::optimize_with;
+use crate::types::RawTraceResult;
+
+/// One transaction of a plan.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SplitPart {
+    /// Top-level calls of the original transaction this one carries.
+    pub calls: Range<usize>,
+    pub list: AccessList,
+    /// Upfront gas cost of `list`.
+    pub list_cost: u64,
+    /// Entries in `list` (see [`list_size`]).
+    pub size: usize,
+}
+
+/// The original transaction's top-level calls cut at `split_points`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SplitPlan {
+    /// Index of the first call of every part after the first; empty for the unsplit batch.
+    pub split_points: Vec<usize>,
+    pub parts: Vec<SplitPart>,
+    /// Sum of the parts' list costs.
+    pub list_cost: u64,
+    /// `list_cost` plus the base cost of each transaction.
+    pub upfront_cost: u64,
+    /// Whether every part's list is within the size budget (always true without one).
+    pub fits: bool,
+}
+
+/// Entries in an access list: addresses plus storage keys.
+pub fn list_size(list: &AccessList) -> usize {
+    list.0.iter().map(|item| 1 + item.storage_keys.len()).sum()
+}
+
+/// Plan sending `calls` (each top-level call with its own trace, see
+/// [`crate::tracer::generate_access_list_per_call`]) as one transaction per run between
+/// `split_points`, each from `tx_from` to `tx_to`.
+///
+/// Split points must be strictly increasing and fall between calls, so no part is empty.
+pub fn plan_split(
+    calls: &[(CallFrame, RawTraceResult)],
+    split_points: &[usize],
+    tx_from: Address,
+    tx_to: Address,
+    coinbase: Address,
+    config: &OptimizerConfig,
+    max_size: Option<usize>,
+) -> Result<SplitPlan, HammerError> {
+    let mut bounds = vec![0];
+    for &point in split_points {
+        if point == 0 || point >= calls.len() {
+            return Err(HammerError::InvalidSplit(format!(
+                "split point {} must fall between the {} top-level calls",
+                point,
+                calls.len()
+            )));
+        }
+        if point <= *bounds.last().unwrap() {
+            return Err(HammerError::InvalidSplit(format!(
+                "split points must be strictly increasing, got {} after {}",
+                point,
+                bounds.last().unwrap()
+            )));
+        }
+        bounds.push(point);
+    }
+    bounds.push(calls.len());
+
+    let parts: Vec<SplitPart> = bounds
+        .windows(2)
+        .map(|w| {
+            let range = w[0]..w[1];
+            let raw = part_trace(&calls[range.clone()]);
+            let list = optimize_with(raw, tx_from, tx_to, coinbase, config).list;
+            SplitPart {
+                calls: range,
+                list_cost: access_list_gas_cost(&list),
+                size: list_size(&list),
+                list,
+            }
+        })
+        .collect();
+
+    let list_cost = parts.iter().map(|part| part.list_cost).sum::<u64>();
+    Ok(SplitPlan {
+        split_points: split_points.to_vec(),
+        upfront_cost: list_cost + parts.len() as u64 * TX_BASE_COST,
+        fits: max_size.is_none_or(|max| parts.iter().all(|part| part.size <= max)),
+        parts,
+        list_cost,
+    })
+}
+
+/// Merge the traces of a run of calls, adding each call's target and code account, which
+/// the original transaction warmed outside the calls themselves.
+fn part_trace(calls: &[(CallFrame, RawTraceResult)]) -> RawTraceResult {
+    let mut touched: BTreeMap<Address, BTreeSet<B256>> = BTreeMap::new();
+    let mut created_contracts = Vec::new();
+    for (frame, raw) in calls {
+        touched.entry(frame.target).or_default();
+        if let Some(code) = frame.code_address {
+            touched.entry(code).or_default();
+        }
+        for item in &raw.access_list.0 {
+            touched
+                .entry(item.address)
+                .or_default()
+                .extend(item.storage_keys.iter().copied());
+        }
+        created_contracts.extend(raw.created_contracts.iter().copied());
+    }
+    RawTraceResult {
+        access_list: AccessList(
+            touched
+                .into_iter()
+                .map(|(address, slots)| AccessListItem {
+                    address,
+                    storage_keys: slots.into_iter().collect(),
+                })
+                .collect(),
+        ),
+        created_contracts,
+        authorities: Vec::new(),
+        new_accounts: Vec::new(),
+        precompile_calls: BTreeMap::new(),
+        callbacks: Vec::new(),
+        oracle_reads: BTreeMap::new(),
+        state_diff: Vec::new(),
+        logs: Vec::new(),
+        calls: Vec::new(),
+        access_counts: BTreeMap::new(),
+        gas_sensitive: Vec::new(),
+        transient: BTreeMap::new(),
+        gas_used: calls.iter().map(|(_, raw)| raw.gas_used).sum(),
+        gas_refunded: 0,
+        refund_counter: 0,
+        success: calls.iter().all(|(_, raw)| raw.success),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calls::{CallKind, CallPath};
+
+    fn addr(n: u8) -> Address {
+        Address::with_last_byte(n)
+    }
+
+    fn call(index: usize, target: Address, slots: &[u8]) -> (CallFrame, RawTraceResult) {
+        let frame = CallFrame {
+            path: CallPath(vec![index]),
+            kind: CallKind::Call,
+            caller: addr(2),
+            target,
+            code_address: None,
+            selector: None,
+        };
+        let mut raw = part_trace(&[]);
+        raw.access_list = AccessList(vec![AccessListItem {
+            address: target,
+            storage_keys: slots.iter().map(|n| B256::with_last_byte(*n)).collect(),
+        }]);
+        (frame, raw)
+    }
+
+    #[test]
+    fn test_parts_carry_their_calls_targets_and_slots() {
+        let calls = vec![
+            call(0, addr(0x40), &[1]),
+            call(1, addr(0x41), &[1, 2]),
+            call(2, addr(0x40), &[3]),
+        ];
+        let config = OptimizerConfig::default();
+
+        let whole = plan_split(&calls, &[], addr(1), addr(2), addr(3), &config, Some(4)).unwrap();
+        assert_eq!(whole.parts.len(), 1);
+        assert_eq!(whole.parts[0].size, 6);
+        assert!(!whole.fits);
+        assert_eq!(whole.upfront_cost, whole.list_cost + TX_BASE_COST);
+
+        let split = plan_split(&calls, &[1], addr(1), addr(2), addr(3), &config, Some(4)).unwrap();
+        assert_eq!(split.parts[0].calls, 0..1);
+        assert_eq!(split.parts[1].calls, 1..3);
+        assert_eq!(split.parts[0].size, 2);
+        assert_eq!(split.parts[1].size, 5);
+        assert!(!split.fits);
+        // addr(0x40) is declared by both parts.
+        assert!(split.list_cost > whole.list_cost);
+        assert_eq!(split.upfront_cost, split.list_cost + 2 * TX_BASE_COST);
+    }
+
+    #[test]
+    fn test_invalid_split_points_rejected() {
+        let calls = vec![call(0, addr(0x40), &[]), call(1, addr(0x41), &[])];
+        let config = OptimizerConfig::default();
+        for points in [&[0][..], &[2], &[1, 1]] {
+            let err = plan_split(&calls, points, addr(1), addr(2), addr(3), &config, None);
+            assert!(
+                matches!(err, Err(HammerError::InvalidSplit(_))),
+                "{:?}",
+                points
+            );
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! Post-execution state diff: what a traced transaction changed, read off the same
+//! execution that produced the access list.
+
+use alloy_primitives::{Address, B256, U256};
+#[cfg(feature = "evm")]
+use revm::state::{Account, EvmState};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A value before and after execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+#[cfg(feature = "evm")]
+impl<T: PartialEq> Change<T> {
+    fn of(from: T, to: T) -> Option<Self> {
+        (from != to).then_some(Self { from, to })
+    }
+}
+
+/// Everything execution changed on one account. Unchanged fields are `None`/empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AccountDiff {
+    pub address: Address,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub balance: Option<Change<U256>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub nonce: Option<Change<u64>>,
+    /// Code hash change: a deployment, or a contract destroyed in the same transaction.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code_hash: Option<Change<B256>>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub storage: BTreeMap<B256, Change<B256>>,
+}
+
+#[cfg(feature = "evm")]
+/// Diff of every account whose balance, nonce, code or storage changed, by address.
+pub fn state_diff(state: &EvmState) -> Vec<AccountDiff> {
+    let mut diffs: Vec<AccountDiff> = state
+        .iter()
+        .filter(|(_, account)| account.is_touched())
+        .filter_map(|(address, account)| account_diff(*address, account))
+        .collect();
+    diffs.sort_by_key(|d| d.address);
+    diffs
+}
+
+#[cfg(feature = "evm")]
+fn account_diff(address: Address, account: &Account) -> Option<AccountDiff> {
+    let before = &account.original_info;
+    let destroyed = account.is_selfdestructed();
+    let after = if destroyed {
+        Default::default()
+    } else {
+        account.info.clone()
+    };
+    let storage: BTreeMap<B256, Change<B256>> = account
+        .storage
+        .iter()
+        .filter_map(|(key, slot)| {
+            let present = if destroyed {
+                U256::ZERO
+            } else {
+                slot.present_value
+            };
+            Change::of(slot.original_value, present)
+                .map(|c| (B256::from(*key), Change::of_words(c)))
+        })
+        .collect();
+    let diff = AccountDiff {
+        address,
+        balance: Change::of(before.balance, after.balance),
+        nonce: Change::of(before.nonce, after.nonce),
+        code_hash: Change::of(before.code_hash, after.code_hash),
+        storage,
+    };
+    let changed = diff.balance.is_some()
+        || diff.nonce.is_some()
+        || diff.code_hash.is_some()
+        || !diff.storage.is_empty();
+    changed.then_some(diff)
+}
+
+#[cfg(feature = "evm")]
+impl Change<U256> {
+    /// Storage words as 32-byte values, the way access lists and RPCs show them.
+    fn of_words(self) -> Change<B256> {
+        Change {
+            from: B256::from(self.from),
+            to: B256::from(self.to),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "evm"))]
+mod tests {
+    use super::*;
+    use revm::state::{AccountInfo, EvmStorageSlot};
+
+    fn touched(info: AccountInfo) -> Account {
+        let mut account = Account::from(info);
+        account.mark_touch();
+        account
+    }
+
+    #[test]
+    fn test_unchanged_and_untouched_accounts_skipped() {
+        let mut state = EvmState::default();
+        state.insert(Address::repeat_byte(1), touched(AccountInfo::default()));
+        let mut loaded = Account::from(AccountInfo::default());
+        loaded.info.balance = U256::from(5);
+        state.insert(Address::repeat_byte(2), loaded);
+        assert!(state_diff(&state).is_empty());
+    }
+
+    #[test]
+    fn test_balance_nonce_and_storage_changes() {
+        let mut account = touched(AccountInfo {
+            balance: U256::from(100),
+            nonce: 7,
+            ..Default::default()
+        });
+        account.info.balance = U256::from(40);
+        account.info.nonce = 8;
+        account.storage.insert(
+            U256::from(1),
+            EvmStorageSlot::new_changed(U256::from(3), U256::from(4), 0),
+        );
+        account
+            .storage
+            .insert(U256::from(2), EvmStorageSlot::new(U256::from(9), 0));
+        let mut state = EvmState::default();
+        state.insert(Address::repeat_byte(1), account);
+
+        let diff = state_diff(&state);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(
+            diff[0].balance,
+            Some(Change {
+                from: U256::from(100),
+                to: U256::from(40)
+            })
+        );
+        assert_eq!(diff[0].nonce, Some(Change { from: 7, to: 8 }));
+        assert_eq!(diff[0].code_hash, None);
+        assert_eq!(
+            diff[0].storage,
+            BTreeMap::from([(
+                B256::with_last_byte(1),
+                Change {
+                    from: B256::with_last_byte(3),
+                    to: B256::with_last_byte(4)
+                }
+            )])
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! State patched in before tracing, for transactions that depend on state not yet on chain.
+//!
+//! An approval still in the mempool, a balance topped up by an earlier transaction of the
+//! same bundle, a contract about to be upgraded: the transaction takes a different path
+//! against the chain as it is. Overrides follow `eth_call`'s state override set, keyed by
+//! address. They change what the accounts hold, not whether they start warm, so an
+//! overridden account is listed like any other.
+
+use alloy_primitives::{Address, Bytes, B256, U256};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// What to replace in one account. Fields left `None` keep the account's own.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(rename_all = "camelCase", deny_unknown_fields)
+)]
+pub struct AccountOverride {
+    /// The balance, in wei.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub balance: Option<U256>,
+    /// The nonce; the sender's is used as the transaction's.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none", with = "quantity")
+    )]
+    pub nonce: Option<u64>,
+    /// The runtime bytecode.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub code: Option<Bytes>,
+    /// Replaces all of the account's storage: slots not listed read zero.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub state: Option<BTreeMap<B256, B256>>,
+    /// Sets the listed slots and keeps the rest.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub state_diff: Option<BTreeMap<B256, B256>>,
+}
+
+/// Overrides by account, in `eth_call`'s schema.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct StateOverrides(pub BTreeMap<Address, AccountOverride>);
+
+impl StateOverrides {
+    /// Whether no account is overridden.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Reject overrides that contradict themselves: `state` and `stateDiff` for the same
+    /// account, as nodes do.
+    pub fn check(&self) -> Result<(), crate::HammerError> {
+        match self
+            .0
+            .iter()
+            .find(|(_, o)| o.state.is_some() && o.state_diff.is_some())
+        {
+            Some((address, _)) => Err(crate::HammerError::InvalidStateOverride(format!(
+                "{} sets both state and stateDiff",
+                address
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Write the overrides into `db`, over whatever it holds or would fetch.
+    #[cfg(feature = "evm")]
+    pub fn apply<ExtDB>(
+        &self,
+        db: &mut revm::database::CacheDB<ExtDB>,
+    ) -> Result<(), crate::HammerError>
+    where
+        ExtDB: revm::DatabaseRef,
+        ExtDB::Error: std::error::Error + Send + Sync + 'static,
+    {
+        use revm::Database;
+
+        self.check()?;
+        for (&address, account) in &self.0 {
+            let mut info = db
+                .basic(address)
+                .map_err(|e| crate::HammerError::RpcError(Box::new(e)))?
+                .unwrap_or_default();
+            if let Some(balance) = account.balance {
+                info.balance = balance;
+            }
+            if let Some(nonce) = account.nonce {
+                info.nonce = nonce;
+            }
+            if let Some(code) = &account.code {
+                let code = revm::bytecode::Bytecode::new_raw(code.clone());
+                info.code_hash = code.hash_slow();
+                info.code = Some(code);
+            }
+            db.insert_account_info(address, info);
+            if let Some(state) = &account.state {
+                let storage = state.iter().map(|(slot, value)| {
+                    (U256::from_be_bytes(slot.0), U256::from_be_bytes(value.0))
+                });
+                db.replace_account_storage(address, storage.collect())
+                    .map_err(|e| crate::HammerError::RpcError(Box::new(e)))?;
+            }
+            for (slot, value) in account.state_diff.iter().flatten() {
+                db.insert_account_storage(
+                    address,
+                    U256::from_be_bytes(slot.0),
+                    U256::from_be_bytes(value.0),
+                )
+                .map_err(|e| crate::HammerError::RpcError(Box::new(e)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `Option<u64>` as a hex quantity, as JSON-RPC writes nonces.
+#[cfg(feature = "serde")]
+mod quantity {
+    use alloy_primitives::U64;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<u64>, serializer: S) -> Result<S::Ok, S::Error> {
+        value.map(U64::from).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u64>, D::Error> {
+        Ok(Option::<U64>::deserialize(deserializer)?.map(|n| n.to::<u64>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(account: AccountOverride) -> StateOverrides {
+        StateOverrides(BTreeMap::from([(Address::with_last_byte(0x40), account)]))
+    }
+
+    #[test]
+    fn test_state_and_state_diff_conflict() {
+        let slots = BTreeMap::from([(B256::ZERO, B256::with_last_byte(1))]);
+        let both = overrides(AccountOverride {
+            state: Some(slots.clone()),
+            state_diff: Some(slots.clone()),
+            ..Default::default()
+        });
+        assert!(both.check().is_err());
+        let diff_only = overrides(AccountOverride {
+            state_diff: Some(slots),
+            ..Default::default()
+        });
+        assert!(diff_only.check().is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_parses_eth_call_schema() {
+        let json = r#"{
+            "0x0000000000000000000000000000000000000040": {
+                "balance": "0xde0b6b3a7640000",
+                "nonce": "0x7",
+                "code": "0x600054",
+                "stateDiff": {
+                    "0x0000000000000000000000000000000000000000000000000000000000000001": "0x00000000000000000000000000000000000000000000000000000000000000ff"
+                }
+            }
+        }"#;
+        let parsed: StateOverrides = serde_json::from_str(json).unwrap();
+        let account = &parsed.0[&Address::with_last_byte(0x40)];
+        assert_eq!(
+            account.balance,
+            Some(U256::from(1_000_000_000_000_000_000u64))
+        );
+        assert_eq!(account.nonce, Some(7));
+        assert_eq!(account.code, Some(Bytes::from_static(&[0x60, 0x00, 0x54])));
+        assert_eq!(account.state, None);
+        assert_eq!(
+            account.state_diff,
+            Some(BTreeMap::from([(
+                B256::with_last_byte(1),
+                B256::with_last_byte(0xff)
+            )]))
+        );
+        assert!(serde_json::from_str::<StateOverrides>(
+            r#"{"0x0000000000000000000000000000000000000040": {"balanc": "0x1"}}"#
+        )
+        .is_err());
+    }
+
+    #[cfg(feature = "evm")]
+    #[test]
+    fn test_apply_patches_fetched_state() {
+        use revm::database::{CacheDB, EmptyDB};
+        use revm::state::AccountInfo;
+        use revm::{Database, DatabaseRef};
+
+        let address = Address::with_last_byte(0x40);
+        let mut db = CacheDB::new(EmptyDB::default());
+        db.insert_account_info(address, AccountInfo::from_balance(U256::from(5)));
+        db.insert_account_storage(address, U256::from(1), U256::from(10))
+            .unwrap();
+        db.insert_account_storage(address, U256::from(2), U256::from(20))
+            .unwrap();
+
+        overrides(AccountOverride {
+            nonce: Some(3),
+            state_diff: Some(BTreeMap::from([(
+                B256::with_last_byte(2),
+                B256::with_last_byte(99),
+            )])),
+            ..Default::default()
+        })
+        .apply(&mut db)
+        .unwrap();
+        let info = db.basic(address).unwrap().unwrap();
+        assert_eq!((info.balance, info.nonce), (U256::from(5), 3));
+        assert_eq!(
+            db.storage_ref(address, U256::from(1)).unwrap(),
+            U256::from(10)
+        );
+        assert_eq!(
+            db.storage_ref(address, U256::from(2)).unwrap(),
+            U256::from(99)
+        );
+
+        overrides(AccountOverride {
+            state: Some(BTreeMap::from([(
+                B256::with_last_byte(3),
+                B256::with_last_byte(30),
+            )])),
+            ..Default::default()
+        })
+        .apply(&mut db)
+        .unwrap();
+        assert_eq!(db.storage_ref(address, U256::from(1)).unwrap(), U256::ZERO);
+        assert_eq!(
+            db.storage_ref(address, U256::from(3)).unwrap(),
+            U256::from(30)
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Addresses with protocol-defined roles, per chain: one registry instead of constants
+//! scattered through the optimizer and validator.
+//!
+//! Ethereum's precompiles are warm everywhere (see [`crate::warm`]). Other chains add
+//! their own: Arbitrum's ArbOS precompiles and the OP Stack's P256 verifier are warm from
+//! the start of every transaction, so listing them only costs gas. Predeploys and system
+//! contracts are ordinary accounts that start cold; they are registered so they can be
+//! named, not stripped.
+
+use alloy_primitives::{address, Address};
+use std::collections::BTreeSet;
+
+/// An address the chain gives a role of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemContract {
+    pub address: Address,
+    pub name: &'static str,
+    /// Warm from the start of every transaction, like a precompile.
+    pub warm: bool,
+}
+
+const fn warm(address: Address, name: &'static str) -> SystemContract {
+    SystemContract {
+        address,
+        name,
+        warm: true,
+    }
+}
+
+const fn cold(address: Address, name: &'static str) -> SystemContract {
+    SystemContract {
+        address,
+        name,
+        warm: false,
+    }
+}
+
+/// System contracts of EIP-4788, EIP-2935, EIP-7002 and EIP-7251, deployed at the same
+/// address on Ethereum and the chains that follow its forks.
+const ETHEREUM: &[SystemContract] = &[
+    cold(
+        address!("000F3df6D732807Ef1319fB7B8bB8522d0Beac02"),
+        "BeaconRoots",
+    ),
+    cold(
+        address!("0000F90827F1C53a10cb7A02335B175320002935"),
+        "HistoryStorage",
+    ),
+    cold(
+        address!("00000961Ef480Eb55e80D19ad83579A64c007002"),
+        "WithdrawalRequests",
+    ),
+    cold(
+        address!("0000BBdDc7CE488642fb579F8B00f3a590007251"),
+        "ConsolidationRequests",
+    ),
+];
+
+/// OP Stack predeploys, and the RIP-7212 precompile activated in Fjord.
+const OP_STACK: &[SystemContract] = &[
+    warm(
+        address!("0000000000000000000000000000000000000100"),
+        "P256Verify",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000006"),
+        "WETH9",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000007"),
+        "L2CrossDomainMessenger",
+    ),
+    cold(
+        address!("420000000000000000000000000000000000000F"),
+        "GasPriceOracle",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000010"),
+        "L2StandardBridge",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000011"),
+        "SequencerFeeVault",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000015"),
+        "L1Block",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000016"),
+        "L2ToL1MessagePasser",
+    ),
+    cold(
+        address!("4200000000000000000000000000000000000019"),
+        "BaseFeeVault",
+    ),
+    cold(
+        address!("420000000000000000000000000000000000001A"),
+        "L1FeeVault",
+    ),
+];
+
+/// ArbOS precompiles, warm like Ethereum's.
+const ARBITRUM: &[SystemContract] = &[
+    warm(
+        address!("0000000000000000000000000000000000000064"),
+        "ArbSys",
+    ),
+    warm(
+        address!("0000000000000000000000000000000000000065"),
+        "ArbInfo",
+    ),
+    warm(
+        address!("0000000000000000000000000000000000000066"),
+        "ArbAddressTable",
+    ),
+    warm(
+        address!("0000000000000000000000000000000000000068"),
+        "ArbFunctionTable",
+    ),
+    warm(
+        address!("000000000000000000000000000000000000006b"),
+        "ArbOwnerPublic",
+    ),
+    warm(
+        address!("000000000000000000000000000000000000006c"),
+        "ArbGasInfo",
+    ),
+    warm(
+        address!("000000000000000000000000000000000000006d"),
+        "ArbAggregator",
+    ),
+    warm(
+        address!("000000000000000000000000000000000000006e"),
+        "ArbRetryableTx",
+    ),
+    warm(
+        address!("000000000000000000000000000000000000006f"),
+        "ArbStatistics",
+    ),
+    warm(
+        address!("0000000000000000000000000000000000000070"),
+        "ArbOwner",
+    ),
+    warm(
+        address!("0000000000000000000000000000000000000071"),
+        "ArbWasm",
+    ),
+    warm(
+        address!("0000000000000000000000000000000000000072"),
+        "ArbWasmCache",
+    ),
+];
+
+/// The registry tables that apply on `chain_id`. Unknown chains get Ethereum's.
+fn tables(chain_id: u64) -> &'static [&'static [SystemContract]] {
+    match chain_id {
+        // OP Mainnet, Base, and their Sepolia testnets.
+        10 | 8453 | 11155420 | 84532 => &[ETHEREUM, OP_STACK],
+        // Arbitrum One, Arbitrum Nova, Arbitrum Sepolia.
+        42161 | 42170 | 421614 => &[ETHEREUM, ARBITRUM],
+        _ => &[ETHEREUM],
+    }
+}
+
+/// Every registered address on `chain_id`.
+pub fn system_contracts(chain_id: u64) -> impl Iterator<Item = &'static SystemContract> {
+    tables(chain_id).iter().flat_map(|table| table.iter())
+}
+
+/// The registered role of `address` on `chain_id`, if any.
+pub fn system_contract(chain_id: u64, address: &Address) -> Option<&'static SystemContract> {
+    system_contracts(chain_id).find(|c| c.address == *address)
+}
+
+/// Addresses warm from the start on `chain_id` beyond Ethereum's precompiles, for
+/// [`crate::OptimizerConfig::chain_warm`].
+pub fn chain_warm_addresses(chain_id: u64) -> BTreeSet<Address> {
+    system_contracts(chain_id)
+        .filter(|c| c.warm)
+        .map(|c| c.address)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_warm_addresses() {
+        assert!(chain_warm_addresses(1).is_empty());
+        let arb_sys = address!("0000000000000000000000000000000000000064");
+        assert!(chain_warm_addresses(42161).contains(&arb_sys));
+        assert!(!chain_warm_addresses(10).contains(&arb_sys));
+        assert_eq!(
+            system_contract(8453, &address!("4200000000000000000000000000000000000015")),
+            Some(&cold(
+                address!("4200000000000000000000000000000000000015"),
+                "L1Block"
+            ))
+        );
+        // No address is registered twice on a chain.
+        for chain in [1, 10, 42161] {
+            let all: Vec<_> = system_contracts(chain).map(|c| c.address).collect();
+            let unique: BTreeSet<_> = all.iter().collect();
+            assert_eq!(all.len(), unique.len());
+        }
+    }
+}
@@ -1,32 +1,151 @@
 //! Access list extraction via revm execution tracing.
 
-use alloy_primitives::Address;
-use alloy_rpc_types_eth::AccessList;
-use revm::context::{BlockEnv, TxEnv};
+use alloy_eip2930::AccessList;
+use alloy_primitives::map::HashMap;
+use alloy_primitives::{Address, FixedBytes, B256, U256};
+use revm::bytecode::opcode;
+use revm::context::result::ExecutionResult;
+use revm::context::{BlockEnv, JournalTr, TxEnv};
+use revm::context_interface::transaction::{AuthorizationTr, TransactionType};
 use revm::context_interface::ContextTr;
-use revm::database::Database;
+use revm::context_interface::CreateScheme;
+use revm::context_interface::LocalContextTr;
+use revm::database::{Database, DatabaseCommit};
 use revm::inspector::{Inspector, JournalExt};
+use revm::interpreter::interpreter_types::{InputsTr, Jumps};
+use revm::interpreter::{CallInput, CallScheme, InstructionResult, InterpreterResult};
+use revm::primitives::hardfork::SpecId;
+use revm::state::{AccountStatus, EvmState};
 use revm::{Context, InspectEvm, MainBuilder, MainContext};
 use revm_inspectors::access_list::AccessListInspector;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::callback::{classify, Callback};
+use crate::calls::{CallFrame, CallKind, CallPath};
+use crate::cancel::CancellationToken;
 use crate::error::HammerError;
-use crate::types::RawTraceResult;
+use crate::gas_sensitive::{GasSensitivity, GAS_BRANCH_WINDOW};
+use crate::oracle::is_oracle_read;
+use crate::state_diff::state_diff;
+use crate::types::{AddressAccesses, RawTraceResult};
+use crate::warm::is_precompile;
 
-/// Inspector wrapper that extends AccessListInspector with tracking of
-/// contracts created via nested CREATE/CREATE2.
-pub struct HammerInspector {
+/// Slots touched so far, by address, as [`AccessListInspector`] records them.
+type Touched = HashMap<Address, BTreeSet<B256>>;
+
+/// A call frame whose new accesses are attributed when it ends.
+enum Watched {
+    /// Index into `HammerInspector::callbacks`.
+    Callback(usize),
+    /// A price-feed read (see [`crate::oracle`]).
+    OracleRead,
+}
+
+/// A call chosen as the unit of analysis: everything touched while it runs (including
+/// state its parent had already warmed), recorded as if it were the transaction.
+pub(crate) struct Entry {
+    path: CallPath,
+    /// The call, once it has started.
+    frame: Option<CallFrame>,
+    open: bool,
     inner: AccessListInspector,
-    created_contracts: HashSet<Address>,
+    created_contracts: BTreeSet<Address>,
+    new_accounts: BTreeSet<Address>,
+    precompile_calls: BTreeMap<Address, u64>,
+    gas_used: u64,
+    success: bool,
 }
 
-impl Default for HammerInspector {
-    fn default() -> Self {
+impl Entry {
+    fn new(path: CallPath) -> Self {
         Self {
+            path,
+            frame: None,
+            open: false,
             inner: AccessListInspector::default(),
-            created_contracts: HashSet::new(),
+            created_contracts: BTreeSet::new(),
+            new_accounts: BTreeSet::new(),
+            precompile_calls: BTreeMap::new(),
+            gas_used: 0,
+            success: false,
         }
     }
+
+    /// The call and its trace; fails if the path named no call. Whole-transaction
+    /// annotations (callbacks, feed reads, state diff, logs, access counts) are left empty.
+    fn into_result(self) -> Result<(CallFrame, RawTraceResult), HammerError> {
+        let frame = self.frame.ok_or_else(|| {
+            HammerError::InvalidCallPath(format!("no call at {} in the trace", self.path))
+        })?;
+        let raw = RawTraceResult {
+            access_list: self.inner.into_access_list(),
+            created_contracts: self.created_contracts.into_iter().collect(),
+            authorities: Vec::new(),
+            new_accounts: self.new_accounts.into_iter().collect(),
+            precompile_calls: self.precompile_calls,
+            callbacks: Vec::new(),
+            oracle_reads: BTreeMap::new(),
+            state_diff: Vec::new(),
+            logs: Vec::new(),
+            calls: Vec::new(),
+            access_counts: BTreeMap::new(),
+            gas_sensitive: Vec::new(),
+            transient: BTreeMap::new(),
+            gas_used: self.gas_used,
+            gas_refunded: 0,
+            refund_counter: 0,
+            success: self.success,
+        };
+        Ok((frame, raw))
+    }
+}
+
+/// Inspector wrapper that extends AccessListInspector with tracking of
+/// contracts created via nested CREATE/CREATE2.
+///
+/// The target address of a CREATE/CREATE2 is precomputed when the frame starts, because
+/// EIP-2929 warms it before the init code runs and keeps it warm even if the creation
+/// reverts. An address that was already touched *before* its creation (e.g. an
+/// `EXTCODESIZE` check on a precomputed CREATE2 address) paid the cold surcharge at
+/// that point, so it is not treated as created.
+#[derive(Default)]
+pub struct HammerInspector {
+    inner: AccessListInspector,
+    created_contracts: BTreeSet<Address>,
+    /// Precomputed target of each open CREATE frame; `None` if touched before creation.
+    pending_creates: Vec<Option<Address>>,
+    /// Empty accounts that received value via CALL (each paid the new-account surcharge).
+    new_accounts: BTreeSet<Address>,
+    /// Number of calls that executed each precompile.
+    precompile_calls: BTreeMap<Address, u64>,
+    /// Recognized callbacks, in the order they started.
+    callbacks: Vec<Callback>,
+    /// Slots first touched inside price-feed reads, by address.
+    oracle_reads: BTreeMap<Address, BTreeSet<B256>>,
+    /// One entry per open call frame: for a watched frame, what it is and the touched
+    /// state when it started.
+    frames: Vec<Option<(Watched, Touched)>>,
+    /// Internal calls, in the order they started.
+    calls: Vec<CallFrame>,
+    /// Touches of each address and slot, with how many paid the cold surcharge.
+    access_counts: BTreeMap<Address, AddressAccesses>,
+    /// One entry per open call frame: opcodes run since the frame last read `GAS`, while
+    /// within [`GAS_BRANCH_WINDOW`].
+    gas_reads: Vec<Option<usize>>,
+    /// Branches on the gas left and calls that ran out of it, in trace order.
+    gas_sensitive: Vec<GasSensitivity>,
+    /// Transient slots (EIP-1153) read or written, by address.
+    transient: BTreeMap<Address, BTreeSet<B256>>,
+    /// Path of the innermost open frame (empty for the top-level frame).
+    path: Vec<usize>,
+    /// Number of children started so far by each open frame.
+    children: Vec<usize>,
+    entries: Vec<Entry>,
+    /// Make an entry of every call the top-level frame makes.
+    top_level_entries: bool,
+    cancel: Option<CancellationToken>,
+    /// Refund counter of the top-level frame when it ended, before the EIP-3529 cap.
+    refund_counter: u64,
 }
 
 impl HammerInspector {
@@ -34,10 +153,249 @@ impl HammerInspector {
         Self::default()
     }
 
-    pub fn created_contracts(&self) -> &HashSet<Address> {
+    /// Also record the calls at `paths` as units of their own (see [`Entry`]).
+    pub(crate) fn with_entries(paths: &[CallPath]) -> Self {
+        Self {
+            entries: paths.iter().cloned().map(Entry::new).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn calls(&self) -> &[CallFrame] {
+        &self.calls
+    }
+
+    fn open_entries(&mut self) -> impl Iterator<Item = &mut Entry> {
+        self.entries.iter_mut().filter(|entry| entry.open)
+    }
+
+    /// Number the frame that is starting and record it, unless it is the top-level one.
+    fn enter_frame(
+        &mut self,
+        kind: CallKind,
+        caller: Address,
+        target: Address,
+        code_address: Address,
+        selector: Option<[u8; 4]>,
+    ) {
+        self.gas_reads.push(None);
+        let Some(siblings) = self.children.last_mut() else {
+            self.children.push(0);
+            return;
+        };
+        self.path.push(*siblings);
+        *siblings += 1;
+        self.children.push(0);
+        if self.top_level_entries && self.path.len() == 1 {
+            self.entries.push(Entry::new(CallPath(self.path.clone())));
+        }
+
+        let frame = CallFrame {
+            path: CallPath(self.path.clone()),
+            kind,
+            caller,
+            target,
+            code_address: (code_address != target).then_some(code_address),
+            selector: selector.map(FixedBytes),
+        };
+        for entry in &mut self.entries {
+            if entry.path.0 == self.path {
+                entry.frame = Some(frame.clone());
+                entry.open = true;
+            }
+        }
+        self.calls.push(frame);
+    }
+
+    fn exit_frame(&mut self, result: &InterpreterResult) {
+        for entry in &mut self.entries {
+            if entry.open && entry.path.0 == self.path {
+                entry.open = false;
+                entry.gas_used = result.gas.spent();
+                entry.success = result.result.is_ok();
+            }
+        }
+        if self.path.is_empty() && result.result.is_ok() {
+            self.refund_counter = result.gas.refunded().max(0) as u64;
+        }
+        if !self.path.is_empty() && is_out_of_gas(result.result) {
+            if let Some(frame) = self.calls.iter().rev().find(|c| c.path.0 == self.path) {
+                self.gas_sensitive.push(GasSensitivity::StarvedCall {
+                    path: frame.path.clone(),
+                    target: frame.target,
+                });
+            }
+        }
+        self.gas_reads.pop();
+        self.children.pop();
+        self.path.pop();
+    }
+
+    pub(crate) fn take_entries(&mut self) -> Vec<Entry> {
+        std::mem::take(&mut self.entries)
+    }
+
+    pub fn created_contracts(&self) -> &BTreeSet<Address> {
         &self.created_contracts
     }
 
+    pub fn new_accounts(&self) -> &BTreeSet<Address> {
+        &self.new_accounts
+    }
+
+    pub fn precompile_calls(&self) -> &BTreeMap<Address, u64> {
+        &self.precompile_calls
+    }
+
+    pub fn callbacks(&self) -> &[Callback] {
+        &self.callbacks
+    }
+
+    pub fn oracle_reads(&self) -> &BTreeMap<Address, BTreeSet<B256>> {
+        &self.oracle_reads
+    }
+
+    pub fn access_counts(&self) -> &BTreeMap<Address, AddressAccesses> {
+        &self.access_counts
+    }
+
+    pub fn gas_sensitive(&self) -> &[GasSensitivity] {
+        &self.gas_sensitive
+    }
+
+    pub fn transient(&self) -> &BTreeMap<Address, BTreeSet<B256>> {
+        &self.transient
+    }
+
+    /// Record a conditional jump the frame takes soon after reading `GAS`.
+    fn watch_gas(&mut self, interp: &revm::interpreter::Interpreter) {
+        let Some(window) = self.gas_reads.last_mut() else {
+            return;
+        };
+        match (interp.bytecode.opcode(), *window) {
+            (opcode::GAS, _) => *window = Some(0),
+            (_, None) => {}
+            (opcode::JUMPI, Some(_)) => {
+                *window = None;
+                let code_address = interp
+                    .input
+                    .bytecode_address()
+                    .copied()
+                    .unwrap_or_else(|| interp.input.target_address());
+                let pc = interp.bytecode.pc();
+                let seen = self.gas_sensitive.iter().any(|s| {
+                    matches!(s, GasSensitivity::Branch { code_address: a, pc: p, .. }
+                        if *a == code_address && *p == pc)
+                });
+                if !seen {
+                    self.gas_sensitive.push(GasSensitivity::Branch {
+                        path: CallPath(self.path.clone()),
+                        code_address,
+                        pc,
+                    });
+                }
+            }
+            // Forwarding gasleft() as a call's gas, or jumping unconditionally, is not a
+            // branch on it.
+            (
+                opcode::CALL
+                | opcode::STATICCALL
+                | opcode::DELEGATECALL
+                | opcode::CALLCODE
+                | opcode::JUMP,
+                _,
+            ) => *window = None,
+            (_, Some(n)) => *window = (n + 1 < GAS_BRANCH_WINDOW).then_some(n + 1),
+        }
+    }
+
+    /// Count the access the next opcode makes, cold unless the journal holds it warm.
+    /// Precompiles and the coinbase are warm without being in the journal; the optimizer
+    /// drops them whatever their counts.
+    fn count_access(&mut self, interp: &revm::interpreter::Interpreter, state: &EvmState) {
+        let word = |n: usize| {
+            interp
+                .stack
+                .peek(n)
+                .ok()
+                .map(|v| B256::from(v.to_be_bytes()))
+        };
+        let account_cold = |address: &Address| {
+            state
+                .get(address)
+                .is_none_or(|account| account.status.contains(AccountStatus::Cold))
+        };
+        match interp.bytecode.opcode() {
+            opcode::SLOAD | opcode::SSTORE => {
+                let Some(slot) = word(0) else { return };
+                let address = interp.input.target_address();
+                let cold = state
+                    .get(&address)
+                    .and_then(|account| account.storage.get(&slot.into()))
+                    .is_none_or(|value| value.is_cold);
+                self.access_counts
+                    .entry(address)
+                    .or_default()
+                    .slots
+                    .entry(slot)
+                    .or_default()
+                    .record(cold);
+            }
+            // Transient storage has no cold or warm: recorded apart, never listed.
+            opcode::TLOAD | opcode::TSTORE => {
+                let Some(slot) = word(0) else { return };
+                self.transient
+                    .entry(interp.input.target_address())
+                    .or_default()
+                    .insert(slot);
+            }
+            opcode::EXTCODECOPY
+            | opcode::EXTCODEHASH
+            | opcode::EXTCODESIZE
+            | opcode::BALANCE
+            | opcode::SELFDESTRUCT => {
+                let Some(word) = word(0) else { return };
+                let address = Address::from_word(word);
+                let cold = account_cold(&address);
+                self.access_counts
+                    .entry(address)
+                    .or_default()
+                    .account
+                    .record(cold);
+            }
+            opcode::DELEGATECALL | opcode::CALL | opcode::STATICCALL | opcode::CALLCODE => {
+                let Some(word) = word(1) else { return };
+                let address = Address::from_word(word);
+                let cold = account_cold(&address);
+                self.access_counts
+                    .entry(address)
+                    .or_default()
+                    .account
+                    .record(cold);
+            }
+            _ => {}
+        }
+    }
+
+    /// State touched since `before` was taken.
+    fn touched_since(&self, before: &Touched) -> BTreeMap<Address, BTreeSet<B256>> {
+        let mut touched = BTreeMap::new();
+        for (address, slots) in self.inner.touched_slots() {
+            match before.get(address) {
+                None => {
+                    touched.insert(*address, slots.clone());
+                }
+                Some(earlier) => {
+                    let new: BTreeSet<B256> = slots.difference(earlier).copied().collect();
+                    if !new.is_empty() {
+                        touched.insert(*address, new);
+                    }
+                }
+            }
+        }
+        touched
+    }
+
     pub fn into_access_list(self) -> AccessList {
         self.inner.into_access_list()
     }
@@ -51,7 +409,21 @@ where
     CTX: ContextTr<Journal: JournalExt>,
 {
     fn step(&mut self, interp: &mut revm::interpreter::Interpreter, context: &mut CTX) {
+        // Halting ends this frame; each parent halts at its next step in turn.
+        if self
+            .cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            interp.halt(InstructionResult::OutOfGas);
+            return;
+        }
+        self.count_access(interp, context.journal_ref().evm_state());
+        self.watch_gas(interp);
         self.inner.step(interp, context);
+        for entry in self.entries.iter_mut().filter(|entry| entry.open) {
+            entry.inner.step(interp, context);
+        }
     }
 
     fn call(
@@ -59,14 +431,120 @@ where
         context: &mut CTX,
         inputs: &mut revm::interpreter::CallInputs,
     ) -> Option<revm::interpreter::CallOutcome> {
+        // By the time a nested CALL reaches the hook, the interpreter has already loaded
+        // the target to price it, so the journal holds its pre-transfer state. The
+        // top-level call never pays the surcharge.
+        if context.journal_ref().depth() > 0
+            && inputs.scheme == CallScheme::Call
+            && inputs.transfers_value()
+        {
+            let target = inputs.target_address;
+            let is_empty = context
+                .journal_ref()
+                .evm_state()
+                .get(&target)
+                .is_none_or(|account| account.is_empty());
+            if is_empty {
+                self.new_accounts.insert(target);
+                for entry in self.open_entries() {
+                    entry.new_accounts.insert(target);
+                }
+            }
+        }
+
+        // DELEGATECALL/CALLCODE to a precompile run its code too, so key on the code address.
+        if is_precompile(&inputs.bytecode_address) {
+            *self
+                .precompile_calls
+                .entry(inputs.bytecode_address)
+                .or_default() += 1;
+            for entry in self.open_entries() {
+                *entry
+                    .precompile_calls
+                    .entry(inputs.bytecode_address)
+                    .or_default() += 1;
+            }
+        }
+
+        let selector = selector(context, &inputs.input);
+        let kind = match inputs.scheme {
+            CallScheme::Call => CallKind::Call,
+            CallScheme::StaticCall => CallKind::StaticCall,
+            CallScheme::DelegateCall => CallKind::DelegateCall,
+            CallScheme::CallCode => CallKind::CallCode,
+        };
+        self.enter_frame(
+            kind,
+            inputs.caller,
+            inputs.target_address,
+            inputs.bytecode_address,
+            selector,
+        );
+
+        let watched = selector.and_then(|selector| {
+            if let Some((function, kind)) = classify(selector) {
+                self.callbacks.push(Callback {
+                    kind,
+                    function: function.to_string(),
+                    caller: inputs.caller,
+                    target: inputs.target_address,
+                    touched: BTreeMap::new(),
+                });
+                Some(Watched::Callback(self.callbacks.len() - 1))
+            } else {
+                is_oracle_read(selector).then_some(Watched::OracleRead)
+            }
+        });
+        self.frames
+            .push(watched.map(|w| (w, self.inner.touched_slots().clone())));
+
         self.inner.call(context, inputs)
     }
 
+    fn call_end(
+        &mut self,
+        context: &mut CTX,
+        inputs: &revm::interpreter::CallInputs,
+        outcome: &mut revm::interpreter::CallOutcome,
+    ) {
+        self.inner.call_end(context, inputs, outcome);
+        self.exit_frame(&outcome.result);
+
+        if let Some(Some((watched, before))) = self.frames.pop() {
+            let touched = self.touched_since(&before);
+            match watched {
+                Watched::Callback(index) => self.callbacks[index].touched = touched,
+                Watched::OracleRead => {
+                    for (address, slots) in touched {
+                        self.oracle_reads.entry(address).or_default().extend(slots);
+                    }
+                }
+            }
+        }
+    }
+
     fn create(
         &mut self,
         context: &mut CTX,
         inputs: &mut revm::interpreter::CreateInputs,
     ) -> Option<revm::interpreter::CreateOutcome> {
+        // The caller's nonce has not been bumped yet when the hook runs.
+        let nonce = context
+            .journal_ref()
+            .evm_state()
+            .get(&inputs.caller())
+            .map_or(0, |account| account.info.nonce);
+        let target = inputs.created_address(nonce);
+        let touched_before = self.inner.touched_slots().contains_key(&target);
+        self.pending_creates
+            .push((!touched_before).then_some(target));
+
+        let kind = match inputs.scheme() {
+            CreateScheme::Create2 { .. } => CallKind::Create2,
+            _ => CallKind::Create,
+        };
+        self.enter_frame(kind, inputs.caller(), target, target, None);
+
         self.inner.create(context, inputs)
     }
 
@@ -78,9 +556,62 @@ where
     ) {
         self.inner.create_end(context, inputs, outcome);
 
-        if let Some(addr) = outcome.address {
-            self.created_contracts.insert(addr.into());
+        let target = self.pending_creates.pop().flatten();
+        // These checks fail before revm loads (and warms) the target address.
+        let warmed = !matches!(
+            outcome.instruction_result(),
+            InstructionResult::CallTooDeep | InstructionResult::OutOfFunds
+        );
+        if let Some(addr) = target.filter(|_| warmed) {
+            self.created_contracts.insert(addr);
+            for entry in self.open_entries() {
+                entry.created_contracts.insert(addr);
+            }
         }
+        self.exit_frame(&outcome.result);
+    }
+}
+
+fn is_out_of_gas(result: InstructionResult) -> bool {
+    matches!(
+        result,
+        InstructionResult::OutOfGas
+            | InstructionResult::MemoryOOG
+            | InstructionResult::MemoryLimitOOG
+            | InstructionResult::PrecompileOOG
+            | InstructionResult::InvalidOperandOOG
+            | InstructionResult::ReentrancySentryOOG
+    )
+}
+
+/// Authorities of the transaction's EIP-7702 authorizations that revm processes: those
+/// for this chain (or any chain) whose signature recovers. Each is warmed before the
+/// nonce and code checks that decide whether its delegation is applied.
+fn authorities(tx: &TxEnv, chain_id: u64) -> Vec<Address> {
+    if tx.tx_type != TransactionType::Eip7702 as u8 {
+        return Vec::new();
+    }
+    let authorities: BTreeSet<Address> = tx
+        .authorization_list
+        .iter()
+        .filter(|auth| {
+            let auth_chain = auth.chain_id();
+            (auth_chain.is_zero() || auth_chain == U256::from(chain_id)) && auth.nonce() != u64::MAX
+        })
+        .filter_map(|auth| auth.authority())
+        .collect();
+    authorities.into_iter().collect()
+}
+
+/// First four bytes of a call's input, read without copying the rest of the calldata.
+fn selector<CTX: ContextTr>(context: &CTX, input: &CallInput) -> Option<[u8; 4]> {
+    match input {
+        CallInput::Bytes(bytes) => bytes.get(..4)?.try_into().ok(),
+        CallInput::SharedBuffer(range) if range.len() >= 4 => context
+            .local()
+            .shared_memory_buffer_slice(range.start..range.start + 4)
+            .and_then(|slice| (*slice).try_into().ok()),
+        CallInput::SharedBuffer(_) => None,
     }
 }
 
@@ -101,7 +632,273 @@ where
     DB: Database,
     DB::Error: std::error::Error + Send + Sync + 'static,
 {
-    let inspector = HammerInspector::new();
+    trace(
+        db,
+        tx,
+        block,
+        disable_nonce_check,
+        None,
+        HammerInspector::new(),
+    )
+    .map(|(raw, _, _)| raw)
+}
+
+/// A backend that traces a transaction into the raw accesses the optimizer and validator
+/// work from. [`RevmTracer`] runs it in a local EVM; another implementation can source
+/// the same result elsewhere (a node's debug API, a simulation service) and reach every
+/// entry point that takes a tracer, such as [`crate::generate_with_tracer`].
+///
+/// Implementations must report what the EVM would: every address and slot the
+/// transaction touches, the transaction's sender and target included, before any
+/// warm-address optimization.
+pub trait Tracer {
+    fn trace(&mut self, tx: TxEnv, block: BlockEnv) -> Result<RawTraceResult, HammerError>;
+}
+
+/// The default [`Tracer`]: [`generate_access_list`] over `db`. The database is borrowed
+/// for each trace, so state fetched by one trace is cached for the next.
+#[derive(Debug)]
+pub struct RevmTracer<DB> {
+    db: DB,
+    disable_nonce_check: bool,
+}
+
+impl<DB> RevmTracer<DB> {
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            disable_nonce_check: false,
+        }
+    }
+
+    /// A tracer that skips the nonce check, for replaying mined transactions.
+    pub fn replay(db: DB) -> Self {
+        Self {
+            db,
+            disable_nonce_check: true,
+        }
+    }
+
+    pub fn into_inner(self) -> DB {
+        self.db
+    }
+}
+
+impl<DB> Tracer for RevmTracer<DB>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn trace(&mut self, tx: TxEnv, block: BlockEnv) -> Result<RawTraceResult, HammerError> {
+        generate_access_list(&mut self.db, tx, block, self.disable_nonce_check)
+    }
+}
+
+/// [`generate_access_list`] under the rules of fork `spec` instead of the latest, e.g.
+/// Berlin to replay a transaction mined before London with its era's refunds.
+pub fn generate_access_list_at_spec<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    disable_nonce_check: bool,
+    spec: SpecId,
+) -> Result<RawTraceResult, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    trace(
+        db,
+        tx,
+        block,
+        disable_nonce_check,
+        Some(spec),
+        HammerInspector::new(),
+    )
+    .map(|(raw, _, _)| raw)
+}
+
+/// [`generate_access_list`] that stops at the next opcode once `cancel` is cancelled,
+/// failing with [`HammerError::Cancelled`].
+pub fn generate_access_list_cancellable<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    disable_nonce_check: bool,
+    cancel: &CancellationToken,
+) -> Result<RawTraceResult, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let inspector = HammerInspector {
+        cancel: Some(cancel.clone()),
+        ..HammerInspector::default()
+    };
+    let (raw, _, _) = trace(db, tx, block, disable_nonce_check, None, inspector)?;
+    if cancel.is_cancelled() {
+        return Err(HammerError::Cancelled);
+    }
+    Ok(raw)
+}
+
+/// Trace the transaction and return each call at `paths` with a trace of its own, as if
+/// that call were the transaction: its list records everything touched inside the call,
+/// including state the transaction had warmed before the call started.
+///
+/// Fails if a path names no call in the trace, or a DELEGATECALL/CALLCODE (which runs
+/// against its caller's storage and cannot be sent on its own).
+pub fn generate_access_list_for_calls<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    paths: &[CallPath],
+) -> Result<Vec<(CallFrame, RawTraceResult)>, HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let inspector = HammerInspector::with_entries(paths);
+    let (_, _, entries) = trace(db, tx, block, false, None, inspector)?;
+    entries
+        .into_iter()
+        .map(|entry| {
+            let (frame, raw) = entry.into_result()?;
+            if frame.kind.borrows_context() {
+                return Err(HammerError::InvalidCallPath(format!(
+                    "call {} is a {} and runs in its caller's context",
+                    frame.path,
+                    frame.kind.as_str()
+                )));
+            }
+            Ok((frame, raw))
+        })
+        .collect()
+}
+
+/// Trace the transaction and also return each call the top-level frame made, in order,
+/// with a trace of its own as in [`generate_access_list_for_calls`]. DELEGATECALLs are
+/// included: a multicall that delegates to itself still splits along them.
+pub fn generate_access_list_per_call<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+) -> Result<(RawTraceResult, Vec<(CallFrame, RawTraceResult)>), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let inspector = HammerInspector {
+        top_level_entries: true,
+        ..HammerInspector::default()
+    };
+    let (raw, _, entries) = trace(db, tx, block, false, None, inspector)?;
+    let calls = entries
+        .into_iter()
+        .map(Entry::into_result)
+        .collect::<Result<_, _>>()?;
+    Ok((raw, calls))
+}
+
+/// Trace transactions in order, committing each one's state changes to `db` before the
+/// next runs, so later transactions see balances, nonces and storage written by earlier
+/// ones. Warmth does not carry over: EIP-2929 access sets are per transaction.
+///
+/// Fails on the first transaction the EVM rejects, naming its position.
+pub fn generate_access_list_queue<DB>(
+    db: DB,
+    txs: Vec<TxEnv>,
+    block: BlockEnv,
+) -> Result<Vec<RawTraceResult>, HammerError>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    QueueTraces::new(db, txs, block).collect()
+}
+
+/// [`generate_access_list_queue`] one transaction at a time: each is traced when the
+/// iterator reaches it, so a consumer can write out early results while later ones run.
+/// Yields the error of the first rejected transaction, then ends.
+pub struct QueueTraces<DB> {
+    db: DB,
+    txs: std::iter::Enumerate<std::vec::IntoIter<TxEnv>>,
+    block: BlockEnv,
+    failed: bool,
+}
+
+impl<DB> QueueTraces<DB> {
+    pub fn new(db: DB, txs: Vec<TxEnv>, block: BlockEnv) -> Self {
+        Self {
+            db,
+            txs: txs.into_iter().enumerate(),
+            block,
+            failed: false,
+        }
+    }
+}
+
+impl<DB> Iterator for QueueTraces<DB>
+where
+    DB: Database + DatabaseCommit,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    type Item = Result<RawTraceResult, HammerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let (i, tx) = self.txs.next()?;
+        let traced = trace(
+            &mut self.db,
+            tx,
+            self.block.clone(),
+            false,
+            None,
+            HammerInspector::new(),
+        );
+        match traced {
+            Ok((raw, state, _)) => {
+                self.db.commit(state);
+                Some(Ok(raw))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(match e {
+                    HammerError::EvmExecution(message) => HammerError::EvmExecution(format!(
+                        "transaction {} in queue: {}",
+                        i, message
+                    )),
+                    other => other,
+                }))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.failed {
+            true => (0, Some(0)),
+            false => (0, Some(self.txs.len())),
+        }
+    }
+}
+
+/// Run one transaction under `inspector`, returning the trace, the state it left behind
+/// (not committed) and the inspector's entry calls.
+fn trace<DB>(
+    db: DB,
+    tx: TxEnv,
+    block: BlockEnv,
+    disable_nonce_check: bool,
+    spec: Option<SpecId>,
+    inspector: HammerInspector,
+) -> Result<(RawTraceResult, EvmState, Vec<Entry>), HammerError>
+where
+    DB: Database,
+    DB::Error: std::error::Error + Send + Sync + 'static,
+{
+    let block_number = block.number.saturating_to::<u64>();
 
     let mut ctx_builder = Context::mainnet()
         .with_db(db)
@@ -110,24 +907,64 @@ where
     if disable_nonce_check {
         ctx_builder = ctx_builder.modify_cfg_chained(|cfg| cfg.disable_nonce_check = true);
     }
+    if let Some(spec) = spec {
+        ctx_builder =
+            ctx_builder.modify_cfg_chained(|cfg| cfg.set_spec_and_mainnet_gas_params(spec));
+    }
 
+    let authorities = authorities(&tx, ctx_builder.cfg.chain_id);
     let mut evm = ctx_builder.build_mainnet_with_inspector(inspector);
 
-    let result = evm
-        .inspect_one_tx(tx)
-        .map_err(|e| HammerError::EvmExecution(e.to_string()))?;
+    let output = evm
+        .inspect_tx(tx)
+        .map_err(|e| HammerError::evm(e.to_string(), block_number))?;
+    let result = output.result;
 
-    let inspector = evm.into_inspector();
+    let mut inspector = evm.into_inspector();
+    let entries = inspector.take_entries();
     let created_contracts: Vec<Address> = inspector.created_contracts().iter().copied().collect();
+    let new_accounts: Vec<Address> = inspector.new_accounts().iter().copied().collect();
+    let precompile_calls = inspector.precompile_calls().clone();
+    let callbacks = inspector.callbacks().to_vec();
+    let oracle_reads = inspector.oracle_reads().clone();
+    let calls = inspector.calls().to_vec();
+    let access_counts = inspector.access_counts().clone();
+    let gas_sensitive = inspector.gas_sensitive().to_vec();
+    let transient = inspector.transient().clone();
+    let inspector_refund = inspector.refund_counter;
     let access_list = inspector.into_access_list();
 
     let gas_used = result.gas_used();
+    let gas_refunded = match &result {
+        ExecutionResult::Success { gas_refunded, .. } => *gas_refunded,
+        _ => 0,
+    };
+    let refund_counter = inspector_refund;
     let success = result.is_success();
+    let state_diff = state_diff(&output.state);
+    let logs = result.logs().to_vec();
 
-    Ok(RawTraceResult {
-        access_list,
-        created_contracts,
-        gas_used,
-        success,
-    })
+    Ok((
+        RawTraceResult {
+            access_list,
+            created_contracts,
+            authorities,
+            new_accounts,
+            precompile_calls,
+            callbacks,
+            oracle_reads,
+            state_diff,
+            logs,
+            calls,
+            access_counts,
+            gas_sensitive,
+            transient,
+            gas_used,
+            gas_refunded,
+            refund_counter,
+            success,
+        },
+        output.state,
+        entries,
+    ))
 }
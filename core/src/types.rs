@@ -1,12 +1,23 @@
 //! Domain types for access list validation reports.
 
-use alloy_primitives::Address;
-use alloy_rpc_types_eth::AccessList;
+use alloy_eip2930::AccessList;
+use alloy_primitives::{Address, Log, B256};
+#[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::budget::BudgetExceeded;
+use crate::callback::Callback;
+use crate::calls::CallFrame;
+use crate::gas_sensitive::GasSensitivity;
+use crate::near_miss::NearMiss;
+use crate::origin::ListOrigin;
+use crate::state_diff::AccountDiff;
 
 /// A single diff entry in a validation report.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(tag = "kind", rename_all = "lowercase")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "lowercase"))]
 pub enum DiffEntry {
     /// Address/slots accessed during execution but not in the declared list.
     Missing {
@@ -26,7 +37,8 @@ pub enum DiffEntry {
         missing_slots: Vec<alloy_primitives::B256>,
         gas_waste: u64,
     },
-    /// Address in declared list that is warm-by-default (tx.from, tx.to, coinbase, precompile).
+    /// Address in declared list that is warm-by-default (tx.from, tx.to, coinbase, EIP-7702
+    /// authority, precompile).
     Redundant { address: Address, gas_waste: u64 },
     /// Same (address, slot) appears multiple times in declared list.
     Duplicate {
@@ -37,6 +49,12 @@ pub enum DiffEntry {
 }
 
 impl DiffEntry {
+    /// Whether the entry is something execution needs that the declared list lacks
+    /// (Missing or Incomplete), as opposed to something it declares needlessly.
+    pub fn is_shortfall(&self) -> bool {
+        matches!(self, Self::Missing { .. } | Self::Incomplete { .. })
+    }
+
     pub fn gas_waste(&self) -> u64 {
         match self {
             Self::Missing { gas_waste, .. }
@@ -48,8 +66,40 @@ impl DiffEntry {
     }
 }
 
+/// One edit that moves a declared list towards the optimal one. Applied in order by
+/// [`crate::apply_fixes`], the edits turn the declared list into one that validates
+/// cleanly while leaving untouched entries where they were.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "op", rename_all = "snake_case"))]
+pub enum FixEdit {
+    /// Append a new entry for an address the declared list lacks.
+    AddAddress {
+        address: Address,
+        storage_keys: Vec<alloy_primitives::B256>,
+    },
+    /// Add slots to the first existing entry for `address`.
+    AddSlots {
+        address: Address,
+        storage_keys: Vec<alloy_primitives::B256>,
+    },
+    /// Drop every entry for `address` (stale or warm by default).
+    RemoveAddress { address: Address },
+    /// Drop these slots from every entry for `address`.
+    RemoveSlots {
+        address: Address,
+        storage_keys: Vec<alloy_primitives::B256>,
+    },
+    /// Keep only the first occurrence of a repeated slot.
+    RemoveDuplicate {
+        address: Address,
+        storage_key: alloy_primitives::B256,
+    },
+}
+
 /// Gas cost summary for a validation report.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GasSummary {
     /// Gas cost of the declared access list.
     pub declared_list_cost: u64,
@@ -63,6 +113,67 @@ pub struct GasSummary {
     pub savings_vs_no_list: i64,
 }
 
+/// Why the optimizer dropped an address from the list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum RemovalReason {
+    /// The sender, warm from the start (EIP-2929).
+    TxFrom,
+    /// The call target or deployed contract, warm from the start (EIP-2929).
+    TxTo,
+    /// The block's coinbase, warm from the start (EIP-3651).
+    Coinbase,
+    /// A precompile, always warm (EIP-2929).
+    Precompile,
+    /// Warm from the start on the chain it runs on, e.g. an ArbOS precompile (see
+    /// [`crate::chain_warm_addresses`]).
+    SystemContract,
+    /// Deployed during execution, so warm once created.
+    CreatedContract,
+    /// Authority of one of the transaction's EIP-7702 authorizations, warm from the start.
+    Authority,
+    /// Declared warm by the caller (`--assume-warm`).
+    AssumedWarm,
+    /// Its touches paid too few cold surcharges to repay its upfront cost
+    /// ([`crate::Strategy::NetSavings`]).
+    NetLoss,
+}
+
+impl RemovalReason {
+    /// One-line explanation for human output.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::TxFrom => "tx.from, warm from the start (EIP-2929)",
+            Self::TxTo => "tx.to, warm from the start (EIP-2929)",
+            Self::Coinbase => "block coinbase, warm from the start (EIP-3651)",
+            Self::Precompile => "precompile, always warm (EIP-2929)",
+            Self::SystemContract => "the chain's system contract, always warm there",
+            Self::CreatedContract => "created during execution, warm once deployed",
+            Self::Authority => "EIP-7702 authority, warm from the start",
+            Self::AssumedWarm => "assumed warm (--assume-warm)",
+            Self::NetLoss => {
+                "never paid a cold surcharge, so declaring it costs more than it saves"
+            }
+        }
+    }
+}
+
+/// An address the optimizer dropped, why, and what declaring it would cost for nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RemovedAddress {
+    pub address: Address,
+    pub reason: RemovalReason,
+    /// Upfront gas of declaring it with the slots the trace touched there.
+    pub gas_waste: u64,
+    /// For an address warm only as an account (the sender, target, coinbase, an EIP-7702
+    /// authority, or a contract created during execution): slots the trace read there, each still a cold access.
+    /// Too few to repay the address charge (see [`crate::gas::WARM_ADDRESS_MIN_SLOTS`]).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cold_slots: u64,
+}
+
 /// Optimized access list with metadata about what was removed.
 #[derive(Debug, Clone)]
 pub struct OptimizedAccessList {
@@ -70,6 +181,33 @@ pub struct OptimizedAccessList {
     pub list: AccessList,
     /// Addresses that were removed (warm-by-default).
     pub removed_addresses: Vec<Address>,
+    /// `removed_addresses` with the reason for each, in the same order.
+    pub removed: Vec<RemovedAddress>,
+    /// Warm addresses kept in the list with their slots: enough of those are read cold to
+    /// repay the address charge.
+    pub warm_kept: Vec<Address>,
+    /// EIP-7702 authorities, warm from the start (see [`RawTraceResult::authorities`]).
+    pub authorities: Vec<Address>,
+    /// Accounts created by value transfers during execution (see [`RawTraceResult::new_accounts`]).
+    pub new_accounts: Vec<Address>,
+    /// Precompiles executed, with call counts (see [`RawTraceResult::precompile_calls`]).
+    pub precompile_calls: BTreeMap<Address, u64>,
+    /// Callbacks in the trace, each limited to the list entries first touched inside it.
+    pub callbacks: Vec<Callback>,
+    /// Listed slots expected to move as price feeds update: touched inside feed reads or
+    /// marked volatile in the config.
+    pub volatile: BTreeMap<Address, BTreeSet<B256>>,
+    /// State changes made by the traced execution (see [`RawTraceResult::state_diff`]).
+    pub state_diff: Vec<AccountDiff>,
+    /// Logs emitted by the traced execution (see [`RawTraceResult::logs`]).
+    pub logs: Vec<Log>,
+    /// Internal calls of the traced execution (see [`RawTraceResult::calls`]).
+    pub calls: Vec<CallFrame>,
+    /// Where the gas left could change execution once the list is attached (see
+    /// [`RawTraceResult::gas_sensitive`]).
+    pub gas_sensitive: Vec<GasSensitivity>,
+    /// Transient slots the execution used (see [`RawTraceResult::transient`]).
+    pub transient: BTreeMap<Address, BTreeSet<B256>>,
 }
 
 impl OptimizedAccessList {
@@ -77,12 +215,25 @@ impl OptimizedAccessList {
         Self {
             list,
             removed_addresses,
+            removed: Vec::new(),
+            warm_kept: Vec::new(),
+            authorities: Vec::new(),
+            new_accounts: Vec::new(),
+            precompile_calls: BTreeMap::new(),
+            callbacks: Vec::new(),
+            volatile: BTreeMap::new(),
+            state_diff: Vec::new(),
+            logs: Vec::new(),
+            calls: Vec::new(),
+            gas_sensitive: Vec::new(),
+            transient: BTreeMap::new(),
         }
     }
 }
 
 /// Full validation report comparing declared vs actual access list.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ValidationReport {
     /// Individual diff entries (missing, stale, incomplete, redundant, duplicate).
     pub entries: Vec<DiffEntry>,
@@ -90,8 +241,73 @@ pub struct ValidationReport {
     pub gas_summary: GasSummary,
     /// The optimal access list (suggested fix).
     pub optimal_list: AccessList,
-    /// Whether the declared list matches the optimal (no issues).
+    /// Whether the declared list matches the optimal (no issues). The strict verdict.
     pub is_valid: bool,
+    /// Whether the declared list covers everything execution needs: no Missing or
+    /// Incomplete entries, extra declared entries tolerated. The lenient verdict.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub is_valid_lenient: bool,
+    /// Accounts created by value transfers during execution. Each paid the 25,000 gas
+    /// new-account surcharge, which no access list can avoid — informational only.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub new_accounts: Vec<Address>,
+    /// Precompiles executed, with call counts. They are always warm, so declaring one is
+    /// Redundant even when it was called — informational only.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub precompile_calls: BTreeMap<Address, u64>,
+    /// Minimal edit script from the declared list to a valid one; empty when valid.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fixes: Vec<FixEdit>,
+    /// Heuristic guess at how the declared list was produced.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub declared_origin: ListOrigin,
+    /// Callbacks in the trace and the optimal-list entries first touched inside each.
+    /// Third-party callback code may touch different state next time — informational only.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub callbacks: Vec<Callback>,
+    /// Optimal-list slots expected to move as price feeds update; a precomputed list that
+    /// declares them goes stale when the feed does.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub volatile: BTreeMap<Address, BTreeSet<B256>>,
+    /// Stale declared slots that look like off-by-one misses of a missing slot.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub near_misses: Vec<NearMiss>,
+    /// Lists that do not fit in the transaction's gas limit next to its execution.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub budget_exceeded: Vec<BudgetExceeded>,
+    /// Where the gas left could change execution, so that attaching a list (which
+    /// changes it) could too.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub gas_sensitive: Vec<GasSensitivity>,
+    /// Transient slots (EIP-1153) the execution used. Transient storage is never cold, so
+    /// these are never listed; a declared slot with the same key is a persistent slot and
+    /// judged as one — informational only.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub transient: BTreeMap<Address, BTreeSet<B256>>,
+}
+
+/// How often a trace touched an address or slot, and how many of those touches paid
+/// the cold surcharge. A touch is cold the first time, and again after a reverted frame
+/// un-warms what it touched (EIP-2929).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessCount {
+    pub accesses: u64,
+    pub cold: u64,
+}
+
+impl AccessCount {
+    #[cfg(feature = "evm")]
+    pub(crate) fn record(&mut self, cold: bool) {
+        self.accesses += 1;
+        self.cold += cold as u64;
+    }
+}
+
+/// [`AccessCount`]s of one address: of the account itself and of each of its slots.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddressAccesses {
+    pub account: AccessCount,
+    pub slots: BTreeMap<B256, AccessCount>,
 }
 
 /// Raw result from the tracer before optimization.
@@ -99,10 +315,43 @@ pub struct ValidationReport {
 pub struct RawTraceResult {
     /// Raw access list from the inspector (before warm-address stripping).
     pub access_list: AccessList,
-    /// Addresses of contracts created during execution (CREATE/CREATE2).
+    /// Addresses warmed by CREATE/CREATE2 during execution, including creations that
+    /// reverted. Excludes targets that were accessed before being created.
     pub created_contracts: Vec<Address>,
+    /// Authorities of the transaction's EIP-7702 authorizations that revm processed,
+    /// which warms them before execution whether or not the delegation took effect.
+    pub authorities: Vec<Address>,
+    /// Previously empty accounts that received value via CALL, paying the
+    /// new-account surcharge (`NEW_ACCOUNT_COST`).
+    pub new_accounts: Vec<Address>,
+    /// Number of calls (at any depth) that executed each precompile.
+    pub precompile_calls: BTreeMap<Address, u64>,
+    /// Recognized callbacks (ERC-777 hooks, flash loans, Uniswap V3), with the state first
+    /// touched inside each.
+    pub callbacks: Vec<Callback>,
+    /// Slots first touched inside price-feed reads whose slots move with each update
+    /// (see [`crate::oracle`]).
+    pub oracle_reads: BTreeMap<Address, BTreeSet<B256>>,
+    /// Balances, nonces, code and storage the execution changed, by address.
+    pub state_diff: Vec<AccountDiff>,
+    /// Logs emitted, in execution order. Empty when the transaction reverted.
+    pub logs: Vec<Log>,
+    /// Internal calls and creations in the order they started, below the top-level frame.
+    pub calls: Vec<CallFrame>,
+    /// Touches of each address and slot in the access list, with their cold charges.
+    pub access_counts: BTreeMap<Address, AddressAccesses>,
+    /// Branches on the gas left and internal calls that ran out of it.
+    pub gas_sensitive: Vec<GasSensitivity>,
+    /// Transient slots (EIP-1153) read or written with TLOAD/TSTORE, by address. They
+    /// share keys with persistent storage but not warmth: transient access costs the same
+    /// every time, so they are kept out of `access_list` and never belong in a list.
+    pub transient: BTreeMap<Address, BTreeSet<B256>>,
     /// Gas used during execution.
     pub gas_used: u64,
+    /// Refund paid back after execution (already subtracted from `gas_used`).
+    pub gas_refunded: u64,
+    /// Refund accrued during execution, before the cap (see [`crate::gas::Refund`]).
+    pub refund_counter: u64,
     /// Whether the transaction succeeded.
     pub success: bool,
 }
@@ -110,8 +359,8 @@ pub struct RawTraceResult {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy_eip2930::AccessListItem;
     use alloy_primitives::B256;
-    use alloy_rpc_types_eth::AccessListItem;
 
     fn addr(n: u8) -> Address {
         Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
@@ -171,6 +420,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_diff_entry_serde_tag() {
         let entry = DiffEntry::Missing {
@@ -190,6 +440,7 @@ mod tests {
         assert!(json.contains(r#""kind":"stale""#));
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_diff_entry_serde_all_variants() {
         let cases: &[(&str, DiffEntry)] = &[
@@ -255,6 +506,7 @@ mod tests {
         }
     }
 
+    #[cfg(feature = "serde")]
     #[test]
     fn test_validation_report_serde_roundtrip() {
         let report = ValidationReport {
@@ -274,6 +526,17 @@ mod tests {
                 storage_keys: vec![slot(1)],
             }]),
             is_valid: false,
+            is_valid_lenient: false,
+            new_accounts: vec![addr(9)],
+            precompile_calls: BTreeMap::from([(addr(1), 2)]),
+            fixes: vec![FixEdit::RemoveAddress { address: addr(1) }],
+            declared_origin: ListOrigin::NodeGenerated,
+            callbacks: vec![],
+            volatile: BTreeMap::new(),
+            near_misses: Vec::new(),
+            budget_exceeded: Vec::new(),
+            gas_sensitive: Vec::new(),
+            transient: BTreeMap::new(),
         };
         let json = serde_json::to_string(&report).unwrap();
         let decoded: ValidationReport = serde_json::from_str(&json).unwrap();
@@ -283,6 +546,10 @@ mod tests {
             report.gas_summary.declared_list_cost
         );
         assert_eq!(decoded.entries.len(), 1);
+        assert_eq!(decoded.new_accounts, vec![addr(9)]);
+        assert_eq!(decoded.precompile_calls[&addr(1)], 2);
+        assert_eq!(decoded.fixes, report.fixes);
+        assert_eq!(decoded.declared_origin, ListOrigin::NodeGenerated);
     }
 
     #[test]
@@ -1,13 +1,14 @@
 //! Validation engine — diff declared vs actual access lists.
 
+use alloy_eip2930::AccessList;
 use alloy_primitives::Address;
-use alloy_rpc_types_eth::AccessList;
 use std::collections::{BTreeMap, BTreeSet};
 
-use crate::gas::{
-    access_list_gas_cost, ACCESS_LIST_ADDRESS_COST, ACCESS_LIST_STORAGE_KEY_COST,
-    COLD_ACCOUNT_ACCESS_COST, COLD_SLOAD_COST, WARM_STORAGE_READ_COST,
-};
+use crate::config::OptimizerConfig;
+use crate::fix::fix_edits;
+use crate::gas::GasSchedule;
+use crate::near_miss::near_misses;
+use crate::origin::classify;
 use crate::types::{DiffEntry, GasSummary, OptimizedAccessList, ValidationReport};
 use crate::warm::precompile_addresses;
 
@@ -19,6 +20,29 @@ pub fn validate(
     tx_to: Address,
     coinbase: Address,
 ) -> ValidationReport {
+    validate_with(
+        declared,
+        optimal,
+        tx_from,
+        tx_to,
+        coinbase,
+        &OptimizerConfig::default(),
+    )
+}
+
+/// [`validate`], also treating the config's `assume_warm` and `chain_warm` addresses as
+/// warm by default. Its `force_include` entries are part of `optimal`, so declaring them
+/// is never Redundant. Waste and list costs are priced with the config's [`GasSchedule`],
+/// and the coinbase is warm only where it says so.
+pub fn validate_with(
+    declared: &AccessList,
+    optimal: &OptimizedAccessList,
+    tx_from: Address,
+    tx_to: Address,
+    coinbase: Address,
+    config: &OptimizerConfig,
+) -> ValidationReport {
+    let schedule = &config.schedule;
     let precompiles = precompile_addresses();
 
     // Detect duplicate entries before merging into BTreeMap (which silently deduplicates).
@@ -32,7 +56,7 @@ pub fn validate(
                 duplicate_entries.push(DiffEntry::Duplicate {
                     address: item.address,
                     storage_key: slot,
-                    gas_waste: ACCESS_LIST_STORAGE_KEY_COST,
+                    gas_waste: schedule.access_list_storage_key_cost,
                 });
             }
         }
@@ -53,9 +77,17 @@ pub fn validate(
     let mut entries = duplicate_entries;
 
     for (addr, decl_slots) in &declared_map {
-        if *addr == tx_from || *addr == tx_to || *addr == coinbase || precompiles.contains(addr) {
-            let gas_waste =
-                ACCESS_LIST_ADDRESS_COST + (decl_slots.len() as u64) * ACCESS_LIST_STORAGE_KEY_COST;
+        let warm = *addr == tx_from
+            || *addr == tx_to
+            || (*addr == coinbase && schedule.warm_coinbase)
+            || optimal.authorities.contains(addr)
+            || precompiles.contains(addr)
+            || config.chain_warm.contains(addr)
+            || config.assume_warm.contains(addr);
+        // A warm address the optimizer kept for its slots is diffed like any other.
+        if warm && !config.force_include.contains_key(addr) && !optimal_map.contains_key(addr) {
+            let gas_waste = schedule.access_list_address_cost
+                + (decl_slots.len() as u64) * schedule.access_list_storage_key_cost;
             entries.push(DiffEntry::Redundant {
                 address: *addr,
                 gas_waste,
@@ -66,7 +98,7 @@ pub fn validate(
         if let Some(opt_slots) = optimal_map.get(addr) {
             let missing: Vec<_> = opt_slots.difference(decl_slots).copied().collect();
             if !missing.is_empty() {
-                let gas_waste = (missing.len() as u64) * (COLD_SLOAD_COST - WARM_STORAGE_READ_COST);
+                let gas_waste = (missing.len() as u64) * schedule.cold_sload_surcharge();
                 entries.push(DiffEntry::Incomplete {
                     address: *addr,
                     missing_slots: missing,
@@ -76,7 +108,7 @@ pub fn validate(
 
             let stale: Vec<_> = decl_slots.difference(opt_slots).copied().collect();
             if !stale.is_empty() {
-                let gas_waste = (stale.len() as u64) * ACCESS_LIST_STORAGE_KEY_COST;
+                let gas_waste = (stale.len() as u64) * schedule.access_list_storage_key_cost;
                 entries.push(DiffEntry::Stale {
                     address: *addr,
                     storage_keys: stale,
@@ -84,8 +116,8 @@ pub fn validate(
                 });
             }
         } else {
-            let gas_waste =
-                ACCESS_LIST_ADDRESS_COST + (decl_slots.len() as u64) * ACCESS_LIST_STORAGE_KEY_COST;
+            let gas_waste = schedule.access_list_address_cost
+                + (decl_slots.len() as u64) * schedule.access_list_storage_key_cost;
             entries.push(DiffEntry::Stale {
                 address: *addr,
                 storage_keys: decl_slots.iter().copied().collect(),
@@ -96,7 +128,7 @@ pub fn validate(
 
     for (addr, opt_slots) in &optimal_map {
         if !declared_map.contains_key(addr) {
-            let gas_waste = (opt_slots.len() as u64) * (COLD_SLOAD_COST - WARM_STORAGE_READ_COST);
+            let gas_waste = (opt_slots.len() as u64) * schedule.cold_sload_surcharge();
             entries.push(DiffEntry::Missing {
                 address: *addr,
                 storage_keys: opt_slots.iter().copied().collect(),
@@ -105,10 +137,10 @@ pub fn validate(
         }
     }
 
-    let declared_list_cost = access_list_gas_cost(declared);
-    let optimal_list_cost = access_list_gas_cost(&optimal.list);
+    let declared_list_cost = schedule.access_list_cost(declared);
+    let optimal_list_cost = schedule.access_list_cost(&optimal.list);
     let waste_per_tx = declared_list_cost as i64 - optimal_list_cost as i64;
-    let no_list_cost = compute_no_list_cost(&optimal_map);
+    let no_list_cost = compute_no_list_cost(schedule, &optimal_map);
     let savings_vs_no_list = no_list_cost as i64 - optimal_list_cost as i64;
 
     let gas_summary = GasSummary {
@@ -120,20 +152,38 @@ pub fn validate(
     };
 
     let is_valid = entries.is_empty();
+    let is_valid_lenient = !entries.iter().any(DiffEntry::is_shortfall);
+    let fixes = fix_edits(&entries, &optimal.list);
+    let declared_origin = classify(declared, &entries);
+    let near_misses = near_misses(&entries);
 
     ValidationReport {
         entries,
         gas_summary,
         optimal_list: optimal.list.clone(),
         is_valid,
+        is_valid_lenient,
+        new_accounts: optimal.new_accounts.clone(),
+        precompile_calls: optimal.precompile_calls.clone(),
+        fixes,
+        declared_origin,
+        callbacks: optimal.callbacks.clone(),
+        volatile: optimal.volatile.clone(),
+        near_misses,
+        budget_exceeded: Vec::new(),
+        gas_sensitive: optimal.gas_sensitive.clone(),
+        transient: optimal.transient.clone(),
     }
 }
 
-fn compute_no_list_cost(optimal_map: &BTreeMap<Address, BTreeSet<alloy_primitives::B256>>) -> u64 {
+fn compute_no_list_cost(
+    schedule: &GasSchedule,
+    optimal_map: &BTreeMap<Address, BTreeSet<alloy_primitives::B256>>,
+) -> u64 {
     let mut cost = 0u64;
-    for (_, slots) in optimal_map {
-        cost += COLD_ACCOUNT_ACCESS_COST;
-        cost += (slots.len() as u64) * COLD_SLOAD_COST;
+    for slots in optimal_map.values() {
+        cost += schedule.cold_account_access_cost;
+        cost += (slots.len() as u64) * schedule.cold_sload_cost;
     }
     cost
 }
@@ -141,10 +191,13 @@ fn compute_no_list_cost(optimal_map: &BTreeMap<Address, BTreeSet<alloy_primitive
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::gas::access_list_gas_cost;
+    use crate::gas::{
+        access_list_gas_cost, ACCESS_LIST_ADDRESS_COST, ACCESS_LIST_STORAGE_KEY_COST,
+        COLD_ACCOUNT_ACCESS_COST, COLD_SLOAD_COST, WARM_STORAGE_READ_COST,
+    };
     use crate::types::{DiffEntry, OptimizedAccessList};
+    use alloy_eip2930::AccessListItem;
     use alloy_primitives::B256;
-    use alloy_rpc_types_eth::AccessListItem;
 
     fn addr(n: u8) -> Address {
         Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
@@ -250,6 +303,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_redundant_assumed_warm() {
+        let config = OptimizerConfig {
+            assume_warm: BTreeSet::from([contract_b()]),
+            ..Default::default()
+        };
+        let optimal = make_optimal(vec![]);
+        let declared = make_declared(vec![(contract_b(), vec![slot(1)])]);
+        let report = validate_with(
+            &declared,
+            &optimal,
+            from_addr(),
+            to_addr(),
+            coinbase_addr(),
+            &config,
+        );
+        assert_eq!(
+            report.entries,
+            vec![DiffEntry::Redundant {
+                address: contract_b(),
+                gas_waste: ACCESS_LIST_ADDRESS_COST + ACCESS_LIST_STORAGE_KEY_COST,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_redundant_eip7702_authority() {
+        let mut optimal = make_optimal(vec![]);
+        optimal.authorities = vec![contract_b()];
+        let declared = make_declared(vec![(contract_b(), vec![])]);
+        let report = validate(&declared, &optimal, from_addr(), to_addr(), coinbase_addr());
+        assert_eq!(
+            report.entries,
+            vec![DiffEntry::Redundant {
+                address: contract_b(),
+                gas_waste: ACCESS_LIST_ADDRESS_COST,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_forced_warm_address_not_redundant() {
+        let config = OptimizerConfig {
+            force_include: BTreeMap::from([(coinbase_addr(), BTreeSet::new())]),
+            ..Default::default()
+        };
+        let optimal = make_optimal(vec![(coinbase_addr(), vec![])]);
+        let declared = make_declared(vec![(coinbase_addr(), vec![])]);
+        let report = validate_with(
+            &declared,
+            &optimal,
+            from_addr(),
+            to_addr(),
+            coinbase_addr(),
+            &config,
+        );
+        assert!(report.is_valid, "{:?}", report.entries);
+    }
+
     #[test]
     fn test_incomplete_and_stale_same_address() {
         // Optimal: {s1, s2}; Declared: {s1, s3} → Incomplete(s2) + Stale(s3)
@@ -296,6 +408,25 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_coinbase_diffed_like_any_address_before_shanghai() {
+        let config = OptimizerConfig {
+            schedule: crate::Hardfork::London.schedule(),
+            ..Default::default()
+        };
+        let optimal = make_optimal(vec![(coinbase_addr(), vec![slot(1)])]);
+        let declared = make_declared(vec![(coinbase_addr(), vec![slot(1)])]);
+        let report = validate_with(
+            &declared,
+            &optimal,
+            from_addr(),
+            to_addr(),
+            coinbase_addr(),
+            &config,
+        );
+        assert!(report.is_valid, "{:?}", report.entries);
+    }
+
     #[test]
     fn test_redundant_precompile() {
         let precompile = addr(1); // 0x01 — well within precompile range
@@ -874,6 +1005,37 @@ mod tests {
         assert!(!report2.entries.is_empty());
     }
 
+    #[test]
+    fn test_warm_address_kept_for_slots_not_redundant() {
+        let slots: Vec<B256> = (0..25).map(slot).collect();
+        let mut optimal = make_optimal(vec![(to_addr(), slots.clone())]);
+        optimal.warm_kept = vec![to_addr()];
+        let declared = make_declared(vec![(to_addr(), slots[..24].to_vec())]);
+        let report = validate(&declared, &optimal, from_addr(), to_addr(), coinbase_addr());
+        assert_eq!(report.entries.len(), 1);
+        assert!(matches!(
+            &report.entries[0],
+            DiffEntry::Incomplete { missing_slots, .. } if missing_slots == &[slot(24)]
+        ));
+    }
+
+    #[test]
+    fn test_lenient_verdict_tolerates_extra_entries() {
+        let optimal = make_optimal(vec![(contract_a(), vec![slot(1)])]);
+        let extra = make_declared(vec![
+            (contract_a(), vec![slot(1), slot(2)]),
+            (contract_b(), vec![]),
+        ]);
+        let report = validate(&extra, &optimal, from_addr(), to_addr(), coinbase_addr());
+        assert!(!report.is_valid);
+        assert!(report.is_valid_lenient);
+
+        let short = make_declared(vec![(contract_a(), vec![]), (contract_b(), vec![])]);
+        let report = validate(&short, &optimal, from_addr(), to_addr(), coinbase_addr());
+        assert!(!report.is_valid);
+        assert!(!report.is_valid_lenient);
+    }
+
     #[test]
     fn test_no_list_cost_formula() {
         // 1 address, 0 slots: no_list_cost = COLD_ACCOUNT_ACCESS_COST + 0 * COLD_SLOAD_COST
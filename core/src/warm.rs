@@ -10,6 +10,30 @@ pub fn precompile_addresses() -> BTreeSet<Address> {
         .collect()
 }
 
+/// Whether `addr` is one of the precompiles in [`precompile_addresses`].
+pub fn is_precompile(addr: &Address) -> bool {
+    addr.0[..19].iter().all(|b| *b == 0) && (1..=10).contains(&addr.0[19])
+}
+
+/// Conventional name of a precompile, for display.
+pub fn precompile_name(addr: &Address) -> Option<&'static str> {
+    if !is_precompile(addr) {
+        return None;
+    }
+    Some(match addr.0[19] {
+        1 => "ecrecover",
+        2 => "sha256",
+        3 => "ripemd160",
+        4 => "identity",
+        5 => "modexp",
+        6 => "ecadd",
+        7 => "ecmul",
+        8 => "ecpairing",
+        9 => "blake2f",
+        _ => "point_evaluation",
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -42,4 +66,27 @@ mod tests {
             "0x0b must not be in precompile set"
         );
     }
+
+    #[test]
+    fn test_is_precompile_matches_set() {
+        let set = precompile_addresses();
+        for i in 0u8..=12 {
+            assert_eq!(
+                is_precompile(&addr(i)),
+                set.contains(&addr(i)),
+                "0x{:02x}",
+                i
+            );
+        }
+        let mut high = addr(1);
+        high.0[0] = 1;
+        assert!(!is_precompile(&high));
+    }
+
+    #[test]
+    fn test_precompile_name() {
+        assert_eq!(precompile_name(&addr(1)), Some("ecrecover"));
+        assert_eq!(precompile_name(&addr(10)), Some("point_evaluation"));
+        assert_eq!(precompile_name(&addr(11)), None);
+    }
 }
@@ -2,12 +2,18 @@
 //
 // Uses revm::database::InMemoryDB to construct deterministic EVM state without any RPC calls.
 
-use alloy_primitives::{Address, Bytes, U256};
-use hammer_core::generate;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use hammer_core::{
+    generate, generate_access_list, generate_entry_call, generate_self_checked,
+    generate_split_plans, generate_with_strategy, generate_with_tracer, AccessListItem,
+    AccountOverride, CallKind, CallPath, CallbackKind, Change, GasSensitivity, HammerError,
+    OptimizerConfig, RawTraceResult, RevmTracer, StateOverrides, Strategy, Tracer,
+};
 use revm::context::{BlockEnv, TxEnv};
-use revm::database::InMemoryDB;
+use revm::database::{CacheDB, EmptyDB, InMemoryDB};
 use revm::primitives::TxKind;
 use revm::state::{AccountInfo, Bytecode};
+use std::collections::{BTreeMap, BTreeSet};
 
 fn addr(n: u8) -> Address {
     Address::from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, n])
@@ -455,8 +461,8 @@ fn test_generate_third_party_storage_in_output() {
     assert!(!addresses.contains(&to), "tx.to must not be in list");
 }
 
-/// TxKind::Create sets tx_to = Address::ZERO in lib.rs. This test exercises that branch
-/// and documents that generate() returns Ok without panicking.
+/// TxKind::Create resolves tx_to to the would-be deployed address in lib.rs. This test
+/// exercises that branch with empty initcode and documents that generate() returns Ok.
 #[test]
 fn test_generate_create_tx_does_not_panic() {
     let from = addr(100);
@@ -491,6 +497,133 @@ fn test_generate_create_tx_does_not_panic() {
     );
 }
 
+/// A deployment whose constructor CALLs into `third` (which SLOADs slot 0) must list `third`
+/// with slot 0, while the deployed address itself is stripped as warm.
+#[test]
+fn test_generate_create_tx_lists_constructor_reads() {
+    let from = addr(100);
+    let third = addr(102);
+    let coinbase = addr(50);
+
+    // Initcode: CALL third with all gas, then STOP (deploys empty runtime code).
+    let mut initcode: Vec<u8> = vec![
+        0x60, 0x00, // PUSH1 0 (retSize)
+        0x60, 0x00, // PUSH1 0 (retOffset)
+        0x60, 0x00, // PUSH1 0 (argsSize)
+        0x60, 0x00, // PUSH1 0 (argsOffset)
+        0x60, 0x00, // PUSH1 0 (value)
+        0x73, // PUSH20
+    ];
+    initcode.extend_from_slice(third.as_slice());
+    initcode.extend_from_slice(&[0x5a, 0xf1, 0x00]); // GAS, CALL, STOP
+
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(1_000_000_000_000_000_000u64),
+            nonce: 7,
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        third,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(sload_slot0_bytecode())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    let tx = TxEnv::builder()
+        .caller(from)
+        .nonce(7)
+        .kind(TxKind::Create)
+        .gas_limit(1_000_000)
+        .gas_price(1_000_000_000u128)
+        .value(U256::ZERO)
+        .data(Bytes::from(initcode))
+        .build()
+        .unwrap();
+
+    let optimized = generate(db, tx, default_block(coinbase)).unwrap();
+    let addresses: Vec<Address> = optimized.list.0.iter().map(|i| i.address).collect();
+    assert_eq!(addresses, vec![third], "got {:?}", optimized.list);
+    assert_eq!(
+        optimized.list.0[0].storage_keys,
+        vec![alloy_primitives::B256::ZERO]
+    );
+    assert!(!addresses.contains(&from.create(7)));
+}
+
+/// A set-code transaction: `to` CALLs `authority`, an EOA the transaction delegates to
+/// code that SLOADs slot 0. The delegated code reads the authority's own storage, but the
+/// authority itself is warm from the start (EIP-7702) and stripped, not listed.
+#[test]
+fn test_generate_eip7702_authority_stripped() {
+    use revm::context_interface::transaction::{
+        Authorization, RecoveredAuthority, RecoveredAuthorization,
+    };
+
+    let from = addr(100);
+    let to = addr(101);
+    let authority = addr(103);
+    let wallet = addr(104);
+    let coinbase = addr(50);
+
+    let mut code: Vec<u8> = vec![
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+    ];
+    code.extend_from_slice(authority.as_slice());
+    code.extend_from_slice(&[0x5a, 0xf1, 0x00]); // GAS, CALL, STOP
+
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(1_000_000_000_000_000_000u64),
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        to,
+        AccountInfo::default().with_code(Bytecode::new_raw(Bytes::from(code))),
+    );
+    db.insert_account_info(
+        wallet,
+        AccountInfo::default().with_code(Bytecode::new_raw(sload_slot0_bytecode())),
+    );
+
+    let authorization = RecoveredAuthorization::new_unchecked(
+        Authorization {
+            chain_id: U256::from(1),
+            address: wallet,
+            nonce: 0,
+        },
+        RecoveredAuthority::Valid(authority),
+    );
+    let tx = TxEnv::builder()
+        .caller(from)
+        .nonce(0)
+        .kind(TxKind::Call(to))
+        .gas_limit(1_000_000)
+        .gas_price(1_000_000_000u128)
+        .authorization_list_recovered(vec![authorization])
+        .build()
+        .unwrap();
+
+    let optimized = generate(db, tx, default_block(coinbase)).unwrap();
+    assert!(optimized.list.0.iter().all(|i| i.address != authority));
+    let removed = optimized
+        .removed
+        .iter()
+        .find(|r| r.address == authority)
+        .expect("authority's storage read, then stripped");
+    assert_eq!(removed.reason, hammer_core::RemovalReason::Authority);
+    assert_eq!(removed.cold_slots, 1);
+    assert_eq!(optimized.authorities, vec![authority]);
+}
+
 /// `to` makes two sequential CALLs: first to `third_a`, then to `third_b`.
 /// Both third-party contracts SLOAD slot 0. Both must appear in the output list.
 /// This exercises the inspector's accumulation across multiple nested calls.
@@ -739,3 +872,1036 @@ fn test_generate_out_of_gas_returns_ok() {
         result.err()
     );
 }
+
+/// Bytecode for `to`: optionally EXTCODESIZE the precomputed CREATE2 address, then
+/// CREATE2 a contract from `initcode` (at most 32 bytes) with salt 0.
+fn create2_factory_bytecode(precheck: Option<Address>, initcode: &[u8]) -> Bytes {
+    let mut code = Vec::new();
+    if let Some(predicted) = precheck {
+        code.push(0x73); // PUSH20 <predicted>
+        code.extend_from_slice(predicted.as_slice());
+        code.extend_from_slice(&[0x3b, 0x50]); // EXTCODESIZE, POP
+    }
+    for (i, byte) in initcode.iter().enumerate() {
+        code.extend_from_slice(&[0x60, *byte, 0x60, i as u8, 0x53]); // MSTORE8(i, byte)
+    }
+    code.extend_from_slice(&[
+        0x60,
+        0x00, // PUSH1 0 (salt)
+        0x60,
+        initcode.len() as u8, // PUSH1 <size>
+        0x60,
+        0x00, // PUSH1 0 (offset)
+        0x60,
+        0x00, // PUSH1 0 (value)
+        0xf5, // CREATE2
+        0x50, // POP
+        0x00, // STOP
+    ]);
+    Bytes::from(code)
+}
+
+fn create2_db(from: Address, to: Address, code: Bytes) -> InMemoryDB {
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(1_000_000_000_000_000_000u64),
+            nonce: 0,
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(code)),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    db
+}
+
+/// A CREATE2 whose init code reverts still warms the target (EIP-2929), so the
+/// precomputed address must be stripped rather than listed.
+#[test]
+fn test_generate_reverted_create2_target_stripped() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    // Initcode: ADDRESS, BALANCE (touches the new address), INVALID → init frame halts.
+    let initcode = [0x30, 0x31, 0xfe];
+    let predicted = to.create2_from_code(B256::ZERO, initcode);
+
+    let db = create2_db(from, to, create2_factory_bytecode(None, &initcode));
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert!(
+        optimized.list.0.iter().all(|i| i.address != predicted),
+        "CREATE2 target must not be listed, got {:?}",
+        optimized.list
+    );
+}
+
+/// Touching the precomputed CREATE2 address before deploying it pays the cold account
+/// cost at that point, so listing it is beneficial and it must be kept.
+#[test]
+fn test_generate_create2_target_touched_before_creation_kept() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let predicted = to.create2_from_code(B256::ZERO, [0x00]);
+
+    let db = create2_db(from, to, create2_factory_bytecode(Some(predicted), &[0x00]));
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    let addresses: Vec<Address> = optimized.list.0.iter().map(|i| i.address).collect();
+    assert_eq!(addresses, vec![predicted]);
+    assert!(!optimized.removed_addresses.contains(&predicted));
+}
+
+/// Without a pre-creation touch, the deployed CREATE2 contract is warm and stripped.
+#[test]
+fn test_generate_create2_target_without_precheck_stripped() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+
+    let db = create2_db(from, to, create2_factory_bytecode(None, &[0x00]));
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert!(optimized.list.0.is_empty(), "got {:?}", optimized.list);
+}
+
+/// Dispatcher at `to` that CALLs `target` forwarding 1 wei.
+fn value_call_bytecode(target: Address) -> Bytes {
+    let mut code: Vec<u8> = vec![
+        0x60, 0x00, // PUSH1 0 retSize
+        0x60, 0x00, // PUSH1 0 retOffset
+        0x60, 0x00, // PUSH1 0 argsSize
+        0x60, 0x00, // PUSH1 0 argsOffset
+        0x60, 0x01, // PUSH1 1 value
+        0x73, // PUSH20
+    ];
+    code.extend_from_slice(target.as_slice());
+    code.extend_from_slice(&[
+        0x5a, // GAS
+        0xf1, // CALL
+        0x00, // STOP
+    ]);
+    Bytes::from(code)
+}
+
+fn funded_caller_db(from: Address, to: Address, code: Bytes) -> InMemoryDB {
+    let mut db = create2_db(from, to, code.clone());
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            balance: U256::from(1_000u64),
+            code: Some(Bytecode::new_raw(code)),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    db
+}
+
+/// Sending value to an account that does not exist pays the new-account surcharge;
+/// the recipient is reported so the extra gas is not mistaken for access list waste.
+#[test]
+fn test_generate_value_call_to_empty_account_reported() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let recipient = addr(102);
+
+    let db = funded_caller_db(from, to, value_call_bytecode(recipient));
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert_eq!(optimized.new_accounts, vec![recipient]);
+}
+
+/// A funded recipient already exists, so no surcharge is attributed.
+#[test]
+fn test_generate_value_call_to_existing_account_not_reported() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let recipient = addr(102);
+
+    let mut db = funded_caller_db(from, to, value_call_bytecode(recipient));
+    db.insert_account_info(
+        recipient,
+        AccountInfo {
+            balance: U256::from(1u64),
+            ..Default::default()
+        },
+    );
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert!(
+        optimized.new_accounts.is_empty(),
+        "got {:?}",
+        optimized.new_accounts
+    );
+}
+
+/// The state diff shows the value moving from the dispatcher to the new account, and the
+/// sender's nonce bump.
+#[test]
+fn test_generate_state_diff_records_value_transfer() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let recipient = addr(102);
+
+    let db = funded_caller_db(from, to, value_call_bytecode(recipient));
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    let diff = |address| {
+        optimized
+            .state_diff
+            .iter()
+            .find(|d| d.address == address)
+            .unwrap_or_else(|| panic!("no diff for {}: {:?}", address, optimized.state_diff))
+    };
+    assert_eq!(
+        diff(to).balance,
+        Some(Change {
+            from: U256::from(1_000u64),
+            to: U256::from(999u64)
+        })
+    );
+    assert_eq!(
+        diff(recipient).balance,
+        Some(Change {
+            from: U256::ZERO,
+            to: U256::from(1u64)
+        })
+    );
+    let nonce = diff(from).nonce.unwrap();
+    assert_eq!(nonce.to, nonce.from + 1);
+    assert!(optimized
+        .state_diff
+        .windows(2)
+        .all(|w| w[0].address < w[1].address));
+}
+
+/// Logs emitted during the trace are carried through to the optimized result.
+#[test]
+fn test_generate_captures_logs() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+
+    let code = Bytes::from(vec![
+        0x60, 0xaa, // PUSH1 0xaa topic
+        0x60, 0x20, // PUSH1 32 size
+        0x60, 0x00, // PUSH1 0 offset
+        0xa1, // LOG1
+        0x00, // STOP
+    ]);
+    let db = create2_db(from, to, code);
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert_eq!(optimized.logs.len(), 1);
+    let log = &optimized.logs[0];
+    assert_eq!(log.address, to);
+    assert_eq!(log.topics(), &[B256::with_last_byte(0xaa)]);
+    assert_eq!(log.data.data, Bytes::from(vec![0u8; 32]));
+}
+
+/// Dispatcher at `to` that CALLs `target` with the 4-byte `selector` as calldata.
+fn selector_call_bytecode(selector: [u8; 4], target: Address) -> Bytes {
+    let mut code: Vec<u8> = vec![0x63]; // PUSH4 selector
+    code.extend_from_slice(&selector);
+    code.extend_from_slice(&[
+        0x60, 0xe0, // PUSH1 224
+        0x1b, // SHL
+        0x60, 0x00, // PUSH1 0
+        0x52, // MSTORE
+        0x60, 0x00, // PUSH1 0 retSize
+        0x60, 0x00, // PUSH1 0 retOffset
+        0x60, 0x04, // PUSH1 4 argsSize
+        0x60, 0x00, // PUSH1 0 argsOffset
+        0x60, 0x00, // PUSH1 0 value
+        0x73, // PUSH20
+    ]);
+    code.extend_from_slice(target.as_slice());
+    code.extend_from_slice(&[
+        0x5a, // GAS
+        0xf1, // CALL
+        0x00, // STOP
+    ]);
+    Bytes::from(code)
+}
+
+/// DB where `to` calls `target` with `selector`, and `target` reads its slot 0.
+fn selector_call_db(from: Address, to: Address, selector: [u8; 4], target: Address) -> InMemoryDB {
+    let mut db = create2_db(from, to, selector_call_bytecode(selector, target));
+    db.insert_account_info(
+        target,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(sload_slot0_bytecode())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    db
+}
+
+/// A call into `uniswapV3SwapCallback` is recognized, and the slot the callback reads is
+/// attributed to it.
+#[test]
+fn test_generate_annotates_callback_entries() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let receiver = addr(102);
+
+    let db = selector_call_db(from, to, [0xfa, 0x46, 0x1e, 0x33], receiver);
+
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert_eq!(optimized.callbacks.len(), 1, "{:?}", optimized.callbacks);
+    let callback = &optimized.callbacks[0];
+    assert_eq!(callback.kind, CallbackKind::UniswapV3);
+    assert_eq!(callback.function, "uniswapV3SwapCallback");
+    assert_eq!((callback.caller, callback.target), (to, receiver));
+    assert!(callback.touched(&receiver, Some(&B256::ZERO)));
+    assert!(!callback.touched(&to, None));
+}
+
+/// Slots read inside `latestRoundData()` are flagged volatile; the feed address is not.
+#[test]
+fn test_generate_flags_oracle_slots_volatile() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let feed = addr(102);
+
+    let db = selector_call_db(from, to, [0xfe, 0xaf, 0x96, 0x8c], feed);
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert_eq!(
+        optimized.volatile,
+        BTreeMap::from([(feed, BTreeSet::from([B256::ZERO]))])
+    );
+    assert!(optimized.callbacks.is_empty());
+}
+
+/// `to` calls `mid`, which calls `leaf` (reading its slot 0). Analyzing call `0` treats
+/// `to -> mid` as the transaction; call `0.0` has nothing left to declare.
+#[test]
+fn test_generate_entry_call_as_transaction() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let mid = addr(102);
+    let leaf = addr(103);
+
+    let mut db = selector_call_db(from, to, [0x11; 4], mid);
+    db.insert_account_info(
+        mid,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(selector_call_bytecode([0x22; 4], leaf))),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        leaf,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(sload_slot0_bytecode())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    let whole = generate(db.clone(), default_tx(from, to), default_block(coinbase)).unwrap();
+    let paths: Vec<String> = whole.calls.iter().map(|c| c.path.to_string()).collect();
+    assert_eq!(paths, vec!["0", "0.0"]);
+    assert_eq!(whole.calls[1].kind, CallKind::Call);
+    assert_eq!(whole.calls[1].selector, Some([0x22; 4].into()));
+
+    let config = Default::default();
+    let (frame, optimized) = generate_entry_call(
+        db.clone(),
+        default_tx(from, to),
+        default_block(coinbase),
+        &"0".parse().unwrap(),
+        &config,
+    )
+    .unwrap();
+    assert_eq!((frame.caller, frame.target), (to, mid));
+    assert_eq!(optimized.list.0.len(), 1);
+    assert_eq!(optimized.list.0[0].address, leaf);
+    assert_eq!(optimized.list.0[0].storage_keys, vec![B256::ZERO]);
+
+    let (frame, optimized) = generate_entry_call(
+        db.clone(),
+        default_tx(from, to),
+        default_block(coinbase),
+        &CallPath(vec![0, 0]),
+        &config,
+    )
+    .unwrap();
+    assert_eq!((frame.caller, frame.target), (mid, leaf));
+    assert!(optimized.list.0.is_empty());
+
+    let err = generate_entry_call(
+        db,
+        default_tx(from, to),
+        default_block(coinbase),
+        &CallPath(vec![1]),
+        &config,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("no call at 1"), "{}", err);
+}
+
+/// A batch calling two contracts that each read slot 0 splits into one transaction per
+/// call; with a budget of two entries only the split plan fits.
+#[test]
+fn test_generate_split_plans_by_top_level_call() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let first = addr(102);
+    let second = addr(103);
+
+    let mut batch = selector_call_bytecode([0x11; 4], first).to_vec();
+    batch.pop(); // STOP
+    batch.extend_from_slice(&selector_call_bytecode([0x22; 4], second));
+    let mut db = selector_call_db(from, to, [0x11; 4], first);
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(batch.into())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        second,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(sload_slot0_bytecode())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    let (whole, plans) = generate_split_plans(
+        db,
+        default_tx(from, to),
+        default_block(coinbase),
+        &[vec![], vec![1]],
+        Some(2),
+        &Default::default(),
+    )
+    .unwrap();
+    assert_eq!(whole.list.0.len(), 2);
+
+    let (unsplit, split) = (&plans[0], &plans[1]);
+    assert_eq!(unsplit.parts[0].list, whole.list);
+    assert!(!unsplit.fits);
+    assert!(split.fits);
+    assert_eq!(split.parts[0].list.0[0].address, first);
+    assert_eq!(split.parts[1].list.0[0].address, second);
+    assert_eq!(split.list_cost, unsplit.list_cost);
+    assert_eq!(
+        split.upfront_cost,
+        unsplit.upfront_cost + hammer_core::TX_BASE_COST
+    );
+}
+
+/// State backend of a non-archive node asked for pruned history.
+#[derive(Debug)]
+struct PrunedError;
+
+impl std::fmt::Display for PrunedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("missing trie node 0xabc (path )")
+    }
+}
+
+impl std::error::Error for PrunedError {}
+impl revm::database_interface::DBErrorMarker for PrunedError {}
+
+struct PrunedDB;
+
+impl revm::Database for PrunedDB {
+    type Error = PrunedError;
+
+    fn basic(&mut self, _: Address) -> Result<Option<AccountInfo>, PrunedError> {
+        Err(PrunedError)
+    }
+
+    fn code_by_hash(&mut self, _: B256) -> Result<Bytecode, PrunedError> {
+        Err(PrunedError)
+    }
+
+    fn storage(&mut self, _: Address, _: U256) -> Result<U256, PrunedError> {
+        Err(PrunedError)
+    }
+
+    fn block_hash(&mut self, _: u64) -> Result<B256, PrunedError> {
+        Err(PrunedError)
+    }
+}
+
+/// Pruned state surfaces as a typed error naming the block, not a raw RPC message.
+#[test]
+fn test_generate_on_pruned_state_is_state_unavailable() {
+    let err = generate(
+        PrunedDB,
+        default_tx(addr(1), addr(2)),
+        default_block(addr(50)),
+    )
+    .unwrap_err();
+    match err {
+        hammer_core::HammerError::StateUnavailable { block, .. } => {
+            assert_eq!(block, 20_000_000)
+        }
+        other => panic!("expected StateUnavailable, got {other:?}"),
+    }
+}
+
+/// The second transaction of a queue runs on the first one's state: its nonce follows
+/// on, and the recipient the first one created is no longer new.
+#[test]
+fn test_generate_queue_carries_state_between_transactions() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let recipient = addr(102);
+
+    let db = funded_caller_db(from, to, value_call_bytecode(recipient));
+    let mut second = default_tx(from, to);
+    second.nonce = 1;
+    let lists = hammer_core::generate_queue(
+        db,
+        vec![default_tx(from, to), second],
+        default_block(coinbase),
+    )
+    .unwrap();
+    assert_eq!(lists.len(), 2);
+    assert_eq!(lists[0].new_accounts, vec![recipient]);
+    assert!(lists[1].new_accounts.is_empty());
+    assert_eq!(lists[0].list, lists[1].list);
+}
+
+/// A batch is generated as a queue: in order, each transaction on the last one's state.
+#[test]
+fn test_generate_batch_matches_queue() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+
+    let db = funded_caller_db(from, to, value_call_bytecode(addr(102)));
+    let mut second = default_tx(from, to);
+    second.nonce = 1;
+    let txs = vec![default_tx(from, to), second];
+    let batch =
+        hammer_core::generate_batch(db.clone(), txs.clone(), default_block(coinbase)).unwrap();
+    let queue = hammer_core::generate_queue(db, txs, default_block(coinbase)).unwrap();
+    assert_eq!(batch.len(), 2);
+    assert_eq!(batch[0].new_accounts, vec![addr(102)]);
+    assert!(batch[1].new_accounts.is_empty());
+    for (batch, queue) in batch.iter().zip(&queue) {
+        assert_eq!(batch.list, queue.list);
+    }
+}
+
+/// A bundle's list declares each entry once, however many of its transactions need it.
+#[test]
+fn test_generate_bundle_lists_shared_entries_once() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+
+    let db = funded_caller_db(from, to, value_call_bytecode(addr(102)));
+    let mut second = default_tx(from, to);
+    second.nonce = 1;
+    let txs = vec![default_tx(from, to), second];
+    let lists =
+        hammer_core::generate_queue(db.clone(), txs.clone(), default_block(coinbase)).unwrap();
+    let bundle = hammer_core::generate_bundle(db, txs, default_block(coinbase)).unwrap();
+    assert!(!lists[0].list.0.is_empty());
+    assert_eq!(bundle.list, lists[0].list);
+    assert_eq!(bundle.new_accounts, vec![addr(102)]);
+}
+
+/// A queue entry the EVM rejects is reported by position.
+#[test]
+fn test_generate_queue_names_rejected_entry() {
+    let from = addr(100);
+    let to = addr(101);
+
+    let db = funded_caller_db(from, to, sload_slot0_bytecode());
+    // Both entries use nonce 0: the second is stale once the first is committed.
+    let err = hammer_core::generate_queue(
+        db,
+        vec![default_tx(from, to), default_tx(from, to)],
+        default_block(addr(50)),
+    )
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("transaction 1 in queue"),
+        "got {err}"
+    );
+}
+
+/// The iterator yields each list before tracing the next transaction, and stops after
+/// the first rejected one.
+#[test]
+fn test_generate_queue_iter_stops_after_rejected_entry() {
+    let from = addr(100);
+    let to = addr(101);
+
+    let db = funded_caller_db(from, to, sload_slot0_bytecode());
+    let config = Default::default();
+    let mut lists = hammer_core::generate_queue_iter(
+        db,
+        vec![
+            default_tx(from, to),
+            default_tx(from, to),
+            default_tx(from, to),
+        ],
+        default_block(addr(50)),
+        &config,
+    );
+    assert!(lists.next().unwrap().is_ok());
+    assert!(lists.next().unwrap().is_err());
+    assert!(lists.next().is_none());
+}
+
+/// A cancelled token stops the trace with a typed error; an untouched one changes nothing.
+#[test]
+fn test_generate_cancellable() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let config = Default::default();
+    let cancel = hammer_core::CancellationToken::new();
+
+    let db = funded_caller_db(from, to, sload_slot0_bytecode());
+    let optimized = hammer_core::generate_cancellable(
+        db.clone(),
+        default_tx(from, to),
+        default_block(coinbase),
+        &config,
+        &cancel,
+    )
+    .unwrap();
+    assert_eq!(
+        optimized.list,
+        generate(db.clone(), default_tx(from, to), default_block(coinbase))
+            .unwrap()
+            .list
+    );
+
+    cancel.clone().cancel();
+    let err = hammer_core::generate_cancellable(
+        db,
+        default_tx(from, to),
+        default_block(coinbase),
+        &config,
+        &cancel,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, hammer_core::HammerError::Cancelled),
+        "{err:?}"
+    );
+}
+
+/// One engine shared by several threads gives each the same list as a lone call.
+#[test]
+fn test_engine_concurrent_generate() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let db = funded_caller_db(from, to, sload_slot0_bytecode());
+    let expected = generate(db.clone(), default_tx(from, to), default_block(coinbase))
+        .unwrap()
+        .list;
+
+    let engine = hammer_core::Hammer::default();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let engine = engine.clone();
+                let db = db.clone();
+                scope.spawn(move || {
+                    engine
+                        .generate(db, default_tx(from, to), default_block(coinbase))
+                        .unwrap()
+                        .list
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    });
+}
+
+/// Each calldata variant touches the account it names; the padded list declares all of
+/// them, counting how many runs needed each, and leaves reverting variants out.
+#[test]
+fn test_generate_padded_counts_hits_per_entry() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    // BALANCE of the address in word 0, then REVERT if word 1 is non-zero.
+    let code = Bytes::from(vec![
+        0x60, 0x00, 0x35, 0x31, 0x50, 0x60, 0x20, 0x35, 0x15, 0x60, 0x10, 0x57, 0x60, 0x00, 0x80,
+        0xfd, 0x5b, 0x00,
+    ]);
+    let db = funded_caller_db(from, to, code);
+    let calldata = |target: Address, revert: bool| {
+        let mut data = B256::left_padding_from(target.as_slice()).to_vec();
+        data.extend(B256::with_last_byte(revert as u8));
+        Bytes::from(data)
+    };
+    let (a, b, c) = (addr(0x40), addr(0x41), addr(0x42));
+    let mut tx = default_tx(from, to);
+    tx.data = calldata(a, false);
+
+    let (optimized, padded) = hammer_core::generate_padded(
+        db,
+        tx,
+        default_block(coinbase),
+        &[calldata(b, false), calldata(a, false), calldata(c, true)],
+        &Default::default(),
+    )
+    .unwrap();
+    let addresses =
+        |list: &alloy_eip2930::AccessList| list.0.iter().map(|i| i.address).collect::<Vec<_>>();
+    assert_eq!(addresses(&optimized.list), vec![a]);
+    assert_eq!(addresses(&padded.list), vec![a, b]);
+    assert_eq!((padded.runs, padded.reverted), (3, 1));
+    assert_eq!(padded.hits_of(&a, None), Some(2));
+    assert_eq!(padded.hits_of(&b, None), Some(1));
+}
+
+/// Repeat touches are counted, but only the first pays the cold surcharge.
+#[test]
+fn test_generate_counts_cold_and_warm_touches() {
+    let from = addr(100);
+    let to = addr(101);
+    let third = addr(0x40);
+    let coinbase = addr(50);
+
+    // SLOAD slot 0 twice, then BALANCE(third) twice.
+    let mut code = vec![0x60, 0x00, 0x54, 0x50, 0x60, 0x00, 0x54, 0x50];
+    for _ in 0..2 {
+        code.push(0x73);
+        code.extend_from_slice(third.as_slice());
+        code.extend_from_slice(&[0x31, 0x50]);
+    }
+    code.push(0x00);
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(1_000_000_000_000_000_000u64),
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(code.into())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    let raw = generate_access_list(
+        db.clone(),
+        default_tx(from, to),
+        default_block(coinbase),
+        false,
+    )
+    .unwrap();
+    let slot = raw.access_counts[&to].slots[&B256::ZERO];
+    assert_eq!((slot.accesses, slot.cold), (2, 1));
+    let account = raw.access_counts[&third].account;
+    assert_eq!((account.accesses, account.cold), (2, 1));
+
+    // One cold touch repays the entry, so net-savings keeps it.
+    let optimized = generate_with_strategy(
+        db,
+        default_tx(from, to),
+        default_block(coinbase),
+        &OptimizerConfig::default(),
+        Strategy::NetSavings,
+    )
+    .unwrap();
+    assert_eq!(optimized.list.0.len(), 1);
+    assert_eq!(optimized.list.0[0].address, third);
+}
+
+/// A contract that reads a slot only when enough gas is left takes the other branch once
+/// its cold BALANCE is declared: the list saves 100 more gas than it costs upfront.
+#[test]
+fn test_self_check_catches_gas_dependent_branch() {
+    let from = addr(100);
+    let to = addr(101);
+    let third = addr(0x40);
+    let coinbase = addr(50);
+
+    // BALANCE(third); if gasleft() > 976_440 { SLOAD 7 }. Without the list gasleft() is
+    // 1_000_000 - 23_610 there, with it 1_000_000 - 23_510.
+    let mut code = vec![0x73];
+    code.extend_from_slice(third.as_slice());
+    code.extend_from_slice(&[
+        0x31, 0x50, 0x62, 0x0e, 0xe6, 0x38, 0x5a, 0x11, 0x60, 0x21, 0x57,
+    ]);
+    code.extend_from_slice(&[0x00, 0x5b, 0x60, 0x07, 0x54, 0x50, 0x00]);
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(1_000_000_000_000_000_000u64),
+            ..Default::default()
+        },
+    );
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(code.into())),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    let (optimized, check) = generate_self_checked(
+        db.clone(),
+        default_tx(from, to),
+        default_block(coinbase),
+        &OptimizerConfig::default(),
+        Strategy::Complete,
+    )
+    .unwrap();
+    assert_eq!(optimized.list.0.len(), 1);
+    assert_eq!(optimized.list.0[0].address, third);
+    assert!(!check.is_consistent());
+    assert_eq!(
+        optimized.gas_sensitive,
+        vec![GasSensitivity::Branch {
+            path: CallPath(vec![]),
+            code_address: to,
+            pc: 31,
+        }]
+    );
+    assert!(check.only_without.is_empty());
+    assert_eq!(
+        check.only_with,
+        BTreeMap::from([(to, BTreeSet::from([B256::with_last_byte(7)]))])
+    );
+
+    // A plain read does the same either way.
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(Bytes::from_static(&[
+                0x60, 0x00, 0x54, 0x00,
+            ]))),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    let (_, check) = generate_self_checked(
+        db,
+        default_tx(from, to),
+        default_block(coinbase),
+        &OptimizerConfig::default(),
+        Strategy::Complete,
+    )
+    .unwrap();
+    assert!(check.is_consistent(), "{:?}", check);
+}
+
+/// A call that runs out of the gas it was forwarded is flagged with its frame; forwarding
+/// gasleft() to it is not a branch.
+#[test]
+fn test_generate_flags_call_that_runs_out_of_gas() {
+    let from = addr(100);
+    let to = addr(101);
+    let spinner = addr(0x41);
+    let coinbase = addr(50);
+
+    // CALL(gas: gasleft(), spinner), which loops until it runs out.
+    let mut code = vec![
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+    ];
+    code.extend_from_slice(spinner.as_slice());
+    code.extend_from_slice(&[0x5a, 0xf1, 0x50, 0x00]);
+    let mut db = InMemoryDB::default();
+    db.insert_account_info(
+        from,
+        AccountInfo {
+            balance: U256::from(1_000_000_000_000_000_000u64),
+            ..Default::default()
+        },
+    );
+    for (address, code) in [(to, code), (spinner, vec![0x5b, 0x60, 0x00, 0x56])] {
+        db.insert_account_info(
+            address,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(code.into())),
+                nonce: 1,
+                ..Default::default()
+            },
+        );
+    }
+
+    let optimized = generate(db, default_tx(from, to), default_block(coinbase)).unwrap();
+    assert_eq!(
+        optimized.gas_sensitive,
+        vec![GasSensitivity::StarvedCall {
+            path: CallPath(vec![0]),
+            target: spinner,
+        }]
+    );
+}
+
+/// State overrides take effect before the trace: an unfunded sender calling an account
+/// with no code on chain runs the overriding code, and what it touches is listed.
+#[test]
+fn test_generate_follows_state_overrides() {
+    let from = addr(100);
+    let to = addr(101);
+    let probed = addr(0x42);
+
+    // PUSH20 probed, BALANCE, STOP
+    let mut code = vec![0x73];
+    code.extend_from_slice(probed.as_slice());
+    code.extend_from_slice(&[0x31, 0x00]);
+    let overrides = StateOverrides(BTreeMap::from([
+        (
+            from,
+            AccountOverride {
+                balance: Some(U256::from(1_000_000_000_000_000_000u64)),
+                ..Default::default()
+            },
+        ),
+        (
+            to,
+            AccountOverride {
+                code: Some(Bytes::from(code)),
+                ..Default::default()
+            },
+        ),
+    ]));
+
+    let mut db = CacheDB::new(EmptyDB::default());
+    assert!(generate(&mut db, default_tx(from, to), default_block(addr(50))).is_err());
+
+    overrides.apply(&mut db).unwrap();
+    let optimized = generate(db, default_tx(from, to), default_block(addr(50))).unwrap();
+    let addresses: Vec<Address> = optimized.list.0.iter().map(|i| i.address).collect();
+    assert_eq!(addresses, vec![probed]);
+}
+
+/// Transient storage (EIP-1153) is never cold, so it is reported apart and never listed,
+/// even when the contract also reads persistent storage.
+#[test]
+fn test_generate_reports_transient_slots_unlisted() {
+    let from = addr(100);
+    let to = addr(101);
+    let code = Bytes::from(vec![
+        0x60, 0x01, 0x60, 0x05, 0x5d, // TSTORE(5, 1)
+        0x60, 0x05, 0x5c, 0x50, // TLOAD(5), POP
+        0x60, 0x00, 0x54, 0x00, // SLOAD(0), STOP
+    ]);
+
+    let db = funded_caller_db(from, to, code.clone());
+    let raw =
+        generate_access_list(db, default_tx(from, to), default_block(addr(50)), false).unwrap();
+    let slots: Vec<B256> = raw
+        .access_list
+        .0
+        .iter()
+        .flat_map(|item| item.storage_keys.iter().copied())
+        .collect();
+    assert_eq!(slots, vec![B256::ZERO]);
+    let transient = BTreeMap::from([(to, BTreeSet::from([B256::with_last_byte(5)]))]);
+    assert_eq!(raw.transient, transient);
+
+    let db = funded_caller_db(from, to, code);
+    let result = generate(db, default_tx(from, to), default_block(addr(50))).unwrap();
+    assert_eq!(result.transient, transient);
+}
+
+/// A backend behind the `Tracer` trait reaches the same optimizer as the built-in one.
+#[test]
+fn test_generate_with_tracer_optimizes_backend_trace() {
+    /// Delegates to revm, then reports an extra read the way another backend might.
+    struct Extra(RevmTracer<InMemoryDB>, usize);
+
+    impl Tracer for Extra {
+        fn trace(&mut self, tx: TxEnv, block: BlockEnv) -> Result<RawTraceResult, HammerError> {
+            self.1 += 1;
+            let mut raw = self.0.trace(tx, block)?;
+            raw.access_list.0.push(AccessListItem {
+                address: addr(0x77),
+                storage_keys: vec![B256::with_last_byte(1)],
+            });
+            Ok(raw)
+        }
+    }
+
+    let from = addr(100);
+    let to = addr(101);
+    let db = funded_caller_db(from, to, sload_slot0_bytecode());
+    let builtin = generate(db.clone(), default_tx(from, to), default_block(addr(50))).unwrap();
+
+    let mut tracer = Extra(RevmTracer::new(db), 0);
+    let config = OptimizerConfig::default();
+    let list = generate_with_tracer(
+        &mut tracer,
+        default_tx(from, to),
+        default_block(addr(50)),
+        &config,
+    )
+    .unwrap();
+    assert_eq!(tracer.1, 1);
+    let mut expected = builtin.list.0.clone();
+    expected.push(AccessListItem {
+        address: addr(0x77),
+        storage_keys: vec![B256::with_last_byte(1)],
+    });
+    assert_eq!(list.list.0, expected);
+}
+
+/// Parallel generation over one state matches generating each transaction on its own,
+/// in input order, whatever the number of threads.
+#[test]
+fn test_generate_many_parallel_matches_sequential() {
+    let from = addr(100);
+    let to = addr(101);
+    let db = funded_caller_db(from, to, sload_slot0_bytecode());
+
+    // Calls to the contract and to accounts with no code, interleaved across shards.
+    let txs: Vec<TxEnv> = [to, addr(102), to, addr(103), to]
+        .into_iter()
+        .map(|target| default_tx(from, target))
+        .collect();
+    let block = default_block(addr(50));
+    let sequential: Vec<_> = txs
+        .iter()
+        .map(|tx| {
+            generate(db.clone(), tx.clone(), block.clone())
+                .unwrap()
+                .list
+        })
+        .collect();
+    for jobs in [0, 1, 2, 8] {
+        let parallel: Vec<_> = hammer_core::generate_many_parallel(
+            &db,
+            txs.clone(),
+            &block,
+            &OptimizerConfig::default(),
+            jobs,
+        )
+        .into_iter()
+        .map(|result| result.unwrap().list)
+        .collect();
+        assert_eq!(parallel, sequential, "jobs = {jobs}");
+    }
+}
@@ -1,8 +1,8 @@
 // Integration tests for hammer_core::validate() and hammer_core::validate_replay().
 
+use alloy_eip2930::{AccessList, AccessListItem};
 use alloy_primitives::{Address, Bytes, U256};
-use alloy_rpc_types_eth::{AccessList, AccessListItem};
-use hammer_core::{validate, validate_replay};
+use hammer_core::{validate, validate_candidates, validate_replay, DiffEntry, OptimizerConfig};
 use revm::context::{BlockEnv, TxEnv};
 use revm::database::InMemoryDB;
 use revm::primitives::TxKind;
@@ -311,3 +311,252 @@ fn test_validate_redundant_warm_addresses_flagged() {
         "expected 2 Redundant entries for tx.from and tx.to"
     );
 }
+
+/// A mined creation replays its constructor at the address it deploys to: declaring that
+/// address is Redundant like declaring a call target, and what the constructor reads
+/// elsewhere is Missing from an empty list.
+#[test]
+fn test_validate_replay_create_tx_treats_deployed_address_as_target() {
+    let from = addr(100);
+    let third = addr(102);
+    let coinbase = addr(50);
+    let mut db = funded_db(from);
+    db.insert_account_info(
+        third,
+        AccountInfo {
+            code: Some(sload_slot0_bytecode()),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    // Initcode: CALL third with all gas, then STOP.
+    let mut initcode = vec![
+        0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x60, 0x00, 0x73,
+    ];
+    initcode.extend_from_slice(third.as_slice());
+    initcode.extend_from_slice(&[0x5a, 0xf1, 0x00]); // GAS, CALL, STOP
+    let tx = TxEnv::builder()
+        .caller(from)
+        .nonce(3)
+        .kind(TxKind::Create)
+        .gas_limit(1_000_000)
+        .gas_price(1_000_000_000u128)
+        .data(Bytes::from(initcode))
+        .build()
+        .unwrap();
+    let deployed = from.create(3);
+    let declared = AccessList(vec![AccessListItem {
+        address: deployed,
+        storage_keys: vec![],
+    }]);
+
+    let report = validate_replay(db, tx, default_block(coinbase), declared).unwrap();
+    assert!(report.entries.iter().any(|e| matches!(
+        e,
+        hammer_core::DiffEntry::Redundant { address, .. } if *address == deployed
+    )));
+    assert!(report.entries.iter().any(|e| matches!(
+        e,
+        hammer_core::DiffEntry::Missing { address, .. } if *address == third
+    )));
+    let optimal: Vec<Address> = report.optimal_list.0.iter().map(|i| i.address).collect();
+    assert_eq!(optimal, vec![third]);
+}
+
+/// A declared precompile is Redundant even when it was called; the report lists the
+/// call so users can see why.
+#[test]
+fn test_validate_called_precompile_redundant_and_surfaced() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let identity = addr(4);
+
+    // STATICCALL(gas, 0x04, 0, 0, 0, 0) twice, then STOP.
+    let call_identity = [
+        0x60, 0x00, // PUSH1 0 retSize
+        0x60, 0x00, // PUSH1 0 retOffset
+        0x60, 0x00, // PUSH1 0 argsSize
+        0x60, 0x00, // PUSH1 0 argsOffset
+        0x60, 0x04, // PUSH1 4 (identity)
+        0x5a, // GAS
+        0xfa, // STATICCALL
+        0x50, // POP
+    ];
+    let mut code = call_identity.to_vec();
+    code.extend_from_slice(&call_identity);
+    code.push(0x00);
+
+    let mut db = funded_db(from);
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(Bytes::from(code))),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    let declared = AccessList(vec![AccessListItem {
+        address: identity,
+        storage_keys: vec![],
+    }]);
+
+    let report = validate(
+        db,
+        default_tx(from, to, 0),
+        default_block(coinbase),
+        declared,
+    )
+    .unwrap();
+    assert!(report.entries.iter().any(
+        |e| matches!(e, hammer_core::DiffEntry::Redundant { address, .. } if *address == identity)
+    ));
+    assert_eq!(report.precompile_calls.get(&identity), Some(&2));
+    assert_eq!(report.precompile_calls.len(), 1);
+}
+
+/// Clearing a slot earns a refund; before London it is larger but capped at half the
+/// gas spent, so part of any gas saved comes back off the refund.
+#[test]
+fn test_validate_replay_at_spec_uses_era_refunds() {
+    use hammer_core::{
+        validate_replay_at_spec, MAX_REFUND_QUOTIENT, MAX_REFUND_QUOTIENT_PRE_LONDON,
+    };
+    use revm::primitives::hardfork::SpecId;
+
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let mut db = funded_db(from);
+    // PUSH1 0, PUSH1 0, SSTORE, STOP: clears slot 0.
+    let code = Bytes::from(vec![0x60, 0x00, 0x60, 0x00, 0x55, 0x00]);
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(code)),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+    db.insert_account_storage(to, U256::ZERO, U256::from(1))
+        .unwrap();
+
+    let (_, berlin) = validate_replay_at_spec(
+        db.clone(),
+        default_tx(from, to, 0),
+        default_block(coinbase),
+        AccessList::default(),
+        SpecId::BERLIN,
+    )
+    .unwrap();
+    assert_eq!(berlin.quotient, MAX_REFUND_QUOTIENT_PRE_LONDON);
+    assert_eq!(berlin.counter, 15_000);
+    assert!(berlin.is_capped());
+    assert_eq!(berlin.net_savings(2_000), 1_000);
+
+    let (_, london) = validate_replay_at_spec(
+        db,
+        default_tx(from, to, 0),
+        default_block(coinbase),
+        AccessList::default(),
+        SpecId::LONDON,
+    )
+    .unwrap();
+    assert_eq!(london.quotient, MAX_REFUND_QUOTIENT);
+    assert_eq!(london.counter, 4_800);
+    assert!(!london.is_capped());
+    assert_eq!(london.net_savings(2_000), 2_000);
+}
+
+/// A declared list that does not fit next to intrinsic gas is flagged; the (empty) optimal
+/// list fits exactly.
+#[test]
+fn test_validate_flags_list_over_gas_limit() {
+    let from = addr(100);
+    let to = addr(101);
+    let coinbase = addr(50);
+    let mut tx = default_tx(from, to, 0);
+    tx.gas_limit = 21_000;
+    let declared = AccessList(vec![AccessListItem {
+        address: addr(0x40),
+        storage_keys: vec![],
+    }]);
+
+    let report = validate(funded_db(from), tx, default_block(coinbase), declared).unwrap();
+    assert_eq!(
+        report.budget_exceeded.len(),
+        1,
+        "{:?}",
+        report.budget_exceeded
+    );
+    let exceeded = &report.budget_exceeded[0];
+    assert_eq!(exceeded.list, hammer_core::BudgetList::Declared);
+    assert_eq!(
+        (
+            exceeded.intrinsic_gas,
+            exceeded.list_cost,
+            exceeded.execution_gas
+        ),
+        (21_000, 2_400, 0)
+    );
+    assert_eq!(exceeded.shortfall, 2_400);
+    assert!(exceeded.upfront_exceeds());
+}
+
+/// Several declared lists are checked against one trace, each getting its own report.
+#[test]
+fn test_validate_candidates_share_one_trace() {
+    let from = addr(100);
+    let to = addr(101);
+    let probed = addr(0x42);
+
+    // PUSH20 probed, BALANCE, STOP
+    let mut code = vec![0x73];
+    code.extend_from_slice(probed.as_slice());
+    code.extend_from_slice(&[0x31, 0x00]);
+    let mut db = funded_db(from);
+    db.insert_account_info(
+        to,
+        AccountInfo {
+            code: Some(Bytecode::new_raw(Bytes::from(code))),
+            nonce: 1,
+            ..Default::default()
+        },
+    );
+
+    let list = |addresses: &[Address]| {
+        AccessList(
+            addresses
+                .iter()
+                .map(|&address| AccessListItem {
+                    address,
+                    storage_keys: vec![],
+                })
+                .collect(),
+        )
+    };
+    let candidates = [list(&[]), list(&[probed]), list(&[probed, addr(0x43)])];
+    let reports = validate_candidates(
+        db,
+        default_tx(from, to, 0),
+        default_block(addr(50)),
+        &candidates,
+        &OptimizerConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(reports.len(), 3);
+    assert!(matches!(
+        reports[0].entries.as_slice(),
+        [DiffEntry::Missing { address, .. }] if *address == probed
+    ));
+    assert!(reports[1].is_valid);
+    assert!(matches!(
+        reports[2].entries.as_slice(),
+        [DiffEntry::Stale { address, .. }] if *address == addr(0x43)
+    ));
+    assert!(reports
+        .iter()
+        .all(|report| report.optimal_list == candidates[1]));
+}